@@ -1,8 +1,21 @@
 // Bring the modules into scope
 pub mod accounts_manager;
+pub mod billterms_manager;
 pub mod books_manager;
+pub mod categorization_rules_manager;
 pub mod commodities_manager;
+pub mod customers_manager;
+pub mod employees_manager;
+pub mod entries_manager;
+pub mod gnclock_manager;
+pub mod invoices_manager;
+pub mod jobs_manager;
 pub mod lots_manager;
+pub mod prices_manager;
 pub mod slots_manager;
 pub mod splits_manager;
-pub mod transactions_manager;
\ No newline at end of file
+pub mod taxtables_manager;
+pub mod transaction_templates_manager;
+pub mod transactions_manager;
+pub mod vendors_manager;
+pub mod versions_manager;
\ No newline at end of file
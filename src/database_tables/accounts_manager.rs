@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 
+use crate::database_tables::{slots_manager, transactions_manager};
 use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
 use crate::utility::sql_helper_utility as shu;
 use std::collections::HashMap;
 use uuid::Uuid;
@@ -9,18 +11,142 @@ use uuid::Uuid;
 use serde_repr::*;
 use std::fmt;
 
+/// AccountType is the GnuCash type of an account. CHECKING, SAVINGS, MONEYMRKT, and CREDITLINE
+/// are only kept for old books, so they aren't offered for new accounts.
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(from = "String", into = "String")]
 pub enum AccountType {
     ASSET,
     BANK,
     CASH,
     CREDIT,
+    CURRENCY,
     EQUITY,
     EXPENSE,
     INCOME,
     LIABILITY,
+    MUTUAL,
+    PAYABLE,
     RECEIVABLE,
     ROOT,
+    STOCK,
+    TRADING,
+    CHECKING,
+    SAVINGS,
+    MONEYMRKT,
+    CREDITLINE,
+    OTHER(String), //OTHER is a type GnuCash doesn't write, kept as it was read so it's saved back the same.
+}
+
+impl AccountType {
+    /// SELECTABLE are the types a new account can be given in the account editor.
+    pub const SELECTABLE: [AccountType; 14] = [
+        AccountType::ASSET,
+        AccountType::BANK,
+        AccountType::CASH,
+        AccountType::CREDIT,
+        AccountType::CURRENCY,
+        AccountType::EQUITY,
+        AccountType::EXPENSE,
+        AccountType::INCOME,
+        AccountType::LIABILITY,
+        AccountType::MUTUAL,
+        AccountType::PAYABLE,
+        AccountType::RECEIVABLE,
+        AccountType::STOCK,
+        AccountType::TRADING,
+    ];
+
+    /// as_str returns the type as GnuCash saves it. (Ex: 'BANK')
+    pub fn as_str(&self) -> &str {
+        match self {
+            AccountType::ASSET => "ASSET",
+            AccountType::BANK => "BANK",
+            AccountType::CASH => "CASH",
+            AccountType::CREDIT => "CREDIT",
+            AccountType::CURRENCY => "CURRENCY",
+            AccountType::EQUITY => "EQUITY",
+            AccountType::EXPENSE => "EXPENSE",
+            AccountType::INCOME => "INCOME",
+            AccountType::LIABILITY => "LIABILITY",
+            AccountType::MUTUAL => "MUTUAL",
+            AccountType::PAYABLE => "PAYABLE",
+            AccountType::RECEIVABLE => "RECEIVABLE",
+            AccountType::ROOT => "ROOT",
+            AccountType::STOCK => "STOCK",
+            AccountType::TRADING => "TRADING",
+            AccountType::CHECKING => "CHECKING",
+            AccountType::SAVINGS => "SAVINGS",
+            AccountType::MONEYMRKT => "MONEYMRKT",
+            AccountType::CREDITLINE => "CREDITLINE",
+            AccountType::OTHER(account_type) => account_type,
+        }
+    }
+
+    /// top_level_type returns the type of the top level account this type belongs under.
+    /// (Ex: a STOCK is an ASSET, and a PAYABLE is a LIABILITY)
+    pub fn top_level_type(&self) -> AccountType {
+        match self {
+            AccountType::BANK
+            | AccountType::CASH
+            | AccountType::CURRENCY
+            | AccountType::MUTUAL
+            | AccountType::RECEIVABLE
+            | AccountType::STOCK
+            | AccountType::CHECKING
+            | AccountType::SAVINGS
+            | AccountType::MONEYMRKT => AccountType::ASSET,
+            AccountType::CREDIT | AccountType::PAYABLE | AccountType::CREDITLINE => {
+                AccountType::LIABILITY
+            }
+            AccountType::TRADING => AccountType::EQUITY,
+            account_type => account_type.clone(),
+        }
+    }
+
+    /// balance_sign returns what a balance of this type is multiplied by to show it. Splits are
+    /// saved as debits, so LIABILITY, INCOME, and EQUITY balances, and the types under them, are
+    /// negated to show credits as positive amounts. (Ex: a CREDIT card owing 50.00 shows 50.00)
+    pub fn balance_sign(&self) -> f64 {
+        match self.top_level_type() {
+            AccountType::LIABILITY | AccountType::INCOME | AccountType::EQUITY => -1.0,
+            _ => 1.0,
+        }
+    }
+}
+
+impl From<String> for AccountType {
+    fn from(account_type: String) -> AccountType {
+        match account_type.as_str() {
+            "ASSET" => AccountType::ASSET,
+            "BANK" => AccountType::BANK,
+            "CASH" => AccountType::CASH,
+            "CREDIT" => AccountType::CREDIT,
+            "CURRENCY" => AccountType::CURRENCY,
+            "EQUITY" => AccountType::EQUITY,
+            "EXPENSE" => AccountType::EXPENSE,
+            "INCOME" => AccountType::INCOME,
+            "LIABILITY" => AccountType::LIABILITY,
+            "MUTUAL" => AccountType::MUTUAL,
+            "PAYABLE" => AccountType::PAYABLE,
+            "RECEIVABLE" => AccountType::RECEIVABLE,
+            "ROOT" => AccountType::ROOT,
+            "STOCK" => AccountType::STOCK,
+            "TRADING" => AccountType::TRADING,
+            "CHECKING" => AccountType::CHECKING,
+            "SAVINGS" => AccountType::SAVINGS,
+            "MONEYMRKT" => AccountType::MONEYMRKT,
+            "CREDITLINE" => AccountType::CREDITLINE,
+            _ => AccountType::OTHER(account_type),
+        }
+    }
+}
+
+impl From<AccountType> for String {
+    fn from(account_type: AccountType) -> String {
+        account_type.as_str().to_string()
+    }
 }
 
 #[derive(Serialize_repr, Deserialize_repr, PartialEq, Debug, Clone)]
@@ -32,7 +158,7 @@ pub enum Bool {
 
 impl std::fmt::Display for AccountType {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        write!(fmt, "{:?}", self)
+        write!(fmt, "{}", self.as_str())
     }
 }
 
@@ -77,40 +203,42 @@ pub struct Account {
     pub tags: HashMap<String, String>, //tags is a hash map of data for this record, such as balance amount
 }
 
+/// AccountWithBalance is an account row along with its balance, and commodity mnemonic.
+#[derive(Debug, Deserialize)]
+pub struct AccountWithBalance {
+    #[serde(flatten)]
+    pub account: Account, //account is the account record itself.
+    pub balance: f64,     //balance is the account's balance.
+    pub mnemonic: String, //mnemonic is the mnemonic of the account's commodity. Ex: USD
+}
+
 //_Fields: guid,name,account_type,commodity_guid,commodity_scu,non_std_scu,
 //         parent_guid,code,description,hidden,placeholder "
 pub const FIELDS : &str = "guid,name,account_type,commodity_guid,commodity_scu,non_std_scu,parent_guid,code,description,hidden,placeholder";
 
 /// save_new_and_delete_current saves a new account record, but first deletes the
 /// current one with the given account.guid.
-pub fn save_new_and_delete_current(account: Account) -> Result<bool, String> {
+pub fn save_new_and_delete_current(account: Account) -> Result<bool, MoneyManagerError> {
     //var db = new sqlContext.Database();
     //// Run a query without reading the results
     //db.run("CREATE TABLE test (col1, col2);");
     //// Insert two rows: (1,111) and (2,222)
     //db.run("INSERT INTO test VALUES (?,?), (?,?)", [1, 111, 2, 222]);
-    if crate::DATABASE.lock().unwrap().len() == 0 {
-        return Err("Please select a database in order to save this new database.".to_string());
-    }
-
     {
         //Delete the Account Record first
         let binding_object =
-            serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(&account.guid)])
-                .unwrap();
-        crate::DATABASE.lock().unwrap()[0]
-            .run_with_parameters("DELETE FROM Accounts WHERE guid=?", binding_object);
+            serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(&account.guid)])?;
+        dhu::execute("DELETE FROM Accounts WHERE guid=?", Some(binding_object))?;
 
         //Insert The Account Record
         let binding_object = serde_wasm_bindgen::to_value(&vec![
             &dhu::convert_guid_to_sqlite_string(&account.guid),
             &account.name,
-            &format!("{:?}", &account.account_type),
+            &account.account_type.as_str().to_string(),
             &account.non_std_scu.to_string(),
             &account.code,
             &account.description,
-        ])
-        .unwrap();
+        ])?;
 
         let mut commodity_guid = "NULL".to_string();
         let mut commodity_scu = "0".to_string();
@@ -127,7 +255,7 @@ pub fn save_new_and_delete_current(account: Account) -> Result<bool, String> {
             parent_guid = format!("'{}'", guid);
         }
 
-        crate::DATABASE.lock().unwrap()[0].run_with_parameters(&format!("
+        dhu::execute(&format!("
                 INSERT INTO Accounts(guid,name,account_type,commodity_guid,
                                     commodity_scu,non_std_scu,parent_guid,code,description,hidden,placeholder) 
                              VALUES ({guid},{name},{account_type},{commodity_guid},{commodity_scu},{non_std_scu},
@@ -155,7 +283,7 @@ pub fn save_new_and_delete_current(account: Account) -> Result<bool, String> {
                                             "0"
                                         }
                                     }
-                                ), binding_object);
+                                ), Some(binding_object))?;
 
         //js::log(&format!("Account GUID '{}'", &account.guid));
     }
@@ -163,143 +291,553 @@ pub fn save_new_and_delete_current(account: Account) -> Result<bool, String> {
     return Ok(true);
 }
 
+/// retrieve_all_accounts_with_balances retrieves all the accounts along with their
+/// balances, and commodity mnemonics. This includes transactions in the future.
+pub fn retrieve_all_accounts_with_balances() -> Result<Vec<AccountWithBalance>, MoneyManagerError> {
+    dhu::query_rows(&shu::load_accounts_with_balances(), None)
+}
+
 /// retrieve_account_for_account_type retrieves an account for a given account_type as a result.
-pub fn retrieve_account_for_account_type(account_type: String) -> Result<Account, String> {
-    if crate::DATABASE.lock().unwrap().len() == 0 {
-        panic!("Please select a database to refresh your accounts view.");
-    }
+pub fn retrieve_account_for_account_type(account_type: String) -> Result<Account, MoneyManagerError> {
+    let binding_object = serde_wasm_bindgen::to_value(&vec![account_type])?;
 
-    //Prepare a statement
-    let stmt = crate::DATABASE.lock().unwrap()[0].prepare("
+    dhu::query_first_row("
         SELECT * FROM accounts WHERE account_type=?
         AND (
             parent_guid IN (SELECT guid FROM accounts WHERE parent_guid IS NULL and name = 'Root Account') 
             OR 
             parent_guid IN (SELECT guid FROM accounts WHERE name = 'Liabilities') 
-        )");
+        )", Some(binding_object), "No account for this name!")
+}
 
-    let binding_object = serde_wasm_bindgen::to_value(&vec![account_type]).unwrap();
+/// retrieve_account_for_guid retrieves an account for a given guid as a result.
+pub fn retrieve_account_for_guid(account_guid: Uuid) -> Result<Account, MoneyManagerError> {
+    let now = chrono::Local::now().naive_local();
+    let binding_object = serde_wasm_bindgen::to_value(&vec![
+        &dhu::convert_date_to_string_format(now),
+        &dhu::convert_date_to_iso_string_format(now),
+        &dhu::convert_guid_to_sqlite_string(&account_guid),
+    ])?;
 
-    stmt.bind(binding_object.clone());
+    dhu::query_first_row(
+        &shu::load_account_with_balance_for_date_and_guid(),
+        Some(binding_object),
+        "No account for this guid",
+    )
+}
 
-    let mut accounts = Vec::new();
+/// load_account_for_guid loads an account for the given date.
+pub fn load_account_for_guid(account_guid: Uuid) -> Result<Account, MoneyManagerError> {
+    retrieve_account_for_guid(account_guid)
+}
 
-    while stmt.step() {
-        let row = stmt.getAsObject();
-        //js::log(&("Here is a row: ".to_owned() + &js::stringify(row.clone()).to_owned()));
+/// AccountName is the guid, name and type of an account, for use in select lists. The
+/// account_type is kept as a string so any GnuCash account type can be listed.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AccountName {
+    pub guid: Uuid,           //guid is the GUID for this account.
+    pub name: String,         //name is the name of the account.
+    pub account_type: String, //account_type is the account type. (Ex: 'PAYABLE' or 'INCOME')
+}
 
-        let account: Account = serde_wasm_bindgen::from_value(row.clone()).unwrap();
+/// retrieve_all_account_names retrieves the names of all accounts except the root and template
+/// accounts, ordered by name.
+pub fn retrieve_all_account_names() -> Result<Vec<AccountName>, MoneyManagerError> {
+    dhu::query_rows(
+        "SELECT guid,name,account_type FROM accounts
+         WHERE account_type <> 'ROOT' AND NOT(name = 'Template Root')
+         ORDER BY name",
+        None,
+    )
+}
 
-        accounts.push(account);
-    }
+/// AccountNode is an account's place in the account tree, for walking the full names of the
+/// accounts. The account_type is kept as a string so any GnuCash account type can be listed.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AccountNode {
+    pub guid: Uuid,                   //guid is the GUID for this account.
+    pub name: String,                 //name is the name of the account.
+    pub account_type: String,         //account_type is the account type. (Ex: 'BANK' or 'ROOT')
+    pub commodity_guid: Option<Uuid>, //commodity_guid is the commodity guid the account uses.
+    pub parent_guid: Option<Uuid>,    //parent_guid is the parent of this account's GUID.
+}
 
-    stmt.free();
+/// retrieve_all_account_nodes retrieves every account, including the root and template
+/// accounts, with the parent of each one.
+pub fn retrieve_all_account_nodes() -> Result<Vec<AccountNode>, MoneyManagerError> {
+    dhu::query_rows(
+        "SELECT guid,name,account_type,commodity_guid,parent_guid FROM accounts ORDER BY name",
+        None,
+    )
+}
 
-    if accounts.len() > 0 {
-        return Ok(accounts[0].clone());
-    } else {
-        return Err(String::from("No account for this name!"));
+/// account_full_names returns the full name of every account under the book's root account,
+/// with the names of its parents joined by ':'. (Ex: 'Expenses:Dining')
+pub fn account_full_names(nodes: &[AccountNode]) -> HashMap<Uuid, String> {
+    let nodes_by_guid: HashMap<Uuid, &AccountNode> =
+        nodes.iter().map(|node| (node.guid, node)).collect();
+    let mut full_names = HashMap::new();
+    for node in nodes.iter().filter(|node| node.account_type != "ROOT") {
+        let mut names = vec![node.name.as_str()];
+        let mut parent_guid = node.parent_guid;
+        while let Some(parent) = parent_guid.and_then(|guid| nodes_by_guid.get(&guid)) {
+            if parent.account_type == "ROOT" {
+                if parent.name != "Template Root" {
+                    names.reverse();
+                    full_names.insert(node.guid, names.join(":"));
+                }
+                break;
+            }
+            //A parent loop would never reach the root
+            if names.len() > nodes.len() {
+                break;
+            }
+            names.push(parent.name.as_str());
+            parent_guid = parent.parent_guid;
+        }
     }
+    full_names
 }
 
-/// retrieve_account_for_guid retrieves an account for a given guid as a result.
-pub fn retrieve_account_for_guid(account_guid: Uuid) -> Result<Account, String> {
-    if crate::DATABASE.lock().unwrap().len() == 0 {
-        panic!("Please select a database to refresh your accounts view.");
+/// descendant_guids returns the guids of every account under the given account.
+pub fn descendant_guids(nodes: &[AccountNode], account_guid: Uuid) -> Vec<Uuid> {
+    let mut descendants = Vec::new();
+    let mut parents = vec![account_guid];
+    while let Some(parent_guid) = parents.pop() {
+        for node in nodes.iter().filter(|node| node.parent_guid == Some(parent_guid)) {
+            if node.guid != account_guid && !descendants.contains(&node.guid) {
+                descendants.push(node.guid);
+                parents.push(node.guid);
+            }
+        }
     }
+    descendants
+}
 
-    //Prepare a statement
-    let stmt = crate::DATABASE.lock().unwrap()[0]
-        .prepare(&shu::load_account_with_balance_for_date_and_guid());
-
-    let binding_object = serde_wasm_bindgen::to_value(&vec![
-        &dhu::convert_date_to_string_format(chrono::Local::now().naive_local()),
-        &dhu::convert_guid_to_sqlite_string(&account_guid),
-    ])
-    .unwrap();
-
-    stmt.bind(binding_object.clone());
-
-    let mut accounts = Vec::new();
-
-    while stmt.step() {
-        let row = stmt.getAsObject();
-        //js::log(&("Here is a row: ".to_owned() + &js::stringify(row.clone()).to_owned()));
-
-        let account: Account = serde_wasm_bindgen::from_value(row.clone()).unwrap();
-
-        accounts.push(account);
+/// validate_account_move checks that the given account, given the account_type, can be moved
+/// with its sub-accounts under the given parent. Every account in the moved subtree has to be
+/// of the parent's kind, such as an ASSET, or of the account's kind when it goes under the root.
+pub fn validate_account_move(
+    nodes: &[AccountNode],
+    account_guid: Uuid,
+    account_type: &AccountType,
+    parent_guid: Uuid,
+) -> Result<(), MoneyManagerError> {
+    let Some(parent) = nodes.iter().find(|node| node.guid == parent_guid) else {
+        return Err(MoneyManagerError::Validation(
+            "The new parent account was not found.".to_string(),
+        ));
+    };
+    let descendants = descendant_guids(nodes, account_guid);
+    if parent_guid == account_guid || descendants.contains(&parent_guid) {
+        return Err(MoneyManagerError::Validation(
+            "An account can't be moved under itself, or one of its sub-accounts.".to_string(),
+        ));
     }
 
-    stmt.free();
-
-    if accounts.len() > 0 {
-        return Ok(accounts[0].clone());
+    let parent_type = AccountType::from(parent.account_type.clone());
+    let kind = if parent_type == AccountType::ROOT {
+        account_type.top_level_type()
     } else {
-        return Err(String::from("No account for this guid"));
+        parent_type.top_level_type()
+    };
+    if account_type.top_level_type() != kind {
+        return Err(MoneyManagerError::Validation(format!(
+            "A {account_type} account can't go under the {name} {parent_type} account.",
+            name = parent.name,
+        )));
+    }
+    for node in nodes.iter().filter(|node| descendants.contains(&node.guid)) {
+        let node_type = AccountType::from(node.account_type.clone());
+        if node_type.top_level_type() != kind {
+            return Err(MoneyManagerError::Validation(format!(
+                "The sub-account {name} is a {node_type} account, which can't go under the \
+                 {parent_name} {parent_type} account.",
+                name = node.name,
+                parent_name = parent.name,
+            )));
+        }
     }
+    Ok(())
 }
 
-/// load_account_for_guid loads an account for the given date.
-pub fn load_account_for_guid(account_guid: Uuid) -> Account {
-    if crate::DATABASE.lock().unwrap().len() == 0 {
-        panic!("Please select a database to refresh your accounts view.");
+/// validate_account_merge checks that the source account can be merged into the target
+/// account. Both have to be the same kind of account, in the same commodity, and the target
+/// can't be under the source, since the source's sub-accounts move to the target.
+pub fn validate_account_merge(
+    nodes: &[AccountNode],
+    source_guid: Uuid,
+    target_guid: Uuid,
+) -> Result<(), MoneyManagerError> {
+    let find_node = |guid: Uuid| {
+        nodes.iter().find(|node| node.guid == guid).ok_or_else(|| {
+            MoneyManagerError::Validation("The account to merge was not found.".to_string())
+        })
+    };
+    let source = find_node(source_guid)?;
+    let target = find_node(target_guid)?;
+    let source_type = AccountType::from(source.account_type.clone());
+    let target_type = AccountType::from(target.account_type.clone());
+
+    if source_guid == target_guid {
+        return Err(MoneyManagerError::Validation(
+            "An account can't be merged into itself.".to_string(),
+        ));
+    }
+    if source_type == AccountType::ROOT || target_type == AccountType::ROOT {
+        return Err(MoneyManagerError::Validation(
+            "The root account can't be merged.".to_string(),
+        ));
+    }
+    if descendant_guids(nodes, source_guid).contains(&target_guid) {
+        return Err(MoneyManagerError::Validation(format!(
+            "{} can't be merged into its own sub-account {}.",
+            source.name, target.name
+        )));
+    }
+    if source_type.top_level_type() != target_type.top_level_type() {
+        return Err(MoneyManagerError::Validation(format!(
+            "The {} {source_type} account can't be merged into the {} {target_type} account.",
+            source.name, target.name
+        )));
     }
+    if source.commodity_guid != target.commodity_guid {
+        return Err(MoneyManagerError::Validation(format!(
+            "{} and {} don't use the same commodity, so they can't be merged.",
+            source.name, target.name
+        )));
+    }
+    Ok(())
+}
 
-    //Prepare a statement
-    let stmt = crate::DATABASE.lock().unwrap()[0]
-        .prepare(&shu::load_account_with_balance_for_date_and_guid());
+/// merge_accounts moves the splits, lots, sub-accounts, and everything else that refers to the
+/// source account over to the target account, and then deletes the source account. Either all
+/// of it is done, or none of it is. An account with transactions in the closed period can't be
+/// merged.
+pub fn merge_accounts(source_guid: Uuid, target_guid: Uuid) -> Result<(), MoneyManagerError> {
+    validate_account_merge(&retrieve_all_account_nodes()?, source_guid, target_guid)?;
+    transactions_manager::check_accounts_are_open(&[source_guid])?;
 
-    let binding_object = serde_wasm_bindgen::to_value(&vec![
-        &dhu::convert_date_to_string_format(chrono::Local::now().naive_local()),
-        &dhu::convert_guid_to_sqlite_string(&account_guid),
-    ])
-    .unwrap();
+    dhu::in_transaction(|| merge_account_rows(source_guid, target_guid))
+}
 
-    stmt.bind(binding_object.clone());
+/// merge_account_rows points every row that refers to the source account at the target account,
+/// and deletes the source account. It should be run inside of a database transaction.
+fn merge_account_rows(source_guid: Uuid, target_guid: Uuid) -> Result<(), MoneyManagerError> {
+    let source = dhu::convert_guid_to_sqlite_string(&source_guid);
+    let target = dhu::convert_guid_to_sqlite_string(&target_guid);
+    let guids = vec![source.clone(), target.clone()];
+
+    let statements = vec![
+        ("UPDATE splits SET account_guid=?2 WHERE account_guid=?1", guids.clone()),
+        ("UPDATE lots SET account_guid=?2 WHERE account_guid=?1", guids.clone()),
+        ("UPDATE accounts SET parent_guid=?2 WHERE parent_guid=?1", guids.clone()),
+        ("UPDATE slots SET guid_val=?2 WHERE guid_val=?1", guids.clone()),
+        //The target keeps its own slots, like its notes, over the source's
+        (
+            "DELETE FROM slots WHERE obj_guid=?1
+             AND name IN (SELECT name FROM slots WHERE obj_guid=?2)",
+            guids.clone(),
+        ),
+        ("UPDATE slots SET obj_guid=?2 WHERE obj_guid=?1", guids.clone()),
+        //Rules, and templates are saved as JSON, which writes guids with dashes
+        (
+            "UPDATE slots SET string_val=REPLACE(string_val,?1,?2) WHERE name IN (?3,?4)",
+            vec![
+                source_guid.to_string(),
+                target_guid.to_string(),
+                slots_manager::SLOT_NAME_CATEGORIZATION_RULE.to_string(),
+                slots_manager::SLOT_NAME_TRANSACTION_TEMPLATE.to_string(),
+            ],
+        ),
+        //Budget amounts for a period both accounts have are added together
+        (
+            "UPDATE budget_amounts AS target
+             SET amount_num=target.amount_num*source.amount_denom
+                            + source.amount_num*target.amount_denom,
+                 amount_denom=target.amount_denom*source.amount_denom
+             FROM budget_amounts AS source
+             WHERE target.account_guid=?2 AND source.account_guid=?1
+             AND source.budget_guid=target.budget_guid AND source.period_num=target.period_num",
+            guids.clone(),
+        ),
+        (
+            "DELETE FROM budget_amounts WHERE account_guid=?1
+             AND EXISTS (SELECT 1 FROM budget_amounts AS target
+                         WHERE target.account_guid=?2
+                         AND target.budget_guid=budget_amounts.budget_guid
+                         AND target.period_num=budget_amounts.period_num)",
+            guids.clone(),
+        ),
+        ("UPDATE budget_amounts SET account_guid=?2 WHERE account_guid=?1", guids.clone()),
+        ("UPDATE taxtable_entries SET account=?2 WHERE account=?1", guids.clone()),
+        ("UPDATE entries SET i_acct=?2 WHERE i_acct=?1", guids.clone()),
+        ("UPDATE entries SET b_acct=?2 WHERE b_acct=?1", guids.clone()),
+        ("UPDATE invoices SET post_acc=?2 WHERE post_acc=?1", guids.clone()),
+        ("DELETE FROM accounts WHERE guid=?1", vec![source]),
+    ];
+    for (sql, values) in statements {
+        dhu::execute(sql, Some(serde_wasm_bindgen::to_value(&values)?))?;
+    }
+    Ok(())
+}
 
-    let mut accounts = Vec::new();
+/// AccountDeletion is what happens to the transactions, and everything else in an account that
+/// is deleted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccountDeletion {
+    Delete,       //Delete deletes the account's transactions, and sub-accounts along with it.
+    MoveTo(Uuid), //MoveTo moves all of it to the given account, the same as merging into it.
+}
 
-    while stmt.step() {
-        let row = stmt.getAsObject();
-        //js::log(&("Here is a row: ".to_owned() + &js::stringify(row.clone()).to_owned()));
+/// AccountReferences counts what refers to an account, and its sub-accounts, for deleting it.
+#[derive(Debug, Deserialize, Default, Clone, PartialEq)]
+pub struct AccountReferences {
+    #[serde(default)]
+    pub sub_accounts: i64, //sub_accounts is the number of accounts under the account.
+    pub splits: i64,       //splits is the number of splits in the accounts.
+    pub reconciled_splits: i64, //reconciled_splits is the number of reconciled splits, of any account, in the transactions of the accounts.
+    pub budgets: i64, //budgets is the number of budgets with amounts for the accounts.
+    #[serde(default)]
+    pub scheduled_transactions: i64, //scheduled_transactions is the number of scheduled transactions with splits for the accounts.
+    pub rules_and_templates: i64, //rules_and_templates is the number of categorization rules, and transaction templates using the accounts.
+    pub business_records: i64, //business_records is the number of tax table entries, invoice, and bill lines, and posted invoices using the accounts.
+}
 
-        let account: Account = serde_wasm_bindgen::from_value(row.clone()).unwrap();
+impl AccountReferences {
+    /// describe lists what refers to the account with the given name.
+    pub fn describe(&self, account_name: &str) -> String {
+        format!(
+            "{account_name} has {splits} split(s), {reconciled_splits} reconciled split(s) in \
+             their transactions, {sub_accounts} sub-account(s), {budgets} budget(s), \
+             {scheduled_transactions} scheduled transaction(s), {rules_and_templates} rule(s) \
+             and template(s), and {business_records} tax table, invoice, or bill record(s).",
+            splits = self.splits,
+            reconciled_splits = self.reconciled_splits,
+            sub_accounts = self.sub_accounts,
+            budgets = self.budgets,
+            scheduled_transactions = self.scheduled_transactions,
+            rules_and_templates = self.rules_and_templates,
+            business_records = self.business_records,
+        )
+    }
 
-        accounts.push(account);
+    /// validate_deletion checks that the account can be deleted the given way. Deleting the
+    /// transactions would lose reconciled splits, and break business records, so they have to
+    /// be moved to another account instead.
+    pub fn validate_deletion(&self, deletion: &AccountDeletion) -> Result<(), MoneyManagerError> {
+        if *deletion != AccountDeletion::Delete {
+            return Ok(());
+        }
+        if self.reconciled_splits > 0 {
+            return Err(MoneyManagerError::Validation(format!(
+                "Deleting the transactions would lose {} reconciled split(s). Please move them \
+                 to another account instead.",
+                self.reconciled_splits
+            )));
+        }
+        if self.business_records > 0 {
+            return Err(MoneyManagerError::Validation(format!(
+                "The account is still used by {} tax table, invoice, or bill record(s). Please \
+                 move them to another account instead.",
+                self.business_records
+            )));
+        }
+        Ok(())
     }
+}
 
-    stmt.free();
+/// retrieve_account_references counts what refers to the account with the given guid, and its
+/// sub-accounts.
+pub fn retrieve_account_references(
+    account_guid: Uuid,
+) -> Result<AccountReferences, MoneyManagerError> {
+    let descendants = descendant_guids(&retrieve_all_account_nodes()?, account_guid);
+    let guids: Vec<Uuid> = std::iter::once(account_guid)
+        .chain(descendants.iter().copied())
+        .collect();
 
-    return accounts[0].clone();
-    
+    let binding_object = serde_wasm_bindgen::to_value(&vec![
+        serde_json::to_string(
+            &guids.iter().map(dhu::convert_guid_to_sqlite_string).collect::<Vec<String>>(),
+        )?,
+        slots_manager::SLOT_NAME_CATEGORIZATION_RULE.to_string(),
+        slots_manager::SLOT_NAME_TRANSACTION_TEMPLATE.to_string(),
+        serde_json::to_string(&guids)?,
+    ])?;
+    let mut references: AccountReferences = dhu::query_first_row(
+        &shu::load_account_references(),
+        Some(binding_object),
+        "The account's references couldn't be counted.",
+    )?;
+    references.sub_accounts = descendants.len() as i64;
+    references.scheduled_transactions =
+        scheduled_transactions_for_accounts(&retrieve_scheduled_transaction_slots()?, &guids).len()
+            as i64;
+    Ok(references)
 }
 
-pub fn load_all_accounts_except_root_and_template_from_memory() -> Vec<Account> {
-    
-    if crate::DATABASE.lock().unwrap().len() == 0 {
-        panic!("Please select a database to refresh your accounts view.");
+/// delete_account deletes the account with the given guid. Its transactions, and everything
+/// else in it are either moved to another account, or deleted along with its sub-accounts.
+/// Either all of it is done, or none of it is. An account with transactions in the closed period
+/// can't be deleted.
+pub fn delete_account(
+    account_guid: Uuid,
+    deletion: AccountDeletion,
+) -> Result<(), MoneyManagerError> {
+    if let AccountDeletion::MoveTo(target_guid) = deletion {
+        return merge_accounts(account_guid, target_guid);
     }
+    let nodes = retrieve_all_account_nodes()?;
+    match nodes.iter().find(|node| node.guid == account_guid) {
+        Some(node) if node.account_type == "ROOT" => {
+            return Err(MoneyManagerError::Validation(
+                "The root account can't be deleted.".to_string(),
+            ));
+        }
+        Some(_) => {}
+        None => {
+            return Err(MoneyManagerError::Validation(
+                "The account to delete was not found.".to_string(),
+            ));
+        }
+    }
+    retrieve_account_references(account_guid)?.validate_deletion(&deletion)?;
+
+    let guids: Vec<Uuid> = std::iter::once(account_guid)
+        .chain(descendant_guids(&nodes, account_guid))
+        .collect();
+    transactions_manager::check_accounts_are_open(&guids)?;
+    let scheduled_transactions =
+        scheduled_transactions_for_accounts(&retrieve_scheduled_transaction_slots()?, &guids);
+    dhu::in_transaction(|| {
+        scheduled_transactions
+            .iter()
+            .try_for_each(delete_scheduled_transaction_rows)?;
+        guids.iter().try_for_each(|guid| delete_account_rows(*guid))
+    })
+}
 
-    //Prepare a statement
-    let stmt: dhu::Statement = crate::DATABASE.lock().unwrap()[0]
-        .prepare(&shu::load_all_accounts_except_root_and_template());
-    stmt.getAsObject();
+/// ScheduledTransactionReference is a scheduled transaction with a split for an account.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct ScheduledTransactionReference {
+    pub guid: Uuid,              //guid is the GUID of the scheduled transaction.
+    pub template_act_guid: Uuid, //template_act_guid is the template account holding its splits.
+}
 
-    let mut accounts = Vec::new();
+/// ScheduledTransactionSlot is a slot of a scheduled transaction's template split, or one inside
+/// of the split's frame.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct ScheduledTransactionSlot {
+    pub guid: Uuid,              //guid is the GUID of the scheduled transaction.
+    pub template_act_guid: Uuid, //template_act_guid is the template account holding its splits.
+    pub obj_guid: Uuid,          //obj_guid is the template split, or the frame the slot is in.
+    pub name: String,            //name is the full path of the slot. (Ex: 'sched-xaction/account')
+    pub guid_val: Option<Uuid>,  //guid_val is the value of a guid slot, or a frame's guid.
+}
 
-    while stmt.step() {
-        let row = stmt.getAsObject();
-        //js::log(&("Here is a row: ".to_owned() + &js::stringify(row.clone()).to_owned()));
+/// retrieve_scheduled_transaction_slots retrieves the slots of every scheduled transaction's
+/// template splits, and the slots inside of their frames.
+fn retrieve_scheduled_transaction_slots() -> Result<Vec<ScheduledTransactionSlot>, MoneyManagerError> {
+    let binding_object =
+        serde_wasm_bindgen::to_value(&vec![slots_manager::SLOT_NAME_SCHEDULED_TRANSACTION])?;
+    dhu::query_rows(&shu::load_scheduled_transaction_slots(), Some(binding_object))
+}
 
-        let account: Account = serde_wasm_bindgen::from_value(row.clone()).unwrap();
+/// scheduled_transactions_for_accounts returns the scheduled transactions with a template split
+/// for any of the given accounts. A template split names its real account in the
+/// 'sched-xaction/account' slot, inside of the frame the split's 'sched-xaction' slot points to.
+pub fn scheduled_transactions_for_accounts(
+    slots: &[ScheduledTransactionSlot],
+    account_guids: &[Uuid],
+) -> Vec<ScheduledTransactionReference> {
+    let mut scheduled_transactions: Vec<ScheduledTransactionReference> = Vec::new();
+    for account_slot in slots.iter().filter(|slot| {
+        slot.name == slots_manager::SLOT_NAME_SCHEDULED_TRANSACTION_ACCOUNT
+            && slot.guid_val.is_some_and(|guid_val| account_guids.contains(&guid_val))
+    }) {
+        let in_frame = slots.iter().any(|frame| {
+            frame.guid == account_slot.guid
+                && frame.name == slots_manager::SLOT_NAME_SCHEDULED_TRANSACTION
+                && frame.guid_val == Some(account_slot.obj_guid)
+        });
+        let reference = ScheduledTransactionReference {
+            guid: account_slot.guid,
+            template_act_guid: account_slot.template_act_guid,
+        };
+        if in_frame && !scheduled_transactions.contains(&reference) {
+            scheduled_transactions.push(reference);
+        }
+    }
+    scheduled_transactions
+}
 
-        accounts.push(account);
+/// delete_scheduled_transaction_rows deletes the given scheduled transaction, its template
+/// account, and its template transactions. It should be run inside of a database transaction.
+fn delete_scheduled_transaction_rows(
+    scheduled_transaction: &ScheduledTransactionReference,
+) -> Result<(), MoneyManagerError> {
+    let template_act_guid =
+        dhu::convert_guid_to_sqlite_string(&scheduled_transaction.template_act_guid);
+    delete_account_transaction_rows(&template_act_guid)?;
+    for sql in [
+        "DELETE FROM recurrences WHERE obj_guid=?",
+        "DELETE FROM slots WHERE obj_guid=?",
+        "DELETE FROM schedxactions WHERE guid=?",
+    ] {
+        let binding_object = serde_wasm_bindgen::to_value(&vec![
+            dhu::convert_guid_to_sqlite_string(&scheduled_transaction.guid),
+        ])?;
+        dhu::execute(sql, Some(binding_object))?;
     }
+    Ok(())
+}
+
+/// delete_account_rows deletes the account with the given guid, its transactions, and the
+/// budget amounts, lots, slots, rules, and templates for it. It should be run inside of a
+/// database transaction.
+fn delete_account_rows(account_guid: Uuid) -> Result<(), MoneyManagerError> {
+    let guid = dhu::convert_guid_to_sqlite_string(&account_guid);
+    delete_account_transaction_rows(&guid)?;
+    let binding_object = serde_wasm_bindgen::to_value(&vec![
+        account_guid.to_string(),
+        slots_manager::SLOT_NAME_CATEGORIZATION_RULE.to_string(),
+        slots_manager::SLOT_NAME_TRANSACTION_TEMPLATE.to_string(),
+    ])?;
+    dhu::execute(
+        "DELETE FROM slots WHERE name IN (?2,?3) AND instr(string_val,?1) > 0",
+        Some(binding_object),
+    )?;
+    Ok(())
+}
 
-    stmt.free();
+/// delete_account_transaction_rows deletes the account with the given sqlite guid, its
+/// transactions, including the splits of other accounts in them, and its budget amounts, lots,
+/// and slots. It should be run inside of a database transaction.
+fn delete_account_transaction_rows(account_guid: &str) -> Result<(), MoneyManagerError> {
+    for sql in [
+        //The slots inside of the splits' frames go first, while the frames can still be found
+        "DELETE FROM slots WHERE obj_guid IN (SELECT guid_val FROM slots WHERE slot_type=9
+            AND obj_guid IN (SELECT guid FROM splits WHERE tx_guid IN
+            (SELECT tx_guid FROM splits WHERE account_guid=?1)))",
+        "DELETE FROM slots WHERE obj_guid IN (SELECT guid FROM splits WHERE tx_guid IN
+            (SELECT tx_guid FROM splits WHERE account_guid=?1))",
+        "DELETE FROM slots WHERE obj_guid IN (SELECT tx_guid FROM splits WHERE account_guid=?1)",
+        "DELETE FROM transactions WHERE guid IN (SELECT tx_guid FROM splits WHERE account_guid=?1)",
+        "DELETE FROM splits WHERE tx_guid IN (SELECT tx_guid FROM splits WHERE account_guid=?1)",
+        "DELETE FROM lots WHERE account_guid=?1",
+        "DELETE FROM budget_amounts WHERE account_guid=?1",
+        "DELETE FROM slots WHERE obj_guid=?1 OR guid_val=?1",
+        "DELETE FROM accounts WHERE guid=?1",
+    ] {
+        let binding_object = serde_wasm_bindgen::to_value(&vec![account_guid])?;
+        dhu::execute(sql, Some(binding_object))?;
+    }
+    Ok(())
+}
 
-    return accounts;
+pub fn load_all_accounts_except_root_and_template_from_memory() -> Result<Vec<Account>, MoneyManagerError> {
+    dhu::query_rows(&shu::load_all_accounts_except_root_and_template(), None)
 }
 
 // ///
@@ -835,3 +1373,150 @@ pub fn load_all_accounts_except_root_and_template_from_memory() -> Vec<Account>
 //     Ok(true)
 
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn account_type_reads_every_gnucash_type_and_keeps_unknown_ones() {
+        for account_type in AccountType::SELECTABLE {
+            assert_eq!(AccountType::from(account_type.to_string()), account_type);
+        }
+        assert_eq!(AccountType::from("MONEYMRKT".to_string()), AccountType::MONEYMRKT);
+        assert_eq!(
+            AccountType::from("NONE".to_string()),
+            AccountType::OTHER("NONE".to_string())
+        );
+        assert_eq!(String::from(AccountType::OTHER("NONE".to_string())), "NONE");
+
+        assert_eq!(AccountType::STOCK.top_level_type(), AccountType::ASSET);
+        assert_eq!(AccountType::PAYABLE.top_level_type(), AccountType::LIABILITY);
+        assert_eq!(AccountType::INCOME.top_level_type(), AccountType::INCOME);
+    }
+
+    #[test]
+    fn balance_sign_shows_credits_as_positive_amounts() {
+        for account_type in [
+            AccountType::CREDIT,
+            AccountType::LIABILITY,
+            AccountType::PAYABLE,
+            AccountType::CREDITLINE,
+            AccountType::INCOME,
+            AccountType::EQUITY,
+            AccountType::TRADING,
+        ] {
+            assert_eq!(account_type.balance_sign(), -1.0, "{}", account_type);
+        }
+        for account_type in [
+            AccountType::ASSET,
+            AccountType::BANK,
+            AccountType::RECEIVABLE,
+            AccountType::STOCK,
+            AccountType::EXPENSE,
+            AccountType::ROOT,
+            AccountType::OTHER("NONE".to_string()),
+        ] {
+            assert_eq!(account_type.balance_sign(), 1.0, "{}", account_type);
+        }
+    }
+
+    fn node(guid: u128, name: &str, account_type: &str, parent_guid: Option<u128>) -> AccountNode {
+        AccountNode {
+            guid: Uuid::from_u128(guid),
+            name: name.to_string(),
+            account_type: account_type.to_string(),
+            commodity_guid: Some(Uuid::from_u128(100)),
+            parent_guid: parent_guid.map(Uuid::from_u128),
+        }
+    }
+
+    #[test]
+    fn accounts_move_and_merge_only_within_their_kind() {
+        let mut nodes = vec![
+            node(1, "Root Account", "ROOT", None),
+            node(2, "Assets", "ASSET", Some(1)),
+            node(3, "Checking", "BANK", Some(2)),
+            node(4, "Expenses", "EXPENSE", Some(1)),
+            node(5, "Dining", "EXPENSE", Some(4)),
+            node(6, "Restaurants", "EXPENSE", Some(4)),
+            node(7, "Fast Food", "EXPENSE", Some(5)),
+            node(8, "Template Root", "ROOT", None),
+            node(9, "Template", "BANK", Some(8)),
+        ];
+        let guid = Uuid::from_u128;
+
+        let full_names = account_full_names(&nodes);
+        assert_eq!(full_names[&guid(7)], "Expenses:Dining:Fast Food");
+        assert!(!full_names.contains_key(&guid(9)));
+        assert_eq!(descendant_guids(&nodes, guid(4)).len(), 3);
+
+        assert!(validate_account_move(&nodes, guid(5), &AccountType::EXPENSE, guid(6)).is_ok());
+        assert!(validate_account_move(&nodes, guid(5), &AccountType::EXPENSE, guid(1)).is_ok());
+        assert!(validate_account_move(&nodes, guid(5), &AccountType::EXPENSE, guid(7)).is_err());
+        assert!(validate_account_move(&nodes, guid(5), &AccountType::EXPENSE, guid(2)).is_err());
+        assert!(validate_account_move(&nodes, guid(3), &AccountType::CASH, guid(2)).is_ok());
+        assert!(validate_account_move(&nodes, guid(4), &AccountType::ASSET, guid(1)).is_err());
+
+        assert!(validate_account_merge(&nodes, guid(5), guid(6)).is_ok());
+        assert!(validate_account_merge(&nodes, guid(5), guid(5)).is_err());
+        assert!(validate_account_merge(&nodes, guid(5), guid(7)).is_err());
+        assert!(validate_account_merge(&nodes, guid(5), guid(3)).is_err());
+        nodes[5].commodity_guid = Some(guid(101));
+        assert!(validate_account_merge(&nodes, guid(5), guid(6)).is_err());
+    }
+
+    #[test]
+    fn scheduled_transactions_are_found_through_their_frames() {
+        let scheduled_transaction = Uuid::from_u128(10);
+        let template_account = Uuid::from_u128(11);
+        let template_split = Uuid::from_u128(12);
+        let frame = Uuid::from_u128(13);
+        let checking = Uuid::from_u128(3);
+        let dining = Uuid::from_u128(5);
+        let slot = |obj_guid: Uuid, name: &str, guid_val: Option<Uuid>| ScheduledTransactionSlot {
+            guid: scheduled_transaction,
+            template_act_guid: template_account,
+            obj_guid,
+            name: name.to_string(),
+            guid_val,
+        };
+        //The frame on the template split, and the account slot inside of it, as GnuCash saves them
+        let slots = vec![
+            slot(template_split, "sched-xaction", Some(frame)),
+            slot(frame, "sched-xaction/account", Some(checking)),
+            slot(frame, "sched-xaction/credit-formula", None),
+            slot(template_split, "sched-xaction/account", Some(dining)),
+        ];
+
+        assert_eq!(
+            scheduled_transactions_for_accounts(&slots, &[checking, dining]),
+            vec![ScheduledTransactionReference {
+                guid: scheduled_transaction,
+                template_act_guid: template_account,
+            }]
+        );
+        //An account slot that isn't inside of the split's frame doesn't count
+        assert!(scheduled_transactions_for_accounts(&slots, &[dining]).is_empty());
+        assert!(scheduled_transactions_for_accounts(&slots[1..], &[checking]).is_empty());
+    }
+
+    #[test]
+    fn deleting_an_account_refuses_to_lose_reconciled_splits() {
+        let mut references = AccountReferences {
+            splits: 4,
+            ..Default::default()
+        };
+        let move_to = AccountDeletion::MoveTo(Uuid::from_u128(6));
+        assert!(references.validate_deletion(&AccountDeletion::Delete).is_ok());
+        assert!(references.describe("Dining").starts_with("Dining has 4 split(s), 0 reconciled"));
+
+        references.reconciled_splits = 1;
+        assert!(references.validate_deletion(&AccountDeletion::Delete).is_err());
+        assert!(references.validate_deletion(&move_to).is_ok());
+
+        references.reconciled_splits = 0;
+        references.business_records = 1;
+        assert!(references.validate_deletion(&AccountDeletion::Delete).is_err());
+    }
+}
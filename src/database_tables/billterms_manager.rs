@@ -0,0 +1,107 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
+
+/// BILLTERM_TYPE_PROXIMO is the type of a bill term that is due on a day of a following month.
+/// Any other type is due a number of days after the date posted.
+pub const BILLTERM_TYPE_PROXIMO: &str = "GNC_TERM_TYPE_PROXIMO";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BillTerm {
+    pub guid: Uuid,               //guid is the GUID for this bill term.
+    pub name: String,             //name is the name of the bill term. (Ex: 'Net 30')
+    pub description: String,      //description is the description of the bill term.
+    pub refcount: i64,            //refcount is the number of customers, vendors and invoices using this term.
+    pub invisible: i64,           //invisible is 1 if this bill term is hidden from selection.
+    pub parent: Option<Uuid>,     //parent is the bill term this one was copied from, or null.
+    #[serde(rename = "type")]
+    pub term_type: String, //term_type is 'GNC_TERM_TYPE_DAYS' or 'GNC_TERM_TYPE_PROXIMO'.
+    pub duedays: Option<i64>,     //duedays is the days until due, or the day of the month it is due for proximo.
+    pub discountdays: Option<i64>, //discountdays is the days a discount is available, or the day of the month.
+    pub discount_num: Option<i64>, //discount_num is the numerator of the discount percent.
+    pub discount_denom: Option<i64>, //discount_denom is the denominator of the discount percent.
+    pub cutoff: Option<i64>,      //cutoff is the day of the month after which proximo terms move to the next month.
+}
+
+pub const FIELDS: &str = "guid,name,description,refcount,invisible,parent,type,duedays,discountdays,discount_num,discount_denom,cutoff";
+
+impl BillTerm {
+    /// due_date returns the date an invoice posted on the given date is due.
+    pub fn due_date(&self, date_posted: NaiveDate) -> NaiveDate {
+        let duedays = self.duedays.unwrap_or(0);
+
+        if self.term_type != BILLTERM_TYPE_PROXIMO {
+            return date_posted + Duration::days(duedays);
+        }
+
+        //Proximo terms are due on a day of next month, or the month after when past the cutoff
+        let cutoff = self.cutoff.unwrap_or(0);
+        let mut months_ahead = 1;
+        if cutoff > 0 && date_posted.day() as i64 > cutoff {
+            months_ahead += 1;
+        }
+        let months = date_posted.year() * 12 + date_posted.month0() as i32 + months_ahead;
+        let (year, month) = (months / 12, months as u32 % 12 + 1);
+
+        //Don't go past the end of a short month
+        let mut day = duedays.clamp(1, 31) as u32;
+        loop {
+            if let Some(due_date) = NaiveDate::from_ymd_opt(year, month, day) {
+                return due_date;
+            }
+            day -= 1;
+        }
+    }
+}
+
+/// retrieve_all_billterms retrieves all the bill terms, ordered by name.
+pub fn retrieve_all_billterms() -> Result<Vec<BillTerm>, MoneyManagerError> {
+    dhu::query_rows(&format!("SELECT {} FROM billterms ORDER BY name", FIELDS), None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn billterm(term_type: &str, duedays: i64, cutoff: i64) -> BillTerm {
+        BillTerm {
+            guid: Uuid::nil(),
+            name: String::new(),
+            description: String::new(),
+            refcount: 0,
+            invisible: 0,
+            parent: None,
+            term_type: term_type.to_string(),
+            duedays: Some(duedays),
+            discountdays: None,
+            discount_num: None,
+            discount_denom: None,
+            cutoff: Some(cutoff),
+        }
+    }
+
+    #[test]
+    fn due_date_adds_days() {
+        let date_posted = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        assert_eq!(
+            billterm("GNC_TERM_TYPE_DAYS", 30, 0).due_date(date_posted),
+            NaiveDate::from_ymd_opt(2024, 2, 14).unwrap()
+        );
+    }
+
+    #[test]
+    fn due_date_uses_next_month_for_proximo() {
+        let proximo = billterm(BILLTERM_TYPE_PROXIMO, 31, 25);
+        assert_eq!(
+            proximo.due_date(NaiveDate::from_ymd_opt(2024, 1, 10).unwrap()),
+            NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()
+        );
+        assert_eq!(
+            proximo.due_date(NaiveDate::from_ymd_opt(2024, 11, 28).unwrap()),
+            NaiveDate::from_ymd_opt(2025, 1, 31).unwrap()
+        );
+    }
+}
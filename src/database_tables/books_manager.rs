@@ -1,7 +1,11 @@
+use serde::Deserialize;
 use uuid::Uuid;
 
+use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
+
 #[allow(dead_code)]
-#[derive(Debug)]
+#[derive(Debug, Deserialize)]
 pub struct Book {
     pub guid: Uuid, //guid is the GUID for this book.
     pub root_account_guid: Uuid, //rootAccountGuid is the root account GUID for this book.
@@ -16,6 +20,15 @@ pub fn _fields() -> String {
          )
 } 
 
+/// retrieve_book retrieves the book record, which holds the guids of the root accounts.
+pub fn retrieve_book() -> Result<Book, MoneyManagerError> {
+    dhu::query_first_row(
+        &format!("SELECT {} FROM books", _fields()),
+        None,
+        "This book doesn't have a books record, so its root account can't be found.",
+    )
+}
+
 // ///retrieve_all_books retrieves all the Book records.
 // pub fn retrieve_all_books(file_path : &str) -> Result<Vec<Book>> {
 //     //Attempt to open the file from the given path to perform this operation
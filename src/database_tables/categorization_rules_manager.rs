@@ -0,0 +1,222 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::database_tables::slots_manager;
+use crate::utility::error_helper_utility::MoneyManagerError;
+
+/// RuleMatchType is how a categorization rule's pattern is matched against a description.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleMatchType {
+    Contains, //Contains matches descriptions holding the pattern, ignoring case.
+    Regex,    //Regex matches descriptions where the pattern, as a regular expression, finds a match.
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CategorizationRule {
+    pub guid: Uuid,                  //guid is the GUID for this rule, and the obj_guid of its slot.
+    pub name: String,                //name is the name of the rule. (Ex: 'Groceries')
+    pub priority: i64,               //priority is the order rules are tried in, lowest first.
+    pub match_type: RuleMatchType,   //match_type is how the pattern is matched.
+    pub pattern: String,             //pattern is what the description is matched against. (Ex: 'KROGER')
+    pub minimum_amount: Option<f64>, //minimum_amount is the smallest amount matched, or None for no minimum.
+    pub maximum_amount: Option<f64>, //maximum_amount is the largest amount matched, or None for no maximum.
+    pub account_guid: Uuid,          //account_guid is the category account of the matched transactions.
+    pub description: String, //description rewrites the matched description, with $1 for regex groups, or is blank to keep it.
+}
+
+impl CategorizationRule {
+    /// matches checks whether this rule fires for the given description, and amount. The amount
+    /// is compared without its sign, and a rule with an amount range doesn't fire without one.
+    pub fn matches(&self, description: &str, amount: Option<f64>) -> bool {
+        let pattern_matches = match self.match_type {
+            RuleMatchType::Contains => {
+                !self.pattern.trim().is_empty()
+                    && description
+                        .to_lowercase()
+                        .contains(&self.pattern.trim().to_lowercase())
+            }
+            RuleMatchType::Regex => Regex::new(&self.pattern)
+                .map(|regex| regex.is_match(description))
+                .unwrap_or(false),
+        };
+        if !pattern_matches {
+            return false;
+        }
+
+        if self.minimum_amount.is_none() && self.maximum_amount.is_none() {
+            return true;
+        }
+        match amount.map(f64::abs) {
+            Some(amount) => {
+                self.minimum_amount.is_none_or(|minimum| amount >= minimum)
+                    && self.maximum_amount.is_none_or(|maximum| amount <= maximum)
+            }
+            None => false,
+        }
+    }
+
+    /// rewrite_description returns the description this rule gives the given description. A
+    /// regex rule can use the groups it matched, like '$1'.
+    pub fn rewrite_description(&self, description: &str) -> String {
+        if self.description.trim().is_empty() {
+            return description.to_string();
+        }
+        if self.match_type == RuleMatchType::Regex
+            && let Ok(regex) = Regex::new(&self.pattern)
+            && let Some(captures) = regex.captures(description)
+        {
+            let mut rewritten = String::new();
+            captures.expand(&self.description, &mut rewritten);
+            return rewritten;
+        }
+        self.description.clone()
+    }
+}
+
+/// find_matching_rule finds the first of the given rules, in order, that fires for the given
+/// description, and amount.
+pub fn find_matching_rule<'a>(
+    rules: &'a [CategorizationRule],
+    description: &str,
+    amount: Option<f64>,
+) -> Option<&'a CategorizationRule> {
+    rules.iter().find(|rule| rule.matches(description, amount))
+}
+
+/// validate_categorization_rule checks the given rule has a name, a pattern that can be
+/// matched, and an amount range that isn't backwards.
+pub fn validate_categorization_rule(rule: &CategorizationRule) -> Result<(), MoneyManagerError> {
+    if rule.name.trim().is_empty() {
+        return Err(MoneyManagerError::Validation(
+            "Please enter a name for the rule.".to_string(),
+        ));
+    }
+    if rule.pattern.trim().is_empty() {
+        return Err(MoneyManagerError::Validation(
+            "Please enter a pattern for the rule to match.".to_string(),
+        ));
+    }
+    if rule.match_type == RuleMatchType::Regex
+        && let Err(error) = Regex::new(&rule.pattern)
+    {
+        return Err(MoneyManagerError::Validation(format!(
+            "The pattern is not a valid regular expression: {}",
+            error
+        )));
+    }
+    if let (Some(minimum), Some(maximum)) = (rule.minimum_amount, rule.maximum_amount)
+        && minimum > maximum
+    {
+        return Err(MoneyManagerError::Validation(
+            "The minimum amount can't be more than the maximum amount.".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// retrieve_all_categorization_rules retrieves every rule, in the order they are tried.
+pub fn retrieve_all_categorization_rules() -> Result<Vec<CategorizationRule>, MoneyManagerError> {
+    let slots = slots_manager::load_slots_for_name(
+        slots_manager::SLOT_NAME_CATEGORIZATION_RULE.to_string(),
+    )?;
+
+    let mut rules = Vec::new();
+    for slot in slots {
+        rules.push(serde_json::from_str::<CategorizationRule>(&slot.string_val)?);
+    }
+    rules.sort_by_key(|rule| (rule.priority, rule.name.to_lowercase()));
+
+    Ok(rules)
+}
+
+/// retrieve_categorization_rule_for_guid retrieves the rule with the given guid.
+pub fn retrieve_categorization_rule_for_guid(
+    guid: Uuid,
+) -> Result<CategorizationRule, MoneyManagerError> {
+    retrieve_all_categorization_rules()?
+        .into_iter()
+        .find(|rule| rule.guid == guid)
+        .ok_or(MoneyManagerError::NotFound(
+            "The categorization rule was not found.".to_string(),
+        ))
+}
+
+/// save_categorization_rule saves the given rule into its slot, replacing the current one with
+/// the same guid.
+pub fn save_categorization_rule(rule: CategorizationRule) -> Result<bool, MoneyManagerError> {
+    validate_categorization_rule(&rule)?;
+
+    slots_manager::save_string_val_for_obj_guid_and_name(
+        rule.guid,
+        slots_manager::SLOT_NAME_CATEGORIZATION_RULE,
+        serde_json::to_string(&rule)?,
+    )
+}
+
+/// delete_categorization_rule deletes the slot of the rule with the given guid.
+pub fn delete_categorization_rule(guid: Uuid) -> Result<bool, MoneyManagerError> {
+    slots_manager::delete_slots_for_obj_guid_and_name(
+        guid,
+        slots_manager::SLOT_NAME_CATEGORIZATION_RULE,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(match_type: RuleMatchType, pattern: &str, description: &str) -> CategorizationRule {
+        CategorizationRule {
+            guid: Uuid::new_v4(),
+            name: pattern.to_string(),
+            priority: 0,
+            match_type,
+            pattern: pattern.to_string(),
+            minimum_amount: None,
+            maximum_amount: None,
+            account_guid: Uuid::new_v4(),
+            description: description.to_string(),
+        }
+    }
+
+    #[test]
+    fn find_matching_rule_checks_the_pattern_and_amount_range() {
+        let mut large_purchase = rule(RuleMatchType::Contains, "amazon", "");
+        large_purchase.minimum_amount = Some(100.0);
+        let small_purchase = rule(RuleMatchType::Contains, "AMAZON", "Amazon");
+        let rules = vec![large_purchase.clone(), small_purchase.clone()];
+
+        assert_eq!(
+            find_matching_rule(&rules, "AMAZON MKTPLACE", Some(-250.0)),
+            Some(&large_purchase)
+        );
+        assert_eq!(
+            find_matching_rule(&rules, "Amazon Mktplace", Some(-25.0)),
+            Some(&small_purchase)
+        );
+        assert_eq!(
+            find_matching_rule(&rules, "AMAZON MKTPLACE", None),
+            Some(&small_purchase)
+        );
+        assert_eq!(find_matching_rule(&rules, "KROGER #123", Some(25.0)), None);
+    }
+
+    #[test]
+    fn rewrite_description_expands_regex_groups() {
+        let kroger = rule(RuleMatchType::Regex, r"^KROGER #(\d+)", "Kroger (store $1)");
+        assert!(kroger.matches("KROGER #123 CINCINNATI", None));
+        assert_eq!(
+            kroger.rewrite_description("KROGER #123 CINCINNATI"),
+            "Kroger (store 123)"
+        );
+        assert_eq!(
+            rule(RuleMatchType::Contains, "KROGER", "").rewrite_description("KROGER #123"),
+            "KROGER #123"
+        );
+
+        assert!(validate_categorization_rule(&rule(RuleMatchType::Regex, "(", "")).is_err());
+    }
+}
@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
 use crate::utility::sql_helper_utility as shu;
 
 //guid,namespace,mnemonic,fullname,cusip,fraction,quote_flag,quote_source,quote_tz
@@ -24,60 +25,66 @@ pub const FIELDS: &str =
     "guid,namespace,mnemonic,fullname,cusip,fraction,quote_flag,quote_source,quote_tz";
 
 /// retrieve_all_commodities retrieves all the commodities in the system.
-pub fn retrieve_all_commodities() -> Vec<Commodity> {
-    if crate::DATABASE.lock().unwrap().len() == 0 {
-        panic!("Please select a database to select from you commodities.");
-    }
-
-    //Prepare a statement
-    let stmt = crate::DATABASE.lock().unwrap()[0].prepare("SELECT * FROM commodities");
-
-    let mut commodities = Vec::new();
-
-    while stmt.step() {
-        let row = stmt.getAsObject();
-        //js::log(&("Here is a row: ".to_owned() + &js::stringify(row.clone()).to_owned()));
-
-        let commodity: Commodity = serde_wasm_bindgen::from_value(row.clone()).unwrap();
-
-        commodities.push(commodity);
-    }
-
-    stmt.free();
-
-    return commodities;
+pub fn retrieve_all_commodities() -> Result<Vec<Commodity>, MoneyManagerError> {
+    dhu::query_rows("SELECT * FROM commodities", None)
 }
 
-/// retrieve_commodity_for_guid retrieves a commodity for a guid. Will cause an exception,
-/// if it fails, so be careful!
-pub fn retrieve_commodity_for_guid(commodity_guid: Uuid) -> Commodity {
-    if crate::DATABASE.lock().unwrap().len() == 0 {
-        panic!("Please select a database to select from you commodities.");
-    }
-
-    //Prepare a statement
-    let stmt = crate::DATABASE.lock().unwrap()[0].prepare(&shu::load_commodity_for_guid());
-
+/// retrieve_commodity_for_guid retrieves a commodity for a guid.
+pub fn retrieve_commodity_for_guid(commodity_guid: Uuid) -> Result<Commodity, MoneyManagerError> {
     let binding_object =
-        serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(&commodity_guid)])
-            .unwrap();
-
-    stmt.bind(binding_object.clone());
-
-    let mut commodities = Vec::new();
-
-    while stmt.step() {
-        let row = stmt.getAsObject();
-        //js::log(&("Here is a row: ".to_owned() + &js::stringify(row.clone()).to_owned()));
+        serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(&commodity_guid)])?;
 
-        let commodity: Commodity = serde_wasm_bindgen::from_value(row.clone()).unwrap();
+    dhu::query_first_row(
+        &shu::load_commodity_for_guid(),
+        Some(binding_object),
+        "No commodity for this guid",
+    )
+}
 
-        commodities.push(commodity);
-    }
+/// save_new_and_delete_current saves a new commodity record, but first deletes the
+/// current one with the given commodity.guid.
+pub fn save_new_and_delete_current(commodity: Commodity) -> Result<bool, MoneyManagerError> {
+    let binding_object =
+        serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(&commodity.guid)])?;
+    dhu::execute("DELETE FROM commodities WHERE guid=?", Some(binding_object))?;
+
+    let binding_object = dhu::convert_to_binding_object(&vec![
+        Some(dhu::convert_guid_to_sqlite_string(&commodity.guid)), //guid
+        Some(commodity.namespace),                                 //namespace
+        Some(commodity.mnemonic),                                  //mnemonic
+        Some(commodity.fullname),                                  //fullname
+        Some(commodity.cusip),                                     //cusip
+        Some(commodity.fraction.to_string()),                      //fraction
+        Some(commodity.quote_flag.to_string()),                    //quote_flag
+        Some(commodity.quote_source),                              //quote_source
+        Some(commodity.quote_tz),                                  //quote_tz
+    ]);
+    dhu::execute(
+        &format!("INSERT INTO commodities({fields}) VALUES (?,?,?,?,?,?,?,?,?)", fields = FIELDS),
+        Some(binding_object),
+    )?;
+
+    Ok(true)
+}
 
-    stmt.free();
+/// CommodityGuid holds just the guid of a commodity.
+#[derive(Debug, Deserialize)]
+struct CommodityGuid {
+    guid: Uuid,
+}
 
-    return commodities[0].clone();
+/// retrieve_default_currency_guid retrieves the guid of the book's currency, which is the
+/// root account's commodity, or else the first currency in the book.
+pub fn retrieve_default_currency_guid() -> Result<Uuid, MoneyManagerError> {
+    let commodity: CommodityGuid = dhu::query_first_row(
+        "SELECT guid FROM commodities WHERE namespace='CURRENCY'
+         ORDER BY guid IN (SELECT commodity_guid FROM accounts WHERE account_type='ROOT') DESC
+         LIMIT 1",
+        None,
+        "Please add a currency to your book first.",
+    )?;
+
+    Ok(commodity.guid)
 }
 
 // pub fn save_new(file_path : &str, incoming_commodity : &Commodity) -> Result<bool> {
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Customer {
+    pub guid: Uuid,               //guid is the GUID for this customer.
+    pub name: String,             //name is the name of the customer. (Ex: 'Acme Corporation')
+    pub id: String,               //id is the customer number. (Ex: '000001')
+    pub active: i64,              //active is 1 if the customer is active, 0 otherwise.
+    pub currency: Uuid,           //currency is the commodity guid the customer is billed in.
+    pub terms: Option<Uuid>,      //terms is the billterm guid used for this customer's invoices, or null.
+    pub tax_included: Option<i64>, //tax_included is 1 for yes, 2 for no, and 3 to use the global setting.
+    pub taxtable: Option<Uuid>,   //taxtable is the default tax table guid for this customer, or null.
+}
+
+pub const FIELDS: &str = "guid,name,id,active,currency,terms,tax_included,taxtable";
+
+/// retrieve_all_customers retrieves all the customers, ordered by name.
+pub fn retrieve_all_customers() -> Result<Vec<Customer>, MoneyManagerError> {
+    dhu::query_rows(&format!("SELECT {} FROM customers ORDER BY name", FIELDS), None)
+}
+
+/// retrieve_customer_for_guid retrieves the customer for the given guid as a result.
+pub fn retrieve_customer_for_guid(customer_guid: Uuid) -> Result<Customer, MoneyManagerError> {
+    let binding_object =
+        serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(&customer_guid)])?;
+
+    dhu::query_first_row(
+        &format!("SELECT {} FROM customers WHERE guid=?", FIELDS),
+        Some(binding_object),
+        "No customer for this guid",
+    )
+}
@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Employee {
+    pub guid: Uuid,                //guid is the GUID for this employee.
+    pub username: String,          //username is the employee's username. (Ex: 'jsmith')
+    pub id: String,                //id is the employee number. (Ex: '000001')
+    pub language: String,          //language is the employee's language, blank by default.
+    pub acl: String,               //acl is the employee's access control list, blank by default.
+    pub active: i64,               //active is 1 if the employee is active, 0 otherwise.
+    pub currency: Uuid,            //currency is the commodity guid the employee is reimbursed in.
+    pub ccard_guid: Option<Uuid>,  //ccard_guid is the employee's credit card account guid, or null.
+    pub workday_num: i64,          //workday_num is the numerator of the hours in a work day.
+    pub workday_denom: i64,        //workday_denom is the denominator of the hours in a work day.
+    pub rate_num: i64,             //rate_num is the numerator of the employee's default rate.
+    pub rate_denom: i64,           //rate_denom is the denominator of the employee's default rate.
+    pub addr_name: Option<String>, //addr_name is the employee's full name. (Ex: 'Jane Smith')
+}
+
+pub const FIELDS: &str = "guid,username,id,language,acl,active,currency,ccard_guid,workday_num,workday_denom,rate_num,rate_denom,addr_name";
+
+impl Employee {
+    /// display_name returns the employee's full name, or their username if there isn't one.
+    pub fn display_name(&self) -> String {
+        match &self.addr_name {
+            Some(addr_name) if !addr_name.trim().is_empty() => addr_name.clone(),
+            _ => self.username.clone(),
+        }
+    }
+
+    /// binding_parameters returns the values of this employee in the order of FIELDS, with None
+    /// for the columns that are null.
+    pub fn binding_parameters(&self) -> Vec<Option<String>> {
+        vec![
+            Some(dhu::convert_guid_to_sqlite_string(&self.guid)), //guid
+            Some(self.username.clone()),                          //username
+            Some(self.id.clone()),                                //id
+            Some(self.language.clone()),                          //language
+            Some(self.acl.clone()),                               //acl
+            Some(self.active.to_string()),                        //active
+            Some(dhu::convert_guid_to_sqlite_string(&self.currency)), //currency
+            dhu::convert_option_guid_to_sqlite_string(&self.ccard_guid), //ccard_guid
+            Some(self.workday_num.to_string()),                   //workday_num
+            Some(self.workday_denom.to_string()),                 //workday_denom
+            Some(self.rate_num.to_string()),                      //rate_num
+            Some(self.rate_denom.to_string()),                    //rate_denom
+            self.addr_name.clone(),                               //addr_name
+        ]
+    }
+}
+
+/// retrieve_all_employees retrieves all the employees, ordered by username.
+pub fn retrieve_all_employees() -> Result<Vec<Employee>, MoneyManagerError> {
+    dhu::query_rows(&format!("SELECT {} FROM employees ORDER BY username", FIELDS), None)
+}
+
+/// retrieve_employee_for_guid retrieves the employee for the given guid as a result.
+pub fn retrieve_employee_for_guid(employee_guid: Uuid) -> Result<Employee, MoneyManagerError> {
+    let binding_object =
+        serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(&employee_guid)])?;
+
+    dhu::query_first_row(
+        &format!("SELECT {} FROM employees WHERE guid=?", FIELDS),
+        Some(binding_object),
+        "No employee for this guid",
+    )
+}
+
+/// save_new_and_delete_current saves a new employee record, but first deletes the current one
+/// with the given employee.guid.
+pub fn save_new_and_delete_current(employee: Employee) -> Result<bool, MoneyManagerError> {
+    dhu::require_database()?;
+
+    if employee.username.trim().is_empty() {
+        return Err(MoneyManagerError::Validation(
+            "Please give the employee a username.".to_string(),
+        ));
+    }
+
+    let binding_object =
+        serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(&employee.guid)])?;
+    dhu::execute("DELETE FROM employees WHERE guid=?", Some(binding_object))?;
+
+    let binding_object = dhu::convert_to_binding_object(&employee.binding_parameters());
+    dhu::execute(
+        &format!(
+            "INSERT INTO employees({}) VALUES (?,?,?,?,?,?,?,?,?,?,?,?,?)",
+            FIELDS
+        ),
+        Some(binding_object),
+    )?;
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn employee(addr_name: Option<&str>) -> Employee {
+        Employee {
+            guid: Uuid::from_u128(1),
+            username: "jsmith".to_string(),
+            id: "000001".to_string(),
+            language: String::new(),
+            acl: String::new(),
+            active: 1,
+            currency: Uuid::from_u128(2),
+            ccard_guid: None,
+            workday_num: 8,
+            workday_denom: 1,
+            rate_num: 2550,
+            rate_denom: 100,
+            addr_name: addr_name.map(|addr_name| addr_name.to_string()),
+        }
+    }
+
+    #[test]
+    fn display_name_falls_back_to_username() {
+        assert_eq!(employee(Some("Jane Smith")).display_name(), "Jane Smith");
+        assert_eq!(employee(Some("  ")).display_name(), "jsmith");
+        assert_eq!(employee(None).display_name(), "jsmith");
+    }
+
+    #[test]
+    fn binding_parameters_follow_fields() {
+        let parameters = employee(None).binding_parameters();
+
+        assert_eq!(parameters.len(), FIELDS.split(',').count());
+        assert_eq!(parameters[0], Some(dhu::convert_guid_to_sqlite_string(&Uuid::from_u128(1))));
+        assert_eq!(parameters[1], Some("jsmith".to_string()));
+        assert_eq!(parameters[6], Some(dhu::convert_guid_to_sqlite_string(&Uuid::from_u128(2))));
+        assert_eq!(parameters[7], None);
+        assert_eq!(parameters[10..12], [Some("2550".to_string()), Some("100".to_string())]);
+        assert_eq!(parameters[12], None);
+    }
+}
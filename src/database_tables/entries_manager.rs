@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
+
+/// PRICE_DENOM is the denominator used when saving an entry's quantity or price.
+pub const PRICE_DENOM: i64 = 1000000;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Entry {
+    pub guid: Uuid,                     //guid is the GUID for this entry.
+    pub date: String,                   //date is the date of this line. (Ex: '20120801040000' is 'Aug 1 2012')
+    pub date_entered: Option<String>,   //date_entered is the date this line was entered.
+    pub description: Option<String>,    //description is the description for this line.
+    pub action: Option<String>,         //action is the action of this line. (Ex: 'Hours' or 'Material')
+    pub notes: Option<String>,          //notes is any notes about this line.
+    pub quantity_num: Option<i64>,      //quantity_num is the numerator of the quantity for this line.
+    pub quantity_denom: Option<i64>,    //quantity_denom is the denominator of the quantity for this line.
+    pub i_acct: Option<Uuid>,           //i_acct is the income account guid when this line is on an invoice.
+    pub i_price_num: Option<i64>,       //i_price_num is the numerator of the price when on an invoice.
+    pub i_price_denom: Option<i64>,     //i_price_denom is the denominator of the price when on an invoice.
+    pub invoice: Option<Uuid>,          //invoice is the invoice guid this line belongs to, or null.
+    pub i_taxable: Option<i64>,         //i_taxable is 1 if this invoice line is taxable, 0 otherwise.
+    pub i_taxincluded: Option<i64>,     //i_taxincluded is 1 if the invoice price already includes the tax.
+    pub i_taxtable: Option<Uuid>,       //i_taxtable is the tax table guid used for this invoice line, or null.
+    pub b_acct: Option<Uuid>,           //b_acct is the expense account guid when this line is on a bill.
+    pub b_price_num: Option<i64>,       //b_price_num is the numerator of the price when on a bill.
+    pub b_price_denom: Option<i64>,     //b_price_denom is the denominator of the price when on a bill.
+    pub bill: Option<Uuid>,             //bill is the bill guid this line belongs to, or null.
+    pub b_taxable: Option<i64>,         //b_taxable is 1 if this bill line is taxable, 0 otherwise.
+    pub b_taxincluded: Option<i64>,     //b_taxincluded is 1 if the bill price already includes the tax.
+    pub b_taxtable: Option<Uuid>,       //b_taxtable is the tax table guid used for this bill line, or null.
+}
+
+/// FIELDS is the field list used when selecting entries. The date column is named 'Date' in
+/// GnuCash's schema, so it is aliased to match the struct.
+pub const FIELDS: &str = "guid,Date AS date,date_entered,description,action,notes,quantity_num,quantity_denom,i_acct,i_price_num,i_price_denom,invoice,i_taxable,i_taxincluded,i_taxtable,b_acct,b_price_num,b_price_denom,bill,b_taxable,b_taxincluded,b_taxtable";
+
+/// LineAmounts is the account, price and tax information of an entry, whether it is on an
+/// invoice or on a bill.
+pub struct LineAmounts {
+    pub account: Option<Uuid>,  //account is the income or expense account for this line.
+    pub subtotal: f64,          //subtotal is the quantity times the price.
+    pub taxable: bool,          //taxable is whether tax applies to this line.
+    pub tax_included: bool,     //tax_included is whether the subtotal already includes the tax.
+    pub taxtable: Option<Uuid>, //taxtable is the tax table to use when taxable.
+}
+
+fn fraction_to_float(num: Option<i64>, denom: Option<i64>) -> f64 {
+    match (num, denom) {
+        (Some(num), Some(denom)) if denom != 0 => num as f64 / denom as f64,
+        _ => 0.0,
+    }
+}
+
+impl Entry {
+    /// quantity returns the quantity of this line as a float.
+    pub fn quantity(&self) -> f64 {
+        fraction_to_float(self.quantity_num, self.quantity_denom)
+    }
+
+    /// line_amounts returns the invoice side of this entry if it is on an invoice, or the bill
+    /// side of this entry if it is on a bill.
+    pub fn line_amounts(&self) -> LineAmounts {
+        if self.invoice.is_some() {
+            LineAmounts {
+                account: self.i_acct,
+                subtotal: self.quantity() * fraction_to_float(self.i_price_num, self.i_price_denom),
+                taxable: self.i_taxable == Some(1),
+                tax_included: self.i_taxincluded == Some(1),
+                taxtable: self.i_taxtable,
+            }
+        } else {
+            LineAmounts {
+                account: self.b_acct,
+                subtotal: self.quantity() * fraction_to_float(self.b_price_num, self.b_price_denom),
+                taxable: self.b_taxable == Some(1),
+                tax_included: self.b_taxincluded == Some(1),
+                taxtable: self.b_taxtable,
+            }
+        }
+    }
+}
+
+/// retrieve_entries_for_invoice_guid retrieves the lines of the given invoice or bill guid.
+pub fn retrieve_entries_for_invoice_guid(
+    invoice_guid: Uuid,
+) -> Result<Vec<Entry>, MoneyManagerError> {
+    let binding_object = serde_wasm_bindgen::to_value(&vec![
+        dhu::convert_guid_to_sqlite_string(&invoice_guid),
+        dhu::convert_guid_to_sqlite_string(&invoice_guid),
+    ])?;
+
+    dhu::query_rows(
+        &format!(
+            "SELECT {} FROM entries WHERE invoice=? OR bill=? ORDER BY date_entered, Date",
+            FIELDS
+        ),
+        Some(binding_object),
+    )
+}
+
+/// save_entries_and_delete_current saves the given lines for an invoice or bill, but first
+/// deletes all the current lines of the given invoice guid.
+pub fn save_entries_and_delete_current(
+    invoice_guid: Uuid,
+    entries: Vec<Entry>,
+) -> Result<bool, MoneyManagerError> {
+    let binding_object = serde_wasm_bindgen::to_value(&vec![
+        dhu::convert_guid_to_sqlite_string(&invoice_guid),
+        dhu::convert_guid_to_sqlite_string(&invoice_guid),
+    ])?;
+    dhu::execute("DELETE FROM entries WHERE invoice=? OR bill=?", Some(binding_object))?;
+
+    for entry in entries {
+        let is_invoice_line = entry.invoice.is_some();
+        let binding_object = dhu::convert_to_binding_object(&vec![
+            Some(dhu::convert_guid_to_sqlite_string(&entry.guid)), //guid
+            Some(entry.date),                                      //Date
+            entry.date_entered,                                    //date_entered
+            entry.description,                                     //description
+            entry.action,                                          //action
+            entry.notes,                                           //notes
+            entry.quantity_num.map(|value| value.to_string()),     //quantity_num
+            entry.quantity_denom.map(|value| value.to_string()),   //quantity_denom
+            dhu::convert_option_guid_to_sqlite_string(&entry.i_acct), //i_acct
+            entry.i_price_num.map(|value| value.to_string()),      //i_price_num
+            entry.i_price_denom.map(|value| value.to_string()),    //i_price_denom
+            is_invoice_line.then(|| "0".to_string()),              //i_discount_num
+            is_invoice_line.then(|| "1".to_string()),              //i_discount_denom
+            dhu::convert_option_guid_to_sqlite_string(&entry.invoice), //invoice
+            is_invoice_line.then(|| "PERCENT".to_string()),        //i_disc_type
+            is_invoice_line.then(|| "PRETAX".to_string()),         //i_disc_how
+            entry.i_taxable.map(|value| value.to_string()),        //i_taxable
+            entry.i_taxincluded.map(|value| value.to_string()),    //i_taxincluded
+            dhu::convert_option_guid_to_sqlite_string(&entry.i_taxtable), //i_taxtable
+            dhu::convert_option_guid_to_sqlite_string(&entry.b_acct), //b_acct
+            entry.b_price_num.map(|value| value.to_string()),      //b_price_num
+            entry.b_price_denom.map(|value| value.to_string()),    //b_price_denom
+            dhu::convert_option_guid_to_sqlite_string(&entry.bill), //bill
+            entry.b_taxable.map(|value| value.to_string()),        //b_taxable
+            entry.b_taxincluded.map(|value| value.to_string()),    //b_taxincluded
+            dhu::convert_option_guid_to_sqlite_string(&entry.b_taxtable), //b_taxtable
+            (!is_invoice_line).then(|| "1".to_string()),           //b_paytype
+            Some("0".to_string()),                                 //billable
+        ]);
+        dhu::execute(
+            "INSERT INTO entries(
+                    guid,Date,date_entered,description,action,notes,quantity_num,quantity_denom,
+                    i_acct,i_price_num,i_price_denom,i_discount_num,i_discount_denom,invoice,
+                    i_disc_type,i_disc_how,i_taxable,i_taxincluded,i_taxtable,b_acct,b_price_num,
+                    b_price_denom,bill,b_taxable,b_taxincluded,b_taxtable,b_paytype,billable
+                ) VALUES (?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?)",
+            Some(binding_object),
+        )?;
+    }
+
+    Ok(true)
+}
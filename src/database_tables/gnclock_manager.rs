@@ -0,0 +1,112 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Lock {
+    pub hostname: String, //hostname is the name of the computer that has the file open. (Ex: 'my-laptop')
+    pub pid: i64,         //pid is the process id of the program that has the file open.
+}
+
+pub const FIELDS: &str = "Hostname AS hostname,PID AS pid";
+
+/// LOCK_HOSTNAME is the hostname this app writes in its lock row, since a browser can't
+/// know the computer's name.
+pub const LOCK_HOSTNAME: &str = env!("CARGO_PKG_NAME");
+
+impl Lock {
+    /// new_for_this_app creates a lock row for this app, with a random process id so that two
+    /// browser tabs can be told apart.
+    pub fn new_for_this_app() -> Lock {
+        Lock {
+            hostname: LOCK_HOSTNAME.to_string(),
+            pid: rand::rng().random_range(1..i32::MAX as i64),
+        }
+    }
+
+    /// is_from_this_app returns whether this lock was written by this app, instead of GnuCash.
+    pub fn is_from_this_app(&self) -> bool {
+        self.hostname == LOCK_HOSTNAME
+    }
+}
+
+/// TableCount holds the number of tables found with a name.
+#[derive(Debug, Deserialize)]
+struct TableCount {
+    count: i64,
+}
+
+/// retrieve_all_locks retrieves the rows of the gnclock table, which GnuCash uses to mark
+/// the file as open, or none if the file doesn't have a gnclock table.
+pub fn retrieve_all_locks() -> Result<Vec<Lock>, MoneyManagerError> {
+    let table_counts: Vec<TableCount> = dhu::query_rows(
+        "SELECT COUNT(*) AS count FROM sqlite_master WHERE type='table' AND name='gnclock'",
+        None,
+    )?;
+
+    if table_counts.iter().all(|table_count| table_count.count == 0) {
+        return Ok(Vec::new());
+    }
+
+    dhu::query_rows(&format!("SELECT {} FROM gnclock", FIELDS), None)
+}
+
+/// locks_held_by_others returns the given locks without the ones this app wrote, since those
+/// are left behind by copies saved before they were finished, and are replaced on loading.
+pub fn locks_held_by_others(locks: Vec<Lock>) -> Vec<Lock> {
+    locks.into_iter().filter(|lock| !lock.is_from_this_app()).collect()
+}
+
+/// save_new_and_delete_current saves the given lock, but first deletes any current locks,
+/// which takes the file over from whoever had it open.
+pub fn save_new_and_delete_current(lock: Lock) -> Result<bool, MoneyManagerError> {
+    //Older files written by this app may not have the table yet
+    dhu::execute(
+        "CREATE TABLE IF NOT EXISTS gnclock ( Hostname varchar(255), PID int )",
+        None,
+    )?;
+    delete_all_locks()?;
+
+    let binding_object = serde_wasm_bindgen::to_value(&vec![lock.hostname, lock.pid.to_string()])?;
+    dhu::execute(
+        "INSERT INTO gnclock(Hostname,PID) VALUES (?,?)",
+        Some(binding_object),
+    )?;
+
+    Ok(true)
+}
+
+/// delete_all_locks deletes all the rows of the gnclock table, so GnuCash can open the file
+/// without a warning.
+pub fn delete_all_locks() -> Result<bool, MoneyManagerError> {
+    dhu::execute("DELETE FROM gnclock", None)?;
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_for_this_app_is_from_this_app() {
+        let lock = Lock::new_for_this_app();
+
+        assert!(lock.is_from_this_app());
+        assert!(lock.pid > 0);
+    }
+
+    #[test]
+    fn locks_held_by_others_drops_this_apps_locks() {
+        let gnucash_lock = Lock {
+            hostname: "my-laptop".to_string(),
+            pid: 4242,
+        };
+        let locks = vec![Lock::new_for_this_app(), gnucash_lock.clone(), Lock::new_for_this_app()];
+
+        assert_eq!(locks_held_by_others(locks), vec![gnucash_lock]);
+        assert!(locks_held_by_others(vec![Lock::new_for_this_app()]).is_empty());
+    }
+}
@@ -0,0 +1,478 @@
+use serde::{Deserialize, Serialize};
+use serde_repr::*;
+use uuid::Uuid;
+
+use crate::database_tables::accounts_manager;
+use crate::database_tables::commodities_manager;
+use crate::database_tables::entries_manager;
+use crate::database_tables::jobs_manager;
+use crate::database_tables::lots_manager;
+use crate::database_tables::slots_manager;
+use crate::database_tables::splits_manager;
+use crate::database_tables::taxtables_manager;
+use crate::database_tables::transactions_manager;
+use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
+
+/// OwnerType is the kind of owner of an invoice, bill or job, matching GnuCash's values.
+#[derive(Serialize_repr, Deserialize_repr, PartialEq, Debug, Clone, Copy)]
+#[repr(u8)]
+pub enum OwnerType {
+    None = 0,
+    Undefined = 1,
+    Customer = 2,
+    Job = 3,
+    Vendor = 4,
+    Employee = 5,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Invoice {
+    pub guid: Uuid,                    //guid is the GUID for this invoice or bill.
+    pub id: String,                    //id is the invoice number. (Ex: '000001')
+    pub date_opened: Option<String>,   //date_opened is the date this invoice was opened.
+    pub date_posted: Option<String>,   //date_posted is the date this invoice was posted, or null if not posted.
+    pub notes: String,                 //notes is any notes about this invoice.
+    pub active: i64,                   //active is 1 if this invoice is active, 0 otherwise.
+    pub currency: Uuid,                //currency is the commodity guid this invoice is in.
+    pub owner_type: Option<OwnerType>, //owner_type is whether a customer (invoice), vendor (bill), employee (voucher) or job owns this.
+    pub owner_guid: Option<Uuid>,      //owner_guid is the guid of the customer or vendor.
+    pub terms: Option<Uuid>,           //terms is the billterm guid for this invoice, or null.
+    pub billing_id: Option<String>,    //billing_id is the owner's reference for this invoice.
+    pub post_txn: Option<Uuid>,        //post_txn is the transaction guid created when this was posted.
+    pub post_lot: Option<Uuid>,        //post_lot is the lot guid created when this was posted.
+    pub post_acc: Option<Uuid>,        //post_acc is the A/R or A/P account guid this was posted to.
+    pub billto_type: Option<OwnerType>, //billto_type is the type of who a bill or voucher is charged to, like a job.
+    pub billto_guid: Option<Uuid>,     //billto_guid is the guid of who a bill or voucher is charged to.
+}
+
+/// AccountAmounts is an amount for each account, in the order the accounts were first seen.
+pub type AccountAmounts = Vec<(Uuid, f64)>;
+
+pub const FIELDS: &str = "guid,id,date_opened,date_posted,notes,active,currency,owner_type,owner_guid,terms,billing_id,post_txn,post_lot,post_acc,billto_type,billto_guid";
+
+impl Invoice {
+    /// document_owner_type returns whether a customer, vendor or employee this is for. An
+    /// invoice or bill owned by a job is for the job's owner.
+    pub fn document_owner_type(&self) -> OwnerType {
+        match (self.owner_type, self.owner_guid) {
+            (Some(OwnerType::Job), Some(job_guid)) => jobs_manager::retrieve_job_for_guid(job_guid)
+                .ok()
+                .and_then(|job| job.owner_type)
+                .unwrap_or(OwnerType::Customer),
+            (Some(owner_type), _) => owner_type,
+            (None, _) => OwnerType::Customer,
+        }
+    }
+}
+
+/// retrieve_all_invoices retrieves all the invoices and bills, with the newest ones last.
+pub fn retrieve_all_invoices() -> Result<Vec<Invoice>, MoneyManagerError> {
+    dhu::query_rows(
+        &format!("SELECT {} FROM invoices ORDER BY date_opened, id", FIELDS),
+        None,
+    )
+}
+
+/// retrieve_invoice_for_guid retrieves the invoice or bill for the given guid as a result.
+pub fn retrieve_invoice_for_guid(invoice_guid: Uuid) -> Result<Invoice, MoneyManagerError> {
+    let binding_object =
+        serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(&invoice_guid)])?;
+
+    dhu::query_first_row(
+        &format!("SELECT {} FROM invoices WHERE guid=?", FIELDS),
+        Some(binding_object),
+        "No invoice for this guid",
+    )
+}
+
+/// retrieve_post_accounts_for_owner_type retrieves the accounts an invoice or bill can be
+/// posted to. Invoices post to RECEIVABLE accounts, and bills post to PAYABLE accounts.
+pub fn retrieve_post_accounts_for_owner_type(
+    owner_type: OwnerType,
+) -> Result<Vec<accounts_manager::AccountName>, MoneyManagerError> {
+    let account_type = if owner_type == OwnerType::Customer {
+        "RECEIVABLE"
+    } else {
+        "PAYABLE"
+    };
+
+    Ok(accounts_manager::retrieve_all_account_names()?
+        .into_iter()
+        .filter(|account_name| account_name.account_type == account_type)
+        .collect())
+}
+
+/// next_invoice_id gives the next invoice number to use, one more than the largest number
+/// currently used by any invoice or bill.
+pub fn next_invoice_id() -> String {
+    let invoices = retrieve_all_invoices().unwrap_or_default();
+
+    let largest_id = invoices
+        .iter()
+        .filter_map(|invoice| invoice.id.trim().parse::<i64>().ok())
+        .max()
+        .unwrap_or(0);
+
+    format!("{:06}", largest_id + 1)
+}
+
+/// save_new_and_delete_current saves an invoice or bill along with its lines, but first deletes
+/// the current one with the given invoice.guid. Posted invoices cannot be changed.
+pub fn save_new_and_delete_current(
+    invoice: Invoice,
+    entries: Vec<entries_manager::Entry>,
+) -> Result<bool, MoneyManagerError> {
+    dhu::require_database()?;
+
+    if let Ok(current_invoice) = retrieve_invoice_for_guid(invoice.guid)
+        && current_invoice.post_txn.is_some()
+    {
+        return Err(MoneyManagerError::Validation(
+            "This invoice has already been posted, so it can't be changed.".to_string(),
+        ));
+    }
+
+    let binding_object =
+        serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(&invoice.guid)])?;
+    dhu::execute("DELETE FROM invoices WHERE guid=?", Some(binding_object))?;
+
+    let binding_object = dhu::convert_to_binding_object(&vec![
+        Some(dhu::convert_guid_to_sqlite_string(&invoice.guid)), //guid
+        Some(invoice.id),                                       //id
+        invoice.date_opened,                                    //date_opened
+        invoice.date_posted,                                    //date_posted
+        Some(invoice.notes),                                    //notes
+        Some(invoice.active.to_string()),                       //active
+        Some(dhu::convert_guid_to_sqlite_string(&invoice.currency)), //currency
+        invoice.owner_type.map(|owner_type| (owner_type as u8).to_string()), //owner_type
+        dhu::convert_option_guid_to_sqlite_string(&invoice.owner_guid), //owner_guid
+        dhu::convert_option_guid_to_sqlite_string(&invoice.terms), //terms
+        invoice.billing_id,                                     //billing_id
+        dhu::convert_option_guid_to_sqlite_string(&invoice.post_txn), //post_txn
+        dhu::convert_option_guid_to_sqlite_string(&invoice.post_lot), //post_lot
+        dhu::convert_option_guid_to_sqlite_string(&invoice.post_acc), //post_acc
+        invoice.billto_type.map(|billto_type| (billto_type as u8).to_string()), //billto_type
+        dhu::convert_option_guid_to_sqlite_string(&invoice.billto_guid), //billto_guid
+    ]);
+    dhu::execute(
+        &format!(
+            "INSERT INTO invoices({}) VALUES (?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?)",
+            FIELDS
+        ),
+        Some(binding_object),
+    )?;
+
+    entries_manager::save_entries_and_delete_current(invoice.guid, entries)
+}
+
+/// add_amount_for_account adds the given amount to the running total of the given account,
+/// keeping the accounts in the order they were first seen.
+fn add_amount_for_account(amounts: &mut AccountAmounts, account: Uuid, amount: f64) {
+    match amounts.iter_mut().find(|(guid, _)| *guid == account) {
+        Some((_, total)) => *total += amount,
+        None => amounts.push((account, amount)),
+    }
+}
+
+/// calculate_posting_amounts calculates the net amount for each income or expense account, and
+/// the tax amount for each tax account, of the given lines.
+pub fn calculate_posting_amounts(
+    entries: &[entries_manager::Entry],
+) -> Result<(AccountAmounts, AccountAmounts), MoneyManagerError> {
+    let mut account_amounts = Vec::new();
+    let mut tax_amounts = Vec::new();
+
+    for entry in entries {
+        let line = entry.line_amounts();
+        let account = match line.account {
+            Some(account) => account,
+            None => {
+                return Err(MoneyManagerError::Validation(format!(
+                    "The line '{}' needs an account before it can be posted.",
+                    entry.description.clone().unwrap_or_default()
+                )));
+            }
+        };
+
+        let taxtable_entries = match (line.taxable, line.taxtable) {
+            (true, Some(taxtable)) => {
+                taxtables_manager::retrieve_taxtable_entries_for_taxtable_guid(taxtable)?
+            }
+            _ => Vec::new(),
+        };
+
+        let (net, taxes) =
+            taxtables_manager::calculate_tax(line.subtotal, line.tax_included, &taxtable_entries);
+
+        add_amount_for_account(&mut account_amounts, account, net);
+        for (tax_account, tax) in taxes {
+            add_amount_for_account(&mut tax_amounts, tax_account, tax);
+        }
+    }
+
+    Ok((account_amounts, tax_amounts))
+}
+
+/// TXN_TYPE_INVOICE is the trans-txn-type GnuCash gives the transaction of a posted invoice,
+/// or bill.
+pub const TXN_TYPE_INVOICE: &str = "I";
+
+/// post_invoice posts the invoice or bill with the given guid to the given A/R or A/P account.
+/// It creates a transaction with a split for each income or expense account, a split for each
+/// tax account, and the A/R or A/P split in a new lot, the same way GnuCash does.
+pub fn post_invoice(
+    invoice_guid: Uuid,
+    post_account_guid: Uuid,
+    post_date: String,
+    description: String,
+) -> Result<bool, MoneyManagerError> {
+    let invoice = retrieve_invoice_for_guid(invoice_guid)?;
+    if invoice.post_txn.is_some() {
+        return Err(MoneyManagerError::Validation(
+            "This invoice has already been posted.".to_string(),
+        ));
+    }
+
+    let entries = entries_manager::retrieve_entries_for_invoice_guid(invoice_guid)?;
+    if entries.is_empty() {
+        return Err(MoneyManagerError::Validation(
+            "Please add at least one line before posting.".to_string(),
+        ));
+    }
+
+    let (account_amounts, tax_amounts) = calculate_posting_amounts(&entries)?;
+
+    let fraction = commodities_manager::retrieve_commodity_for_guid(invoice.currency)?.fraction;
+    let transaction_guid = Uuid::new_v4();
+    let lot_guid = Uuid::new_v4();
+    let splits = build_posting_splits(
+        transaction_guid,
+        lot_guid,
+        post_account_guid,
+        invoice.document_owner_type(),
+        &account_amounts,
+        &tax_amounts,
+        fraction,
+    );
+
+    let transaction = transactions_manager::Transaction {
+        guid: transaction_guid,
+        currency_guid: invoice.currency,
+        num: invoice.id.clone(),
+        post_date: post_date.clone(),
+        enter_date: dhu::convert_date_to_string_format(chrono::Local::now().naive_local()),
+        description,
+    };
+
+    //The transaction, lot, slots, and invoice are all saved, or none of them are, so an invoice
+    //is never left half posted
+    dhu::in_transaction(|| {
+        transactions_manager::save_transaction_with_splits(transaction, splits)?;
+
+        lots_manager::save_new_and_delete_current(lots_manager::Lot {
+            guid: lot_guid,
+            account_guid: post_account_guid,
+            is_closed: false,
+        })?;
+
+        //Link the lot back to the invoice
+        let frame_guid = Uuid::new_v4();
+        slots_manager::save_slot(new_slot(
+            lot_guid,
+            "gncInvoice",
+            slots_manager::SLOT_TYPE_FRAME,
+            None,
+            Some(dhu::convert_guid_to_sqlite_string(&frame_guid)),
+        ))?;
+        slots_manager::save_slot(new_slot(
+            frame_guid,
+            "gncInvoice/invoice-guid",
+            slots_manager::SLOT_TYPE_GUID,
+            None,
+            Some(dhu::convert_guid_to_sqlite_string(&invoice_guid)),
+        ))?;
+
+        //Mark the transaction as generated from an invoice
+        slots_manager::save_slot(new_slot(
+            transaction_guid,
+            "trans-txn-type",
+            slots_manager::SLOT_TYPE_STRING,
+            Some(TXN_TYPE_INVOICE.to_string()),
+            None,
+        ))?;
+        slots_manager::save_slot(new_slot(
+            transaction_guid,
+            "trans-read-only",
+            slots_manager::SLOT_TYPE_STRING,
+            Some("Generated from an invoice. Try unposting the invoice.".to_string()),
+            None,
+        ))?;
+
+        let binding_object = serde_wasm_bindgen::to_value(&vec![
+            post_date,                                                //date_posted
+            dhu::convert_guid_to_sqlite_string(&transaction_guid),   //post_txn
+            dhu::convert_guid_to_sqlite_string(&lot_guid),           //post_lot
+            dhu::convert_guid_to_sqlite_string(&post_account_guid),  //post_acc
+            dhu::convert_guid_to_sqlite_string(&invoice_guid),       //guid
+        ])?;
+        dhu::execute(
+            "UPDATE invoices SET date_posted=?, post_txn=?, post_lot=?, post_acc=? WHERE guid=?",
+            Some(binding_object),
+        )?;
+
+        Ok(true)
+    })
+}
+
+/// build_posting_splits builds the splits of a posted invoice, bill or voucher for the given
+/// owner type. Invoices credit income and tax collected, while bills and vouchers debit
+/// expenses and tax paid, and the A/R or A/P split balances the rest, and holds the lot.
+pub fn build_posting_splits(
+    transaction_guid: Uuid,
+    lot_guid: Uuid,
+    post_account_guid: Uuid,
+    document_owner_type: OwnerType,
+    account_amounts: &AccountAmounts,
+    tax_amounts: &AccountAmounts,
+    fraction: i64,
+) -> Vec<splits_manager::Split> {
+    let sign = if document_owner_type == OwnerType::Customer { -1.0 } else { 1.0 };
+
+    let mut splits = Vec::new();
+    let mut total_num = 0;
+
+    for (account_guid, amount) in account_amounts.iter().chain(tax_amounts.iter()) {
+        let value_num = (amount * sign * fraction as f64).round() as i64;
+        total_num += value_num;
+        splits.push(splits_manager::Split {
+            guid: Uuid::new_v4(),
+            tx_guid: transaction_guid,
+            account_guid: *account_guid,
+            memo: String::new(),
+            action: String::new(),
+            reconcile_state: "n".to_string(),
+            reconcile_date: None,
+            value_num,
+            value_denom: fraction,
+            quantity_num: value_num,
+            quantity_denom: fraction,
+            lot_guid: None,
+        });
+    }
+
+    //The A/R or A/P split balances the transaction, and holds the lot
+    splits.push(splits_manager::Split {
+        guid: Uuid::new_v4(),
+        tx_guid: transaction_guid,
+        account_guid: post_account_guid,
+        memo: String::new(),
+        action: match document_owner_type {
+            OwnerType::Customer => "Invoice",
+            OwnerType::Employee => "Voucher",
+            _ => "Bill",
+        }
+        .to_string(),
+        reconcile_state: "n".to_string(),
+        reconcile_date: None,
+        value_num: -total_num,
+        value_denom: fraction,
+        quantity_num: -total_num,
+        quantity_denom: fraction,
+        lot_guid: Some(lot_guid),
+    });
+
+    splits
+}
+
+/// new_slot creates a slot for the given object with either a string_val or a guid_val.
+fn new_slot(
+    obj_guid: Uuid,
+    name: &str,
+    slot_type: i64,
+    string_val: Option<String>,
+    guid_val: Option<String>,
+) -> slots_manager::Slot {
+    slots_manager::Slot {
+        id: -1,
+        obj_guid,
+        name: name.to_string(),
+        slot_type,
+        int64_val: 0,
+        string_val: string_val.unwrap_or_default(),
+        double_val: None,
+        timespec_val: None,
+        guid_val,
+        numeric_val_num: None,
+        numeric_val_denom: None,
+        gdate_val: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// account_values returns the account, value_num, and action of each split.
+    fn account_values(splits: &[splits_manager::Split]) -> Vec<(Uuid, i64, String)> {
+        splits
+            .iter()
+            .map(|split| (split.account_guid, split.value_num, split.action.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn build_posting_splits_debits_expenses_for_vouchers() {
+        let (payable, travel, meals, tax) =
+            (Uuid::from_u128(1), Uuid::from_u128(2), Uuid::from_u128(3), Uuid::from_u128(4));
+        let lot_guid = Uuid::from_u128(5);
+
+        let splits = build_posting_splits(
+            Uuid::from_u128(6),
+            lot_guid,
+            payable,
+            OwnerType::Employee,
+            &vec![(travel, 120.0), (meals, 35.255)],
+            &vec![(tax, 9.6)],
+            100,
+        );
+
+        assert_eq!(
+            account_values(&splits),
+            vec![
+                (travel, 12000, String::new()),
+                (meals, 3526, String::new()),
+                (tax, 960, String::new()),
+                (payable, -16486, "Voucher".to_string()),
+            ]
+        );
+        assert!(splits.iter().all(|split| split.quantity_num == split.value_num));
+        assert_eq!(splits.iter().map(|split| split.value_num).sum::<i64>(), 0);
+        assert_eq!(splits[3].lot_guid, Some(lot_guid));
+        assert!(splits[..3].iter().all(|split| split.lot_guid.is_none()));
+    }
+
+    #[test]
+    fn build_posting_splits_credits_income_for_invoices() {
+        let (receivable, sales, tax) = (Uuid::from_u128(1), Uuid::from_u128(2), Uuid::from_u128(3));
+
+        let splits = build_posting_splits(
+            Uuid::from_u128(4),
+            Uuid::from_u128(5),
+            receivable,
+            OwnerType::Customer,
+            &vec![(sales, 200.0)],
+            &vec![(tax, 16.5)],
+            100,
+        );
+
+        assert_eq!(
+            account_values(&splits),
+            vec![
+                (sales, -20000, String::new()),
+                (tax, -1650, String::new()),
+                (receivable, 21650, "Invoice".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,269 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::database_tables::entries_manager;
+use crate::database_tables::invoices_manager::{self, OwnerType};
+use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Job {
+    pub guid: Uuid,                    //guid is the GUID for this job.
+    pub id: String,                    //id is the job number. (Ex: '000001')
+    pub name: String,                  //name is the name of the job. (Ex: 'Kitchen Remodel')
+    pub reference: String,             //reference is the owner's reference for this job.
+    pub active: i64,                   //active is 1 if the job is active, 0 otherwise.
+    pub owner_type: Option<OwnerType>, //owner_type is whether a customer or a vendor owns this job.
+    pub owner_guid: Option<Uuid>,      //owner_guid is the guid of the customer or vendor.
+}
+
+/// JobProfitability is how much a job has made, and cost.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobProfitability {
+    pub job_guid: Uuid, //job_guid is the guid of the job.
+    pub revenue: f64,   //revenue is the total before tax of the posted invoices for the job.
+    pub costs: f64,     //costs is the total before tax of the posted bills and vouchers for the job.
+}
+
+impl JobProfitability {
+    /// add adds the net amount of an invoice to the revenue of the job, or of a bill or voucher
+    /// to its costs.
+    pub fn add(&mut self, net: f64, is_revenue: bool) {
+        if is_revenue {
+            self.revenue += net;
+        } else {
+            self.costs += net;
+        }
+    }
+}
+
+pub const FIELDS: &str = "guid,id,name,reference,active,owner_type,owner_guid";
+
+impl Job {
+    /// binding_parameters returns the values of this job in the order of FIELDS, with None for
+    /// the columns that are null.
+    pub fn binding_parameters(&self) -> Vec<Option<String>> {
+        vec![
+            Some(dhu::convert_guid_to_sqlite_string(&self.guid)), //guid
+            Some(self.id.clone()),                                //id
+            Some(self.name.clone()),                              //name
+            Some(self.reference.clone()),                         //reference
+            Some(self.active.to_string()),                        //active
+            self.owner_type.map(|owner_type| (owner_type as u8).to_string()), //owner_type
+            dhu::convert_option_guid_to_sqlite_string(&self.owner_guid), //owner_guid
+        ]
+    }
+}
+
+/// retrieve_all_jobs retrieves all the jobs, ordered by name.
+pub fn retrieve_all_jobs() -> Result<Vec<Job>, MoneyManagerError> {
+    dhu::query_rows(&format!("SELECT {} FROM jobs ORDER BY name", FIELDS), None)
+}
+
+/// retrieve_job_for_guid retrieves the job for the given guid as a result.
+pub fn retrieve_job_for_guid(job_guid: Uuid) -> Result<Job, MoneyManagerError> {
+    let binding_object =
+        serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(&job_guid)])?;
+
+    dhu::query_first_row(
+        &format!("SELECT {} FROM jobs WHERE guid=?", FIELDS),
+        Some(binding_object),
+        "No job for this guid",
+    )
+}
+
+/// save_new_and_delete_current saves a new job record, but first deletes the current one with
+/// the given job.guid.
+pub fn save_new_and_delete_current(job: Job) -> Result<bool, MoneyManagerError> {
+    dhu::require_database()?;
+
+    if job.name.trim().is_empty() {
+        return Err(MoneyManagerError::Validation(
+            "Please give the job a name.".to_string(),
+        ));
+    }
+
+    let binding_object =
+        serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(&job.guid)])?;
+    dhu::execute("DELETE FROM jobs WHERE guid=?", Some(binding_object))?;
+
+    let binding_object = dhu::convert_to_binding_object(&job.binding_parameters());
+    dhu::execute(
+        &format!("INSERT INTO jobs({}) VALUES (?,?,?,?,?,?,?)", FIELDS),
+        Some(binding_object),
+    )?;
+
+    Ok(true)
+}
+
+/// job_for_invoice returns the guid of the job a posted invoice, bill or voucher counts toward,
+/// and whether it is revenue for the job, or None if it isn't posted, or linked to a job. An
+/// invoice owned by a customer's job is revenue, while a bill owned by a vendor's job, or a bill
+/// or voucher billed to a job, is a cost.
+pub fn job_for_invoice(invoice: &invoices_manager::Invoice, jobs: &[Job]) -> Option<(Uuid, bool)> {
+    invoice.post_txn?;
+
+    if invoice.owner_type == Some(OwnerType::Job) {
+        let job_guid = invoice.owner_guid?;
+        let job_owner_type = jobs
+            .iter()
+            .find(|job| job.guid == job_guid)
+            .and_then(|job| job.owner_type);
+        Some((job_guid, job_owner_type == Some(OwnerType::Customer)))
+    } else if invoice.billto_type == Some(OwnerType::Job) {
+        Some((invoice.billto_guid?, false))
+    } else {
+        None
+    }
+}
+
+/// retrieve_job_profitability retrieves the revenue, and costs of each job from the posted
+/// invoices, bills and vouchers linked to it. Invoices are linked by being owned by the job,
+/// and bills and vouchers are linked by being billed to the job.
+pub fn retrieve_job_profitability() -> Result<Vec<JobProfitability>, MoneyManagerError> {
+    let jobs = retrieve_all_jobs()?;
+    let mut job_profitability: Vec<JobProfitability> = jobs
+        .iter()
+        .map(|job| JobProfitability {
+            job_guid: job.guid,
+            revenue: 0.0,
+            costs: 0.0,
+        })
+        .collect();
+
+    for invoice in invoices_manager::retrieve_all_invoices()? {
+        let (job_guid, is_revenue) = match job_for_invoice(&invoice, &jobs) {
+            Some(job_for_invoice) => job_for_invoice,
+            None => continue,
+        };
+        let profitability = match job_profitability
+            .iter_mut()
+            .find(|profitability| profitability.job_guid == job_guid)
+        {
+            Some(profitability) => profitability,
+            None => continue,
+        };
+
+        let entries = entries_manager::retrieve_entries_for_invoice_guid(invoice.guid)?;
+        let (account_amounts, _) = invoices_manager::calculate_posting_amounts(&entries)?;
+        let net: f64 = account_amounts.iter().map(|(_, amount)| amount).sum();
+        profitability.add(net, is_revenue);
+    }
+
+    Ok(job_profitability)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(guid: u128, owner_type: OwnerType) -> Job {
+        Job {
+            guid: Uuid::from_u128(guid),
+            id: format!("{:06}", guid),
+            name: format!("Job {}", guid),
+            reference: String::new(),
+            active: 1,
+            owner_type: Some(owner_type),
+            owner_guid: Some(Uuid::from_u128(100 + guid)),
+        }
+    }
+
+    fn invoice(
+        owner: (OwnerType, u128),
+        billto: Option<(OwnerType, u128)>,
+        posted: bool,
+    ) -> invoices_manager::Invoice {
+        invoices_manager::Invoice {
+            guid: Uuid::from_u128(50),
+            id: "000001".to_string(),
+            date_opened: None,
+            date_posted: None,
+            notes: String::new(),
+            active: 1,
+            currency: Uuid::from_u128(60),
+            owner_type: Some(owner.0),
+            owner_guid: Some(Uuid::from_u128(owner.1)),
+            terms: None,
+            billing_id: None,
+            post_txn: posted.then(|| Uuid::from_u128(70)),
+            post_lot: None,
+            post_acc: None,
+            billto_type: billto.map(|billto| billto.0),
+            billto_guid: billto.map(|billto| Uuid::from_u128(billto.1)),
+        }
+    }
+
+    #[test]
+    fn job_for_invoice_links_revenue_and_costs() {
+        let jobs = vec![job(1, OwnerType::Customer), job(2, OwnerType::Vendor)];
+
+        //An invoice owned by a customer's job is revenue
+        assert_eq!(
+            job_for_invoice(&invoice((OwnerType::Job, 1), None, true), &jobs),
+            Some((Uuid::from_u128(1), true))
+        );
+        //A bill owned by a vendor's job is a cost
+        assert_eq!(
+            job_for_invoice(&invoice((OwnerType::Job, 2), None, true), &jobs),
+            Some((Uuid::from_u128(2), false))
+        );
+        //A voucher billed to a job is a cost
+        assert_eq!(
+            job_for_invoice(
+                &invoice((OwnerType::Employee, 9), Some((OwnerType::Job, 1)), true),
+                &jobs
+            ),
+            Some((Uuid::from_u128(1), false))
+        );
+    }
+
+    #[test]
+    fn job_for_invoice_skips_unposted_and_unlinked() {
+        let jobs = vec![job(1, OwnerType::Customer)];
+
+        assert_eq!(job_for_invoice(&invoice((OwnerType::Job, 1), None, false), &jobs), None);
+        assert_eq!(job_for_invoice(&invoice((OwnerType::Customer, 9), None, true), &jobs), None);
+        assert_eq!(
+            job_for_invoice(
+                &invoice((OwnerType::Vendor, 9), Some((OwnerType::Customer, 1)), true),
+                &jobs
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn add_totals_revenue_and_costs() {
+        let mut profitability = JobProfitability {
+            job_guid: Uuid::from_u128(1),
+            revenue: 0.0,
+            costs: 0.0,
+        };
+
+        profitability.add(1500.0, true);
+        profitability.add(250.5, true);
+        profitability.add(400.25, false);
+
+        assert_eq!(profitability.revenue, 1750.5);
+        assert_eq!(profitability.costs, 400.25);
+    }
+
+    #[test]
+    fn binding_parameters_follow_fields() {
+        let parameters = job(1, OwnerType::Customer).binding_parameters();
+
+        assert_eq!(parameters.len(), FIELDS.split(',').count());
+        assert_eq!(parameters[2], Some("Job 1".to_string()));
+        assert_eq!(parameters[5], Some("2".to_string()));
+        assert_eq!(parameters[6], Some(dhu::convert_guid_to_sqlite_string(&Uuid::from_u128(101))));
+
+        let parameters = Job {
+            owner_type: None,
+            owner_guid: None,
+            ..job(1, OwnerType::Customer)
+        }
+        .binding_parameters();
+        assert_eq!(parameters[5..], [None, None]);
+    }
+}
@@ -1,5 +1,10 @@
+use serde::Deserialize;
 use uuid::Uuid;
 
+use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
+use crate::utility::sql_helper_utility as shu;
+
 //guid, account_guid, is_closed
 
 #[allow(dead_code)]
@@ -17,6 +22,43 @@ pub fn _fields() -> String {
          )
 } 
 
+/// save_new_and_delete_current saves a new lot record, but first deletes the
+/// current one with the given lot.guid.
+pub fn save_new_and_delete_current(lot: Lot) -> Result<bool, MoneyManagerError> {
+    let binding_object =
+        serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(&lot.guid)])?;
+    dhu::execute("DELETE FROM lots WHERE guid=?", Some(binding_object))?;
+
+    let binding_object = serde_wasm_bindgen::to_value(&vec![
+        dhu::convert_guid_to_sqlite_string(&lot.guid),         //guid
+        dhu::convert_guid_to_sqlite_string(&lot.account_guid), //account_guid
+        if lot.is_closed { "1" } else { "0" }.to_string(),     //is_closed
+    ])?;
+    dhu::execute(
+        "INSERT INTO lots(guid,account_guid,is_closed) VALUES (?,?,?)",
+        Some(binding_object),
+    )?;
+
+    Ok(true)
+}
+
+/// AgingLot is an open lot on a RECEIVABLE or PAYABLE account, with who it belongs to.
+#[derive(Debug, Deserialize, Clone)]
+pub struct AgingLot {
+    pub account_type: String,        //account_type is 'RECEIVABLE' or 'PAYABLE'.
+    pub terms: Option<Uuid>,         //terms is the billterm guid of the invoice, if any.
+    pub date_posted: Option<String>, //date_posted is when the invoice was posted, or the first split's date.
+    pub owner_guid: Option<Uuid>,    //owner_guid is the guid of the customer, vendor or employee.
+    pub owner_name: String,          //owner_name is the name of the customer, vendor or employee.
+    pub balance: f64,                //balance is the amount still open in the lot.
+}
+
+/// retrieve_open_lots_for_aging retrieves the open lots with a balance on the RECEIVABLE
+/// and PAYABLE accounts.
+pub fn retrieve_open_lots_for_aging() -> Result<Vec<AgingLot>, MoneyManagerError> {
+    dhu::query_rows(&shu::load_open_lots_for_aging(), None)
+}
+
 // ///retrieve_all_lots retrieves all the records.
 // pub fn retrieve_all_lots(file_path : &str) -> Result<Vec<Lot>> {
 //     //Attempt to open the file from the given path to perform this operation
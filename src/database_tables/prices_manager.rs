@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::database_tables::splits_manager;
+use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Price {
+    pub guid: Uuid,                 //guid is the GUID for this price.
+    pub commodity_guid: Uuid,       //commodity_guid is the commodity being priced. (Ex: 'AAPL')
+    pub currency_guid: Uuid,        //currency_guid is the currency of the price. (Ex: 'USD')
+    pub date: String,               //date is when the price was quoted. (Ex: '20120801040000')
+    pub source: Option<String>,     //source is where the price came from. (Ex: 'user:price')
+    pub price_type: Option<String>, //price_type is the type of price. (Ex: 'last' or 'unknown')
+    pub value_num: i64,             //value_num is the numerator of the price.
+    pub value_denom: i64,           //value_denom is the denominator of the price.
+}
+
+pub const FIELDS: &str = "guid,commodity_guid,currency_guid,date,source,type,value_num,value_denom";
+
+/// SOURCE_USER_PRICE is the source GnuCash gives prices that were entered by hand.
+pub const SOURCE_USER_PRICE: &str = "user:price";
+
+/// SOURCE_TRANSFER is the source GnuCash gives prices recorded from a transfer between accounts
+/// in different commodities.
+pub const SOURCE_TRANSFER: &str = "user:xfer-dialog";
+
+/// PRICE_TYPE_TRANSACTION is the type GnuCash gives prices that come from a transaction.
+pub const PRICE_TYPE_TRANSACTION: &str = "transaction";
+
+/// save_new_and_delete_current saves a new price record, but first deletes the
+/// current one with the given price.guid.
+pub fn save_new_and_delete_current(price: Price) -> Result<bool, MoneyManagerError> {
+    let binding_object =
+        serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(&price.guid)])?;
+    dhu::execute("DELETE FROM prices WHERE guid=?", Some(binding_object))?;
+
+    let binding_object = dhu::convert_to_binding_object(&vec![
+        Some(dhu::convert_guid_to_sqlite_string(&price.guid)), //guid
+        Some(dhu::convert_guid_to_sqlite_string(&price.commodity_guid)), //commodity_guid
+        Some(dhu::convert_guid_to_sqlite_string(&price.currency_guid)), //currency_guid
+        Some(price.date),                                      //date
+        price.source,                                          //source
+        price.price_type,                                      //type
+        Some(price.value_num.to_string()),                     //value_num
+        Some(price.value_denom.to_string()),                   //value_denom
+    ]);
+    dhu::execute(
+        &format!("INSERT INTO prices({fields}) VALUES (?,?,?,?,?,?,?,?)", fields = FIELDS),
+        Some(binding_object),
+    )?;
+
+    Ok(true)
+}
+
+/// price_for_value_and_quantity works out the price of one unit of a split's commodity in the
+/// transaction currency, as value / quantity reduced to lowest terms. A split without a quantity
+/// has no price.
+pub fn price_for_value_and_quantity(
+    value_num: i64,
+    value_denom: i64,
+    quantity_num: i64,
+    quantity_denom: i64,
+) -> Option<(i64, i64)> {
+    if value_denom == 0 || quantity_num == 0 {
+        return None;
+    }
+
+    let num = (value_num as i128 * quantity_denom as i128).abs();
+    let denom = (quantity_num as i128 * value_denom as i128).abs();
+    let (mut a, mut b) = (num, denom);
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    let gcd = a.max(1);
+
+    Some((
+        i64::try_from(num / gcd).ok()?,
+        i64::try_from(denom / gcd).ok()?,
+    ))
+}
+
+/// save_price_for_split records the price a split in another commodity than its transaction's
+/// currency was transferred at, like GnuCash does for transfers between currencies. It replaces
+/// any transaction price already recorded for the same commodity, currency, and date.
+pub fn save_price_for_split(
+    split: &splits_manager::Split,
+    commodity_guid: Uuid,
+    currency_guid: Uuid,
+    date: String,
+) -> Result<bool, MoneyManagerError> {
+    let Some((value_num, value_denom)) = price_for_value_and_quantity(
+        split.value_num,
+        split.value_denom,
+        split.quantity_num,
+        split.quantity_denom,
+    ) else {
+        return Ok(false);
+    };
+
+    let binding_object = serde_wasm_bindgen::to_value(&vec![
+        dhu::convert_guid_to_sqlite_string(&commodity_guid),
+        dhu::convert_guid_to_sqlite_string(&currency_guid),
+        date.clone(),
+        PRICE_TYPE_TRANSACTION.to_string(),
+    ])?;
+    dhu::execute(
+        "DELETE FROM prices WHERE commodity_guid=? AND currency_guid=? AND date=? AND type=?",
+        Some(binding_object),
+    )?;
+
+    save_new_and_delete_current(Price {
+        guid: Uuid::new_v4(),
+        commodity_guid,
+        currency_guid,
+        date,
+        source: Some(SOURCE_TRANSFER.to_string()),
+        price_type: Some(PRICE_TYPE_TRANSACTION.to_string()),
+        value_num,
+        value_denom,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn price_for_value_and_quantity_reduces_the_price() {
+        //$100.00 moved into an account that received 92.00 EUR
+        assert_eq!(
+            price_for_value_and_quantity(-10000, 100, -9200, 100),
+            Some((25, 23))
+        );
+        //$150.00 spent on 3 shares of a fund held to 4 decimal places
+        assert_eq!(
+            price_for_value_and_quantity(15000, 100, 30000, 10000),
+            Some((50, 1))
+        );
+        assert_eq!(price_for_value_and_quantity(10000, 100, 0, 100), None);
+    }
+}
@@ -3,6 +3,7 @@ use uuid::Uuid;
 
 use crate::utility::sql_helper_utility as shu;
 use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
 
 pub const SLOT_NAME_NOTES: &str = "notes";
 
@@ -16,6 +17,56 @@ pub const SLOT_NAME_DISPLAY_TRANSACTIONS_OLDER_THAN_ONE_YEAR: &str =
 /// SLOT_NAME_SETTINGS is the correct spelling for settings.
 pub const SLOT_NAME_SETTINGS: &str = "settings";
 
+/// SLOT_NAME_TRANSACTION_TEMPLATE is the name of the string slots holding a memorized
+/// transaction template each, as JSON. The obj_guid of the slot is the template's guid.
+pub const SLOT_NAME_TRANSACTION_TEMPLATE: &str = "transaction_template";
+
+/// SLOT_NAME_CATEGORIZATION_RULE is the name of the string slots holding an auto-categorization
+/// rule each, as JSON. The obj_guid of the slot is the rule's guid.
+pub const SLOT_NAME_CATEGORIZATION_RULE: &str = "categorization_rule";
+
+/// SLOT_NAME_LOCK_DATE is the name of the gdate slot on the book holding the date the books
+/// are closed through. Transactions posted on, or before it can't be changed.
+pub const SLOT_NAME_LOCK_DATE: &str = "closed_period_lock_date";
+
+/// SLOT_NAME_SCHEDULED_TRANSACTION is the name of the frame slot on a scheduled transaction's
+/// template split, holding the slots that say what the split really does.
+pub const SLOT_NAME_SCHEDULED_TRANSACTION: &str = "sched-xaction";
+
+/// SLOT_NAME_SCHEDULED_TRANSACTION_ACCOUNT is the name of the guid slot inside of a template
+/// split's SLOT_NAME_SCHEDULED_TRANSACTION frame, holding the real account of the split.
+pub const SLOT_NAME_SCHEDULED_TRANSACTION_ACCOUNT: &str = "sched-xaction/account";
+
+/// SLOT_TYPE_INT64 is the slot_type GnuCash uses for a slot holding an int64_val.
+pub const SLOT_TYPE_INT64: i64 = 1;
+
+/// SLOT_TYPE_DOUBLE is the slot_type GnuCash uses for a slot holding a double_val.
+pub const SLOT_TYPE_DOUBLE: i64 = 2;
+
+/// SLOT_TYPE_NUMERIC is the slot_type GnuCash uses for a slot holding a numeric_val_num, and
+/// numeric_val_denom.
+pub const SLOT_TYPE_NUMERIC: i64 = 3;
+
+/// SLOT_TYPE_STRING is the slot_type GnuCash uses for a slot holding a string_val.
+pub const SLOT_TYPE_STRING: i64 = 4;
+
+/// SLOT_TYPE_GUID is the slot_type GnuCash uses for a slot holding a guid_val.
+pub const SLOT_TYPE_GUID: i64 = 5;
+
+/// SLOT_TYPE_TIMESPEC is the slot_type GnuCash uses for a slot holding a timespec_val.
+pub const SLOT_TYPE_TIMESPEC: i64 = 6;
+
+/// SLOT_NAME_PAYABLE_ACCOUNT is the name of the guid slot on an employee holding the PAYABLE
+/// account that the employee's expense vouchers are posted to.
+pub const SLOT_NAME_PAYABLE_ACCOUNT: &str = "payable-account";
+
+/// SLOT_TYPE_FRAME is the slot_type GnuCash uses for a slot that holds other slots. The guid_val
+/// of the frame is the obj_guid of the slots inside of it.
+pub const SLOT_TYPE_FRAME: i64 = 9;
+
+/// SLOT_TYPE_GDATE is the slot_type GnuCash uses for a slot holding a gdate_val.
+pub const SLOT_TYPE_GDATE: i64 = 10;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Slot {
     pub id: i64, //id is the Slot's id, it's an autoincrementing integer. Set to -1 to allow it to do that.
@@ -48,26 +99,20 @@ pub fn save_slot_for_name_and_string_val_and_int64_val(
     name: String,
     string_val: String,
     int64_val: i64,
-) -> Result<bool, String> {
+) -> Result<bool, MoneyManagerError> {
     //var db = new sqlContext.Database();
     //// Run a query without reading the results
     //db.run("CREATE TABLE test (col1, col2);");
     //// Insert two rows: (1,111) and (2,222)
     //db.run("INSERT INTO test VALUES (?,?), (?,?)", [1, 111, 2, 222]);
 
-    if crate::DATABASE.lock().unwrap().len() == 0 {
-        return Err(
-            "Please select a database in order to view the account by the given guid.".to_string(),
-        );
-    }
-
     {
         //Delete the slot record
-        let binding_object = serde_wasm_bindgen::to_value(&vec![&name, &string_val]).unwrap();
-        crate::DATABASE.lock().unwrap()[0].run_with_parameters(
+        let binding_object = serde_wasm_bindgen::to_value(&vec![&name, &string_val])?;
+        dhu::execute(
             "DELETE FROM slots WHERE name=? AND string_val=?",
-            binding_object,
-        );
+            Some(binding_object),
+        )?;
 
         //Insert The slot record
         let slot = Slot {
@@ -90,83 +135,219 @@ pub fn save_slot_for_name_and_string_val_and_int64_val(
             &slot.slot_type.to_string(),                          //slot_type
             &slot.int64_val.to_string(),                          //int64_val
             &slot.string_val,                                     //string_val
-        ])
-        .unwrap();
-        crate::DATABASE.lock().unwrap()[0].run_with_parameters("
+        ])?;
+        dhu::execute("
                 INSERT INTO slots(
                                  obj_guid,name,slot_type,int64_val,string_val,double_val,timespec_val,
                                  guid_val,numeric_val_num,numeric_val_denom,gdate_val                                   
                                  ) VALUES (                                                 
                                  ?,       ?,   ?,        ?,        ?,         NULL,      NULL,
-                                 NULL,    NULL,           NULL,             NULL);", binding_object);
+                                 NULL,    NULL,           NULL,             NULL);", Some(binding_object))?;
     }
 
     return Ok(true);
 }
 
-/// load_slots_for_name loads a slot for the given name value.
-pub fn load_slots_for_name(name: String) -> Result<Vec<Slot>, String> {
-    if crate::DATABASE.lock().unwrap().len() == 0 {
-        return Err("Please select a database in order to continue.".to_string());
-    }
-
-    //Prepare a statement
-    let stmt = crate::DATABASE.lock().unwrap()[0].prepare(&shu::load_slots_for_name());
-
-    let binding_object = serde_wasm_bindgen::to_value(&vec![name]).unwrap();
-
-    stmt.bind(binding_object.clone());
-
-    let mut slots = Vec::new();
-
-    while stmt.step() {
-        let row = stmt.getAsObject();
-        //js::log(&("Here is a row: ".to_owned() + &js::stringify(row.clone()).to_owned()));
-
-        let slot: Slot = serde_wasm_bindgen::from_value(row.clone()).unwrap();
-
-        slots.push(slot);
-    }
+/// save_slot saves a new slot record with all of its values. The id is ignored, since the
+/// database will assign the next one.
+pub fn save_slot(slot: Slot) -> Result<bool, MoneyManagerError> {
+    let binding_object = dhu::convert_to_binding_object(&vec![
+        Some(dhu::convert_guid_to_sqlite_string(&slot.obj_guid)), //obj_guid
+        Some(slot.name),                                          //name
+        Some(slot.slot_type.to_string()),                         //slot_type
+        Some(slot.int64_val.to_string()),                         //int64_val
+        Some(slot.string_val),                                    //string_val
+        slot.double_val.map(|value| value.to_string()),           //double_val
+        slot.timespec_val,                                        //timespec_val
+        slot.guid_val,                                            //guid_val
+        slot.numeric_val_num.map(|value| value.to_string()),      //numeric_val_num
+        slot.numeric_val_denom.map(|value| value.to_string()),    //numeric_val_denom
+        slot.gdate_val,                                           //gdate_val
+    ]);
+    dhu::execute(
+        "INSERT INTO slots(
+                          obj_guid,name,slot_type,int64_val,string_val,double_val,timespec_val,
+                          guid_val,numeric_val_num,numeric_val_denom,gdate_val
+                          ) VALUES (?,?,?,?,?,?,?,?,?,?,?)",
+        Some(binding_object),
+    )?;
+
+    Ok(true)
+}
 
-    stmt.free();
+/// load_slots_for_name loads a slot for the given name value.
+pub fn load_slots_for_name(name: String) -> Result<Vec<Slot>, MoneyManagerError> {
+    let binding_object = serde_wasm_bindgen::to_value(&vec![name])?;
 
-    return Ok(slots);
+    dhu::query_rows(&shu::load_slots_for_name(), Some(binding_object))
 }
 
 /// load_slot_for_name_and_string_val loads a slot for the given name, and string_val.
 pub fn load_slots_for_name_and_string_val(
     name: String,
     string_val: String,
-) -> Result<Vec<Slot>, String> {
-    if crate::DATABASE.lock().unwrap().len() == 0 {
-        return Err(
-            "Please select a database in order to load a slot for the given parameters."
-                .to_string(),
-        );
-    }
-
-    //Prepare a statement
-    let stmt =
-        crate::DATABASE.lock().unwrap()[0].prepare(&shu::load_slots_for_name_and_string_val());
+) -> Result<Vec<Slot>, MoneyManagerError> {
+    let binding_object = serde_wasm_bindgen::to_value(&vec![name, string_val])?;
 
-    let binding_object = serde_wasm_bindgen::to_value(&vec![name, string_val]).unwrap();
+    dhu::query_rows(
+        &shu::load_slots_for_name_and_string_val(),
+        Some(binding_object),
+    )
+}
 
-    stmt.bind(binding_object.clone());
+/// save_string_val_for_obj_guid_and_name replaces the slot with the given obj_guid, and name
+/// with a string slot holding the given string_val.
+pub fn save_string_val_for_obj_guid_and_name(
+    obj_guid: Uuid,
+    name: &str,
+    string_val: String,
+) -> Result<bool, MoneyManagerError> {
+    delete_slots_for_obj_guid_and_name(obj_guid, name)?;
+
+    save_slot(Slot {
+        id: -1,
+        obj_guid,
+        name: name.to_string(),
+        slot_type: SLOT_TYPE_STRING,
+        int64_val: 0,
+        string_val,
+        double_val: None,
+        timespec_val: None,
+        guid_val: None,
+        numeric_val_num: None,
+        numeric_val_denom: None,
+        gdate_val: None,
+    })
+}
 
-    let mut slots = Vec::new();
+/// delete_slots_for_obj_guid_and_name deletes the slots with the given obj_guid, and name.
+pub fn delete_slots_for_obj_guid_and_name(
+    obj_guid: Uuid,
+    name: &str,
+) -> Result<bool, MoneyManagerError> {
+    let binding_object = serde_wasm_bindgen::to_value(&vec![
+        dhu::convert_guid_to_sqlite_string(&obj_guid),
+        name.to_string(),
+    ])?;
+    dhu::execute(
+        "DELETE FROM slots WHERE obj_guid=? AND name=?",
+        Some(binding_object),
+    )?;
+
+    Ok(true)
+}
 
-    while stmt.step() {
-        let row = stmt.getAsObject();
-        //js::log(&("Here is a row: ".to_owned() + &js::stringify(row.clone()).to_owned()));
+/// GuidVal holds just the guid_val of a slot.
+#[derive(Debug, Deserialize)]
+struct GuidVal {
+    guid_val: Option<Uuid>,
+}
 
-        let slot: Slot = serde_wasm_bindgen::from_value(row.clone()).unwrap();
+/// load_guid_val_for_obj_guid_and_name loads the guid_val of the slot with the given obj_guid,
+/// and name, if there is one.
+pub fn load_guid_val_for_obj_guid_and_name(
+    obj_guid: Uuid,
+    name: &str,
+) -> Result<Option<Uuid>, MoneyManagerError> {
+    let binding_object = serde_wasm_bindgen::to_value(&vec![
+        dhu::convert_guid_to_sqlite_string(&obj_guid),
+        name.to_string(),
+    ])?;
+
+    let slots: Vec<GuidVal> = dhu::query_rows(
+        "SELECT guid_val FROM slots WHERE obj_guid=? AND name=? LIMIT 1",
+        Some(binding_object),
+    )?;
+
+    Ok(slots.into_iter().next().and_then(|slot| slot.guid_val))
+}
 
-        slots.push(slot);
+/// save_guid_val_for_obj_guid_and_name replaces the slot with the given obj_guid, and name
+/// with a guid slot holding the given guid_val. A None guid_val just removes the slot.
+pub fn save_guid_val_for_obj_guid_and_name(
+    obj_guid: Uuid,
+    name: &str,
+    guid_val: Option<Uuid>,
+) -> Result<bool, MoneyManagerError> {
+    let binding_object = serde_wasm_bindgen::to_value(&vec![
+        dhu::convert_guid_to_sqlite_string(&obj_guid),
+        name.to_string(),
+    ])?;
+    dhu::execute(
+        "DELETE FROM slots WHERE obj_guid=? AND name=?",
+        Some(binding_object),
+    )?;
+
+    match guid_val {
+        Some(guid_val) => save_slot(Slot {
+            id: -1,
+            obj_guid,
+            name: name.to_string(),
+            slot_type: SLOT_TYPE_GUID,
+            int64_val: 0,
+            string_val: String::new(),
+            double_val: None,
+            timespec_val: None,
+            guid_val: Some(dhu::convert_guid_to_sqlite_string(&guid_val)),
+            numeric_val_num: None,
+            numeric_val_denom: None,
+            gdate_val: None,
+        }),
+        None => Ok(true),
     }
+}
 
-    stmt.free();
+/// GdateVal holds just the gdate_val of a slot.
+#[derive(Debug, Deserialize)]
+struct GdateVal {
+    gdate_val: Option<String>,
+}
 
-    return Ok(slots);
+/// load_gdate_val_for_obj_guid_and_name loads the gdate_val of the slot with the given obj_guid,
+/// and name, if there is one. (Ex: '20241231')
+pub fn load_gdate_val_for_obj_guid_and_name(
+    obj_guid: Uuid,
+    name: &str,
+) -> Result<Option<String>, MoneyManagerError> {
+    let binding_object = serde_wasm_bindgen::to_value(&vec![
+        dhu::convert_guid_to_sqlite_string(&obj_guid),
+        name.to_string(),
+    ])?;
+
+    let slots: Vec<GdateVal> = dhu::query_rows(
+        "SELECT gdate_val FROM slots WHERE obj_guid=? AND name=? LIMIT 1",
+        Some(binding_object),
+    )?;
+
+    Ok(slots.into_iter().next().and_then(|slot| slot.gdate_val))
+}
+
+/// save_gdate_val_for_obj_guid_and_name replaces the slot with the given obj_guid, and name
+/// with a gdate slot holding the given gdate_val. A None gdate_val just removes the slot.
+pub fn save_gdate_val_for_obj_guid_and_name(
+    obj_guid: Uuid,
+    name: &str,
+    gdate_val: Option<String>,
+) -> Result<bool, MoneyManagerError> {
+    delete_slots_for_obj_guid_and_name(obj_guid, name)?;
+
+    match gdate_val {
+        Some(gdate_val) => save_slot(Slot {
+            id: -1,
+            obj_guid,
+            name: name.to_string(),
+            slot_type: SLOT_TYPE_GDATE,
+            int64_val: 0,
+            string_val: String::new(),
+            double_val: None,
+            timespec_val: None,
+            guid_val: None,
+            numeric_val_num: None,
+            numeric_val_denom: None,
+            gdate_val: Some(gdate_val),
+        }),
+        None => Ok(true),
+    }
 }
 
 // pub fn read_row_into_new_slot(incoming_row: &rusqlite::Row<'_>) -> Result<Slot> {
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
 use crate::utility::sql_helper_utility as shu;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -30,40 +32,90 @@ pub fn retrieve_splits_for_dates_report(
     from_date: chrono::NaiveDateTime,
     thru_date: chrono::NaiveDateTime,
     incoming_account_type: String,
-) -> Vec<SplitWithTransactionInformation> {
-    let mut splits = Vec::new();
+) -> Result<Vec<SplitWithTransactionInformation>, MoneyManagerError> {
+    let mut parameters = dhu::convert_dates_to_post_date_bounds(
+        from_date.date().and_time(chrono::NaiveTime::MIN),
+        thru_date
+            .date()
+            .and_hms_opt(23, 59, 59)
+            .unwrap_or(thru_date),
+    );
+    parameters.push(incoming_account_type);
+    let binding_object = serde_wasm_bindgen::to_value(&parameters)?;
 
-    if crate::DATABASE.lock().unwrap().len() == 0 {
-        panic!("Please select a database in order to continue!");
-    }
+    dhu::query_rows(
+        &shu::load_splits_for_last_30_day_report(),
+        Some(binding_object),
+    )
+}
 
-    {
-        let stmt =
-            crate::DATABASE.lock().unwrap()[0].prepare(&shu::load_splits_for_last_30_day_report());
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Split {
+    pub guid: Uuid,              //guid is the guid for this split.
+    pub tx_guid: Uuid,           //tx_GUID is the transaction guid that this split belongs to.
+    pub account_guid: Uuid,      //account_guid is the account guid that is in this transaction.
+    pub memo: String,            //memo is the memo about this split.
+    pub action: String,          //action is the associated action with this split.
+    pub reconcile_state: String, //reconcile_state is the whether the item has been reconciled or not as 'n', 'c' or 'y'
+    pub reconcile_date: Option<String>, //reconcile_date is the datetime that this split had been reconciled, or null.
+    pub value_num: i64,       //value_num is the value of this split in the transaction's currency.
+    pub value_denom: i64,     //value_denom is the denomination of this split. (Ex: 100 means divide by 100 to get the value.)
+    pub quantity_num: i64,    //quantity_num is the quantity of this split in the account's commodity.
+    pub quantity_denom: i64,  //quantity_denom is the denomination of the quantity.
+    pub lot_guid: Option<Uuid>, //lot_guid is the lot's guid of this split, or null if not applicable.
+}
 
-        let binding_object = serde_wasm_bindgen::to_value(&vec![
-            &from_date.format("%Y-%m-%d 00:00:00").to_string(),
-            &thru_date.format("%Y-%m-%d 23:59:59").to_string(),
-            &incoming_account_type,
-        ])
-        .unwrap();
+pub const FIELDS: &str = "guid,tx_guid,account_guid,memo,action,reconcile_state,reconcile_date,value_num,value_denom,quantity_num,quantity_denom,lot_guid";
 
-        stmt.bind(binding_object.clone());
+/// save_split saves a new split record. It does not delete any existing split first, so
+/// make sure the transaction's old splits are gone if you are replacing them.
+pub fn save_split(split: Split) -> Result<bool, MoneyManagerError> {
+    let binding_object = dhu::convert_to_binding_object(&vec![
+        Some(dhu::convert_guid_to_sqlite_string(&split.guid)), //guid
+        Some(dhu::convert_guid_to_sqlite_string(&split.tx_guid)), //tx_guid
+        Some(dhu::convert_guid_to_sqlite_string(&split.account_guid)), //account_guid
+        Some(split.memo),                                      //memo
+        Some(split.action),                                    //action
+        Some(split.reconcile_state),                           //reconcile_state
+        split.reconcile_date,                                  //reconcile_date
+        Some(split.value_num.to_string()),                     //value_num
+        Some(split.value_denom.to_string()),                   //value_denom
+        Some(split.quantity_num.to_string()),                  //quantity_num
+        Some(split.quantity_denom.to_string()),                //quantity_denom
+        dhu::convert_option_guid_to_sqlite_string(&split.lot_guid), //lot_guid
+    ]);
 
-        while stmt.step() {
-            let row = stmt.getAsObject();
-            //js::log(&("Here is a row: ".to_owned() + &js::stringify(row.clone()).to_owned()));
+    dhu::execute(
+        &format!(
+            "INSERT INTO splits({fields}) VALUES (?,?,?,?,?,?,?,?,?,?,?,?)",
+            fields = FIELDS
+        ),
+        Some(binding_object),
+    )?;
 
-            let split: SplitWithTransactionInformation =
-                serde_wasm_bindgen::from_value(row.clone()).unwrap();
+    Ok(true)
+}
 
-            splits.push(split);
-        }
+/// TaxSplit is a split posted to an account used by a tax table.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TaxSplit {
+    pub post_date: String,    //post_date is the post date of the split's transaction.
+    pub account_name: String, //account_name is the name of the tax account.
+    pub value_num: i64,       //value_num is the value of this split.
+    pub value_denom: i64,     //value_denom is the denomination of this split.
+    pub txn_type: String,     //txn_type is the transaction's trans-txn-type. (Ex: 'I' for a posted invoice, or bill)
+}
 
-        //Free the memory for the statement, and the bindings
-        stmt.free();
-        stmt.freemem();
-    }
+/// retrieve_tax_splits_between_dates retrieves the splits posted to tax table accounts between
+/// the given dates, including both dates.
+pub fn retrieve_tax_splits_between_dates(
+    from_date: chrono::NaiveDate,
+    thru_date: chrono::NaiveDate,
+) -> Result<Vec<TaxSplit>, MoneyManagerError> {
+    let binding_object = serde_wasm_bindgen::to_value(&dhu::convert_dates_to_post_date_bounds(
+        from_date.and_time(chrono::NaiveTime::MIN),
+        thru_date.and_hms_opt(23, 59, 59).unwrap_or_default(),
+    ))?;
 
-    return splits;
+    dhu::query_rows(&shu::load_tax_splits_between_dates(), Some(binding_object))
 }
@@ -0,0 +1,255 @@
+use serde::{Deserialize, Serialize};
+use serde_repr::*;
+use uuid::Uuid;
+
+use crate::database_tables::accounts_manager::Bool;
+use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
+use crate::utility::sql_helper_utility as shu;
+
+/// TAX_AMOUNT_DENOM is the denominator used when saving a tax table entry amount,
+/// so a percent like 6.625 can be stored exactly.
+pub const TAX_AMOUNT_DENOM: i64 = 100000;
+
+/// TaxAmountType is how the amount of a tax table entry is applied, matching GnuCash's values.
+#[derive(Serialize_repr, Deserialize_repr, PartialEq, Debug, Clone, Copy)]
+#[repr(u8)]
+pub enum TaxAmountType {
+    Value = 1,
+    Percent = 2,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TaxTable {
+    pub guid: Uuid,           //guid is the GUID for this tax table.
+    pub name: String,         //name is the name of the tax table. (Ex: 'State Sales Tax')
+    pub refcount: i64,        //refcount is the number of customers, vendors and entries using this table.
+    pub invisible: Bool,      //invisible is whether this tax table is hidden from selection.
+    pub parent: Option<Uuid>, //parent is the tax table this one was copied from, or null.
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TaxTableEntry {
+    pub id: i64, //id is the entry's id, it's an autoincrementing integer. Set to -1 to allow it to do that.
+    pub taxtable: Uuid, //taxtable is the guid of the tax table this entry belongs to.
+    pub account: Uuid,  //account is the guid of the account the tax is posted to.
+    pub amount_num: i64, //amount_num is the numerator of the percent or value of this entry.
+    pub amount_denom: i64, //amount_denom is the denominator of the percent or value of this entry.
+    #[serde(rename = "type")]
+    pub amount_type: TaxAmountType, //amount_type is whether the amount is a percent or a fixed value.
+}
+
+/// TaxTableUsages holds how many records are using a tax table.
+#[derive(Debug, Deserialize)]
+struct TaxTableUsages {
+    usages: i64,
+}
+
+pub const FIELDS: &str = "guid,name,refcount,invisible,parent";
+
+pub const ENTRY_FIELDS: &str = "id,taxtable,account,amount_num,amount_denom,type";
+
+impl TaxTableEntry {
+    /// amount returns the amount of this entry as a float, either a percent or a value.
+    pub fn amount(&self) -> f64 {
+        self.amount_num as f64 / self.amount_denom as f64
+    }
+}
+
+/// calculate_tax calculates the tax for the given subtotal using the given tax table entries.
+/// If tax_included is true, the subtotal already has the tax in it, and it is backed out first.
+/// It returns the net amount before tax, and the tax amount for each of the tax accounts.
+pub fn calculate_tax(
+    subtotal: f64,
+    tax_included: bool,
+    taxtable_entries: &[TaxTableEntry],
+) -> (f64, Vec<(Uuid, f64)>) {
+    let total_percent: f64 = taxtable_entries
+        .iter()
+        .filter(|entry| entry.amount_type == TaxAmountType::Percent)
+        .map(|entry| entry.amount())
+        .sum();
+    let total_value: f64 = taxtable_entries
+        .iter()
+        .filter(|entry| entry.amount_type == TaxAmountType::Value)
+        .map(|entry| entry.amount())
+        .sum();
+
+    let net = if tax_included {
+        (subtotal - total_value) / (1.0 + total_percent / 100.0)
+    } else {
+        subtotal
+    };
+
+    let taxes = taxtable_entries
+        .iter()
+        .map(|entry| match entry.amount_type {
+            TaxAmountType::Percent => (entry.account, net * entry.amount() / 100.0),
+            TaxAmountType::Value => (entry.account, entry.amount()),
+        })
+        .collect();
+
+    (net, taxes)
+}
+
+/// retrieve_all_taxtables retrieves all the tax tables, ordered by name.
+pub fn retrieve_all_taxtables() -> Result<Vec<TaxTable>, MoneyManagerError> {
+    dhu::query_rows(&format!("SELECT {} FROM taxtables ORDER BY name", FIELDS), None)
+}
+
+/// retrieve_taxtable_for_guid retrieves the tax table for the given guid as a result.
+pub fn retrieve_taxtable_for_guid(taxtable_guid: Uuid) -> Result<TaxTable, MoneyManagerError> {
+    let binding_object =
+        serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(&taxtable_guid)])?;
+
+    dhu::query_first_row(
+        &format!("SELECT {} FROM taxtables WHERE guid=?", FIELDS),
+        Some(binding_object),
+        "No tax table for this guid",
+    )
+}
+
+/// retrieve_taxtable_entries_for_taxtable_guid retrieves the entries for the given tax table.
+pub fn retrieve_taxtable_entries_for_taxtable_guid(
+    taxtable_guid: Uuid,
+) -> Result<Vec<TaxTableEntry>, MoneyManagerError> {
+    let binding_object =
+        serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(&taxtable_guid)])?;
+
+    dhu::query_rows(
+        &format!(
+            "SELECT {} FROM taxtable_entries WHERE taxtable=? ORDER BY id",
+            ENTRY_FIELDS
+        ),
+        Some(binding_object),
+    )
+}
+
+/// save_new_and_delete_current saves a tax table along with its entries, but first deletes the
+/// current tax table, and entries with the given taxtable.guid.
+pub fn save_new_and_delete_current(
+    taxtable: TaxTable,
+    taxtable_entries: Vec<TaxTableEntry>,
+) -> Result<bool, MoneyManagerError> {
+    dhu::require_database()?;
+
+    if taxtable.name.trim().is_empty() {
+        return Err(MoneyManagerError::Validation(
+            "Please give the tax table a name.".to_string(),
+        ));
+    }
+
+    //Delete the tax table, and its entries first
+    let binding_object =
+        serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(&taxtable.guid)])?;
+    dhu::execute("DELETE FROM taxtables WHERE guid=?", Some(binding_object.clone()))?;
+    dhu::execute(
+        "DELETE FROM taxtable_entries WHERE taxtable=?",
+        Some(binding_object),
+    )?;
+
+    //Insert The Tax Table Record
+    let binding_object = dhu::convert_to_binding_object(&vec![
+        Some(dhu::convert_guid_to_sqlite_string(&taxtable.guid)), //guid
+        Some(taxtable.name),                                      //name
+        Some(taxtable.refcount.to_string()),                      //refcount
+        Some(if taxtable.invisible == Bool::True { "1" } else { "0" }.to_string()), //invisible
+        dhu::convert_option_guid_to_sqlite_string(&taxtable.parent), //parent
+    ]);
+    dhu::execute(
+        &format!("INSERT INTO taxtables({}) VALUES (?,?,?,?,?)", FIELDS),
+        Some(binding_object),
+    )?;
+
+    //Insert each of the entries
+    for taxtable_entry in taxtable_entries {
+        let binding_object = serde_wasm_bindgen::to_value(&vec![
+            dhu::convert_guid_to_sqlite_string(&taxtable.guid), //taxtable
+            dhu::convert_guid_to_sqlite_string(&taxtable_entry.account), //account
+            taxtable_entry.amount_num.to_string(),              //amount_num
+            taxtable_entry.amount_denom.to_string(),            //amount_denom
+            (taxtable_entry.amount_type as u8).to_string(),     //type
+        ])?;
+        dhu::execute(
+            "INSERT INTO taxtable_entries(taxtable,account,amount_num,amount_denom,type)
+                                  VALUES (?,?,?,?,?)",
+            Some(binding_object),
+        )?;
+    }
+
+    Ok(true)
+}
+
+/// delete_taxtable deletes the tax table with the given guid, and its entries. It refuses
+/// to delete a tax table that is still used by an invoice or bill line.
+pub fn delete_taxtable(taxtable_guid: Uuid) -> Result<bool, MoneyManagerError> {
+    let binding_object = serde_wasm_bindgen::to_value(&vec![
+        dhu::convert_guid_to_sqlite_string(&taxtable_guid),
+        dhu::convert_guid_to_sqlite_string(&taxtable_guid),
+        dhu::convert_guid_to_sqlite_string(&taxtable_guid),
+        dhu::convert_guid_to_sqlite_string(&taxtable_guid),
+    ])?;
+
+    let usages: i64 = dhu::query_rows::<TaxTableUsages>(
+        &shu::load_count_of_taxtable_usages(),
+        Some(binding_object),
+    )?
+    .iter()
+    .map(|count| count.usages)
+    .sum();
+
+    if usages > 0 {
+        return Err(MoneyManagerError::Validation(format!(
+            "This tax table is still used {} time(s) by invoices, bills, customers or vendors.",
+            usages
+        )));
+    }
+
+    let binding_object =
+        serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(&taxtable_guid)])?;
+    dhu::execute("DELETE FROM taxtables WHERE guid=?", Some(binding_object.clone()))?;
+    dhu::execute(
+        "DELETE FROM taxtable_entries WHERE taxtable=?",
+        Some(binding_object),
+    )?;
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(amount_num: i64, amount_type: TaxAmountType) -> TaxTableEntry {
+        TaxTableEntry {
+            id: -1,
+            taxtable: Uuid::nil(),
+            account: Uuid::nil(),
+            amount_num,
+            amount_denom: TAX_AMOUNT_DENOM,
+            amount_type,
+        }
+    }
+
+    #[test]
+    fn calculate_tax_adds_percent_to_net() {
+        let (net, taxes) = calculate_tax(100.0, false, &[entry(625000, TaxAmountType::Percent)]);
+        assert_eq!(net, 100.0);
+        assert!((taxes[0].1 - 6.25).abs() < 0.000001);
+    }
+
+    #[test]
+    fn calculate_tax_backs_out_included_tax() {
+        let (net, taxes) = calculate_tax(
+            115.0,
+            true,
+            &[
+                entry(1000000, TaxAmountType::Percent),
+                entry(500000, TaxAmountType::Value),
+            ],
+        );
+        assert!((net - 100.0).abs() < 0.000001);
+        assert!((taxes[0].1 - 10.0).abs() < 0.000001);
+        assert!((taxes[1].1 - 5.0).abs() < 0.000001);
+    }
+}
@@ -0,0 +1,259 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::database_tables::slots_manager;
+use crate::utility::error_helper_utility::MoneyManagerError;
+
+/// BALANCE_TOLERANCE is how far the fixed amounts of a template may be from adding up to zero,
+/// since they are entered as floats.
+const BALANCE_TOLERANCE: f64 = 0.000001;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TransactionTemplateSplit {
+    pub account_guid: Option<Uuid>, //account_guid is the split's account, or None for the account the template is entered from.
+    pub amount: Option<f64>, //amount is the value of the split, positive for an increase, or None to fill it in when entered.
+    pub memo: String,        //memo is the memo for the split.
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TransactionTemplate {
+    pub guid: Uuid,          //guid is the GUID for this template, and the obj_guid of its slot.
+    pub name: String,        //name is the name of the template. (Ex: 'Paycheck')
+    pub description: String, //description is the description of the transactions entered from it.
+    pub num: String,         //num is the num of the transactions entered from it.
+    pub memo: String,        //memo is the notes of the transactions entered from it.
+    pub splits: Vec<TransactionTemplateSplit>, //splits are the splits of the transactions entered from it.
+}
+
+impl TransactionTemplateSplit {
+    /// is_for_account checks whether this split belongs to the given account, which is the
+    /// case for splits without an account too, since they take the account they're entered from.
+    pub fn is_for_account(&self, account_guid: Uuid) -> bool {
+        self.account_guid.is_none_or(|split_account_guid| split_account_guid == account_guid)
+    }
+}
+
+impl TransactionTemplate {
+    /// category_splits returns the split for the given account, and the split for the category,
+    /// when this template fits the transaction editor's single category. That is two splits,
+    /// one of them for the given account, and no split memos.
+    pub fn category_splits(
+        &self,
+        account_guid: Uuid,
+    ) -> Option<(&TransactionTemplateSplit, &TransactionTemplateSplit)> {
+        if self.splits.len() != 2 || self.splits.iter().any(|split| !split.memo.trim().is_empty())
+        {
+            return None;
+        }
+        match (
+            self.splits[0].is_for_account(account_guid),
+            self.splits[1].is_for_account(account_guid),
+        ) {
+            (true, false) if self.splits[1].account_guid.is_some() => {
+                Some((&self.splits[0], &self.splits[1]))
+            }
+            (false, true) if self.splits[0].account_guid.is_some() => {
+                Some((&self.splits[1], &self.splits[0]))
+            }
+            _ => None,
+        }
+    }
+
+    /// resolve_splits fills in the accounts, and blank amounts of this template's splits for
+    /// entering it from the given account. The given account's split gets the given amount when
+    /// it's blank, and one other blank split can be left to balance the rest.
+    pub fn resolve_splits(
+        &self,
+        account_guid: Uuid,
+        account_amount: Option<f64>,
+    ) -> Result<Vec<TransactionTemplateSplit>, MoneyManagerError> {
+        let mut splits: Vec<TransactionTemplateSplit> = self
+            .splits
+            .iter()
+            .map(|split| TransactionTemplateSplit {
+                account_guid: Some(split.account_guid.unwrap_or(account_guid)),
+                amount: split.amount.or(if split.is_for_account(account_guid) {
+                    account_amount
+                } else {
+                    None
+                }),
+                memo: split.memo.clone(),
+            })
+            .collect();
+
+        let blank_splits = splits.iter().filter(|split| split.amount.is_none()).count();
+        if blank_splits > 1 {
+            return Err(MoneyManagerError::Validation(format!(
+                "The template '{}' has {} blank amounts, but only one can be worked out.",
+                self.name, blank_splits
+            )));
+        }
+
+        let total: f64 = splits.iter().filter_map(|split| split.amount).sum();
+        if let Some(blank_split) = splits.iter_mut().find(|split| split.amount.is_none()) {
+            blank_split.amount = Some(-total);
+        } else if total.abs() > BALANCE_TOLERANCE {
+            return Err(MoneyManagerError::Validation(format!(
+                "The amounts of the template '{}' are off by {}, so it doesn't balance.",
+                self.name, total
+            )));
+        }
+
+        Ok(splits)
+    }
+}
+
+/// validate_transaction_template checks the given template can be entered, which needs a name,
+/// at least two splits, at most one split without an account, and fixed amounts that balance
+/// when none of them are blank.
+pub fn validate_transaction_template(
+    template: &TransactionTemplate,
+) -> Result<(), MoneyManagerError> {
+    if template.name.trim().is_empty() {
+        return Err(MoneyManagerError::Validation(
+            "Please enter a name for the template.".to_string(),
+        ));
+    }
+    if template.splits.len() < 2 {
+        return Err(MoneyManagerError::Validation(
+            "A template needs at least two splits.".to_string(),
+        ));
+    }
+    if template.splits.iter().filter(|split| split.account_guid.is_none()).count() > 1 {
+        return Err(MoneyManagerError::Validation(
+            "Only one split of a template can be for the account it's entered from.".to_string(),
+        ));
+    }
+    if template.splits.iter().all(|split| split.amount.is_some()) {
+        template.resolve_splits(Uuid::nil(), None)?;
+    }
+
+    Ok(())
+}
+
+/// find_transaction_template_for_name finds the template with the given name, ignoring case,
+/// and surrounding spaces.
+pub fn find_transaction_template_for_name<'a>(
+    templates: &'a [TransactionTemplate],
+    name: &str,
+) -> Option<&'a TransactionTemplate> {
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+    templates
+        .iter()
+        .find(|template| template.name.trim().eq_ignore_ascii_case(name))
+}
+
+/// retrieve_all_transaction_templates retrieves every transaction template, ordered by name.
+pub fn retrieve_all_transaction_templates() -> Result<Vec<TransactionTemplate>, MoneyManagerError>
+{
+    let slots = slots_manager::load_slots_for_name(
+        slots_manager::SLOT_NAME_TRANSACTION_TEMPLATE.to_string(),
+    )?;
+
+    let mut templates = Vec::new();
+    for slot in slots {
+        templates.push(serde_json::from_str::<TransactionTemplate>(&slot.string_val)?);
+    }
+    templates.sort_by_key(|template| template.name.to_lowercase());
+
+    Ok(templates)
+}
+
+/// retrieve_transaction_template_for_guid retrieves the transaction template with the given guid.
+pub fn retrieve_transaction_template_for_guid(
+    guid: Uuid,
+) -> Result<TransactionTemplate, MoneyManagerError> {
+    retrieve_all_transaction_templates()?
+        .into_iter()
+        .find(|template| template.guid == guid)
+        .ok_or(MoneyManagerError::NotFound(
+            "The transaction template was not found.".to_string(),
+        ))
+}
+
+/// save_transaction_template saves the given template into its slot, replacing the current one
+/// with the same guid.
+pub fn save_transaction_template(template: TransactionTemplate) -> Result<bool, MoneyManagerError> {
+    validate_transaction_template(&template)?;
+
+    slots_manager::save_string_val_for_obj_guid_and_name(
+        template.guid,
+        slots_manager::SLOT_NAME_TRANSACTION_TEMPLATE,
+        serde_json::to_string(&template)?,
+    )
+}
+
+/// delete_transaction_template deletes the slot of the template with the given guid.
+pub fn delete_transaction_template(guid: Uuid) -> Result<bool, MoneyManagerError> {
+    slots_manager::delete_slots_for_obj_guid_and_name(
+        guid,
+        slots_manager::SLOT_NAME_TRANSACTION_TEMPLATE,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(account_guid: Option<Uuid>, amount: Option<f64>) -> TransactionTemplateSplit {
+        TransactionTemplateSplit {
+            account_guid,
+            amount,
+            memo: String::new(),
+        }
+    }
+
+    fn template(splits: Vec<TransactionTemplateSplit>) -> TransactionTemplate {
+        TransactionTemplate {
+            guid: Uuid::new_v4(),
+            name: "Paycheck".to_string(),
+            description: "ACME Payroll".to_string(),
+            num: String::new(),
+            memo: String::new(),
+            splits,
+        }
+    }
+
+    #[test]
+    fn resolve_splits_fills_in_the_blank_amounts() {
+        let checking = Uuid::new_v4();
+        let salary = Uuid::new_v4();
+        let taxes = Uuid::new_v4();
+        let paycheck = template(vec![
+            split(None, None),
+            split(Some(taxes), Some(250.0)),
+            split(Some(salary), Some(-1000.0)),
+        ]);
+
+        let splits = paycheck.resolve_splits(checking, None).unwrap();
+        assert_eq!(splits[0].account_guid, Some(checking));
+        assert_eq!(splits[0].amount, Some(750.0));
+
+        assert!(paycheck.resolve_splits(checking, Some(700.0)).is_err());
+        assert!(template(vec![split(None, None), split(Some(salary), None)])
+            .resolve_splits(checking, None)
+            .is_err());
+    }
+
+    #[test]
+    fn category_splits_only_fits_two_splits_with_the_account() {
+        let checking = Uuid::new_v4();
+        let groceries = Uuid::new_v4();
+        let grocery_run = template(vec![split(Some(groceries), Some(50.0)), split(None, None)]);
+
+        let (account_split, category_split) = grocery_run.category_splits(checking).unwrap();
+        assert_eq!(account_split.account_guid, None);
+        assert_eq!(category_split.account_guid, Some(groceries));
+        assert!(grocery_run.category_splits(groceries).is_none());
+
+        let templates = vec![grocery_run.clone()];
+        assert_eq!(
+            find_transaction_template_for_name(&templates, " paycheck "),
+            Some(&grocery_run)
+        );
+        assert!(validate_transaction_template(&template(vec![split(None, None)])).is_err());
+    }
+}
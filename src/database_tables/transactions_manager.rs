@@ -1,31 +1,20 @@
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
-use crate::database_tables::accounts_manager::Account;
+use crate::database_tables::accounts_manager::AccountWithBalance;
+use crate::database_tables::books_manager;
+use crate::database_tables::prices_manager;
 use crate::database_tables::slots_manager;
+use crate::database_tables::splits_manager;
 use crate::database_tables::transactions_manager;
 use crate::utility::database_helper_utility as dhu;
-use crate::utility::js_helper_utility as js;
+use crate::utility::error_helper_utility::MoneyManagerError;
 use crate::utility::sql_helper_utility as shu;
 use chrono::prelude::*;
 use uuid::Uuid;
 //use time::Duration;
 use serde_repr::*;
-use std::fmt;
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub enum AccountType {
-    ASSET,
-    BANK,
-    CASH,
-    CREDIT,
-    EQUITY,
-    EXPENSE,
-    INCOME,
-    LIABILITY,
-    RECEIVABLE,
-    ROOT,
-}
+use std::collections::HashMap;
 
 #[allow(dead_code)]
 #[derive(Serialize_repr, Deserialize_repr, PartialEq, Debug, Clone)]
@@ -35,12 +24,6 @@ pub enum Bool {
     True = 1,
 }
 
-impl std::fmt::Display for AccountType {
-    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        write!(fmt, "{:?}", self)
-    }
-}
-
 // fn convert_to_account_type(incoming_result : Result<String>) -> Result<AccountType> {
 //     let incoming_account_type : String = incoming_result.unwrap();
 //     match incoming_account_type.as_str() {
@@ -93,6 +76,8 @@ pub struct TransactionWithSplitInformation {
     pub account_name: String, //account_name is the account.name for this transaction
     pub account_guid: Uuid, //account_guid is the account.guid for this transaction
     pub memo: String,       //memo is the memo attached to this transaction
+    #[serde(default)]
+    pub balance: Option<f64>, //balance is the account's balance after this transaction, when the query works it out.
 }
 
 //_Fields: guid,name,account_type,commodity_guid,commodity_scu,non_std_scu,
@@ -107,82 +92,171 @@ pub fn _fields() -> String {
     )
 }
 
-/// delete_transaction deletes a transaction for the given transaction guid, along with the splits
-/// associated with it.
-pub fn delete_transaction(transaction_guid: Uuid) -> Result<bool, String> {
-    if crate::DATABASE.lock().unwrap().len() == 0 {
-        return Err(
-            "Please select a database in order to view the account by the given guid.".to_string(),
-        );
+/// retrieve_lock_date retrieves the date the book is closed through, if it has one. A book
+/// without a books record can't have one.
+pub fn retrieve_lock_date() -> Result<Option<NaiveDate>, MoneyManagerError> {
+    let Ok(book) = books_manager::retrieve_book() else {
+        return Ok(None);
+    };
+    let lock_date = slots_manager::load_gdate_val_for_obj_guid_and_name(
+        book.guid,
+        slots_manager::SLOT_NAME_LOCK_DATE,
+    )?;
+    lock_date
+        .map(|gdate_val| {
+            NaiveDate::parse_from_str(&gdate_val, "%Y%m%d").map_err(|_| {
+                MoneyManagerError::Validation(format!(
+                    "The book's lock date '{}' is not a valid date.",
+                    gdate_val
+                ))
+            })
+        })
+        .transpose()
+}
+
+/// save_lock_date saves the date the book is closed through, or unlocks it for None.
+pub fn save_lock_date(lock_date: Option<NaiveDate>) -> Result<bool, MoneyManagerError> {
+    slots_manager::save_gdate_val_for_obj_guid_and_name(
+        books_manager::retrieve_book()?.guid,
+        slots_manager::SLOT_NAME_LOCK_DATE,
+        lock_date.map(|lock_date| lock_date.format("%Y%m%d").to_string()),
+    )
+}
+
+/// check_post_date_is_open checks that a transaction posted on the given post_date is after the
+/// given lock date, so it can be changed.
+pub fn check_post_date_is_open(
+    post_date: &str,
+    lock_date: NaiveDate,
+) -> Result<(), MoneyManagerError> {
+    let posted_date = dhu::convert_string_to_date(post_date)?.date();
+    if posted_date <= lock_date {
+        return Err(MoneyManagerError::Validation(format!(
+            "The books are closed through {}, so a transaction posted on {} can't be changed.",
+            lock_date.format("%Y-%m-%d"),
+            posted_date.format("%Y-%m-%d")
+        )));
     }
+    Ok(())
+}
 
+/// PostDateRow holds just the post_date of a transaction.
+#[derive(Debug, Deserialize)]
+struct PostDateRow {
+    post_date: Option<String>,
+}
+
+/// check_transaction_is_open checks that the transaction with the given guid isn't in the closed
+/// period, both as it's saved now, and with the given post_date it's about to be saved with.
+pub fn check_transaction_is_open(
+    transaction_guid: Uuid,
+    post_date: Option<&str>,
+) -> Result<(), MoneyManagerError> {
+    let Some(lock_date) = retrieve_lock_date()? else {
+        return Ok(());
+    };
+    let binding_object = serde_wasm_bindgen::to_value(&vec![dhu::convert_guid_to_sqlite_string(
+        &transaction_guid,
+    )])?;
+    let saved_transactions: Vec<PostDateRow> = dhu::query_rows(
+        "SELECT post_date FROM transactions WHERE guid=?",
+        Some(binding_object),
+    )?;
+
+    for post_date in saved_transactions
+        .iter()
+        .filter_map(|saved_transaction| saved_transaction.post_date.as_deref())
+        .chain(post_date)
+    {
+        check_post_date_is_open(post_date, lock_date)?;
+    }
+    Ok(())
+}
+
+/// check_accounts_are_open checks that none of the transactions with splits in the accounts with
+/// the given guids are in the closed period, so the accounts can be merged, or deleted.
+pub fn check_accounts_are_open(account_guids: &[Uuid]) -> Result<(), MoneyManagerError> {
+    let Some(lock_date) = retrieve_lock_date()? else {
+        return Ok(());
+    };
+    for account_guid in account_guids {
+        let binding_object = serde_wasm_bindgen::to_value(&vec![
+            dhu::convert_guid_to_sqlite_string(account_guid),
+        ])?;
+        //The earliest transaction is the one to check, with the dates compared as digits only
+        let earliest_transactions: Vec<PostDateRow> = dhu::query_rows(
+            "SELECT t.post_date AS post_date FROM transactions t
+             JOIN splits s ON s.tx_guid = t.guid
+             WHERE s.account_guid = ? AND t.post_date IS NOT NULL
+             ORDER BY replace(replace(replace(t.post_date,'-',''),':',''),' ','') LIMIT 1",
+            Some(binding_object),
+        )?;
+        for post_date in earliest_transactions
+            .iter()
+            .filter_map(|earliest_transaction| earliest_transaction.post_date.as_deref())
+        {
+            check_post_date_is_open(post_date, lock_date)?;
+        }
+    }
+    Ok(())
+}
+
+/// delete_transaction deletes a transaction for the given transaction guid, along with the splits
+/// associated with it. Transactions in the closed period can't be deleted.
+pub fn delete_transaction(transaction_guid: Uuid) -> Result<bool, MoneyManagerError> {
+    check_transaction_is_open(transaction_guid, None)?;
     {
         //Delete the Transaction Records, and the associated records first
         let binding_object =
             serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(
                 &transaction_guid,
-            )])
-            .unwrap();
-        crate::DATABASE.lock().unwrap()[0]
-            .run_with_parameters("DELETE FROM Transactions WHERE guid=?", binding_object);
+            )])?;
+        dhu::execute("DELETE FROM Transactions WHERE guid=?", Some(binding_object))?;
 
         //Delete the Split records
         let binding_object =
             serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(
                 &transaction_guid,
-            )])
-            .unwrap();
-        crate::DATABASE.lock().unwrap()[0]
-            .run_with_parameters("DELETE FROM splits WHERE tx_guid=?", binding_object);
+            )])?;
+        dhu::execute("DELETE FROM splits WHERE tx_guid=?", Some(binding_object))?;
 
         //Delete the Slot record(s) for this transaction
         let binding_object =
             serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(
                 &transaction_guid,
-            )])
-            .unwrap();
-        crate::DATABASE.lock().unwrap()[0]
-            .run_with_parameters("DELETE FROM slots WHERE obj_guid=@guid", binding_object);
+            )])?;
+        dhu::execute("DELETE FROM slots WHERE obj_guid=@guid", Some(binding_object))?;
     }
 
     return Ok(true);
 }
 
-/// save_transaction saves a transaction, by first deleting the original transaction.
-pub fn save_transaction(txn: TransactionWithSplitInformation) -> Result<bool, String> {
+/// save_transaction saves a transaction, by first deleting the original transaction. Transactions
+/// in the closed period can't be saved.
+pub fn save_transaction(txn: TransactionWithSplitInformation) -> Result<bool, MoneyManagerError> {
+    check_transaction_is_open(txn.guid, Some(&txn.post_date))?;
+
     //var db = new sqlContext.Database();
     //// Run a query without reading the results
     //db.run("CREATE TABLE test (col1, col2);");
     //// Insert two rows: (1,111) and (2,222)
     //db.run("INSERT INTO test VALUES (?,?), (?,?)", [1, 111, 2, 222]);
 
-    if crate::DATABASE.lock().unwrap().len() == 0 {
-        return Err(
-            "Please select a database in order to view the account by the given guid.".to_string(),
-        );
-    }
-
     {
         //Delete the Transaction Records, and the associated records first
         let binding_object =
-            serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(&txn.guid)])
-                .unwrap();
-        crate::DATABASE.lock().unwrap()[0]
-            .run_with_parameters("DELETE FROM Transactions WHERE guid=?", binding_object);
+            serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(&txn.guid)])?;
+        dhu::execute("DELETE FROM Transactions WHERE guid=?", Some(binding_object))?;
 
         //Delete the Split records
         let binding_object =
-            serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(&txn.guid)])
-                .unwrap();
-        crate::DATABASE.lock().unwrap()[0]
-            .run_with_parameters("DELETE FROM splits WHERE tx_guid=?", binding_object);
+            serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(&txn.guid)])?;
+        dhu::execute("DELETE FROM splits WHERE tx_guid=?", Some(binding_object))?;
 
         //Delete the Slot record(s) for this transaction
         let binding_object =
-            serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(&txn.guid)])
-                .unwrap();
-        crate::DATABASE.lock().unwrap()[0]
-            .run_with_parameters("DELETE FROM slots WHERE obj_guid=@guid", binding_object);
+            serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(&txn.guid)])?;
+        dhu::execute("DELETE FROM slots WHERE obj_guid=@guid", Some(binding_object))?;
 
         //Insert The Transaction Record
         let binding_object = serde_wasm_bindgen::to_value(&vec![
@@ -192,16 +266,15 @@ pub fn save_transaction(txn: TransactionWithSplitInformation) -> Result<bool, St
             &txn.post_date,                                 //post_date
             &txn.enter_date,                                //enter_date
             &txn.description,                               //description
-        ])
-        .unwrap();
-        crate::DATABASE.lock().unwrap()[0].run_with_parameters(
+        ])?;
+        dhu::execute(
             "
                 INSERT INTO Transactions(
                                             guid,currency_guid,num,post_date,enter_date,description
                                         ) VALUES (                                                 
                                             ?,    ?,           ?,  ?,        ?,         ?) ",
-            binding_object,
-        );
+            Some(binding_object),
+        )?;
         //js::log(&format!("Transaction GUID '{}'", txn.guid));
 
         //Create the Split to subtract from the From Account
@@ -213,15 +286,14 @@ pub fn save_transaction(txn: TransactionWithSplitInformation) -> Result<bool, St
             &txn.value_denom.to_string(),                         //value_denom
             &(txn.value_num as f64 * -1.0).to_string(),           //quantity_num
             &txn.value_denom.to_string(),                         //quantity_denom
-        ])
-        .unwrap();
-        crate::DATABASE.lock().unwrap()[0].run_with_parameters("
+        ])?;
+        dhu::execute("
                 INSERT INTO Splits(
                                     guid,tx_guid,account_guid,memo,action,reconcile_state,reconcile_date,
                                     value_num,value_denom,quantity_num,quantity_denom,lot_guid
                                 ) VALUES (
                                     ?,   ?,      ?,           '',  '',    'n',            NULL,
-                                    ?,        ?,          ?,           ?,             NULL)", binding_object);
+                                    ?,        ?,          ?,           ?,             NULL)", Some(binding_object))?;
 
         //Create the other Split to add to the To Account
         let binding_object = serde_wasm_bindgen::to_value(&vec![
@@ -232,15 +304,14 @@ pub fn save_transaction(txn: TransactionWithSplitInformation) -> Result<bool, St
             &txn.value_denom.to_string(),                         //value_denom
             &txn.value_num.to_string(),                           //quantity_num
             &txn.value_denom.to_string(),                         //quantity_denom
-        ])
-        .unwrap();
-        crate::DATABASE.lock().unwrap()[0].run_with_parameters("
+        ])?;
+        dhu::execute("
                 INSERT INTO Splits(
                                     guid,tx_guid,account_guid,memo,action,reconcile_state,reconcile_date,
                                     value_num,value_denom,quantity_num,quantity_denom,lot_guid
                                 ) VALUES (
                                     ?,   ?,      ?,           '',  '',    'n',            NULL,
-                                    ?,        ?,          ?,           ?,             NULL)", binding_object);
+                                    ?,        ?,          ?,           ?,             NULL)", Some(binding_object))?;
 
         if txn.memo.trim() != "" {
             //Create a notes slot for this transaction
@@ -249,9 +320,8 @@ pub fn save_transaction(txn: TransactionWithSplitInformation) -> Result<bool, St
                 &slots_manager::SLOT_NAME_NOTES.to_string(),    //name
                 &slots_manager::SLOT_NAME_NOTES_SLOT_TYPE_VALUE.to_string(), //slot_type
                 &txn.memo,                                      //string_val
-            ])
-            .unwrap();
-            crate::DATABASE.lock().unwrap()[0].run_with_parameters("
+            ])?;
+            dhu::execute("
                     INSERT INTO Slots(
                                         id,obj_guid,name,slot_type,int64_val,string_val,double_val,
                                         timespec_val,guid_val,numeric_val_num,numeric_val_denom,gdate_val
@@ -260,133 +330,181 @@ pub fn save_transaction(txn: TransactionWithSplitInformation) -> Result<bool, St
                                            ?,       ?,   ?,        0,        ?,         0,               
                                         NULL,        NULL,    0,              1,                NULL
                                     )",
-                                    binding_object);
+                                    Some(binding_object))?;
         }
     }
 
     return Ok(true);
 }
 
-pub fn retrieve_transaction_with_split_information_for_account_guid_and_description(
-    account_guid: Uuid,
-    description: String,
-) -> Vec<TransactionWithSplitInformation> {
-    let mut transaction_with_split = Vec::new();
+pub const FIELDS: &str = "guid,currency_guid,num,post_date,enter_date,description";
+
+/// save_transaction_with_splits saves a transaction along with all of the given splits, by first
+/// deleting the original transaction, and its splits. Slots for the transaction are left alone.
+/// Transactions in the closed period can't be saved.
+pub fn save_transaction_with_splits(
+    transaction: Transaction,
+    splits: Vec<splits_manager::Split>,
+) -> Result<bool, MoneyManagerError> {
+    check_transaction_is_open(transaction.guid, Some(&transaction.post_date))?;
+
+    //Delete the Transaction Record, and the Split records first
+    let binding_object = serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(
+        &transaction.guid,
+    )])?;
+    dhu::execute("DELETE FROM transactions WHERE guid=?", Some(binding_object.clone()))?;
+    dhu::execute("DELETE FROM splits WHERE tx_guid=?", Some(binding_object))?;
+
+    //Insert The Transaction Record
+    let binding_object = serde_wasm_bindgen::to_value(&vec![
+        &dhu::convert_guid_to_sqlite_string(&transaction.guid), //guid
+        &dhu::convert_guid_to_sqlite_string(&transaction.currency_guid), //currency_guid
+        &transaction.num,                                       //num
+        &transaction.post_date,                                 //post_date
+        &transaction.enter_date,                                //enter_date
+        &transaction.description,                               //description
+    ])?;
+    dhu::execute(
+        &format!(
+            "INSERT INTO transactions({fields}) VALUES (?,?,?,?,?,?)",
+            fields = FIELDS
+        ),
+        Some(binding_object),
+    )?;
 
-    if crate::DATABASE.lock().unwrap().len() == 0 {
-        panic!("Please select a database in order to continue!");
+    //Then each of the splits
+    for split in splits {
+        splits_manager::save_split(split)?;
     }
 
-    {
-        let stmt = crate::DATABASE.lock().unwrap()[0]
-            .prepare(&shu::load_transaction_for_account_guid_and_description());
-
-        let binding_object = serde_wasm_bindgen::to_value(&vec![
-            &dhu::convert_guid_to_sqlite_string(&account_guid),
-            &dhu::convert_guid_to_sqlite_string(&account_guid),
-            &dhu::convert_guid_to_sqlite_string(&account_guid),
-            &description,
-            &dhu::convert_guid_to_sqlite_string(&account_guid),
-            &dhu::convert_guid_to_sqlite_string(&account_guid),
-        ])
-        .unwrap();
-
-        stmt.bind(binding_object.clone());
-
-        while stmt.step() {
-            let row = stmt.getAsObject();
-            //js::log(&("Here is a row: ".to_owned() + &js::stringify(row.clone()).to_owned()));
-
-            let txn: TransactionWithSplitInformation =
-                serde_wasm_bindgen::from_value(row.clone()).unwrap();
+    Ok(true)
+}
 
-            transaction_with_split.push(txn);
+/// save_transfer_transaction saves the given transaction like save_transaction does, except its
+/// account is in another commodity than the transaction's currency. That account's split gets
+/// the given quantity in its own commodity, which is recorded as a price too.
+pub fn save_transfer_transaction(
+    txn: TransactionWithSplitInformation,
+    quantity_num: i64,
+    quantity_denom: i64,
+    commodity_guid: Uuid,
+) -> Result<bool, MoneyManagerError> {
+    let transaction = Transaction {
+        guid: txn.guid,
+        currency_guid: txn.currency_guid,
+        num: txn.num,
+        post_date: txn.post_date,
+        enter_date: txn.enter_date,
+        description: txn.description,
+    };
+    let split = |account_guid: Uuid, value_num: i64, quantity_num: i64, quantity_denom: i64| {
+        splits_manager::Split {
+            guid: Uuid::new_v4(),
+            tx_guid: txn.guid,
+            account_guid,
+            memo: String::new(),
+            action: String::new(),
+            reconcile_state: "n".to_string(),
+            reconcile_date: None,
+            value_num,
+            value_denom: txn.value_denom,
+            quantity_num,
+            quantity_denom,
+            lot_guid: None,
         }
+    };
 
-        //Free the memory for the statement, and the bindings
-        stmt.free();
-        stmt.freemem();
+    //The account the transaction was entered from is in the transaction's currency
+    let from_split = split(
+        txn.excluded_account_guid,
+        txn.value_num,
+        txn.value_num,
+        txn.value_denom,
+    );
+    let to_split = split(
+        txn.account_guid,
+        -txn.value_num,
+        quantity_num,
+        quantity_denom,
+    );
+    let price_date = transaction.post_date.clone();
+    let currency_guid = transaction.currency_guid;
+    let price_split = to_split.clone();
+    save_transaction_with_splits(transaction, vec![from_split, to_split])?;
+    prices_manager::save_price_for_split(&price_split, commodity_guid, currency_guid, price_date)?;
+
+    //The notes are replaced, not added to, when the transaction is saved again
+    slots_manager::delete_slots_for_obj_guid_and_name(txn.guid, slots_manager::SLOT_NAME_NOTES)?;
+    if txn.memo.trim() != "" {
+        slots_manager::save_slot(slots_manager::Slot {
+            id: -1,
+            obj_guid: txn.guid,
+            name: slots_manager::SLOT_NAME_NOTES.to_string(),
+            slot_type: slots_manager::SLOT_TYPE_STRING,
+            int64_val: 0,
+            string_val: txn.memo,
+            double_val: Some(0.0),
+            timespec_val: None,
+            guid_val: None,
+            numeric_val_num: Some(0),
+            numeric_val_denom: Some(1),
+            gdate_val: None,
+        })?;
     }
 
-    return transaction_with_split;
+    Ok(true)
 }
 
-pub fn retrieve_transactions_with_split_information_for_account_guid_for_past_year(account_guid: Uuid) -> Result<Vec<TransactionWithSplitInformation>, String> {
-    if crate::DATABASE.lock().unwrap().len() == 0 {
-        return Err("No Database Loaded!".to_string());
-    }
+pub fn retrieve_transaction_with_split_information_for_account_guid_and_description(
+    account_guid: Uuid,
+    description: String,
+) -> Result<Vec<TransactionWithSplitInformation>, MoneyManagerError> {
+    let binding_object = serde_wasm_bindgen::to_value(&vec![
+        &description,
+        &dhu::convert_guid_to_sqlite_string(&account_guid),
+        &dhu::convert_guid_to_sqlite_string(&account_guid),
+    ])?;
 
+    dhu::query_rows(
+        &shu::load_transaction_for_account_guid_and_description(),
+        Some(binding_object),
+    )
+}
+
+pub fn retrieve_transactions_with_split_information_for_account_guid_for_past_year(account_guid: Uuid) -> Result<Vec<TransactionWithSplitInformation>, MoneyManagerError> {
     //Get the date we want to limit results to start at 1 year so far
     let date_to_use = chrono::NaiveDateTime::new(
-        NaiveDate::from_ymd_opt(
-            Local::now().naive_local().date().year(),
-            Local::now().naive_local().date().month(),
-            Local::now().naive_local().date().day(),
-        )
-        .unwrap(),
-        NaiveTime::from_hms_milli_opt(0, 0, 0, 000).unwrap(),
+        Local::now().naive_local().date(),
+        NaiveTime::MIN,
     )
     .checked_sub_days(chrono::Days::new(365))
-    .unwrap();
+    .unwrap_or_default();
 
     //Get the balance, and account information for the previous year
-    let mut accounts = Vec::new();
-
-    let stmt = crate::DATABASE.lock().unwrap()[0]
-        .prepare(&shu::load_account_with_balance_for_date_and_guid());
-
     let binding_object = serde_wasm_bindgen::to_value(&vec![
-        &date_to_use.format("%Y-%m-%d 00:00:00").to_string(),
-        &dhu::convert_guid_to_sqlite_string(&account_guid),
-    ])
-    .unwrap();
-
-    //js::log(&("Here is the binding_object! ".to_owned() + &js::stringify(binding_object.clone())));
-
-    stmt.bind(binding_object.clone());
-
-    while stmt.step() {
-        let row = stmt.getAsObject();
-        //js::log(&("Here is a row: ".to_owned() + &js::stringify(row.clone()).to_owned()));
-
-        let mut account: Account = serde_wasm_bindgen::from_value(row.clone()).unwrap();
-
-        let tags: serde_json::Value =
-            serde_json::from_str(js::stringify(row.clone()).as_str()).unwrap();
-
-        let balance = format!("{}", tags["balance"])
-            .parse::<f64>()
-            .expect("Balance is not valid!");
-        account
-            .tags
-            .insert("balance".to_string(), balance.to_string());
-
-        let mnemonic: String =
-            dhu::remove_first_and_last_double_quotes_from_string(tags["mnemonic"].to_string());
-        account
-            .tags
-            .insert("mnemonic".to_string(), mnemonic.clone());
-
-        accounts.push(account);
-    }
+        dhu::convert_date_to_string_format(date_to_use),
+        dhu::convert_date_to_iso_string_format(date_to_use),
+        dhu::convert_guid_to_sqlite_string(&account_guid),
+    ])?;
 
-    //Free the memory for the statement, and the bindings
-    stmt.free();
-    stmt.freemem();
+    let accounts: Vec<AccountWithBalance> = dhu::query_rows(
+        &shu::load_account_with_balance_for_date_and_guid(),
+        Some(binding_object),
+    )?;
 
     //Exit if there were no results returned
     if accounts.len() != 1 {
-        let date = date_to_use.clone().to_string();
-        let account_guid_string = account_guid.clone();
-        return Err(format!("Cannot continue! There were 0 accounts retrieved for guid '{account_guid_string}', as of '{date}'."));
+        let date = date_to_use.to_string();
+        return Err(MoneyManagerError::NotFound(format!("Cannot continue! There were 0 accounts retrieved for guid '{account_guid}', as of '{date}'.")));
     }
+    let account = &accounts[0];
 
     //Next now that we have a single account record, we can continue, and get the transactions loaded for the past year
     let mut transactions_with_splits = Vec::new();
     let transactions_before_year = transactions_manager::TransactionWithSplitInformation {
-        excluded_account_guid: accounts[0].guid,
-        excluded_account_name: accounts[0].name.clone(),
-        excluded_account_mnemonic: accounts[0].tags["mnemonic"].clone(),
+        excluded_account_guid: account.account.guid,
+        excluded_account_name: account.account.name.clone(),
+        excluded_account_mnemonic: account.mnemonic.clone(),
         guid: uuid::Uuid::nil(),
         currency_guid: uuid::Uuid::nil(),
         num: "".to_string(),
@@ -394,73 +512,200 @@ pub fn retrieve_transactions_with_split_information_for_account_guid_for_past_ye
         enter_date: dhu::convert_date_to_string_format(date_to_use),
         description: format!(
             "Balance Prior To {}",
-            date_to_use.format("%m/%d/%Y").to_string()
+            date_to_use.format("%m/%d/%Y")
         ),
-        value_num: (accounts[0].tags["balance"].parse::<f64>().unwrap()
-            * accounts[0].commodity_scu as f64)
-            .round() as i64,
-        value_denom: -1 * accounts[0].commodity_scu, //-1 because this is from the account side which is negative for our current view
+        value_num: (account.balance * account.account.commodity_scu as f64).round() as i64,
+        value_denom: -account.account.commodity_scu, //-1 because this is from the account side which is negative for our current view
         account_name: "".to_string(),
         account_guid: uuid::Uuid::nil(),
         memo: "".to_string(),
+        balance: None,
     };
 
     transactions_with_splits.push(transactions_before_year);
 
-    let stmt = crate::DATABASE.lock().unwrap()[0]
-        .prepare(&shu::load_transactions_for_account_between_dates());
-
-    let from_date = chrono::NaiveDateTime::new(
-        NaiveDate::from_ymd_opt(
-            Local::now().naive_local().date().year(),
-            Local::now().naive_local().date().month(),
-            Local::now().naive_local().date().day(),
-        )
-        .unwrap(),
-        NaiveTime::from_hms_milli_opt(0, 0, 0, 000).unwrap(),
-    )
-    .checked_sub_days(chrono::Days::new(365))
-    .unwrap();
-
-    let from_date = from_date.format("%Y-%m-%d 00:00:00").to_string();
-
     let thru_date = chrono::NaiveDateTime::new(
-        NaiveDate::from_ymd_opt(9999, 12, 31).unwrap(),
-        NaiveTime::from_hms_milli_opt(23, 59, 59, 999).unwrap(),
+        NaiveDate::from_ymd_opt(9999, 12, 31).unwrap_or_default(),
+        NaiveTime::from_hms_opt(23, 59, 59).unwrap_or_default(),
     );
 
-    let thru_date = thru_date.format("%Y-%m-%d 23:59:59").to_string();
-
-    let binding_object = serde_wasm_bindgen::to_value(&vec![
+    let mut parameters = vec![
         dhu::convert_guid_to_sqlite_string(&account_guid),
         dhu::convert_guid_to_sqlite_string(&account_guid),
         dhu::convert_guid_to_sqlite_string(&account_guid),
         dhu::convert_guid_to_sqlite_string(&account_guid),
-        from_date,
-        thru_date,
-    ])
-    .unwrap();
+    ];
+    parameters.extend(dhu::convert_dates_to_post_date_bounds(date_to_use, thru_date));
+    let binding_object = serde_wasm_bindgen::to_value(&parameters)?;
 
-    //js::log(&("Here is the binding_object! ".to_owned() + &js::stringify(binding_object.clone())));
+    transactions_with_splits.extend(dhu::query_rows::<TransactionWithSplitInformation>(
+        &shu::load_transactions_for_account_between_dates(),
+        Some(binding_object),
+    )?);
 
-    stmt.bind(binding_object.clone());
+    Ok(transactions_with_splits)
+}
 
-    while stmt.step() {
-        let row = stmt.getAsObject();
-        //js::log(&("Here is a row: ".to_owned() + &js::stringify(row.clone()).to_owned()));
+/// RegisterFilter narrows the transactions shown in an account's register. Each filter left
+/// as None, or blank doesn't narrow them.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RegisterFilter {
+    pub from_date: Option<NaiveDate>,      //from_date is the first post date shown.
+    pub thru_date: Option<NaiveDate>,      //thru_date is the last post date shown.
+    pub minimum_amount: Option<f64>,       //minimum_amount is the smallest change shown, without its sign.
+    pub maximum_amount: Option<f64>,       //maximum_amount is the largest change shown, without its sign.
+    pub text: String,                      //text is matched against the description, and notes.
+    pub reconcile_state: Option<String>,   //reconcile_state is the account split's state. (Ex: 'n', 'c' or 'y')
+    pub counter_account_guid: Option<Uuid>, //counter_account_guid is an account the transactions must also use.
+}
 
-        let txn: transactions_manager::TransactionWithSplitInformation =
-            serde_wasm_bindgen::from_value(row.clone()).unwrap();
+impl RegisterFilter {
+    /// is_empty checks whether this filter leaves every transaction in the register.
+    pub fn is_empty(&self) -> bool {
+        RegisterFilter {
+            text: self.text.trim().to_string(),
+            ..self.clone()
+        } == RegisterFilter::default()
+    }
 
-        transactions_with_splits.push(txn);
+    /// binding_parameters returns the parameters of load_filtered_transactions_for_account for
+    /// this filter on the given account, with None for each filter that isn't used.
+    pub fn binding_parameters(&self, account_guid: Uuid) -> Vec<Option<String>> {
+        let from_date = self.from_date.map(|from_date| from_date.and_time(NaiveTime::MIN));
+        let thru_date = self
+            .thru_date
+            .and_then(|thru_date| thru_date.and_hms_opt(23, 59, 59));
+        let text = self.text.trim();
+
+        vec![
+            Some(dhu::convert_guid_to_sqlite_string(&account_guid)),
+            from_date.map(dhu::convert_date_to_string_format),
+            from_date.map(dhu::convert_date_to_iso_string_format),
+            thru_date.map(dhu::convert_date_to_string_format),
+            thru_date.map(dhu::convert_date_to_iso_string_format),
+            self.minimum_amount.map(|amount| amount.abs().to_string()),
+            self.maximum_amount.map(|amount| amount.abs().to_string()),
+            (!text.is_empty()).then(|| text.to_string()),
+            self.reconcile_state.clone(),
+            self.counter_account_guid
+                .map(|counter_account_guid| dhu::convert_guid_to_sqlite_string(&counter_account_guid)),
+        ]
     }
+}
+
+/// retrieve_filtered_transactions_with_split_information_for_account_guid retrieves the
+/// transactions for the given account_guid that pass the given filter. Each has the account's
+/// balance after it, worked out from every transaction, so it stays the true balance.
+pub fn retrieve_filtered_transactions_with_split_information_for_account_guid(
+    account_guid: Uuid,
+    filter: &RegisterFilter,
+) -> Result<Vec<TransactionWithSplitInformation>, MoneyManagerError> {
+    let binding_object = dhu::convert_to_binding_object(&filter.binding_parameters(account_guid));
 
-    //Free the memory for the statement, and the bindings
-    stmt.free();
-    stmt.freemem();
+    dhu::query_rows(
+        &shu::load_filtered_transactions_for_account(),
+        Some(binding_object),
+    )
+}
 
-    Ok(transactions_with_splits)
+/// REGISTER_PAGE_SIZE is how many transactions one page of an account's register holds.
+pub const REGISTER_PAGE_SIZE: i64 = 100;
+
+/// TransactionCount holds how many transactions an account has.
+#[derive(Debug, Deserialize)]
+struct TransactionCount {
+    count: i64,
+}
+
+/// count_register_pages works out how many register pages the given number of transactions
+/// fills. There is always at least one page, even when it's empty.
+pub fn count_register_pages(transaction_count: i64, page_size: i64) -> i64 {
+    ((transaction_count + page_size - 1) / page_size).max(1)
+}
 
+/// retrieve_count_of_register_pages_for_account_guid retrieves how many register pages the
+/// transactions of the given account_guid fill.
+pub fn retrieve_count_of_register_pages_for_account_guid(
+    account_guid: Uuid,
+) -> Result<i64, MoneyManagerError> {
+    let binding_object = serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(
+        &account_guid,
+    )])?;
+    let transaction_count = dhu::query_first_row::<TransactionCount>(
+        "SELECT COUNT(DISTINCT tx_guid) AS count FROM splits WHERE account_guid=?",
+        Some(binding_object),
+        "The count of transactions was not found.",
+    )?;
+
+    Ok(count_register_pages(transaction_count.count, REGISTER_PAGE_SIZE))
+}
+
+/// retrieve_transactions_with_split_information_for_account_guid_for_page retrieves one page of
+/// the transactions for the given account_guid, where page 0 has the oldest ones. Pages after the
+/// first start with the balance of the transactions before them, so running balances stay right.
+pub fn retrieve_transactions_with_split_information_for_account_guid_for_page(
+    account_guid: Uuid,
+    page: i64,
+) -> Result<Vec<TransactionWithSplitInformation>, MoneyManagerError> {
+    let account_guid_string = dhu::convert_guid_to_sqlite_string(&account_guid);
+    let offset = page.max(0) * REGISTER_PAGE_SIZE;
+
+    //Get the account, and its balance before this page. LIMIT, and OFFSET are bound as numbers
+    let binding_object =
+        serde_wasm_bindgen::to_value(&(&account_guid_string, offset, &account_guid_string))?;
+    let accounts: Vec<AccountWithBalance> = dhu::query_rows(
+        &shu::load_account_with_balance_before_page_for_guid(),
+        Some(binding_object),
+    )?;
+    if accounts.len() != 1 {
+        let count = accounts.len();
+        return Err(MoneyManagerError::NotFound(format!("Cannot continue! There were {count} accounts retrieved for guid '{account_guid}'.")));
+    }
+    let account = &accounts[0];
+
+    let binding_object = serde_wasm_bindgen::to_value(&(
+        &account_guid_string,
+        REGISTER_PAGE_SIZE,
+        offset,
+        &account_guid_string,
+        &account_guid_string,
+        &account_guid_string,
+        &account_guid_string,
+    ))?;
+    let transactions: Vec<TransactionWithSplitInformation> = dhu::query_rows(
+        &shu::load_transactions_for_account_page(),
+        Some(binding_object),
+    )?;
+
+    if offset == 0 || transactions.is_empty() {
+        return Ok(transactions);
+    }
+
+    let post_date = transactions[0].post_date.clone();
+    let balance_prior_date = dhu::convert_string_to_date(&post_date)?;
+    let mut transactions_with_splits = vec![TransactionWithSplitInformation {
+        excluded_account_guid: account.account.guid,
+        excluded_account_name: account.account.name.clone(),
+        excluded_account_mnemonic: account.mnemonic.clone(),
+        guid: uuid::Uuid::nil(),
+        currency_guid: uuid::Uuid::nil(),
+        num: "".to_string(),
+        post_date: post_date.clone(),
+        enter_date: post_date,
+        description: format!(
+            "Balance Prior To {}",
+            balance_prior_date.format("%m/%d/%Y")
+        ),
+        value_num: (account.balance * account.account.commodity_scu as f64).round() as i64,
+        value_denom: -account.account.commodity_scu, //-1 because this is from the account side which is negative for our current view
+        account_name: "".to_string(),
+        account_guid: uuid::Uuid::nil(),
+        memo: "".to_string(),
+        balance: None,
+    }];
+    transactions_with_splits.extend(transactions);
+
+    Ok(transactions_with_splits)
 }
 
 // ///
@@ -996,3 +1241,701 @@ pub fn retrieve_transactions_with_split_information_for_account_guid_for_past_ye
 //     Ok(true)
 
 // }
+
+/// BulkAction is a change made at once to the transactions checked in an account's register.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BulkAction {
+    ChangeCategory(Uuid), //ChangeCategory moves the other split of each transaction to the given account.
+    Delete,               //Delete deletes each transaction, with its splits, and slots.
+    MarkCleared,          //MarkCleared marks the register account's splits that aren't reconciled as cleared.
+    ShiftDate(i64),       //ShiftDate moves the post date of each transaction by the given number of days.
+}
+
+/// TransactionSnapshot holds a transaction, with its splits, and slots as they are saved, so a
+/// bulk action can be previewed on it, or undone by saving it back.
+#[derive(Debug, Clone)]
+pub struct TransactionSnapshot {
+    pub transaction: Transaction,            //transaction is the transaction record.
+    pub splits: Vec<splits_manager::Split>, //splits are the transaction's split records.
+    pub slots: Vec<slots_manager::Slot>,    //slots are the slot records for the transaction, like its notes.
+}
+
+/// shift_post_date moves the given post date by the given number of days, keeping the format
+/// it was saved in.
+pub fn shift_post_date(post_date: &str, days: i64) -> Result<String, MoneyManagerError> {
+    let shifted_date = dhu::convert_string_to_date(post_date)? + chrono::Duration::days(days);
+
+    Ok(if post_date.len() == 14 {
+        dhu::convert_date_to_string_format(shifted_date)
+    } else {
+        dhu::convert_date_to_iso_string_format(shifted_date)
+    })
+}
+
+impl TransactionSnapshot {
+    /// apply_bulk_action returns this transaction as the given action, from the given account's
+    /// register, leaves it, or None when it's deleted. A transaction the action can't be applied
+    /// to, like one in the period closed through the given lock date, returns a Validation error
+    /// saying why, so it can be skipped.
+    pub fn apply_bulk_action(
+        &self,
+        account_guid: Uuid,
+        action: &BulkAction,
+        lock_date: Option<NaiveDate>,
+    ) -> Result<Option<TransactionSnapshot>, MoneyManagerError> {
+        if let Some(lock_date) = lock_date {
+            check_post_date_is_open(&self.transaction.post_date, lock_date)?;
+        }
+        let mut changed = self.clone();
+        match action {
+            BulkAction::ChangeCategory(category_guid) => {
+                let mut other_splits = changed
+                    .splits
+                    .iter_mut()
+                    .filter(|split| split.account_guid != account_guid)
+                    .collect::<Vec<_>>();
+                if other_splits.len() != 1 {
+                    return Err(MoneyManagerError::Validation(format!(
+                        "It has {} categories, so it has to be changed in the split editor.",
+                        other_splits.len()
+                    )));
+                }
+                let other_split = &mut other_splits[0];
+                if other_split.account_guid == *category_guid {
+                    return Err(MoneyManagerError::Validation(
+                        "It is in that category already.".to_string(),
+                    ));
+                }
+                if other_split.quantity_num * other_split.value_denom
+                    != other_split.value_num * other_split.quantity_denom
+                {
+                    return Err(MoneyManagerError::Validation(
+                        "It is a transfer between commodities.".to_string(),
+                    ));
+                }
+                other_split.account_guid = *category_guid;
+            }
+            BulkAction::Delete => return Ok(None),
+            BulkAction::MarkCleared => {
+                let mut marked = false;
+                for split in changed.splits.iter_mut() {
+                    if split.account_guid == account_guid && split.reconcile_state == "n" {
+                        split.reconcile_state = "c".to_string();
+                        marked = true;
+                    }
+                }
+                if !marked {
+                    return Err(MoneyManagerError::Validation(
+                        "It is cleared, or reconciled already.".to_string(),
+                    ));
+                }
+            }
+            BulkAction::ShiftDate(days) => {
+                changed.transaction.post_date =
+                    shift_post_date(&changed.transaction.post_date, *days)?;
+                if let Some(lock_date) = lock_date {
+                    check_post_date_is_open(&changed.transaction.post_date, lock_date)?;
+                }
+            }
+        }
+
+        Ok(Some(changed))
+    }
+
+    /// is_same_as checks whether this snapshot holds the same transaction, splits, and slots as
+    /// the given one, in any order, and whatever ids the slots were saved with.
+    pub fn is_same_as(&self, other: &TransactionSnapshot) -> bool {
+        let comparable = |snapshot: &TransactionSnapshot| {
+            let mut splits = snapshot.splits.clone();
+            splits.sort_by_key(|split| split.guid);
+            let mut slots = snapshot
+                .slots
+                .iter()
+                .map(|slot| slots_manager::Slot { id: -1, ..slot.clone() })
+                .map(|slot| serde_json::to_string(&slot).unwrap_or_default())
+                .collect::<Vec<_>>();
+            slots.sort();
+            (
+                serde_json::to_string(&snapshot.transaction).unwrap_or_default(),
+                serde_json::to_string(&splits).unwrap_or_default(),
+                slots,
+            )
+        };
+        comparable(self) == comparable(other)
+    }
+
+    /// describe_bulk_action describes what the given action, from the given account's register,
+    /// does to this transaction, with the book closed through the given lock date, using the given
+    /// account names, for previewing it.
+    pub fn describe_bulk_action(
+        &self,
+        account_guid: Uuid,
+        action: &BulkAction,
+        lock_date: Option<NaiveDate>,
+        account_names: &HashMap<Uuid, String>,
+    ) -> String {
+        let account_name = |guid: &Uuid| {
+            account_names.get(guid).cloned().unwrap_or_else(|| guid.to_string())
+        };
+        let format_date = |post_date: &str| {
+            dhu::convert_string_to_date(post_date)
+                .map(|date| date.format("%m/%d/%Y").to_string())
+                .unwrap_or_else(|_| post_date.to_string())
+        };
+        let transaction = format!(
+            "{} {}",
+            format_date(&self.transaction.post_date),
+            self.transaction.description
+        );
+
+        let change = match (self.apply_bulk_action(account_guid, action, lock_date), action) {
+            (Err(error), _) => format!("skipped. {}", error),
+            (Ok(_), BulkAction::ChangeCategory(category_guid)) => format!(
+                "{} to {}",
+                self.splits
+                    .iter()
+                    .filter(|split| split.account_guid != account_guid)
+                    .map(|split| account_name(&split.account_guid))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                account_name(category_guid)
+            ),
+            (Ok(_), BulkAction::Delete) => "deleted".to_string(),
+            (Ok(_), BulkAction::MarkCleared) => "marked cleared".to_string(),
+            (Ok(changed), BulkAction::ShiftDate(_)) => format!(
+                "moved to {}",
+                changed
+                    .map(|changed| format_date(&changed.transaction.post_date))
+                    .unwrap_or_default()
+            ),
+        };
+
+        format!("{}: {}", transaction, change)
+    }
+}
+
+/// retrieve_transaction_snapshot_for_guid retrieves the transaction with the given guid, along
+/// with its splits, and slots.
+pub fn retrieve_transaction_snapshot_for_guid(
+    transaction_guid: Uuid,
+) -> Result<TransactionSnapshot, MoneyManagerError> {
+    let binding_object = serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(
+        &transaction_guid,
+    )])?;
+
+    Ok(TransactionSnapshot {
+        transaction: dhu::query_first_row(
+            &format!("SELECT {fields} FROM transactions WHERE guid=?", fields = FIELDS),
+            Some(binding_object.clone()),
+            "The transaction was not found.",
+        )?,
+        splits: dhu::query_rows(
+            &format!(
+                "SELECT {fields} FROM splits WHERE tx_guid=?",
+                fields = splits_manager::FIELDS
+            ),
+            Some(binding_object.clone()),
+        )?,
+        slots: dhu::query_rows(
+            &format!(
+                "SELECT {fields} FROM slots WHERE obj_guid=?",
+                fields = slots_manager::_fields()
+            ),
+            Some(binding_object),
+        )?,
+    })
+}
+
+/// save_transaction_snapshot saves the given snapshot back, replacing the transaction, its
+/// splits, and its slots with the ones it holds.
+pub fn save_transaction_snapshot(snapshot: TransactionSnapshot) -> Result<bool, MoneyManagerError> {
+    //The closed period is checked before anything is deleted, so a refused save changes nothing
+    check_transaction_is_open(snapshot.transaction.guid, Some(&snapshot.transaction.post_date))?;
+
+    let binding_object = serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(
+        &snapshot.transaction.guid,
+    )])?;
+    dhu::execute("DELETE FROM slots WHERE obj_guid=?", Some(binding_object))?;
+
+    save_transaction_with_splits(snapshot.transaction, snapshot.splits)?;
+    for slot in snapshot.slots {
+        slots_manager::save_slot(slot)?;
+    }
+
+    Ok(true)
+}
+
+/// retrieve_transaction_snapshots_for_guids retrieves the snapshots of the transactions with
+/// the given guids.
+pub fn retrieve_transaction_snapshots_for_guids(
+    transaction_guids: &[Uuid],
+) -> Result<Vec<TransactionSnapshot>, MoneyManagerError> {
+    transaction_guids
+        .iter()
+        .map(|transaction_guid| retrieve_transaction_snapshot_for_guid(*transaction_guid))
+        .collect()
+}
+
+/// BulkActionChange is a transaction a bulk action changed, as it was before, and as the bulk
+/// action saved it, so it can be put back unless it's been changed again since.
+#[derive(Debug, Clone)]
+pub struct BulkActionChange {
+    pub before: TransactionSnapshot, //before is the transaction before the bulk action.
+    pub after: Option<TransactionSnapshot>, //after is the transaction as the bulk action saved it, or None when it was deleted.
+}
+
+/// BulkActionUndo is what undoing a bulk action did.
+#[derive(Debug, Default)]
+pub struct BulkActionUndo {
+    pub restored: usize,      //restored is the number of transactions put back.
+    pub skipped: Vec<String>, //skipped are the transactions changed since, which were left alone.
+}
+
+/// apply_bulk_action applies the given action, from the given account's register, to the
+/// transactions with the given guids, skipping the ones it can't be applied to. Either all of
+/// them are changed, or none of them are. What they were before, and after is kept, so
+/// undo_last_bulk_action can put them back. It returns how many changed.
+pub fn apply_bulk_action(
+    account_guid: Uuid,
+    transaction_guids: &[Uuid],
+    action: &BulkAction,
+) -> Result<usize, MoneyManagerError> {
+    let snapshots = retrieve_transaction_snapshots_for_guids(transaction_guids)?;
+    let lock_date = retrieve_lock_date()?;
+
+    let mut changed_snapshots = Vec::new();
+    for snapshot in snapshots {
+        match snapshot.apply_bulk_action(account_guid, action, lock_date) {
+            Ok(changed) => changed_snapshots.push((snapshot, changed)),
+            Err(MoneyManagerError::Validation(_)) => continue,
+            Err(error) => return Err(error),
+        }
+    }
+
+    let changes = dhu::in_transaction(|| {
+        let mut changes = Vec::new();
+        for (snapshot, changed) in changed_snapshots {
+            let transaction_guid = snapshot.transaction.guid;
+            //What's read back is what undo compares with, so saving can't make it look changed
+            let after = match changed {
+                Some(changed) => {
+                    save_transaction_snapshot(changed)?;
+                    Some(retrieve_transaction_snapshot_for_guid(transaction_guid)?)
+                }
+                None => {
+                    delete_transaction(transaction_guid)?;
+                    None
+                }
+            };
+            changes.push(BulkActionChange {
+                before: snapshot,
+                after,
+            });
+        }
+        Ok(changes)
+    })?;
+
+    let changed_count = changes.len();
+    *crate::BULK_ACTION_UNDO.lock().unwrap() = changes;
+
+    Ok(changed_count)
+}
+
+/// has_bulk_action_to_undo checks whether there is a bulk action that can be undone.
+pub fn has_bulk_action_to_undo() -> bool {
+    !crate::BULK_ACTION_UNDO.lock().unwrap().is_empty()
+}
+
+/// undo_last_bulk_action puts the transactions changed by the last bulk action back the way
+/// they were. Transactions changed again since are skipped, so those changes aren't lost.
+/// Either all of the rest are put back, or none of them are, and the bulk action can be undone
+/// again.
+pub fn undo_last_bulk_action() -> Result<BulkActionUndo, MoneyManagerError> {
+    let changes = crate::BULK_ACTION_UNDO.lock().unwrap().clone();
+    if changes.is_empty() {
+        return Err(MoneyManagerError::Validation(
+            "There is no bulk change to undo.".to_string(),
+        ));
+    }
+
+    let mut undo = BulkActionUndo::default();
+    let mut unchanged = Vec::new();
+    for change in changes {
+        let current = match retrieve_transaction_snapshot_for_guid(change.before.transaction.guid) {
+            Ok(current) => Some(current),
+            Err(MoneyManagerError::NotFound(_)) => None,
+            Err(error) => return Err(error),
+        };
+        let is_unchanged = match (&change.after, &current) {
+            (Some(after), Some(current)) => after.is_same_as(current),
+            (None, None) => true,
+            _ => false,
+        };
+        if is_unchanged {
+            unchanged.push(change.before);
+        } else {
+            undo.skipped.push(format!(
+                "'{}' was changed after the bulk change, so it was left alone.",
+                change.before.transaction.description
+            ));
+        }
+    }
+
+    undo.restored = unchanged.len();
+    dhu::in_transaction(|| {
+        unchanged
+            .into_iter()
+            .try_for_each(|snapshot| save_transaction_snapshot(snapshot).map(|_| ()))
+    })?;
+    crate::BULK_ACTION_UNDO.lock().unwrap().clear();
+
+    Ok(undo)
+}
+
+/// PROBABLE_DUPLICATE_DAYS is how many days apart two transactions can be posted, and still be
+/// taken for the same one entered twice.
+pub const PROBABLE_DUPLICATE_DAYS: i64 = 3;
+
+/// ProbableDuplicate is a transaction already in an account that looks like one being entered,
+/// or imported again.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ProbableDuplicate {
+    pub guid: Uuid,          //guid is the GUID of the transaction.
+    pub post_date: String,   //post_date is the date the transaction is posted.
+    pub description: String, //description is the description of the transaction.
+    pub value_num: i64,      //value_num is the numerator of the account split's value.
+    pub value_denom: i64,    //value_denom is the denominator of the account split's value.
+}
+
+impl ProbableDuplicate {
+    /// describe describes this transaction, for listing it as a match.
+    /// (Ex: "01/30/2024 'KROGER' -25")
+    pub fn describe(&self) -> String {
+        let post_date = dhu::convert_string_to_date(&self.post_date)
+            .map(|date| date.format("%m/%d/%Y").to_string())
+            .unwrap_or_else(|_| self.post_date.clone());
+        format!(
+            "{} '{}' {}",
+            post_date,
+            self.description,
+            self.value_num as f64 / self.value_denom as f64
+        )
+    }
+}
+
+/// description_words returns the words of the given description in lowercase, without its
+/// punctuation. (Ex: 'KROGER #123' is ['kroger', '123'])
+fn description_words(description: &str) -> Vec<String> {
+    description
+        .split(|character: char| !character.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// is_similar_description checks whether the given descriptions could be for the same
+/// transaction. One holding all the words of the other, or sharing at least half of their
+/// words makes them similar, and so does a blank one, since there is nothing to tell them apart.
+pub fn is_similar_description(description: &str, other_description: &str) -> bool {
+    let words = description_words(description);
+    let other_words = description_words(other_description);
+    if words.is_empty() || other_words.is_empty() {
+        return true;
+    }
+
+    let shared = words.iter().filter(|word| other_words.contains(word)).count();
+    let (fewest, most) = if words.len() < other_words.len() {
+        (words.len(), other_words.len())
+    } else {
+        (other_words.len(), words.len())
+    };
+
+    shared == fewest || shared * 2 >= most
+}
+
+/// retrieve_probable_duplicates retrieves the transactions in the given account with a split of
+/// the same value, posted within PROBABLE_DUPLICATE_DAYS of the given date, with a description
+/// similar to the given one.
+pub fn retrieve_probable_duplicates(
+    account_guid: Uuid,
+    post_date: NaiveDateTime,
+    value_num: i64,
+    value_denom: i64,
+    description: &str,
+) -> Result<Vec<ProbableDuplicate>, MoneyManagerError> {
+    let from_date = (post_date.date() - chrono::Duration::days(PROBABLE_DUPLICATE_DAYS))
+        .and_time(NaiveTime::MIN);
+    let thru_date = (post_date.date() + chrono::Duration::days(PROBABLE_DUPLICATE_DAYS))
+        .and_hms_opt(23, 59, 59)
+        .unwrap_or(post_date);
+
+    let mut parameters = vec![
+        dhu::convert_guid_to_sqlite_string(&account_guid),
+        value_num.to_string(),
+        value_denom.to_string(),
+    ];
+    parameters.extend(dhu::convert_dates_to_post_date_bounds(from_date, thru_date));
+    let binding_object = serde_wasm_bindgen::to_value(&parameters)?;
+
+    let transactions: Vec<ProbableDuplicate> = dhu::query_rows(
+        &shu::load_transactions_for_account_and_value_between_dates(),
+        Some(binding_object),
+    )?;
+
+    Ok(transactions
+        .into_iter()
+        .filter(|transaction| is_similar_description(description, &transaction.description))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binding_parameters_leave_unused_filters_null() {
+        let account_guid = Uuid::nil();
+        let filter = RegisterFilter {
+            from_date: NaiveDate::from_ymd_opt(2024, 1, 5),
+            minimum_amount: Some(-25.0),
+            text: "  kroger ".to_string(),
+            ..Default::default()
+        };
+        let parameters = filter.binding_parameters(account_guid);
+
+        assert_eq!(parameters.len(), 10);
+        assert_eq!(parameters[1].as_deref(), Some("20240105000000"));
+        assert_eq!(parameters[2].as_deref(), Some("2024-01-05 00:00:00"));
+        assert_eq!(parameters[3], None);
+        assert_eq!(parameters[5].as_deref(), Some("25"));
+        assert_eq!(parameters[7].as_deref(), Some("kroger"));
+        assert_eq!(parameters[9], None);
+
+        assert!(!filter.is_empty());
+        assert!(RegisterFilter { text: " ".to_string(), ..Default::default() }.is_empty());
+    }
+
+    fn split(account_guid: Uuid, value_num: i64) -> splits_manager::Split {
+        splits_manager::Split {
+            guid: Uuid::new_v4(),
+            tx_guid: Uuid::nil(),
+            account_guid,
+            memo: String::new(),
+            action: String::new(),
+            reconcile_state: "n".to_string(),
+            reconcile_date: None,
+            value_num,
+            value_denom: 100,
+            quantity_num: value_num,
+            quantity_denom: 100,
+            lot_guid: None,
+        }
+    }
+
+    #[test]
+    fn apply_bulk_action_changes_the_snapshot_or_says_why_not() {
+        let checking = Uuid::new_v4();
+        let groceries = Uuid::new_v4();
+        let dining = Uuid::new_v4();
+        let snapshot = TransactionSnapshot {
+            transaction: Transaction {
+                guid: Uuid::nil(),
+                currency_guid: Uuid::nil(),
+                num: String::new(),
+                post_date: "20240130100000".to_string(),
+                enter_date: "20240130100000".to_string(),
+                description: "KROGER".to_string(),
+            },
+            splits: vec![split(checking, -2500), split(groceries, 2500)],
+            slots: Vec::new(),
+        };
+
+        let changed = snapshot
+            .apply_bulk_action(checking, &BulkAction::ChangeCategory(dining), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(changed.splits[0].account_guid, checking);
+        assert_eq!(changed.splits[1].account_guid, dining);
+        assert!(
+            snapshot
+                .apply_bulk_action(checking, &BulkAction::ChangeCategory(groceries), None)
+                .is_err()
+        );
+
+        let cleared = snapshot
+            .apply_bulk_action(checking, &BulkAction::MarkCleared, None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(cleared.splits[0].reconcile_state, "c");
+        assert_eq!(cleared.splits[1].reconcile_state, "n");
+        assert!(
+            cleared
+                .apply_bulk_action(checking, &BulkAction::MarkCleared, None)
+                .is_err()
+        );
+
+        let shifted = snapshot
+            .apply_bulk_action(checking, &BulkAction::ShiftDate(3), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(shifted.transaction.post_date, "20240202100000");
+        assert_eq!(
+            shift_post_date("2024-03-01 10:00:00", -1).unwrap(),
+            "2024-02-29 10:00:00"
+        );
+
+        assert!(
+            snapshot
+                .apply_bulk_action(checking, &BulkAction::Delete, None)
+                .unwrap()
+                .is_none()
+        );
+
+        let account_names = HashMap::from([
+            (groceries, "Groceries".to_string()),
+            (dining, "Dining".to_string()),
+        ]);
+        assert_eq!(
+            snapshot.describe_bulk_action(
+                checking,
+                &BulkAction::ChangeCategory(dining),
+                None,
+                &account_names
+            ),
+            "01/30/2024 KROGER: Groceries to Dining"
+        );
+    }
+
+    #[test]
+    fn is_same_as_ignores_the_order_and_slot_ids() {
+        let notes = slots_manager::Slot {
+            id: 7,
+            obj_guid: Uuid::nil(),
+            name: slots_manager::SLOT_NAME_NOTES.to_string(),
+            slot_type: slots_manager::SLOT_TYPE_STRING,
+            int64_val: 0,
+            string_val: "receipt in the glovebox".to_string(),
+            double_val: None,
+            timespec_val: None,
+            guid_val: None,
+            numeric_val_num: None,
+            numeric_val_denom: None,
+            gdate_val: None,
+        };
+        let snapshot = TransactionSnapshot {
+            transaction: Transaction {
+                guid: Uuid::nil(),
+                currency_guid: Uuid::nil(),
+                num: String::new(),
+                post_date: "20240130100000".to_string(),
+                enter_date: "20240130100000".to_string(),
+                description: "KROGER".to_string(),
+            },
+            splits: vec![split(Uuid::new_v4(), -2500), split(Uuid::new_v4(), 2500)],
+            slots: vec![notes.clone()],
+        };
+
+        //Saved again, the splits can come back in another order, and the slots with new ids
+        let saved_again = TransactionSnapshot {
+            splits: snapshot.splits.iter().rev().cloned().collect(),
+            slots: vec![slots_manager::Slot { id: 12, ..notes.clone() }],
+            ..snapshot.clone()
+        };
+        assert!(snapshot.is_same_as(&saved_again));
+
+        let mut edited = snapshot.clone();
+        edited.transaction.description = "KROGER #402".to_string();
+        assert!(!snapshot.is_same_as(&edited));
+        let mut edited = snapshot.clone();
+        edited.splits[0].reconcile_state = "c".to_string();
+        assert!(!snapshot.is_same_as(&edited));
+        let mut edited = snapshot.clone();
+        edited.slots[0].string_val = "receipt lost".to_string();
+        assert!(!snapshot.is_same_as(&edited));
+    }
+
+    #[test]
+    fn apply_bulk_action_skips_the_closed_period() {
+        let checking = Uuid::new_v4();
+        let snapshot = TransactionSnapshot {
+            transaction: Transaction {
+                guid: Uuid::nil(),
+                currency_guid: Uuid::nil(),
+                num: String::new(),
+                post_date: "20231230100000".to_string(),
+                enter_date: "20231230100000".to_string(),
+                description: "KROGER".to_string(),
+            },
+            splits: vec![split(checking, -2500), split(Uuid::new_v4(), 2500)],
+            slots: Vec::new(),
+        };
+        let lock_date = NaiveDate::from_ymd_opt(2023, 12, 31);
+
+        for action in [BulkAction::Delete, BulkAction::MarkCleared, BulkAction::ShiftDate(5)] {
+            assert!(matches!(
+                snapshot.apply_bulk_action(checking, &action, lock_date),
+                Err(MoneyManagerError::Validation(_))
+            ));
+        }
+
+        //Moving an open transaction into the closed period is refused too
+        let open = TransactionSnapshot {
+            transaction: Transaction {
+                post_date: "20240102100000".to_string(),
+                ..snapshot.transaction.clone()
+            },
+            ..snapshot.clone()
+        };
+        assert!(open.apply_bulk_action(checking, &BulkAction::ShiftDate(-5), lock_date).is_err());
+        assert!(open.apply_bulk_action(checking, &BulkAction::ShiftDate(5), lock_date).is_ok());
+    }
+
+    #[test]
+    fn is_similar_description_matches_shared_words() {
+        assert!(is_similar_description("KROGER #123", "Kroger"));
+        assert!(is_similar_description("Amazon Mktplace Pmts", "AMAZON MKTPLACE"));
+        assert!(is_similar_description("", "Shell Oil"));
+        assert!(!is_similar_description("Shell Oil 5744", "Kroger #123"));
+        assert!(!is_similar_description("Target T-1234 Cincinnati", "Walmart Cincinnati"));
+    }
+
+    #[test]
+    fn register_queries_order_mixed_post_date_formats_by_their_digits() {
+        //A book GnuCash wrote in ISO, with transactions this app added in the 14 char format
+        let mut post_dates = vec!["2024-12-01 10:59:00", "20240101105900", "2024-06-01 10:59:00"];
+        post_dates.sort_by_key(|post_date| post_date.replace(['-', ' ', ':'], ""));
+        assert_eq!(
+            post_dates,
+            vec!["20240101105900", "2024-06-01 10:59:00", "2024-12-01 10:59:00"]
+        );
+
+        for sql in [
+            shu::load_transactions_for_account_page(),
+            shu::load_filtered_transactions_for_account(),
+            shu::load_transactions_for_account_between_dates(),
+            shu::load_account_with_balance_before_page_for_guid(),
+        ] {
+            assert!(!sql.contains("ORDER BY t.post_date"), "{}", sql);
+            assert!(!sql.contains("ORDER BY post_date"), "{}", sql);
+            let sort_key = "replace(replace(replace(t.post_date,'-',''),' ',''),':','')";
+            assert!(sql.contains(&format!("ORDER BY {sort_key}")), "{}", sql);
+        }
+    }
+
+    #[test]
+    fn count_register_pages_rounds_up_to_whole_pages() {
+        assert_eq!(count_register_pages(0, REGISTER_PAGE_SIZE), 1);
+        assert_eq!(count_register_pages(100, 100), 1);
+        assert_eq!(count_register_pages(101, 100), 2);
+        assert_eq!(count_register_pages(5475, 100), 55);
+    }
+
+    #[test]
+    fn check_post_date_is_open_refuses_the_closed_period() {
+        let lock_date = NaiveDate::from_ymd_opt(2023, 12, 31).unwrap();
+        assert!(check_post_date_is_open("20231231235959", lock_date).is_err());
+        assert!(check_post_date_is_open("2023-06-01 10:59:00", lock_date).is_err());
+        assert!(check_post_date_is_open("20240101105900", lock_date).is_ok());
+        assert!(check_post_date_is_open("2024-01-01 00:00:00", lock_date).is_ok());
+    }
+}
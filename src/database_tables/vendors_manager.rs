@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Vendor {
+    pub guid: Uuid,               //guid is the GUID for this vendor.
+    pub name: String,             //name is the name of the vendor. (Ex: 'Office Supply Co')
+    pub id: String,               //id is the vendor number. (Ex: '000001')
+    pub active: i64,              //active is 1 if the vendor is active, 0 otherwise.
+    pub currency: Uuid,           //currency is the commodity guid the vendor bills in.
+    pub terms: Option<Uuid>,      //terms is the billterm guid used for this vendor's bills, or null.
+    pub tax_inc: Option<String>,  //tax_inc is 'YES', 'NO' or 'USEGLOBAL' for whether prices include tax.
+    pub tax_table: Option<Uuid>,  //tax_table is the default tax table guid for this vendor, or null.
+}
+
+pub const FIELDS: &str = "guid,name,id,active,currency,terms,tax_inc,tax_table";
+
+/// retrieve_all_vendors retrieves all the vendors, ordered by name.
+pub fn retrieve_all_vendors() -> Result<Vec<Vendor>, MoneyManagerError> {
+    dhu::query_rows(&format!("SELECT {} FROM vendors ORDER BY name", FIELDS), None)
+}
+
+/// retrieve_vendor_for_guid retrieves the vendor for the given guid as a result.
+pub fn retrieve_vendor_for_guid(vendor_guid: Uuid) -> Result<Vendor, MoneyManagerError> {
+    let binding_object =
+        serde_wasm_bindgen::to_value(&vec![&dhu::convert_guid_to_sqlite_string(&vendor_guid)])?;
+
+    dhu::query_first_row(
+        &format!("SELECT {} FROM vendors WHERE guid=?", FIELDS),
+        Some(binding_object),
+        "No vendor for this guid",
+    )
+}
@@ -0,0 +1,218 @@
+use serde::{Deserialize, Serialize};
+
+use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Version {
+    pub table_name: String, //table_name is the table_name for this record. (Ex: 'accounts', or 'Gnucash')
+    pub table_version: i64, //table_version is the table version for this record. (Ex: 1)
+}
+
+pub const FIELDS: &str = "table_name,table_version";
+
+/// GNUCASH_VERSION is the GnuCash version this app writes files like, as GnuCash stores it in
+/// the 'Gnucash' row. (major * 1000000 + minor)
+pub const GNUCASH_VERSION: i64 = 5000000;
+
+/// GNUCASH_RESAVE_VERSION is the oldest GnuCash version that can open files this app writes,
+/// as GnuCash stores it in the 'Gnucash-Resave' row.
+pub const GNUCASH_RESAVE_VERSION: i64 = 19920;
+
+/// TABLE_VERSIONS are the versions of each table this app knows how to read, and write.
+pub const TABLE_VERSIONS: [(&str, i64); 22] = [
+    ("accounts", 1),
+    ("billterms", 2),
+    ("books", 1),
+    ("budget_amounts", 1),
+    ("budgets", 1),
+    ("commodities", 1),
+    ("customers", 2),
+    ("employees", 2),
+    ("entries", 4),
+    ("invoices", 4),
+    ("jobs", 1),
+    ("lots", 2),
+    ("orders", 1),
+    ("prices", 3),
+    ("recurrences", 2),
+    ("schedxactions", 1),
+    ("slots", 4),
+    ("splits", 5),
+    ("taxtable_entries", 3),
+    ("taxtables", 2),
+    ("transactions", 4),
+    ("vendors", 1),
+];
+
+/// CORE_TABLES are the tables every screen reads, so a newer version of one of them means
+/// the file can't be read safely.
+const CORE_TABLES: [&str; 6] = [
+    "accounts",
+    "books",
+    "commodities",
+    "slots",
+    "splits",
+    "transactions",
+];
+
+/// VersionReport is what checking the versions table found.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct VersionReport {
+    pub gnucash_version: Option<String>, //gnucash_version is the GnuCash version that wrote the file. (Ex: '5.8')
+    pub warnings: Vec<String>,           //warnings are problems the user should know about.
+    pub errors: Vec<String>,             //errors are problems that keep the file from being opened.
+}
+
+/// TableCount holds the number of tables found with a name.
+#[derive(Debug, Deserialize)]
+struct TableCount {
+    count: i64,
+}
+
+/// retrieve_all_versions retrieves all the rows of the versions table, or none if the file
+/// doesn't have a versions table.
+pub fn retrieve_all_versions() -> Result<Vec<Version>, MoneyManagerError> {
+    let table_counts: Vec<TableCount> = dhu::query_rows(
+        "SELECT COUNT(*) AS count FROM sqlite_master WHERE type='table' AND name='versions'",
+        None,
+    )?;
+
+    if table_counts.iter().all(|table_count| table_count.count == 0) {
+        return Ok(Vec::new());
+    }
+
+    dhu::query_rows(&format!("SELECT {} FROM versions", FIELDS), None)
+}
+
+/// format_gnucash_version formats a version from the 'Gnucash' row the way GnuCash displays
+/// it. GnuCash 3 and later store major * 1000000 + minor, while older versions store
+/// major * 1000000 + minor * 10000 + micro * 100.
+pub fn format_gnucash_version(version: i64) -> String {
+    let major = version / 1000000;
+    let rest = version % 1000000;
+
+    if major >= 3 {
+        format!("{}.{}", major, rest)
+    } else {
+        format!("{}.{}.{}", major, rest / 10000, (rest % 10000) / 100)
+    }
+}
+
+/// check_versions checks the given rows of the versions table against the table versions this
+/// app knows. A newer version of a core table is an error, and a newer version of any other
+/// table is a warning, since those screens may not understand it.
+pub fn check_versions(versions: &[Version]) -> VersionReport {
+    let mut report = VersionReport::default();
+
+    if versions.is_empty() {
+        report.warnings.push(
+            "This file doesn't say which version of GnuCash wrote it, so it may not open in GnuCash."
+                .to_string(),
+        );
+        return report;
+    }
+
+    for version in versions {
+        match version.table_name.as_str() {
+            "Gnucash" => {
+                report.gnucash_version = Some(format_gnucash_version(version.table_version))
+            }
+            "Gnucash-Resave" => {
+                if version.table_version > GNUCASH_VERSION {
+                    report.warnings.push(format!(
+                        "This file needs GnuCash {} or newer, so some of it may not be understood.",
+                        format_gnucash_version(version.table_version)
+                    ));
+                }
+            }
+            table_name => {
+                let known_version = TABLE_VERSIONS
+                    .iter()
+                    .find(|(known_table_name, _)| *known_table_name == table_name)
+                    .map(|(_, known_version)| *known_version);
+
+                match known_version {
+                    Some(known_version) if version.table_version > known_version => {
+                        let message = format!(
+                            "The {} table is version {}, but only version {} is supported.",
+                            table_name, version.table_version, known_version
+                        );
+                        if CORE_TABLES.contains(&table_name) {
+                            report.errors.push(message);
+                        } else {
+                            report.warnings.push(message);
+                        }
+                    }
+                    Some(_) => {}
+                    None => report
+                        .warnings
+                        .push(format!("The {} table is not known, and will be ignored.", table_name)),
+                }
+            }
+        }
+    }
+
+    report
+}
+
+/// create_default_versions_sql creates the statements that fill in the versions table the way
+/// GnuCash does for a new book.
+pub fn create_default_versions_sql() -> String {
+    let mut sql = format!(
+        "INSERT INTO versions({fields}) VALUES('Gnucash',{gnucash_version});
+         INSERT INTO versions({fields}) VALUES('Gnucash-Resave',{gnucash_resave_version});",
+        fields = FIELDS,
+        gnucash_version = GNUCASH_VERSION,
+        gnucash_resave_version = GNUCASH_RESAVE_VERSION,
+    );
+
+    for (table_name, table_version) in TABLE_VERSIONS {
+        sql.push_str(&format!(
+            "INSERT INTO versions({}) VALUES('{}',{});",
+            FIELDS, table_name, table_version
+        ));
+    }
+
+    sql
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(table_name: &str, table_version: i64) -> Version {
+        Version {
+            table_name: table_name.to_string(),
+            table_version,
+        }
+    }
+
+    #[test]
+    fn format_gnucash_version_handles_old_and_new_versions() {
+        assert_eq!(format_gnucash_version(5000008), "5.8");
+        assert_eq!(format_gnucash_version(2060600), "2.6.6");
+    }
+
+    #[test]
+    fn check_versions_reports_the_gnucash_version() {
+        let report = check_versions(&[version("Gnucash", 4000013), version("accounts", 1)]);
+
+        assert_eq!(report.gnucash_version, Some("4.13".to_string()));
+        assert!(report.warnings.is_empty());
+        assert!(report.errors.is_empty());
+    }
+
+    #[test]
+    fn check_versions_refuses_newer_core_tables_and_warns_on_others() {
+        let report = check_versions(&[version("splits", 6), version("invoices", 5)]);
+
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.warnings.len(), 1);
+    }
+
+    #[test]
+    fn check_versions_warns_without_versions() {
+        assert_eq!(check_versions(&[]).warnings.len(), 1);
+    }
+}
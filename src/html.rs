@@ -1,7 +1,16 @@
 pub mod accounts_screen;
+pub mod business_screen;
+pub mod categorization_rules_screen;
+pub mod check_and_repair_screen;
+pub mod closing_screen;
+pub mod employees_screen;
+pub mod invoices_screen;
+pub mod jobs_screen;
 pub mod main_menu;
 pub mod reports_screen;
 pub mod settings_screen;
+pub mod taxtables_screen;
+pub mod transaction_templates_screen;
 pub mod transactions_screen;
 
 
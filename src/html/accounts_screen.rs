@@ -4,8 +4,9 @@ use std::collections::HashMap;
 use std::convert::TryInto;
 use crate::database_tables::{accounts_manager, commodities_manager};
 use crate::html::transactions_screen::{currently_loaded_account_guid_string, load_transactions_for_account_into_body_for_all_time, load_transactions_for_account_into_body_for_one_year_from_memory};
-use crate::utility::html_helper_utility::{display_transactions_older_than_one_year, document_create_body_table_header, document_create_element, document_query_selector, show_loading_message};
-use crate::utility::{database_helper_utility as dhu, js_helper_utility as js, sql_helper_utility as shu};
+use crate::utility::html_helper_utility::{display_error_banner, display_message_banner, display_transactions_older_than_one_year, document_create_body_table_header, document_create_element, document_query_selector, show_loading_message};
+use crate::utility::error_helper_utility::MoneyManagerError;
+use crate::utility::{database_helper_utility as dhu, js_helper_utility as js};
 use crate::{
     database_tables::accounts_manager::Account, utility::html_helper_utility,
 };
@@ -238,20 +239,14 @@ pub fn save_account_with_guid(account_guid: Uuid) {
         .dyn_into::<web_sys::HtmlOptionElement>()
         .unwrap();
 
-    let account_type = match option.value().as_str() {
-        "ASSET" => accounts_manager::AccountType::ASSET,
-        "BANK" => accounts_manager::AccountType::BANK,
-        "CASH" => accounts_manager::AccountType::CASH,
-        "CREDIT" => accounts_manager::AccountType::CREDIT,
-        "EQUITY" => accounts_manager::AccountType::EQUITY,
-        "EXPENSE" => accounts_manager::AccountType::EXPENSE,
-        "INCOME" => accounts_manager::AccountType::INCOME,
-        "LIABILITY" => accounts_manager::AccountType::LIABILITY,
-        "RECEIVABLE" => accounts_manager::AccountType::RECEIVABLE,
-        _ => {
-            panic!("The option {} is not valid!", option.value());
-        }
-    };
+    if option.value().is_empty() {
+        display_error_banner(&MoneyManagerError::Validation(format!(
+            "The option {} is not valid!",
+            option.value()
+        )));
+        return;
+    }
+    let account_type = accounts_manager::AccountType::from(option.value());
 
     let commodity_select = document_query_selector("#account_editor_account_commodity_select")
         .dyn_into::<web_sys::HtmlSelectElement>()
@@ -266,8 +261,13 @@ pub fn save_account_with_guid(account_guid: Uuid) {
         .dyn_into::<web_sys::HtmlOptionElement>()
         .unwrap();
 
-    let commodity_guid = dhu::convert_string_to_guid(option.value())
-        .expect(&format!("Failed to turn {} into GUID!", option.value()));
+    let commodity_guid = match dhu::convert_string_to_guid(option.value()) {
+        Ok(commodity_guid) => commodity_guid,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
 
     let account_code = document_query_selector("#account_editor_account_code_input")
         .dyn_into::<web_sys::HtmlInputElement>()
@@ -302,9 +302,38 @@ pub fn save_account_with_guid(account_guid: Uuid) {
         }
     };
 
-    let parent_guid = accounts_manager::retrieve_account_for_account_type(account_type.to_string())
+    //Accounts go under the parent chosen for them, as long as their type fits there. Otherwise
+    //they go under the top level account of their type, or else the one they belong under
+    let parent_value = document_query_selector("#account_editor_account_parent_select")
+        .dyn_into::<web_sys::HtmlSelectElement>()
         .unwrap()
-        .guid;
+        .value();
+    let parent_account = if parent_value.is_empty() {
+        accounts_manager::retrieve_account_for_account_type(account_type.to_string())
+            .or_else(|_| {
+                accounts_manager::retrieve_account_for_account_type(
+                    account_type.top_level_type().to_string(),
+                )
+            })
+            .map(|parent_account| parent_account.guid)
+    } else {
+        dhu::convert_string_to_guid(parent_value).and_then(|parent_guid| {
+            accounts_manager::validate_account_move(
+                &accounts_manager::retrieve_all_account_nodes()?,
+                account_guid,
+                &account_type,
+                parent_guid,
+            )?;
+            Ok(parent_guid)
+        })
+    };
+    let parent_guid = match parent_account {
+        Ok(parent_guid) => parent_guid,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
 
     let account_to_save = Account {
         guid: account_guid,
@@ -324,28 +353,12 @@ pub fn save_account_with_guid(account_guid: Uuid) {
         },
     };
 
-    js::alert(&format!("Let's save a guid: {guid}, name: {name}, account_type: {account_type:?}, 
-                        commodity_guid: {commodity_guid}, commodity_scu: {commodity_scu}, non_std_scu: {non_std_scu},
-                        parent_guid: {parent_guid}, code: {code}, description: {description}, hidden: {hidden:?},
-                        placeholder: {placeholder:?}",
-                        guid=account_to_save.guid,
-                        name=account_to_save.name,
-                        account_type=account_to_save.account_type,
-                        commodity_guid=account_to_save.commodity_guid.unwrap(),
-                        commodity_scu=account_to_save.commodity_scu,
-                        non_std_scu=account_to_save.non_std_scu,
-                        parent_guid=account_to_save.parent_guid.unwrap(),
-                        code=account_to_save.code,
-                        description=account_to_save.description,
-                        hidden=account_to_save.hidden,
-                        placeholder=account_to_save.placeholder,
-                    ));
     match accounts_manager::save_new_and_delete_current(account_to_save) {
         Ok(_e) => {
-            js::alert("Success!");
+            display_message_banner("The account was saved.");
         }
-        Err(e) => {
-            js::alert(&format!("There was an error: '{}'", e));
+        Err(error) => {
+            display_error_banner(&error);
         }
     }
 }
@@ -374,7 +387,8 @@ pub fn load_account_editor_into_body(account_guid: Uuid) {
         },
     };
     //If the result is ok, then we can setup the account for the retrieved value
-    if result.is_ok() {
+    let account_exists = result.is_ok();
+    if account_exists {
         let result_account = result.unwrap();
         account.name = result_account.name;
         account.account_type = result_account.account_type;
@@ -401,66 +415,32 @@ pub fn load_account_editor_into_body(account_guid: Uuid) {
 
     //Create the new account form
     let account_type = document_create_element("div");
+    //An account keeps a type that isn't offered for new accounts
+    let mut account_types = accounts_manager::AccountType::SELECTABLE.to_vec();
+    if !account_types.contains(&account.account_type) {
+        account_types.push(account.account_type.clone());
+    }
+    let account_type_options: String = account_types
+        .iter()
+        .map(|account_type| {
+            format!(
+                "<option {selected}>{account_type}</option>",
+                selected = if *account_type == account.account_type {
+                    "SELECTED"
+                } else {
+                    ""
+                },
+                account_type = dhu::sanitize_string(account_type.to_string()),
+            )
+        })
+        .collect();
     let account_type_html: String = format!(
         "
         <label id='account_editor_account_type_label'>Account Type: 
             <select id='account_editor_account_type_select'>
-                <option {asset}>ASSET</option>
-                <option {bank}>BANK</option>
-                <option {cash}>CASH</option>
-                <option {credit}>CREDIT</option>
-                <option {equity}>EQUITY</option>
-                <option {expense}>EXPENSE</option>
-                <option {income}>INCOME</option>
-                <option {liability}>LIABILITY</option>
-                <option {receivable}>RECEIVABLE</option>
+                {account_type_options}
             </select>
         </label>",
-        asset = if account.account_type == accounts_manager::AccountType::ASSET {
-            "SELECTED"
-        } else {
-            ""
-        },
-        bank = if account.account_type == accounts_manager::AccountType::BANK {
-            "SELECTED"
-        } else {
-            ""
-        },
-        cash = if account.account_type == accounts_manager::AccountType::CASH {
-            "SELECTED"
-        } else {
-            ""
-        },
-        credit = if account.account_type == accounts_manager::AccountType::CREDIT {
-            "SELECTED"
-        } else {
-            ""
-        },
-        equity = if account.account_type == accounts_manager::AccountType::EQUITY {
-            "SELECTED"
-        } else {
-            ""
-        },
-        expense = if account.account_type == accounts_manager::AccountType::EXPENSE {
-            "SELECTED"
-        } else {
-            ""
-        },
-        income = if account.account_type == accounts_manager::AccountType::INCOME {
-            "SELECTED"
-        } else {
-            ""
-        },
-        liability = if account.account_type == accounts_manager::AccountType::LIABILITY {
-            "SELECTED"
-        } else {
-            ""
-        },
-        receivable = if account.account_type == accounts_manager::AccountType::RECEIVABLE {
-            "SELECTED"
-        } else {
-            ""
-        },
     );
     account_type.set_inner_html(&account_type_html);
 
@@ -468,6 +448,50 @@ pub fn load_account_editor_into_body(account_guid: Uuid) {
         .append_child(&account_type)
         .expect("Failed to append child to body_div");
 
+    //Create the account parent elements
+    let account_nodes = match accounts_manager::retrieve_all_account_nodes() {
+        Ok(account_nodes) => account_nodes,
+        Err(error) => {
+            display_error_banner(&error);
+            Vec::new()
+        }
+    };
+    let mut account_full_names: Vec<(Uuid, String)> =
+        accounts_manager::account_full_names(&account_nodes).into_iter().collect();
+    account_full_names.sort_by(|a, b| a.1.cmp(&b.1));
+    {
+        //An account can't go under itself, or its own sub-accounts
+        let descendant_guids = accounts_manager::descendant_guids(&account_nodes, account_guid);
+        let parent_options: String = account_full_names
+            .iter()
+            .filter(|(guid, _)| *guid != account_guid && !descendant_guids.contains(guid))
+            .map(|(guid, full_name)| {
+                format!(
+                    "<option {selected} value='{guid}'>{full_name}</option>",
+                    selected = if account.parent_guid == Some(*guid) {
+                        "SELECTED"
+                    } else {
+                        ""
+                    },
+                    guid = dhu::convert_guid_to_sqlite_string(guid),
+                    full_name = dhu::sanitize_string(full_name.clone()),
+                )
+            })
+            .collect();
+
+        let account_parent_div = document_create_element("div");
+        account_parent_div.set_inner_html(&format!(
+            "
+            <label id='account_editor_account_parent_label'>Parent Account: 
+                <select id='account_editor_account_parent_select'>
+                    <option value=''>(Top Level Account for its Type)</option>
+                    {parent_options}
+                </select>
+            </label>",
+        ));
+        body_div.append_child(&account_parent_div).unwrap();
+    }
+
     //Create the account name elements
     {
         let account_name_div = document_create_element("div");
@@ -536,7 +560,13 @@ pub fn load_account_editor_into_body(account_guid: Uuid) {
 
     //Create the account commodities elements
     {
-        let commodities = commodities_manager::retrieve_all_commodities();
+        let commodities = match commodities_manager::retrieve_all_commodities() {
+            Ok(commodities) => commodities,
+            Err(error) => {
+                display_error_banner(&error);
+                Vec::new()
+            }
+        };
         let account_commodity_div = document_create_element("div");
         body_div.append_child(&account_commodity_div).unwrap();
 
@@ -660,48 +690,230 @@ pub fn load_account_editor_into_body(account_guid: Uuid) {
         okay_button.set_onclick(Some(okay_button_on_click.as_ref().unchecked_ref()));
         okay_button_on_click.forget();
     }
+
+    //Create the merge elements, for moving everything in this account into another one
+    if account_exists {
+        let merge_targets: Vec<&(Uuid, String)> = account_full_names
+            .iter()
+            .filter(|(guid, _)| {
+                accounts_manager::validate_account_merge(&account_nodes, account_guid, *guid)
+                    .is_ok()
+            })
+            .collect();
+        let merge_target_options = |prefix: &str| -> String {
+            merge_targets
+                .iter()
+                .map(|(guid, full_name)| {
+                    format!(
+                        "<option value='{guid}'>{prefix}{full_name}</option>",
+                        guid = dhu::convert_guid_to_sqlite_string(guid),
+                        full_name = dhu::sanitize_string(full_name.clone()),
+                    )
+                })
+                .collect()
+        };
+        let merge_options = merge_target_options("");
+
+        let account_merge_div = document_create_element("div");
+        account_merge_div.set_id("account_editor_merge_div");
+        account_merge_div.set_inner_html(&format!(
+            "
+            <label id='account_editor_merge_account_label'>Merge Into: 
+                <select id='account_editor_merge_account_select'>
+                    <option value=''></option>
+                    {merge_options}
+                </select>
+            </label>",
+        ));
+        body_div.append_child(&account_merge_div).unwrap();
+
+        let merge_button = document_create_element("input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap();
+        merge_button.set_type("button");
+        merge_button.set_id("account_editor_merge_button");
+        merge_button.set_value("Merge");
+        account_merge_div.append_child(&merge_button).unwrap();
+
+        //Setup the merge_button handler
+        let merge_button_on_click = Closure::wrap(Box::new(move || {
+            merge_account_with_guid(account_guid);
+        }) as Box<dyn Fn()>);
+
+        merge_button.set_onclick(Some(merge_button_on_click.as_ref().unchecked_ref()));
+        merge_button_on_click.forget();
+
+        //Create the delete elements, with the choice of what happens to the account's transactions
+        let account_delete_div = document_create_element("div");
+        account_delete_div.set_id("account_editor_delete_div");
+        account_delete_div.set_inner_html(&format!(
+            "
+            <label id='account_editor_delete_action_label'>Its Transactions: 
+                <select id='account_editor_delete_action_select'>
+                    <option value=''>Delete Them, and its Sub-Accounts</option>
+                    {move_options}
+                </select>
+            </label>",
+            move_options = merge_target_options("Move Them To "),
+        ));
+        body_div.append_child(&account_delete_div).unwrap();
+
+        let delete_button = document_create_element("input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap();
+        delete_button.set_type("button");
+        delete_button.set_id("account_editor_delete_button");
+        delete_button.set_value("Delete Account");
+        account_delete_div.append_child(&delete_button).unwrap();
+
+        //Setup the delete_button handler
+        let delete_button_on_click = Closure::wrap(Box::new(move || {
+            delete_account_with_guid(account_guid);
+        }) as Box<dyn Fn()>);
+
+        delete_button.set_onclick(Some(delete_button_on_click.as_ref().unchecked_ref()));
+        delete_button_on_click.forget();
+    }
 }
 
-/// load_accounts_with_balances_from_memory loads all the accounts with balances from memory.
-/// This includes transactions in the future.
-pub fn load_accounts_with_balances_from_memory() {
-    if crate::DATABASE.lock().unwrap().len() == 0 {
-        js::alert("Please select a database to refresh your accounts view.");
+/// delete_account_with_guid deletes the account with the given guid, after showing the user
+/// what refers to it, and what will happen to it. Then it loads the accounts.
+fn delete_account_with_guid(account_guid: Uuid) {
+    let delete_action_select = document_query_selector("#account_editor_delete_action_select")
+        .dyn_into::<web_sys::HtmlSelectElement>()
+        .unwrap();
+    let deletion = if delete_action_select.value().is_empty() {
+        accounts_manager::AccountDeletion::Delete
+    } else {
+        match dhu::convert_string_to_guid(delete_action_select.value()) {
+            Ok(target_guid) => accounts_manager::AccountDeletion::MoveTo(target_guid),
+            Err(error) => {
+                display_error_banner(&error);
+                return;
+            }
+        }
+    };
+
+    let references = match accounts_manager::retrieve_account_references(account_guid) {
+        Ok(references) => references,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+    if let Err(error) = references.validate_deletion(&deletion) {
+        display_error_banner(&error);
         return;
     }
 
-    //Prepare a statement
-    let stmt: dhu::Statement =
-        crate::DATABASE.lock().unwrap()[0].prepare(&shu::load_accounts_with_balances());
-    stmt.getAsObject();
+    let account_name = document_query_selector("#account_editor_account_name_input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap()
+        .value();
+    let what_happens = match deletion {
+        accounts_manager::AccountDeletion::Delete => format!(
+            "Its transactions, including their splits in other accounts, its sub-accounts, \
+             budget amounts, scheduled transactions, rules, and templates will be deleted \
+             along with {account_name}."
+        ),
+        accounts_manager::AccountDeletion::MoveTo(_) => format!(
+            "All of it, and its sub-accounts will be moved to {target_name}, and then \
+             {account_name} will be deleted.",
+            target_name = delete_action_select
+                .selected_options()
+                .item(0)
+                .and_then(|option| option.text_content())
+                .unwrap_or_default()
+                .trim_start_matches("Move Them To "),
+        ),
+    };
+    if !js::confirm(&format!(
+        "{references}\n\n{what_happens}\n\nAre you sure you want to delete {account_name}?",
+        references = references.describe(&account_name),
+    )) {
+        return;
+    }
 
-    let mut accounts = Vec::new();
+    match accounts_manager::delete_account(account_guid, deletion) {
+        Ok(_) => {
+            load_accounts_with_balances_from_memory();
+            display_message_banner(&format!("{account_name} was deleted."));
+        }
+        Err(error) => {
+            display_error_banner(&error);
+        }
+    }
+}
 
-    while stmt.step() {
-        let row = stmt.getAsObject();
-        //js::log(&("Here is a row: ".to_owned() + &js::stringify(row.clone()).to_owned()));
+/// merge_account_with_guid merges the account with the given guid into the account chosen in
+/// the account editor, after asking the user, and then loads the accounts.
+fn merge_account_with_guid(account_guid: Uuid) {
+    let merge_account_select = document_query_selector("#account_editor_merge_account_select")
+        .dyn_into::<web_sys::HtmlSelectElement>()
+        .unwrap();
+    if merge_account_select.value().is_empty() {
+        display_error_banner(&MoneyManagerError::Validation(
+            "Please choose the account to merge into.".to_string(),
+        ));
+        return;
+    }
+    let target_guid = match dhu::convert_string_to_guid(merge_account_select.value()) {
+        Ok(target_guid) => target_guid,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+    let account_name = document_query_selector("#account_editor_account_name_input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap()
+        .value();
+    let target_name = merge_account_select
+        .selected_options()
+        .item(0)
+        .and_then(|option| option.text_content())
+        .unwrap_or_default();
+
+    if !js::confirm(&format!(
+        "Are you sure you want to move all of the transactions, lots, and sub-accounts of \
+         {account_name} into {target_name}, and then delete {account_name}?"
+    )) {
+        return;
+    }
 
-        let mut account: Account = serde_wasm_bindgen::from_value(row.clone()).unwrap();
-        let tags: serde_json::Value =
-            serde_json::from_str(js::stringify(row.clone()).as_str()).unwrap();
+    match accounts_manager::merge_accounts(account_guid, target_guid) {
+        Ok(_) => {
+            load_accounts_with_balances_from_memory();
+            display_message_banner(&format!("{account_name} was merged into {target_name}."));
+        }
+        Err(error) => {
+            display_error_banner(&error);
+        }
+    }
+}
 
-        let balance = format!("{}", tags["balance"])
-            .parse::<f64>()
-            .expect("Balance is not valid!");
-        account
-            .tags
-            .insert("balance".to_string(), balance.to_string());
+/// load_accounts_with_balances_from_memory loads all the accounts with balances from memory.
+/// This includes transactions in the future.
+pub fn load_accounts_with_balances_from_memory() {
+    let accounts_with_balances = match accounts_manager::retrieve_all_accounts_with_balances() {
+        Ok(accounts_with_balances) => accounts_with_balances,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
 
-        let mnemonic: String =
-            dhu::remove_first_and_last_double_quotes_from_string(tags["mnemonic"].to_string());
+    let mut accounts = Vec::new();
+    for account_with_balance in accounts_with_balances {
+        let mut account = account_with_balance.account;
+        let balance = account_with_balance.balance * account.account_type.balance_sign();
+        account.tags.insert("balance".to_string(), balance.to_string());
         account
             .tags
-            .insert("mnemonic".to_string(), mnemonic.clone());
+            .insert("mnemonic".to_string(), account_with_balance.mnemonic);
 
         accounts.push(account);
     }
 
-    stmt.free();
-
     load_accounts_into_body(accounts);
 }
@@ -0,0 +1,66 @@
+use wasm_bindgen::{prelude::Closure, JsCast};
+
+use crate::html::{employees_screen, invoices_screen, jobs_screen, taxtables_screen};
+use crate::utility::html_helper_utility::{document_create_element, document_query_selector};
+
+/// load_business_into_body loads the list of business screens into the body.
+pub fn load_business_into_body() {
+    //Clear out the body, and footer first
+    let body_div = document_query_selector("#body");
+    body_div.set_inner_html("");
+    let footer_div = document_query_selector("#footer");
+    footer_div.set_inner_html("");
+
+    //Create the business form first
+    let business_div = document_create_element("div");
+    business_div.set_id("business");
+    body_div.append_child(&business_div).unwrap();
+
+    //Then the Header
+    let business_header = document_create_element("h3");
+    business_header.set_id("business_header");
+    business_header.set_inner_html("Business");
+    business_div.append_child(&business_header).unwrap();
+
+    //Then a button for each of the screens
+    let screens: Vec<(&str, &str, fn())> = vec![
+        (
+            "business_invoices_button",
+            "Invoices, Bills & Vouchers",
+            invoices_screen::load_invoices_into_body,
+        ),
+        (
+            "business_jobs_button",
+            "Jobs",
+            jobs_screen::load_jobs_into_body,
+        ),
+        (
+            "business_employees_button",
+            "Employees",
+            employees_screen::load_employees_into_body,
+        ),
+        (
+            "business_taxtables_button",
+            "Tax Tables",
+            taxtables_screen::load_taxtables_into_body,
+        ),
+    ];
+
+    for (button_id, button_text, load_screen) in screens {
+        let button = document_create_element("button");
+        button.set_id(button_id);
+        button.set_text_content(Some(button_text));
+        button
+            .class_list()
+            .add_1("business_button")
+            .expect("Failed to add class to element.");
+
+        let button_on_click = Closure::wrap(Box::new(move || {
+            load_screen();
+        }) as Box<dyn Fn()>);
+        button.set_onclick(Some(button_on_click.as_ref().unchecked_ref()));
+        button_on_click.forget();
+
+        business_div.append_child(&button).unwrap();
+    }
+}
@@ -0,0 +1,459 @@
+use uuid::Uuid;
+use wasm_bindgen::{prelude::Closure, JsCast};
+
+use crate::database_tables::accounts_manager;
+use crate::database_tables::categorization_rules_manager::{
+    self, CategorizationRule, RuleMatchType,
+};
+use crate::utility::html_helper_utility::{
+    display_error_banner, document_create_body_table_header, document_create_element,
+    document_create_select, document_query_selector,
+};
+use crate::utility::{database_helper_utility as dhu, js_helper_utility as js};
+use crate::utility::error_helper_utility::MoneyManagerError;
+
+/// load_categorization_rules_into_body loads the list of categorization rules into the body, in
+/// the order they are tried.
+pub fn load_categorization_rules_into_body() {
+    let rules = match categorization_rules_manager::retrieve_all_categorization_rules() {
+        Ok(rules) => rules,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+
+    //Clear out the body, and footer first
+    let body_div = document_query_selector("#body");
+    body_div.set_inner_html("");
+    let footer_div = document_query_selector("#footer");
+    footer_div.set_inner_html("");
+
+    //Create the New Rule Button
+    let new_rule_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .expect("Failed to create New Rule Button!");
+    new_rule_button.set_type("button");
+    new_rule_button.set_id("new_categorization_rule_button");
+    new_rule_button.set_value("New Rule");
+
+    let new_rule_button_on_click = Closure::wrap(Box::new(move || {
+        load_categorization_rule_editor_into_body(Uuid::new_v4());
+    }) as Box<dyn Fn()>);
+
+    new_rule_button.set_onclick(Some(new_rule_button_on_click.as_ref().unchecked_ref()));
+    new_rule_button_on_click.forget();
+
+    body_div
+        .append_child(&new_rule_button)
+        .expect("Failed to append New Rule Button!");
+
+    //Create the header for the body
+    {
+        let headings = vec![
+            "Priority".to_string(),
+            "Name".to_string(),
+            "Match".to_string(),
+            "Category".to_string(),
+        ];
+        let rules_header =
+            document_create_body_table_header("div", headings, "categorization_rule");
+
+        body_div
+            .append_child(&rules_header)
+            .expect("Failed to append rules_header to body!");
+    }
+
+    //Create categorization_rules_div, and place it in the body
+    let rules_div = document_create_element("div");
+    rules_div.set_id("categorization_rules_div");
+    rules_div
+        .class_list()
+        .add_1("body_table")
+        .expect("Failed to add class to element.");
+    body_div
+        .append_child(&rules_div)
+        .expect("Failed to append rules_div to body!");
+
+    let account_names = accounts_manager::retrieve_all_account_names().unwrap_or_default();
+
+    for rule in rules {
+        let rule_guid = rule.guid;
+
+        let rule_div = document_create_element("div");
+        rule_div
+            .class_list()
+            .add_1("body_row")
+            .expect("Failed to add class to element.");
+        rules_div
+            .append_child(&rule_div)
+            .expect("Failed to append rule_div to rules_div!");
+
+        //Describe the match, like "contains 'KROGER', 10.00 to 200.00"
+        let mut match_description = match rule.match_type {
+            RuleMatchType::Contains => format!("contains '{}'", rule.pattern),
+            RuleMatchType::Regex => format!("matches /{}/", rule.pattern),
+        };
+        match (rule.minimum_amount, rule.maximum_amount) {
+            (Some(minimum), Some(maximum)) => match_description.push_str(&format!(
+                ", {} to {}",
+                dhu::format_money(minimum),
+                dhu::format_money(maximum)
+            )),
+            (Some(minimum), None) => match_description
+                .push_str(&format!(", at least {}", dhu::format_money(minimum))),
+            (None, Some(maximum)) => match_description
+                .push_str(&format!(", at most {}", dhu::format_money(maximum))),
+            (None, None) => {}
+        }
+        let mut category_description = account_names
+            .iter()
+            .find(|account_name| account_name.guid == rule.account_guid)
+            .map(|account_name| account_name.name.clone())
+            .unwrap_or_default();
+        if !rule.description.trim().is_empty() {
+            category_description.push_str(&format!(", as '{}'", rule.description));
+        }
+
+        for (class_name, text) in [
+            ("categorization_rule_priority", rule.priority.to_string()),
+            ("categorization_rule_name", rule.name.clone()),
+            ("categorization_rule_match", match_description),
+            ("categorization_rule_category", category_description),
+        ] {
+            let column_div = document_create_element("div");
+            column_div.set_text_content(Some(&text));
+            column_div
+                .class_list()
+                .add_1(class_name)
+                .expect("Failed to add class to element.");
+            rule_div
+                .append_child(&column_div)
+                .expect("Failed to append column_div!");
+        }
+
+        let rule_div_on_click = Closure::wrap(Box::new(move || {
+            load_categorization_rule_editor_into_body(rule_guid);
+        }) as Box<dyn Fn()>);
+        rule_div.set_onclick(Some(rule_div_on_click.as_ref().unchecked_ref()));
+        rule_div_on_click.forget();
+    }
+}
+
+/// read_categorization_rule_from_editor reads the rule in the editor, for the given guid.
+fn read_categorization_rule_from_editor(
+    rule_guid: Uuid,
+) -> Result<CategorizationRule, MoneyManagerError> {
+    let input_value = |selector: &str| {
+        document_query_selector(selector)
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap()
+            .value()
+    };
+    let select_value = |selector: &str| {
+        document_query_selector(selector)
+            .dyn_into::<web_sys::HtmlSelectElement>()
+            .unwrap()
+            .value()
+    };
+    let amount_value = |selector: &str, label: &str| {
+        let amount = input_value(selector);
+        if amount.trim().is_empty() {
+            return Ok(None);
+        }
+        amount.trim().parse::<f64>().map(Some).map_err(|_| {
+            MoneyManagerError::Validation(format!(
+                "Please enter a valid {} amount, or leave it blank.",
+                label
+            ))
+        })
+    };
+
+    let priority = input_value("#categorization_rule_editor_priority_input");
+    let priority = if priority.trim().is_empty() {
+        0
+    } else {
+        priority.trim().parse::<i64>().map_err(|_| {
+            MoneyManagerError::Validation("Please enter a whole number for the priority.".to_string())
+        })?
+    };
+    let account_guid = dhu::convert_string_to_guid(select_value(
+        "#categorization_rule_editor_account_select",
+    ))
+    .map_err(|_| {
+        MoneyManagerError::Validation("Please select a category for the rule.".to_string())
+    })?;
+
+    Ok(CategorizationRule {
+        guid: rule_guid,
+        name: input_value("#categorization_rule_editor_name_input")
+            .trim()
+            .to_string(),
+        priority,
+        match_type: if select_value("#categorization_rule_editor_match_type_select") == "regex" {
+            RuleMatchType::Regex
+        } else {
+            RuleMatchType::Contains
+        },
+        pattern: input_value("#categorization_rule_editor_pattern_input"),
+        minimum_amount: amount_value("#categorization_rule_editor_minimum_amount_input", "minimum")?,
+        maximum_amount: amount_value("#categorization_rule_editor_maximum_amount_input", "maximum")?,
+        account_guid,
+        description: input_value("#categorization_rule_editor_description_input"),
+    })
+}
+
+/// save_categorization_rule_with_guid saves the rule in the editor with the given guid, and
+/// goes back to the list of rules.
+pub fn save_categorization_rule_with_guid(rule_guid: Uuid) {
+    match read_categorization_rule_from_editor(rule_guid)
+        .and_then(categorization_rules_manager::save_categorization_rule)
+    {
+        Ok(_) => load_categorization_rules_into_body(),
+        Err(error) => display_error_banner(&error),
+    }
+}
+
+/// test_categorization_rule_with_guid shows what the rule in the editor does to the test
+/// description, and amount, before it's saved.
+pub fn test_categorization_rule_with_guid(rule_guid: Uuid) {
+    let test_result_div = document_query_selector("#categorization_rule_editor_test_result");
+
+    let rule = match read_categorization_rule_from_editor(rule_guid).and_then(|rule| {
+        categorization_rules_manager::validate_categorization_rule(&rule)?;
+        Ok(rule)
+    }) {
+        Ok(rule) => rule,
+        Err(error) => {
+            test_result_div.set_text_content(Some(&error.to_string()));
+            return;
+        }
+    };
+
+    let test_description = document_query_selector("#categorization_rule_editor_test_description_input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap()
+        .value();
+    let test_amount = document_query_selector("#categorization_rule_editor_test_amount_input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap()
+        .value()
+        .trim()
+        .parse::<f64>()
+        .ok();
+
+    if rule.matches(&test_description, test_amount) {
+        let account_name = accounts_manager::load_account_for_guid(rule.account_guid)
+            .map(|account| account.name)
+            .unwrap_or_default();
+        test_result_div.set_text_content(Some(&format!(
+            "The rule fires: '{}' goes to {}.",
+            rule.rewrite_description(&test_description),
+            account_name
+        )));
+    } else {
+        test_result_div.set_text_content(Some("The rule doesn't fire."));
+    }
+}
+
+/// load_categorization_rule_editor_into_body loads the rule editor into the body for the given
+/// rule_guid. Pass in a new GUID if you want to create a new rule instead.
+pub fn load_categorization_rule_editor_into_body(rule_guid: Uuid) {
+    let account_names = match accounts_manager::retrieve_all_account_names() {
+        Ok(account_names) => account_names,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+    let account_options: Vec<(String, String)> = account_names
+        .iter()
+        .map(|account_name| {
+            (
+                dhu::convert_guid_to_sqlite_string(&account_name.guid),
+                format!("{} ({})", account_name.name, account_name.account_type),
+            )
+        })
+        .collect();
+
+    let rule = categorization_rules_manager::retrieve_categorization_rule_for_guid(rule_guid).ok();
+
+    //Clear out the body, and footer first
+    let body_div = document_query_selector("#body");
+    body_div.set_inner_html("");
+    let footer_div = document_query_selector("#footer");
+    footer_div.set_inner_html("");
+
+    let rule_editor_div = document_create_element("div");
+    rule_editor_div.set_id("categorization_rule_editor");
+    body_div
+        .append_child(&rule_editor_div)
+        .expect("Failed to append rule_editor_div!");
+
+    let header = document_create_element("h3");
+    header.set_inner_html("Categorization Rule");
+    rule_editor_div.append_child(&header).unwrap();
+
+    let append_labeled = |label_text: &str, element: &web_sys::HtmlElement| {
+        let label = document_create_element("label");
+        label.set_inner_html(label_text);
+        label.append_child(element).unwrap();
+        rule_editor_div.append_child(&label).unwrap();
+    };
+    let create_input = |id: &str, value: String| {
+        let input = document_create_element("input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap();
+        input.set_id(id);
+        input.set_value(&value);
+        input
+    };
+    let amount_text = |amount: Option<f64>| amount.map(|amount| amount.to_string()).unwrap_or_default();
+
+    append_labeled(
+        "Name: ",
+        &create_input(
+            "categorization_rule_editor_name_input",
+            rule.as_ref().map(|rule| rule.name.clone()).unwrap_or_default(),
+        ),
+    );
+
+    let priority_input = create_input(
+        "categorization_rule_editor_priority_input",
+        rule.as_ref().map(|rule| rule.priority).unwrap_or(0).to_string(),
+    );
+    priority_input.set_type("number");
+    append_labeled("Priority: ", &priority_input);
+
+    let match_type_select = document_create_select(
+        &[
+            ("contains".to_string(), "Description Contains".to_string()),
+            ("regex".to_string(), "Description Matches Regex".to_string()),
+        ],
+        match rule.as_ref().map(|rule| rule.match_type) {
+            Some(RuleMatchType::Regex) => "regex",
+            _ => "contains",
+        },
+    );
+    match_type_select.set_id("categorization_rule_editor_match_type_select");
+    append_labeled("Match: ", &match_type_select);
+
+    append_labeled(
+        "Pattern: ",
+        &create_input(
+            "categorization_rule_editor_pattern_input",
+            rule.as_ref().map(|rule| rule.pattern.clone()).unwrap_or_default(),
+        ),
+    );
+
+    for (label_text, id, amount) in [
+        (
+            "Minimum Amount: ",
+            "categorization_rule_editor_minimum_amount_input",
+            rule.as_ref().and_then(|rule| rule.minimum_amount),
+        ),
+        (
+            "Maximum Amount: ",
+            "categorization_rule_editor_maximum_amount_input",
+            rule.as_ref().and_then(|rule| rule.maximum_amount),
+        ),
+    ] {
+        let amount_input = create_input(id, amount_text(amount));
+        amount_input.set_type("number");
+        amount_input.set_step("any");
+        amount_input.set_placeholder("Any");
+        append_labeled(label_text, &amount_input);
+    }
+
+    let account_select = document_create_select(
+        &account_options,
+        &rule
+            .as_ref()
+            .map(|rule| dhu::convert_guid_to_sqlite_string(&rule.account_guid))
+            .unwrap_or_default(),
+    );
+    account_select.set_id("categorization_rule_editor_account_select");
+    append_labeled("Category: ", &account_select);
+
+    let description_input = create_input(
+        "categorization_rule_editor_description_input",
+        rule.as_ref().map(|rule| rule.description.clone()).unwrap_or_default(),
+    );
+    description_input.set_placeholder("Keep the description");
+    append_labeled("Rewrite Description To: ", &description_input);
+
+    //Setup the test, so the rule can be tried before it's saved
+    let test_description_input = create_input(
+        "categorization_rule_editor_test_description_input",
+        String::new(),
+    );
+    test_description_input.set_placeholder("Test Description");
+    rule_editor_div.append_child(&test_description_input).unwrap();
+
+    let test_amount_input = create_input("categorization_rule_editor_test_amount_input", String::new());
+    test_amount_input.set_type("number");
+    test_amount_input.set_step("any");
+    test_amount_input.set_placeholder("Test Amount");
+    rule_editor_div.append_child(&test_amount_input).unwrap();
+
+    let test_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    test_button.set_type("button");
+    test_button.set_value("Test");
+    let test_button_on_click = Closure::wrap(Box::new(move || {
+        test_categorization_rule_with_guid(rule_guid);
+    }) as Box<dyn Fn()>);
+    test_button.set_onclick(Some(test_button_on_click.as_ref().unchecked_ref()));
+    test_button_on_click.forget();
+    rule_editor_div.append_child(&test_button).unwrap();
+
+    let test_result_div = document_create_element("div");
+    test_result_div.set_id("categorization_rule_editor_test_result");
+    rule_editor_div.append_child(&test_result_div).unwrap();
+
+    //Setup the buttons
+    let okay_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    okay_button.set_type("button");
+    okay_button.set_value("Okay");
+    let okay_button_on_click = Closure::wrap(Box::new(move || {
+        save_categorization_rule_with_guid(rule_guid);
+    }) as Box<dyn Fn()>);
+    okay_button.set_onclick(Some(okay_button_on_click.as_ref().unchecked_ref()));
+    okay_button_on_click.forget();
+    rule_editor_div.append_child(&okay_button).unwrap();
+
+    let cancel_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    cancel_button.set_type("button");
+    cancel_button.set_value("Cancel");
+    let cancel_button_on_click = Closure::wrap(Box::new(move || {
+        load_categorization_rules_into_body();
+    }) as Box<dyn Fn()>);
+    cancel_button.set_onclick(Some(cancel_button_on_click.as_ref().unchecked_ref()));
+    cancel_button_on_click.forget();
+    rule_editor_div.append_child(&cancel_button).unwrap();
+
+    if rule.is_some() {
+        let delete_button = document_create_element("input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap();
+        delete_button.set_type("button");
+        delete_button.set_value("Delete");
+        let delete_button_on_click = Closure::wrap(Box::new(move || {
+            if !js::confirm("Are you sure you want to delete this rule?") {
+                return;
+            }
+            match categorization_rules_manager::delete_categorization_rule(rule_guid) {
+                Ok(_) => load_categorization_rules_into_body(),
+                Err(error) => display_error_banner(&error),
+            }
+        }) as Box<dyn Fn()>);
+        delete_button.set_onclick(Some(delete_button_on_click.as_ref().unchecked_ref()));
+        delete_button_on_click.forget();
+        rule_editor_div.append_child(&delete_button).unwrap();
+    }
+}
@@ -0,0 +1,191 @@
+use wasm_bindgen::{prelude::Closure, JsCast};
+
+use crate::html::{accounts_screen, transactions_screen};
+use crate::utility::error_helper_utility::MoneyManagerError;
+use crate::utility::integrity_helper_utility::{self as ihu, IntegrityProblem, ProblemKind};
+use crate::utility::html_helper_utility::{
+    display_error_banner, document_create_body_table_header, document_create_element,
+    document_query_selector,
+};
+use crate::utility::{database_helper_utility as dhu, js_helper_utility as js};
+
+/// load_check_and_repair_into_body checks the book, and loads what it found into the body,
+/// with a link to look at each offending item, and a button to repair it.
+pub fn load_check_and_repair_into_body() {
+    let problems = match ihu::retrieve_integrity_problems() {
+        Ok(problems) => problems,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+
+    //Clear out the body, and footer first
+    let body_div = document_query_selector("#body");
+    body_div.set_inner_html("");
+    let footer_div = document_query_selector("#footer");
+    footer_div.set_inner_html("");
+
+    let check_and_repair_div = document_create_element("div");
+    check_and_repair_div.set_id("check_and_repair");
+    body_div.append_child(&check_and_repair_div).unwrap();
+
+    let header = document_create_element("h3");
+    header.set_inner_html("Check & Repair");
+    check_and_repair_div.append_child(&header).unwrap();
+
+    let summary_div = document_create_element("div");
+    summary_div.set_id("check_and_repair_summary");
+    summary_div.set_text_content(Some(&match problems.len() {
+        0 => "No problems were found in this book.".to_string(),
+        1 => "1 problem was found in this book.".to_string(),
+        count => format!("{} problems were found in this book.", count),
+    }));
+    check_and_repair_div.append_child(&summary_div).unwrap();
+
+    if problems.is_empty() {
+        return;
+    }
+
+    //Repair everything at once
+    let repair_all_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    repair_all_button.set_type("button");
+    repair_all_button.set_value("Repair All");
+    let repair_all_problems = problems.clone();
+    let repair_all_button_on_click = Closure::wrap(Box::new(move || {
+        if !js::confirm("Are you sure you want to repair all of these problems?") {
+            return;
+        }
+        //The ones that can't be repaired, like those in the closed period, are skipped
+        let skipped = repair_all_problems
+            .iter()
+            .filter_map(|problem| {
+                ihu::repair_integrity_problem(problem).err().map(|error| {
+                    format!(
+                        "Skipped {}: {}",
+                        problem.description.clone().unwrap_or(problem.kind.title().to_string()),
+                        error
+                    )
+                })
+            })
+            .collect::<Vec<String>>();
+        load_check_and_repair_into_body();
+        if !skipped.is_empty() {
+            display_error_banner(&MoneyManagerError::Validation(skipped.join("\n")));
+        }
+    }) as Box<dyn Fn()>);
+    repair_all_button.set_onclick(Some(repair_all_button_on_click.as_ref().unchecked_ref()));
+    repair_all_button_on_click.forget();
+    check_and_repair_div.append_child(&repair_all_button).unwrap();
+
+    //Create the header for the problems
+    {
+        let headings = vec![
+            "Problem".to_string(),
+            "Item".to_string(),
+            "Amount".to_string(),
+            "".to_string(),
+            "Repair".to_string(),
+        ];
+        let problems_header =
+            document_create_body_table_header("div", headings, "check_and_repair");
+        check_and_repair_div.append_child(&problems_header).unwrap();
+    }
+
+    let problems_div = document_create_element("div");
+    problems_div.set_id("check_and_repair_problems_div");
+    problems_div
+        .class_list()
+        .add_1("body_table")
+        .expect("Failed to add class to element.");
+    check_and_repair_div.append_child(&problems_div).unwrap();
+
+    for problem in problems {
+        problems_div
+            .append_child(&document_create_problem_row(problem))
+            .unwrap();
+    }
+}
+
+/// document_create_problem_row creates a row describing the given problem, with a link to
+/// the offending item and a button to repair it.
+fn document_create_problem_row(problem: IntegrityProblem) -> web_sys::HtmlElement {
+    let row_div = document_create_element("div");
+    row_div
+        .class_list()
+        .add_1("body_row")
+        .expect("Failed to add class to element.");
+
+    let item = match problem.description.clone() {
+        Some(description) if !description.trim().is_empty() => description,
+        _ => dhu::convert_guid_to_sqlite_string(&problem.guid),
+    };
+    let amount = if problem.amount == 0.0 {
+        String::new()
+    } else {
+        dhu::format_money(problem.amount)
+    };
+
+    for (css_class, text) in [
+        ("check_and_repair_problem", problem.kind.title().to_string()),
+        ("check_and_repair_item", item),
+        ("check_and_repair_amount", amount),
+    ] {
+        let cell_div = document_create_element("div");
+        cell_div.set_text_content(Some(&text));
+        cell_div
+            .class_list()
+            .add_1(css_class)
+            .expect("Failed to add class to element.");
+        row_div.append_child(&cell_div).unwrap();
+    }
+
+    //Link to the account editor for account problems, or the register for the rest
+    let view_div = document_create_element("div");
+    view_div
+        .class_list()
+        .add_1("check_and_repair_view")
+        .expect("Failed to add class to element.");
+    if let Some(account_guid) = problem.account_guid {
+        let view_link = document_create_element("a");
+        view_link.set_text_content(Some("View"));
+        view_link.set_attribute("href", "#").unwrap();
+        let kind = problem.kind;
+        let view_link_on_click = Closure::wrap(Box::new(move || {
+            if kind == ProblemKind::AccountWithMissingCommodity {
+                accounts_screen::load_account_editor_into_body(account_guid);
+            } else {
+                transactions_screen::load_transactions_for_account_into_body_for_all_time(
+                    dhu::convert_guid_to_sqlite_string(&account_guid),
+                );
+            }
+        }) as Box<dyn Fn()>);
+        view_link.set_onclick(Some(view_link_on_click.as_ref().unchecked_ref()));
+        view_link_on_click.forget();
+        view_div.append_child(&view_link).unwrap();
+    }
+    row_div.append_child(&view_div).unwrap();
+
+    let repair_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    repair_button.set_type("button");
+    repair_button.set_value(problem.kind.repair_description());
+    repair_button
+        .class_list()
+        .add_1("check_and_repair_repair")
+        .expect("Failed to add class to element.");
+    let repair_button_on_click = Closure::wrap(Box::new(move || {
+        match ihu::repair_integrity_problem(&problem) {
+            Ok(_) => load_check_and_repair_into_body(),
+            Err(error) => display_error_banner(&error),
+        }
+    }) as Box<dyn Fn()>);
+    repair_button.set_onclick(Some(repair_button_on_click.as_ref().unchecked_ref()));
+    repair_button_on_click.forget();
+    row_div.append_child(&repair_button).unwrap();
+
+    row_div
+}
@@ -0,0 +1,254 @@
+use chrono::{Datelike, NaiveDate};
+use wasm_bindgen::{prelude::Closure, JsCast};
+
+use crate::database_tables::accounts_manager::{self, AccountType};
+use crate::database_tables::transactions_manager;
+use crate::utility::closing_helper_utility::{self as chu, RETAINED_EARNINGS_ACCOUNT_NAME};
+use crate::utility::error_helper_utility::MoneyManagerError;
+use crate::utility::html_helper_utility::{
+    display_error_banner, display_message_banner, document_create_element,
+    document_query_selector,
+};
+use crate::utility::{database_helper_utility as dhu, js_helper_utility as js};
+
+/// read_date_input reads the date input with the given selector, or None when it's blank.
+fn read_date_input(selector: &str) -> Result<Option<NaiveDate>, MoneyManagerError> {
+    let value = document_query_selector(selector)
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap()
+        .value();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    NaiveDate::parse_from_str(&value, "%Y-%m-%d").map(Some).map_err(|_| {
+        MoneyManagerError::Validation(format!("The given date '{}' is not a valid date.", value))
+    })
+}
+
+/// save_lock_date_from_input saves the lock date on the closing screen, or unlocks the books
+/// when it's blank.
+fn save_lock_date_from_input() {
+    let result = read_date_input("#closing_lock_date_input")
+        .and_then(transactions_manager::save_lock_date);
+    match result {
+        Ok(_) => {
+            //Reloading clears the footer, so the banner goes after it
+            load_closing_into_body();
+            display_message_banner("Successfully saved the lock date!");
+        }
+        Err(error) => display_error_banner(&error),
+    }
+}
+
+/// close_year_from_inputs closes the year ending on the date on the closing screen, into the
+/// chosen equity account, after asking the user. Then it locks the books through that date, if
+/// asked to.
+fn close_year_from_inputs() {
+    let year_end = match read_date_input("#closing_year_end_input") {
+        Ok(Some(year_end)) => year_end,
+        Ok(None) => {
+            display_error_banner(&MoneyManagerError::Validation(
+                "Please enter the last day of the year to close.".to_string(),
+            ));
+            return;
+        }
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+
+    let equity_account_select = document_query_selector("#closing_equity_account_select")
+        .dyn_into::<web_sys::HtmlSelectElement>()
+        .unwrap();
+    let equity_account_guid = if equity_account_select.value().is_empty() {
+        None
+    } else {
+        match dhu::convert_string_to_guid(equity_account_select.value()) {
+            Ok(equity_account_guid) => Some(equity_account_guid),
+            Err(error) => {
+                display_error_banner(&error);
+                return;
+            }
+        }
+    };
+    let lock_books = document_query_selector("#closing_lock_checkbox")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap()
+        .checked();
+
+    if !js::confirm(&format!(
+        "Are you sure you want to close every income, and expense account into {} on {}?",
+        equity_account_select
+            .selected_options()
+            .item(0)
+            .and_then(|option| option.text_content())
+            .unwrap_or_default(),
+        year_end.format("%Y-%m-%d"),
+    )) {
+        return;
+    }
+
+    let summary = match chu::close_year(year_end, equity_account_guid) {
+        Ok(summary) => summary,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+
+    let mut message = format!("Created {} closing transactions.", summary.transactions);
+    for skipped in &summary.skipped {
+        message.push_str(&format!("\nSkipped: {}", skipped));
+    }
+    if lock_books {
+        //The lock date only ever moves forward from here
+        let result = transactions_manager::retrieve_lock_date().and_then(|lock_date| {
+            match lock_date {
+                Some(lock_date) if lock_date >= year_end => Ok(false),
+                _ => transactions_manager::save_lock_date(Some(year_end)),
+            }
+        });
+        match result {
+            Ok(true) => message.push_str(&format!(
+                "\nThe books are locked through {}.",
+                year_end.format("%Y-%m-%d")
+            )),
+            Ok(false) => {}
+            //The summary banner would replace an error banner, so the error goes in it
+            Err(error) => message.push_str(&format!("\n{}", error)),
+        }
+    }
+    load_closing_into_body();
+    display_message_banner(&message);
+}
+
+/// load_closing_into_body loads the book's lock date, and the year end closing into the body.
+pub fn load_closing_into_body() {
+    let lock_date = match transactions_manager::retrieve_lock_date() {
+        Ok(lock_date) => lock_date,
+        Err(error) => {
+            display_error_banner(&error);
+            None
+        }
+    };
+    let account_nodes = match accounts_manager::retrieve_all_account_nodes() {
+        Ok(account_nodes) => account_nodes,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+
+    //Clear out the body, and footer first
+    let body_div = document_query_selector("#body");
+    body_div.set_inner_html("");
+    let footer_div = document_query_selector("#footer");
+    footer_div.set_inner_html("");
+
+    let closing_div = document_create_element("div");
+    closing_div.set_id("closing");
+    body_div.append_child(&closing_div).unwrap();
+
+    let header = document_create_element("h3");
+    header.set_inner_html("Close Year");
+    closing_div.append_child(&header).unwrap();
+
+    //Create the lock date elements
+    {
+        let lock_date_div = document_create_element("div");
+        lock_date_div.set_id("closing_lock_date_div");
+        lock_date_div.set_inner_html(&format!(
+            "
+            <label id='closing_lock_date_label'>Books Locked Through:
+                <input type='date' id='closing_lock_date_input' value='{lock_date}' />
+            </label>",
+            lock_date = lock_date
+                .map(|lock_date| lock_date.format("%Y-%m-%d").to_string())
+                .unwrap_or_default(),
+        ));
+        closing_div.append_child(&lock_date_div).unwrap();
+
+        let save_lock_date_button = document_create_element("input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap();
+        save_lock_date_button.set_type("button");
+        save_lock_date_button.set_id("closing_save_lock_date_button");
+        save_lock_date_button.set_value("Save Lock Date");
+        lock_date_div.append_child(&save_lock_date_button).unwrap();
+
+        let save_lock_date_button_on_click = Closure::wrap(Box::new(move || {
+            save_lock_date_from_input();
+        }) as Box<dyn Fn()>);
+        save_lock_date_button
+            .set_onclick(Some(save_lock_date_button_on_click.as_ref().unchecked_ref()));
+        save_lock_date_button_on_click.forget();
+    }
+
+    //Create the year end closing elements
+    {
+        let full_names = accounts_manager::account_full_names(&account_nodes);
+        let mut equity_accounts: Vec<(&accounts_manager::AccountNode, &String)> = account_nodes
+            .iter()
+            .filter(|node| node.account_type == AccountType::EQUITY.as_str())
+            .filter_map(|node| full_names.get(&node.guid).map(|full_name| (node, full_name)))
+            .collect();
+        equity_accounts.sort_by(|a, b| a.1.cmp(b.1));
+
+        //Retained Earnings is created when the book doesn't have one yet
+        let mut equity_account_options = String::new();
+        if !equity_accounts
+            .iter()
+            .any(|(node, _)| node.name == RETAINED_EARNINGS_ACCOUNT_NAME)
+        {
+            equity_account_options +=
+                &format!("<option value=''>New {RETAINED_EARNINGS_ACCOUNT_NAME} Account</option>");
+        }
+        for (node, full_name) in equity_accounts {
+            equity_account_options += &format!(
+                "<option {selected} value='{guid}'>{full_name}</option>",
+                selected = if node.name == RETAINED_EARNINGS_ACCOUNT_NAME {
+                    "SELECTED"
+                } else {
+                    ""
+                },
+                guid = dhu::convert_guid_to_sqlite_string(&node.guid),
+                full_name = dhu::sanitize_string(full_name.clone()),
+            );
+        }
+
+        let year_end = NaiveDate::from_ymd_opt(chrono::Local::now().year() - 1, 12, 31).unwrap();
+        let year_end_div = document_create_element("div");
+        year_end_div.set_id("closing_year_end_div");
+        year_end_div.set_inner_html(&format!(
+            "
+            <label id='closing_year_end_label'>Close the Year Ending:
+                <input type='date' id='closing_year_end_input' value='{year_end}' />
+            </label>
+            <label id='closing_equity_account_label'>Into:
+                <select id='closing_equity_account_select'>
+                    {equity_account_options}
+                </select>
+            </label>
+            <label id='closing_lock_label'>Lock the Books Through the Year End?
+                <input type='checkbox' id='closing_lock_checkbox' checked />
+            </label>",
+            year_end = year_end.format("%Y-%m-%d"),
+        ));
+        closing_div.append_child(&year_end_div).unwrap();
+
+        let close_year_button = document_create_element("input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap();
+        close_year_button.set_type("button");
+        close_year_button.set_id("closing_close_year_button");
+        close_year_button.set_value("Close Year");
+        year_end_div.append_child(&close_year_button).unwrap();
+
+        let close_year_button_on_click = Closure::wrap(Box::new(move || {
+            close_year_from_inputs();
+        }) as Box<dyn Fn()>);
+        close_year_button.set_onclick(Some(close_year_button_on_click.as_ref().unchecked_ref()));
+        close_year_button_on_click.forget();
+    }
+}
@@ -0,0 +1,336 @@
+use uuid::Uuid;
+use wasm_bindgen::{prelude::Closure, JsCast};
+
+use crate::database_tables::accounts_manager;
+use crate::database_tables::commodities_manager;
+use crate::database_tables::employees_manager::{self, Employee};
+use crate::database_tables::slots_manager;
+use crate::utility::html_helper_utility::{
+    display_error_banner, document_create_body_table_header, document_create_element,
+    document_create_select,
+    document_query_selector,
+};
+use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
+
+/// load_employees_into_body loads the list of employees into the body.
+pub fn load_employees_into_body() {
+    let employees = match employees_manager::retrieve_all_employees() {
+        Ok(employees) => employees,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+
+    //Clear out the body, and footer first
+    let body_div = document_query_selector("#body");
+    body_div.set_inner_html("");
+    let footer_div = document_query_selector("#footer");
+    footer_div.set_inner_html("");
+
+    //Create the New Employee Button
+    let new_employee_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .expect("Failed to create New Employee Button!");
+    new_employee_button.set_type("button");
+    new_employee_button.set_id("new_employee_button");
+    new_employee_button.set_value("New Employee");
+
+    let new_employee_button_on_click = Closure::wrap(Box::new(move || {
+        load_employee_editor_into_body(Uuid::new_v4());
+    }) as Box<dyn Fn()>);
+
+    new_employee_button.set_onclick(Some(new_employee_button_on_click.as_ref().unchecked_ref()));
+    new_employee_button_on_click.forget();
+
+    body_div
+        .append_child(&new_employee_button)
+        .expect("Failed to append New Employee Button!");
+
+    //Create the header for the body
+    {
+        let headings = vec![
+            "Id".to_string(),
+            "Name".to_string(),
+            "Username".to_string(),
+            "Active".to_string(),
+        ];
+        let employees_header = document_create_body_table_header("div", headings, "employee");
+
+        body_div
+            .append_child(&employees_header)
+            .expect("Failed to append employees_header to body!");
+    }
+
+    //Create employees_div, and place it in the body
+    let employees_div = document_create_element("div");
+    employees_div.set_id("employees_div");
+    employees_div
+        .class_list()
+        .add_1("body_table")
+        .expect("Failed to add class to element.");
+    body_div
+        .append_child(&employees_div)
+        .expect("Failed to append employees_div to body!");
+
+    for employee in employees {
+        let employee_guid = employee.guid;
+
+        let employee_div = document_create_element("div");
+        employee_div
+            .class_list()
+            .add_1("body_row")
+            .expect("Failed to add class to element.");
+        employees_div
+            .append_child(&employee_div)
+            .expect("Failed to append employee_div to employees_div!");
+
+        for (css_class, text) in [
+            ("employee_id", employee.id.clone()),
+            ("employee_name", employee.display_name()),
+            ("employee_username", employee.username.clone()),
+            (
+                "employee_active",
+                if employee.active == 1 { "Yes" } else { "No" }.to_string(),
+            ),
+        ] {
+            let cell_div = document_create_element("div");
+            cell_div.set_text_content(Some(&text));
+            cell_div
+                .class_list()
+                .add_1(css_class)
+                .expect("Failed to add class to element.");
+            employee_div
+                .append_child(&cell_div)
+                .expect("Failed to append cell_div!");
+        }
+
+        let employee_div_on_click = Closure::wrap(Box::new(move || {
+            load_employee_editor_into_body(employee_guid);
+        }) as Box<dyn Fn()>);
+        employee_div.set_onclick(Some(employee_div_on_click.as_ref().unchecked_ref()));
+        employee_div_on_click.forget();
+    }
+}
+
+/// next_employee_id gives the next employee number to use, one more than the largest number
+/// currently used by any employee.
+fn next_employee_id() -> String {
+    let largest_id = employees_manager::retrieve_all_employees()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|employee| employee.id.trim().parse::<i64>().ok())
+        .max()
+        .unwrap_or(0);
+
+    format!("{:06}", largest_id + 1)
+}
+
+/// save_employee_with_guid saves the employee in the editor with the given guid, along with
+/// the payable account their expense vouchers are posted to.
+pub fn save_employee_with_guid(employee_guid: Uuid) -> Result<bool, MoneyManagerError> {
+    let id = document_query_selector("#employee_editor_id_input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap()
+        .value();
+    let username = document_query_selector("#employee_editor_username_input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap()
+        .value();
+    let name = document_query_selector("#employee_editor_name_input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap()
+        .value();
+    let active = document_query_selector("#employee_editor_active_checkbox")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap()
+        .checked();
+    let payable_account = document_query_selector("#employee_editor_payable_account_select")
+        .dyn_into::<web_sys::HtmlSelectElement>()
+        .unwrap()
+        .value();
+
+    if id.trim().is_empty() {
+        return Err(MoneyManagerError::Validation("Please give the employee a number.".to_string()));
+    }
+
+    //Keep what this screen doesn't edit from the existing employee
+    let employee = match employees_manager::retrieve_employee_for_guid(employee_guid) {
+        Ok(employee) => Employee {
+            id,
+            username,
+            active: active as i64,
+            addr_name: Some(name),
+            ..employee
+        },
+        Err(_) => Employee {
+            guid: employee_guid,
+            username,
+            id,
+            language: String::new(),
+            acl: String::new(),
+            active: active as i64,
+            currency: commodities_manager::retrieve_default_currency_guid()?,
+            ccard_guid: None,
+            workday_num: 8,
+            workday_denom: 1,
+            rate_num: 0,
+            rate_denom: 1,
+            addr_name: Some(name),
+        },
+    };
+
+    employees_manager::save_new_and_delete_current(employee)?;
+    slots_manager::save_guid_val_for_obj_guid_and_name(
+        employee_guid,
+        slots_manager::SLOT_NAME_PAYABLE_ACCOUNT,
+        dhu::convert_string_to_guid(payable_account).ok(),
+    )
+}
+
+/// load_employee_editor_into_body loads the employee editor into the body for the given
+/// employee_guid. Pass in a new GUID if you want to create a new employee instead.
+pub fn load_employee_editor_into_body(employee_guid: Uuid) {
+    let account_names = match accounts_manager::retrieve_all_account_names() {
+        Ok(account_names) => account_names,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+    let mut payable_account_options = vec![(String::new(), String::new())];
+    payable_account_options.extend(
+        account_names
+            .iter()
+            .filter(|account_name| account_name.account_type == "PAYABLE")
+            .map(|account_name| {
+                (
+                    dhu::convert_guid_to_sqlite_string(&account_name.guid),
+                    account_name.name.clone(),
+                )
+            }),
+    );
+
+    let employee = employees_manager::retrieve_employee_for_guid(employee_guid).ok();
+    let payable_account = slots_manager::load_guid_val_for_obj_guid_and_name(
+        employee_guid,
+        slots_manager::SLOT_NAME_PAYABLE_ACCOUNT,
+    )
+    .ok()
+    .flatten();
+
+    //Clear out the body, and footer first
+    let body_div = document_query_selector("#body");
+    body_div.set_inner_html("");
+    let footer_div = document_query_selector("#footer");
+    footer_div.set_inner_html("");
+
+    let employee_editor_div = document_create_element("div");
+    employee_editor_div.set_id("employee_editor");
+    body_div
+        .append_child(&employee_editor_div)
+        .expect("Failed to append employee_editor_div!");
+
+    let header = document_create_element("h3");
+    header.set_inner_html("Employee");
+    employee_editor_div.append_child(&header).unwrap();
+
+    //Setup the text fields
+    for (label_text, input_id, value) in [
+        (
+            "Number: ",
+            "employee_editor_id_input",
+            employee
+                .as_ref()
+                .map(|employee| employee.id.clone())
+                .unwrap_or_else(next_employee_id),
+        ),
+        (
+            "Username: ",
+            "employee_editor_username_input",
+            employee
+                .as_ref()
+                .map(|employee| employee.username.clone())
+                .unwrap_or_default(),
+        ),
+        (
+            "Name: ",
+            "employee_editor_name_input",
+            employee
+                .as_ref()
+                .and_then(|employee| employee.addr_name.clone())
+                .unwrap_or_default(),
+        ),
+    ] {
+        let label = document_create_element("label");
+        label.set_inner_html(label_text);
+        let input = document_create_element("input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap();
+        input.set_id(input_id);
+        input.set_value(&value);
+        label.append_child(&input).unwrap();
+        employee_editor_div.append_child(&label).unwrap();
+    }
+
+    let active_label = document_create_element("label");
+    active_label.set_inner_html("Active: ");
+    let active_checkbox = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    active_checkbox.set_type("checkbox");
+    active_checkbox.set_id("employee_editor_active_checkbox");
+    active_checkbox.set_checked(
+        employee
+            .as_ref()
+            .map(|employee| employee.active == 1)
+            .unwrap_or(true),
+    );
+    active_label.append_child(&active_checkbox).unwrap();
+    employee_editor_div.append_child(&active_label).unwrap();
+
+    //Expense vouchers for this employee are posted to their payable account
+    let payable_account_label = document_create_element("label");
+    payable_account_label.set_inner_html("Payable Account: ");
+    let payable_account_select = document_create_select(
+        &payable_account_options,
+        &payable_account
+            .map(|account_guid| dhu::convert_guid_to_sqlite_string(&account_guid))
+            .unwrap_or_default(),
+    );
+    payable_account_select.set_id("employee_editor_payable_account_select");
+    payable_account_label
+        .append_child(&payable_account_select)
+        .unwrap();
+    employee_editor_div
+        .append_child(&payable_account_label)
+        .unwrap();
+
+    let save_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    save_button.set_type("button");
+    save_button.set_value("Save");
+    let save_button_on_click = Closure::wrap(Box::new(move || {
+        match save_employee_with_guid(employee_guid) {
+            Ok(_) => load_employees_into_body(),
+            Err(error) => display_error_banner(&error),
+        }
+    }) as Box<dyn Fn()>);
+    save_button.set_onclick(Some(save_button_on_click.as_ref().unchecked_ref()));
+    save_button_on_click.forget();
+    employee_editor_div.append_child(&save_button).unwrap();
+
+    let cancel_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    cancel_button.set_type("button");
+    cancel_button.set_value("Back");
+    let cancel_button_on_click = Closure::wrap(Box::new(move || {
+        load_employees_into_body();
+    }) as Box<dyn Fn()>);
+    cancel_button.set_onclick(Some(cancel_button_on_click.as_ref().unchecked_ref()));
+    cancel_button_on_click.forget();
+    employee_editor_div.append_child(&cancel_button).unwrap();
+}
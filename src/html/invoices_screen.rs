@@ -0,0 +1,928 @@
+use uuid::Uuid;
+use wasm_bindgen::{prelude::Closure, JsCast};
+
+use crate::database_tables::accounts_manager;
+use crate::database_tables::commodities_manager;
+use crate::database_tables::customers_manager;
+use crate::database_tables::employees_manager;
+use crate::database_tables::entries_manager::{self, Entry};
+use crate::database_tables::invoices_manager::{self, Invoice, OwnerType};
+use crate::database_tables::jobs_manager;
+use crate::database_tables::slots_manager;
+use crate::database_tables::taxtables_manager;
+use crate::database_tables::vendors_manager;
+use crate::utility::html_helper_utility::{
+    display_error_banner, document_create_body_table_header, document_create_element,
+    document_create_select,
+    document_query_selector, document_query_selector_all, element_query_selector,
+};
+use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
+
+/// document_name returns what an invoice is called for the given owner type.
+fn document_name(owner_type: OwnerType) -> &'static str {
+    match owner_type {
+        OwnerType::Customer => "Invoice",
+        OwnerType::Employee => "Expense Voucher",
+        _ => "Bill",
+    }
+}
+
+/// owner_options returns the (guid, name) options of the possible owners for the given
+/// owner type, starting with a blank option.
+fn owner_options(owner_type: OwnerType) -> Vec<(String, String)> {
+    let mut options = vec![(String::new(), String::new())];
+
+    match owner_type {
+        OwnerType::Customer => {
+            for customer in customers_manager::retrieve_all_customers().unwrap_or_default() {
+                options.push((dhu::convert_guid_to_sqlite_string(&customer.guid), customer.name));
+            }
+        }
+        OwnerType::Employee => {
+            for employee in employees_manager::retrieve_all_employees().unwrap_or_default() {
+                options.push((
+                    dhu::convert_guid_to_sqlite_string(&employee.guid),
+                    employee.display_name(),
+                ));
+            }
+        }
+        _ => {
+            for vendor in vendors_manager::retrieve_all_vendors().unwrap_or_default() {
+                options.push((dhu::convert_guid_to_sqlite_string(&vendor.guid), vendor.name));
+            }
+        }
+    }
+
+    options
+}
+
+/// job_options returns the (guid, name) options of the customer's jobs, starting with a blank
+/// option. Each job is named after its customer, so the same job name can be told apart.
+fn job_options() -> Vec<(String, String)> {
+    let mut options = vec![(String::new(), String::new())];
+
+    for job in jobs_manager::retrieve_all_jobs().unwrap_or_default() {
+        if job.owner_type != Some(OwnerType::Customer) {
+            continue;
+        }
+        options.push((
+            dhu::convert_guid_to_sqlite_string(&job.guid),
+            match job.owner_guid {
+                Some(owner_guid) => format!(
+                    "{} - {}",
+                    party_name(OwnerType::Customer, owner_guid),
+                    job.name
+                ),
+                None => job.name,
+            },
+        ));
+    }
+
+    options
+}
+
+/// party_name returns the name of the customer, vendor, employee or job with the given guid,
+/// or a blank string.
+fn party_name(owner_type: OwnerType, owner_guid: Uuid) -> String {
+    match owner_type {
+        OwnerType::Customer => customers_manager::retrieve_customer_for_guid(owner_guid)
+            .map(|customer| customer.name)
+            .unwrap_or_default(),
+        OwnerType::Employee => employees_manager::retrieve_employee_for_guid(owner_guid)
+            .map(|employee| employee.display_name())
+            .unwrap_or_default(),
+        OwnerType::Job => match jobs_manager::retrieve_job_for_guid(owner_guid) {
+            Ok(job) => match (job.owner_type, job.owner_guid) {
+                (Some(job_owner_type), Some(job_owner_guid)) => format!(
+                    "{} - {}",
+                    party_name(job_owner_type, job_owner_guid),
+                    job.name
+                ),
+                _ => job.name,
+            },
+            Err(_) => String::new(),
+        },
+        _ => vendors_manager::retrieve_vendor_for_guid(owner_guid)
+            .map(|vendor| vendor.name)
+            .unwrap_or_default(),
+    }
+}
+
+/// owner_name returns the name of the owner of the given invoice, or a blank string.
+fn owner_name(invoice: &Invoice) -> String {
+    match (invoice.owner_type, invoice.owner_guid) {
+        (Some(owner_type), Some(owner_guid)) => party_name(owner_type, owner_guid),
+        (None, Some(owner_guid)) => party_name(OwnerType::Customer, owner_guid),
+        (_, None) => String::new(),
+    }
+}
+
+/// invoice_total returns the net and tax total of the lines of the given invoice.
+fn invoice_total(invoice_guid: Uuid) -> (f64, f64) {
+    let entries = entries_manager::retrieve_entries_for_invoice_guid(invoice_guid).unwrap_or_default();
+
+    match invoices_manager::calculate_posting_amounts(&entries) {
+        Ok((account_amounts, tax_amounts)) => (
+            account_amounts.iter().map(|(_, amount)| amount).sum(),
+            tax_amounts.iter().map(|(_, amount)| amount).sum(),
+        ),
+        Err(_) => (0.0, 0.0),
+    }
+}
+
+/// load_invoices_into_body loads the list of invoices, bills and expense vouchers into the body.
+pub fn load_invoices_into_body() {
+    let invoices = match invoices_manager::retrieve_all_invoices() {
+        Ok(invoices) => invoices,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+
+    //Clear out the body, and footer first
+    let body_div = document_query_selector("#body");
+    body_div.set_inner_html("");
+    let footer_div = document_query_selector("#footer");
+    footer_div.set_inner_html("");
+
+    //Create the New Invoice, New Bill, and New Voucher Buttons
+    for (button_value, owner_type) in [
+        ("New Invoice", OwnerType::Customer),
+        ("New Bill", OwnerType::Vendor),
+        ("New Voucher", OwnerType::Employee),
+    ] {
+        let new_button = document_create_element("input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .expect("Failed to create New Invoice Button!");
+        new_button.set_type("button");
+        new_button.set_value(button_value);
+
+        let new_button_on_click = Closure::wrap(Box::new(move || {
+            load_invoice_editor_into_body(Uuid::new_v4(), owner_type);
+        }) as Box<dyn Fn()>);
+        new_button.set_onclick(Some(new_button_on_click.as_ref().unchecked_ref()));
+        new_button_on_click.forget();
+
+        body_div
+            .append_child(&new_button)
+            .expect("Failed to append New Invoice Button!");
+    }
+
+    //Create the header for the body
+    {
+        let headings = vec![
+            "Type".to_string(),
+            "Id".to_string(),
+            "Owner".to_string(),
+            "Opened".to_string(),
+            "Posted".to_string(),
+            "Total".to_string(),
+        ];
+        let invoices_header = document_create_body_table_header("div", headings, "invoice");
+
+        body_div
+            .append_child(&invoices_header)
+            .expect("Failed to append invoices_header to body!");
+    }
+
+    //Create invoices_div, and place it in the body
+    let invoices_div = document_create_element("div");
+    invoices_div.set_id("invoices_div");
+    invoices_div
+        .class_list()
+        .add_1("body_table")
+        .expect("Failed to add class to element.");
+    body_div
+        .append_child(&invoices_div)
+        .expect("Failed to append invoices_div to body!");
+
+    for invoice in invoices {
+        let invoice_guid = invoice.guid;
+        let owner_type = invoice.document_owner_type();
+        let (net, tax) = invoice_total(invoice.guid);
+
+        let invoice_div = document_create_element("div");
+        invoice_div
+            .class_list()
+            .add_1("body_row")
+            .expect("Failed to add class to element.");
+        invoices_div
+            .append_child(&invoice_div)
+            .expect("Failed to append invoice_div to invoices_div!");
+
+        for (css_class, text) in [
+            ("invoice_type", document_name(owner_type).to_string()),
+            ("invoice_id", invoice.id.clone()),
+            ("invoice_owner", owner_name(&invoice)),
+            (
+                "invoice_opened",
+                dhu::convert_string_format_to_date_input(
+                    &invoice.date_opened.clone().unwrap_or_default(),
+                ),
+            ),
+            (
+                "invoice_posted",
+                dhu::convert_string_format_to_date_input(
+                    &invoice.date_posted.clone().unwrap_or_default(),
+                ),
+            ),
+            ("invoice_total", dhu::format_money(net + tax)),
+        ] {
+            let cell_div = document_create_element("div");
+            cell_div.set_text_content(Some(&text));
+            cell_div
+                .class_list()
+                .add_1(css_class)
+                .expect("Failed to add class to element.");
+            invoice_div
+                .append_child(&cell_div)
+                .expect("Failed to append cell_div!");
+        }
+
+        let invoice_div_on_click = Closure::wrap(Box::new(move || {
+            load_invoice_editor_into_body(invoice_guid, owner_type);
+        }) as Box<dyn Fn()>);
+        invoice_div.set_onclick(Some(invoice_div_on_click.as_ref().unchecked_ref()));
+        invoice_div_on_click.forget();
+    }
+}
+
+/// document_create_invoice_line_row creates a row for the invoice editor for the given line.
+fn document_create_invoice_line_row(
+    account_options: &[(String, String)],
+    taxtable_options: &[(String, String)],
+    entry: Option<&Entry>,
+) -> web_sys::HtmlElement {
+    let row_div = document_create_element("div");
+    row_div
+        .class_list()
+        .add_1("invoice_line_row")
+        .expect("Failed to add class to element.");
+
+    let line = entry.map(|entry| entry.line_amounts());
+
+    let description_input = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    description_input
+        .class_list()
+        .add_1("invoice_line_description_input")
+        .expect("Failed to add class to element.");
+    description_input.set_placeholder("Description");
+    if let Some(entry) = entry {
+        description_input.set_value(&entry.description.clone().unwrap_or_default());
+    }
+    row_div.append_child(&description_input).unwrap();
+
+    let account_select = document_create_select(
+        account_options,
+        &line
+            .as_ref()
+            .and_then(|line| line.account)
+            .map(|account| dhu::convert_guid_to_sqlite_string(&account))
+            .unwrap_or_default(),
+    );
+    account_select
+        .class_list()
+        .add_1("invoice_line_account_select")
+        .expect("Failed to add class to element.");
+    row_div.append_child(&account_select).unwrap();
+
+    let quantity_input = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    quantity_input.set_type("number");
+    quantity_input.set_step("any");
+    quantity_input
+        .class_list()
+        .add_1("invoice_line_quantity_input")
+        .expect("Failed to add class to element.");
+    quantity_input.set_value(&entry.map(|entry| entry.quantity()).unwrap_or(1.0).to_string());
+    row_div.append_child(&quantity_input).unwrap();
+
+    let price_input = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    price_input.set_type("number");
+    price_input.set_step("any");
+    price_input.set_placeholder("Price");
+    price_input
+        .class_list()
+        .add_1("invoice_line_price_input")
+        .expect("Failed to add class to element.");
+    if let (Some(entry), Some(line)) = (entry, &line) {
+        let quantity = entry.quantity();
+        if quantity != 0.0 {
+            price_input.set_value(&(line.subtotal / quantity).to_string());
+        }
+    }
+    row_div.append_child(&price_input).unwrap();
+
+    for (css_class, label_text, checked) in [
+        (
+            "invoice_line_taxable_checkbox",
+            "Taxable",
+            line.as_ref().map(|line| line.taxable).unwrap_or(false),
+        ),
+        (
+            "invoice_line_tax_included_checkbox",
+            "Tax Included",
+            line.as_ref().map(|line| line.tax_included).unwrap_or(false),
+        ),
+    ] {
+        let label = document_create_element("label");
+        label.set_text_content(Some(label_text));
+        let checkbox = document_create_element("input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap();
+        checkbox.set_type("checkbox");
+        checkbox.set_checked(checked);
+        checkbox
+            .class_list()
+            .add_1(css_class)
+            .expect("Failed to add class to element.");
+        label.append_child(&checkbox).unwrap();
+        row_div.append_child(&label).unwrap();
+    }
+
+    let taxtable_select = document_create_select(
+        taxtable_options,
+        &line
+            .as_ref()
+            .and_then(|line| line.taxtable)
+            .map(|taxtable| dhu::convert_guid_to_sqlite_string(&taxtable))
+            .unwrap_or_default(),
+    );
+    taxtable_select
+        .class_list()
+        .add_1("invoice_line_taxtable_select")
+        .expect("Failed to add class to element.");
+    row_div.append_child(&taxtable_select).unwrap();
+
+    //Let the row remove itself
+    let remove_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    remove_button.set_type("button");
+    remove_button.set_value("Remove");
+    let row_div_to_remove = row_div.clone();
+    let remove_button_on_click = Closure::wrap(Box::new(move || {
+        row_div_to_remove.remove();
+    }) as Box<dyn Fn()>);
+    remove_button.set_onclick(Some(remove_button_on_click.as_ref().unchecked_ref()));
+    remove_button_on_click.forget();
+    row_div.append_child(&remove_button).unwrap();
+
+    row_div
+}
+
+/// save_invoice_with_guid saves the invoice, bill or expense voucher in the editor with the
+/// given guid. An invoice for a job is owned by the job, while a bill or voucher for a job is
+/// billed to it.
+pub fn save_invoice_with_guid(invoice_guid: Uuid, owner_type: OwnerType) -> Result<bool, MoneyManagerError> {
+    let is_bill = owner_type != OwnerType::Customer;
+
+    let id = document_query_selector("#invoice_editor_id_input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap()
+        .value();
+    let owner_guid = document_query_selector("#invoice_editor_owner_select")
+        .dyn_into::<web_sys::HtmlSelectElement>()
+        .unwrap()
+        .value();
+    let job_guid = document_query_selector("#invoice_editor_job_select")
+        .dyn_into::<web_sys::HtmlSelectElement>()
+        .unwrap()
+        .value();
+    let date_opened = document_query_selector("#invoice_editor_date_opened_input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap()
+        .value();
+    let notes = document_query_selector("#invoice_editor_notes_textarea")
+        .dyn_into::<web_sys::HtmlTextAreaElement>()
+        .unwrap()
+        .value();
+
+    if id.trim().is_empty() {
+        return Err(MoneyManagerError::Validation("Please give this a number.".to_string()));
+    }
+    let date_opened = dhu::convert_date_input_to_string_format(&date_opened)?;
+
+    //Bill in the owner's currency, or the book's currency when there is no owner
+    let owner_guid = dhu::convert_string_to_guid(owner_guid).ok();
+    let currency = match (owner_guid, owner_type) {
+        (Some(owner_guid), OwnerType::Customer) => {
+            customers_manager::retrieve_customer_for_guid(owner_guid)?.currency
+        }
+        (Some(owner_guid), OwnerType::Employee) => {
+            employees_manager::retrieve_employee_for_guid(owner_guid)?.currency
+        }
+        (Some(owner_guid), _) => vendors_manager::retrieve_vendor_for_guid(owner_guid)?.currency,
+        (None, _) => commodities_manager::retrieve_default_currency_guid()?,
+    };
+
+    let job_guid = dhu::convert_string_to_guid(job_guid).ok();
+    let (invoice_owner_type, invoice_owner_guid, billto_type) = match job_guid {
+        Some(job_guid) if !is_bill => {
+            let job = jobs_manager::retrieve_job_for_guid(job_guid)?;
+            if owner_guid.is_some() && job.owner_guid != owner_guid {
+                return Err(MoneyManagerError::Validation(format!(
+                    "The job '{}' is not for this customer.",
+                    job.name
+                )));
+            }
+            (OwnerType::Job, Some(job_guid), None)
+        }
+        Some(_) => (owner_type, owner_guid, Some(OwnerType::Job)),
+        None => (owner_type, owner_guid, None),
+    };
+
+    let mut entries = Vec::new();
+    let lines_div = document_query_selector("#invoice_editor_lines_div");
+
+    for row in document_query_selector_all(&lines_div, ".invoice_line_row") {
+        let description = element_query_selector(&row, ".invoice_line_description_input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap()
+            .value();
+        let account = element_query_selector(&row, ".invoice_line_account_select")
+            .dyn_into::<web_sys::HtmlSelectElement>()
+            .unwrap()
+            .value();
+        let quantity = element_query_selector(&row, ".invoice_line_quantity_input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap()
+            .value();
+        let price = element_query_selector(&row, ".invoice_line_price_input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap()
+            .value();
+        let taxable = element_query_selector(&row, ".invoice_line_taxable_checkbox")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap()
+            .checked();
+        let tax_included = element_query_selector(&row, ".invoice_line_tax_included_checkbox")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap()
+            .checked();
+        let taxtable = element_query_selector(&row, ".invoice_line_taxtable_select")
+            .dyn_into::<web_sys::HtmlSelectElement>()
+            .unwrap()
+            .value();
+
+        let quantity: f64 = quantity
+            .trim()
+            .parse()
+            .map_err(|_| {
+                MoneyManagerError::Validation(format!("The quantity '{}' is not a valid number.", quantity))
+            })?;
+        let price: f64 = price
+            .trim()
+            .replace(['$', ','], "")
+            .parse()
+            .map_err(|_| {
+                MoneyManagerError::Validation(format!("The price '{}' is not a valid number.", price))
+            })?;
+        let account = dhu::convert_string_to_guid(account).ok();
+        let taxtable = dhu::convert_string_to_guid(taxtable).ok();
+        if taxable && taxtable.is_none() {
+            return Err(MoneyManagerError::Validation(format!(
+                "Please pick a tax table for the taxable line '{}'.",
+                description
+            )));
+        }
+
+        let price_num = (price * entries_manager::PRICE_DENOM as f64).round() as i64;
+        let mut entry = Entry {
+            guid: Uuid::new_v4(),
+            date: date_opened.clone(),
+            date_entered: Some(dhu::convert_date_to_string_format(
+                chrono::Local::now().naive_local(),
+            )),
+            description: Some(description),
+            action: Some(String::new()),
+            notes: Some(String::new()),
+            quantity_num: Some((quantity * entries_manager::PRICE_DENOM as f64).round() as i64),
+            quantity_denom: Some(entries_manager::PRICE_DENOM),
+            i_acct: None,
+            i_price_num: None,
+            i_price_denom: None,
+            invoice: None,
+            i_taxable: None,
+            i_taxincluded: None,
+            i_taxtable: None,
+            b_acct: None,
+            b_price_num: None,
+            b_price_denom: None,
+            bill: None,
+            b_taxable: None,
+            b_taxincluded: None,
+            b_taxtable: None,
+        };
+        if is_bill {
+            entry.b_acct = account;
+            entry.b_price_num = Some(price_num);
+            entry.b_price_denom = Some(entries_manager::PRICE_DENOM);
+            entry.bill = Some(invoice_guid);
+            entry.b_taxable = Some(taxable as i64);
+            entry.b_taxincluded = Some(tax_included as i64);
+            entry.b_taxtable = taxtable;
+        } else {
+            entry.i_acct = account;
+            entry.i_price_num = Some(price_num);
+            entry.i_price_denom = Some(entries_manager::PRICE_DENOM);
+            entry.invoice = Some(invoice_guid);
+            entry.i_taxable = Some(taxable as i64);
+            entry.i_taxincluded = Some(tax_included as i64);
+            entry.i_taxtable = taxtable;
+        }
+        entries.push(entry);
+    }
+
+    let invoice = Invoice {
+        guid: invoice_guid,
+        id,
+        date_opened: Some(date_opened),
+        date_posted: None,
+        notes,
+        active: 1,
+        currency,
+        owner_type: Some(invoice_owner_type),
+        owner_guid: invoice_owner_guid,
+        terms: None,
+        billing_id: Some(String::new()),
+        post_txn: None,
+        post_lot: None,
+        post_acc: None,
+        billto_type,
+        billto_guid: if is_bill { job_guid } else { None },
+    };
+
+    invoices_manager::save_new_and_delete_current(invoice, entries)
+}
+
+/// load_invoice_editor_into_body loads the invoice editor into the body for the given
+/// invoice_guid. Pass in a new GUID if you want to create a new invoice, bill or voucher instead.
+pub fn load_invoice_editor_into_body(invoice_guid: Uuid, owner_type: OwnerType) {
+    let invoice = invoices_manager::retrieve_invoice_for_guid(invoice_guid).ok();
+    let owner_type = invoice
+        .as_ref()
+        .map(|invoice| invoice.document_owner_type())
+        .unwrap_or(owner_type);
+    //A job's invoice is shown with the job's customer, and the job
+    let (owner_guid, job_guid) = match &invoice {
+        Some(invoice) if invoice.owner_type == Some(OwnerType::Job) => (
+            invoice
+                .owner_guid
+                .and_then(|job_guid| jobs_manager::retrieve_job_for_guid(job_guid).ok())
+                .and_then(|job| job.owner_guid),
+            invoice.owner_guid,
+        ),
+        Some(invoice) => (invoice.owner_guid, invoice.billto_guid),
+        None => (None, None),
+    };
+    let is_bill = owner_type != OwnerType::Customer;
+    let is_posted = invoice
+        .as_ref()
+        .map(|invoice| invoice.post_txn.is_some())
+        .unwrap_or(false);
+    let entries = entries_manager::retrieve_entries_for_invoice_guid(invoice_guid).unwrap_or_default();
+
+    let account_names = match accounts_manager::retrieve_all_account_names() {
+        Ok(account_names) => account_names,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+    //Invoices use income accounts, and bills use expense accounts
+    let line_account_type = if is_bill { "EXPENSE" } else { "INCOME" };
+    let mut account_options = vec![(String::new(), String::new())];
+    account_options.extend(
+        account_names
+            .iter()
+            .filter(|account_name| account_name.account_type == line_account_type)
+            .map(|account_name| {
+                (
+                    dhu::convert_guid_to_sqlite_string(&account_name.guid),
+                    account_name.name.clone(),
+                )
+            }),
+    );
+    let mut taxtable_options = vec![(String::new(), "No Tax Table".to_string())];
+    taxtable_options.extend(
+        taxtables_manager::retrieve_all_taxtables()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|taxtable| (dhu::convert_guid_to_sqlite_string(&taxtable.guid), taxtable.name)),
+    );
+
+    //Clear out the body, and footer first
+    let body_div = document_query_selector("#body");
+    body_div.set_inner_html("");
+    let footer_div = document_query_selector("#footer");
+    footer_div.set_inner_html("");
+
+    let invoice_editor_div = document_create_element("div");
+    invoice_editor_div.set_id("invoice_editor");
+    body_div.append_child(&invoice_editor_div).unwrap();
+
+    let header = document_create_element("h3");
+    header.set_text_content(Some(document_name(owner_type)));
+    invoice_editor_div.append_child(&header).unwrap();
+
+    //Setup the invoice fields
+    let id_label = document_create_element("label");
+    id_label.set_inner_html("Number: ");
+    let id_input = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    id_input.set_id("invoice_editor_id_input");
+    id_input.set_value(
+        &invoice
+            .as_ref()
+            .map(|invoice| invoice.id.clone())
+            .unwrap_or_else(invoices_manager::next_invoice_id),
+    );
+    id_label.append_child(&id_input).unwrap();
+    invoice_editor_div.append_child(&id_label).unwrap();
+
+    let owner_label = document_create_element("label");
+    owner_label.set_inner_html(match owner_type {
+        OwnerType::Customer => "Customer: ",
+        OwnerType::Employee => "Employee: ",
+        _ => "Vendor: ",
+    });
+    let owner_select = document_create_select(
+        &owner_options(owner_type),
+        &owner_guid
+            .map(|owner_guid| dhu::convert_guid_to_sqlite_string(&owner_guid))
+            .unwrap_or_default(),
+    );
+    owner_select.set_id("invoice_editor_owner_select");
+    owner_label.append_child(&owner_select).unwrap();
+    invoice_editor_div.append_child(&owner_label).unwrap();
+
+    let job_label = document_create_element("label");
+    job_label.set_inner_html(if is_bill { "Bill To Job: " } else { "Job: " });
+    let job_select = document_create_select(
+        &job_options(),
+        &job_guid
+            .map(|job_guid| dhu::convert_guid_to_sqlite_string(&job_guid))
+            .unwrap_or_default(),
+    );
+    job_select.set_id("invoice_editor_job_select");
+    job_label.append_child(&job_select).unwrap();
+    invoice_editor_div.append_child(&job_label).unwrap();
+
+    let date_opened_label = document_create_element("label");
+    date_opened_label.set_inner_html("Date Opened: ");
+    let date_opened_input = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    date_opened_input.set_type("date");
+    date_opened_input.set_id("invoice_editor_date_opened_input");
+    date_opened_input.set_value(
+        &invoice
+            .as_ref()
+            .and_then(|invoice| invoice.date_opened.clone())
+            .map(|date_opened| dhu::convert_string_format_to_date_input(&date_opened))
+            .unwrap_or_else(|| chrono::Local::now().format("%Y-%m-%d").to_string()),
+    );
+    date_opened_label.append_child(&date_opened_input).unwrap();
+    invoice_editor_div.append_child(&date_opened_label).unwrap();
+
+    let notes_textarea = document_create_element("textarea")
+        .dyn_into::<web_sys::HtmlTextAreaElement>()
+        .unwrap();
+    notes_textarea.set_id("invoice_editor_notes_textarea");
+    notes_textarea.set_placeholder("Notes");
+    if let Some(invoice) = &invoice {
+        notes_textarea.set_value(&invoice.notes);
+    }
+    invoice_editor_div.append_child(&notes_textarea).unwrap();
+
+    //Setup the lines
+    {
+        let headings = vec![
+            "Description".to_string(),
+            "Account".to_string(),
+            "Quantity".to_string(),
+            "Price".to_string(),
+            "Tax".to_string(),
+        ];
+        let lines_header = document_create_body_table_header("div", headings, "invoice_line");
+        invoice_editor_div.append_child(&lines_header).unwrap();
+    }
+
+    let lines_div = document_create_element("div");
+    lines_div.set_id("invoice_editor_lines_div");
+    invoice_editor_div.append_child(&lines_div).unwrap();
+
+    for entry in &entries {
+        lines_div
+            .append_child(&document_create_invoice_line_row(
+                &account_options,
+                &taxtable_options,
+                Some(entry),
+            ))
+            .unwrap();
+    }
+    if entries.is_empty() {
+        lines_div
+            .append_child(&document_create_invoice_line_row(
+                &account_options,
+                &taxtable_options,
+                None,
+            ))
+            .unwrap();
+    }
+
+    //Show the totals of the saved lines
+    let (net, tax) = invoice_total(invoice_guid);
+    let totals_div = document_create_element("div");
+    totals_div.set_id("invoice_editor_totals_div");
+    totals_div.set_text_content(Some(&format!(
+        "Subtotal: {} Tax: {} Total: {}",
+        dhu::format_money(net),
+        dhu::format_money(tax),
+        dhu::format_money(net + tax)
+    )));
+    invoice_editor_div.append_child(&totals_div).unwrap();
+
+    let cancel_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    cancel_button.set_type("button");
+    cancel_button.set_value("Back");
+    let cancel_button_on_click = Closure::wrap(Box::new(move || {
+        load_invoices_into_body();
+    }) as Box<dyn Fn()>);
+    cancel_button.set_onclick(Some(cancel_button_on_click.as_ref().unchecked_ref()));
+    cancel_button_on_click.forget();
+
+    if is_posted {
+        //Posted invoices are read only, just like in GnuCash
+        for element in document_query_selector_all(&invoice_editor_div, "input, select, textarea") {
+            element.set_attribute("disabled", "disabled").unwrap();
+        }
+        let posted_div = document_create_element("div");
+        posted_div.set_text_content(Some(&format!(
+            "Posted on {}.",
+            dhu::convert_string_format_to_date_input(
+                &invoice
+                    .as_ref()
+                    .and_then(|invoice| invoice.date_posted.clone())
+                    .unwrap_or_default()
+            )
+        )));
+        invoice_editor_div.append_child(&posted_div).unwrap();
+        invoice_editor_div.append_child(&cancel_button).unwrap();
+        return;
+    }
+
+    let add_line_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    add_line_button.set_type("button");
+    add_line_button.set_value("Add Line");
+    let add_line_account_options = account_options.clone();
+    let add_line_taxtable_options = taxtable_options.clone();
+    let add_line_button_on_click = Closure::wrap(Box::new(move || {
+        let lines_div = document_query_selector("#invoice_editor_lines_div");
+        lines_div
+            .append_child(&document_create_invoice_line_row(
+                &add_line_account_options,
+                &add_line_taxtable_options,
+                None,
+            ))
+            .unwrap();
+    }) as Box<dyn Fn()>);
+    add_line_button.set_onclick(Some(add_line_button_on_click.as_ref().unchecked_ref()));
+    add_line_button_on_click.forget();
+    invoice_editor_div.append_child(&add_line_button).unwrap();
+
+    let save_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    save_button.set_type("button");
+    save_button.set_value("Save");
+    let save_button_on_click = Closure::wrap(Box::new(move || {
+        match save_invoice_with_guid(invoice_guid, owner_type) {
+            Ok(_) => load_invoice_editor_into_body(invoice_guid, owner_type),
+            Err(error) => display_error_banner(&error),
+        }
+    }) as Box<dyn Fn()>);
+    save_button.set_onclick(Some(save_button_on_click.as_ref().unchecked_ref()));
+    save_button_on_click.forget();
+    invoice_editor_div.append_child(&save_button).unwrap();
+    invoice_editor_div.append_child(&cancel_button).unwrap();
+
+    //Setup posting to A/R or A/P
+    let post_div = document_create_element("div");
+    post_div.set_id("invoice_editor_post_div");
+    invoice_editor_div.append_child(&post_div).unwrap();
+
+    let post_account_options: Vec<(String, String)> =
+        invoices_manager::retrieve_post_accounts_for_owner_type(owner_type)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|account_name| {
+                (
+                    dhu::convert_guid_to_sqlite_string(&account_name.guid),
+                    account_name.name,
+                )
+            })
+            .collect();
+    let post_account_label = document_create_element("label");
+    post_account_label.set_inner_html("Post To: ");
+    //Vouchers default to the employee's payable account
+    let default_post_account = match (owner_type, owner_guid) {
+        (OwnerType::Employee, Some(owner_guid)) => slots_manager::load_guid_val_for_obj_guid_and_name(
+            owner_guid,
+            slots_manager::SLOT_NAME_PAYABLE_ACCOUNT,
+        )
+        .ok()
+        .flatten()
+        .map(|account_guid| dhu::convert_guid_to_sqlite_string(&account_guid))
+        .unwrap_or_default(),
+        _ => String::new(),
+    };
+    let post_account_select = document_create_select(&post_account_options, &default_post_account);
+    post_account_select.set_id("invoice_editor_post_account_select");
+    post_account_label.append_child(&post_account_select).unwrap();
+    post_div.append_child(&post_account_label).unwrap();
+
+    let post_date_label = document_create_element("label");
+    post_date_label.set_inner_html("Post Date: ");
+    let post_date_input = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    post_date_input.set_type("date");
+    post_date_input.set_id("invoice_editor_post_date_input");
+    post_date_input.set_value(&chrono::Local::now().format("%Y-%m-%d").to_string());
+    post_date_label.append_child(&post_date_input).unwrap();
+    post_div.append_child(&post_date_label).unwrap();
+
+    let post_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    post_button.set_type("button");
+    post_button.set_value("Post");
+    let post_button_on_click = Closure::wrap(Box::new(move || {
+        let post_account = document_query_selector("#invoice_editor_post_account_select")
+            .dyn_into::<web_sys::HtmlSelectElement>()
+            .unwrap()
+            .value();
+        let post_date = document_query_selector("#invoice_editor_post_date_input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap()
+            .value();
+
+        let post_account = match dhu::convert_string_to_guid(post_account) {
+            Ok(post_account) => post_account,
+            Err(_) => {
+                display_error_banner(&MoneyManagerError::Validation(
+                    if is_bill {
+                        "Please add a PAYABLE account to post bills and vouchers to."
+                    } else {
+                        "Please add a RECEIVABLE account to post invoices to."
+                    }
+                    .to_string(),
+                ));
+                return;
+            }
+        };
+        let post_date = match dhu::convert_date_input_to_string_format(&post_date) {
+            Ok(post_date) => post_date,
+            Err(error) => {
+                display_error_banner(&error);
+                return;
+            }
+        };
+
+        //Save what is on screen before posting it
+        if let Err(error) = save_invoice_with_guid(invoice_guid, owner_type) {
+            display_error_banner(&error);
+            return;
+        }
+
+        let invoice = invoices_manager::retrieve_invoice_for_guid(invoice_guid).unwrap();
+        let description = match owner_name(&invoice) {
+            name if name.is_empty() => {
+                format!("{} {}", document_name(owner_type), invoice.id)
+            }
+            name => name,
+        };
+
+        match invoices_manager::post_invoice(invoice_guid, post_account, post_date, description) {
+            Ok(_) => load_invoice_editor_into_body(invoice_guid, owner_type),
+            Err(error) => display_error_banner(&error),
+        }
+    }) as Box<dyn Fn()>);
+    post_button.set_onclick(Some(post_button_on_click.as_ref().unchecked_ref()));
+    post_button_on_click.forget();
+    post_div.append_child(&post_button).unwrap();
+}
@@ -0,0 +1,313 @@
+use uuid::Uuid;
+use wasm_bindgen::{prelude::Closure, JsCast};
+
+use crate::database_tables::customers_manager;
+use crate::database_tables::invoices_manager::OwnerType;
+use crate::database_tables::jobs_manager::{self, Job};
+use crate::utility::html_helper_utility::{
+    display_error_banner, document_create_body_table_header, document_create_element,
+    document_create_select,
+    document_query_selector,
+};
+use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
+
+/// load_jobs_into_body loads the list of jobs into the body, along with how profitable each
+/// job has been from its posted invoices, bills and expense vouchers.
+pub fn load_jobs_into_body() {
+    let jobs = match jobs_manager::retrieve_all_jobs() {
+        Ok(jobs) => jobs,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+    let job_profitability = match jobs_manager::retrieve_job_profitability() {
+        Ok(job_profitability) => job_profitability,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+    let customers = customers_manager::retrieve_all_customers().unwrap_or_default();
+
+    //Clear out the body, and footer first
+    let body_div = document_query_selector("#body");
+    body_div.set_inner_html("");
+    let footer_div = document_query_selector("#footer");
+    footer_div.set_inner_html("");
+
+    //Create the New Job Button
+    let new_job_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .expect("Failed to create New Job Button!");
+    new_job_button.set_type("button");
+    new_job_button.set_id("new_job_button");
+    new_job_button.set_value("New Job");
+
+    let new_job_button_on_click = Closure::wrap(Box::new(move || {
+        load_job_editor_into_body(Uuid::new_v4());
+    }) as Box<dyn Fn()>);
+
+    new_job_button.set_onclick(Some(new_job_button_on_click.as_ref().unchecked_ref()));
+    new_job_button_on_click.forget();
+
+    body_div
+        .append_child(&new_job_button)
+        .expect("Failed to append New Job Button!");
+
+    //Create the header for the body
+    {
+        let headings = vec![
+            "Id".to_string(),
+            "Name".to_string(),
+            "Customer".to_string(),
+            "Revenue".to_string(),
+            "Costs".to_string(),
+            "Profit".to_string(),
+        ];
+        let jobs_header = document_create_body_table_header("div", headings, "job");
+
+        body_div
+            .append_child(&jobs_header)
+            .expect("Failed to append jobs_header to body!");
+    }
+
+    //Create jobs_div, and place it in the body
+    let jobs_div = document_create_element("div");
+    jobs_div.set_id("jobs_div");
+    jobs_div
+        .class_list()
+        .add_1("body_table")
+        .expect("Failed to add class to element.");
+    body_div
+        .append_child(&jobs_div)
+        .expect("Failed to append jobs_div to body!");
+
+    for job in jobs {
+        let job_guid = job.guid;
+        let customer_name = customers
+            .iter()
+            .find(|customer| Some(customer.guid) == job.owner_guid)
+            .map(|customer| customer.name.clone())
+            .unwrap_or_default();
+        let (revenue, costs) = job_profitability
+            .iter()
+            .find(|profitability| profitability.job_guid == job.guid)
+            .map(|profitability| (profitability.revenue, profitability.costs))
+            .unwrap_or((0.0, 0.0));
+
+        let job_div = document_create_element("div");
+        job_div
+            .class_list()
+            .add_1("body_row")
+            .expect("Failed to add class to element.");
+        jobs_div
+            .append_child(&job_div)
+            .expect("Failed to append job_div to jobs_div!");
+
+        for (css_class, text) in [
+            ("job_id", job.id.clone()),
+            ("job_name", job.name.clone()),
+            ("job_customer", customer_name),
+            ("job_revenue", dhu::format_money(revenue)),
+            ("job_costs", dhu::format_money(costs)),
+            ("job_profit", dhu::format_money(revenue - costs)),
+        ] {
+            let cell_div = document_create_element("div");
+            cell_div.set_text_content(Some(&text));
+            cell_div
+                .class_list()
+                .add_1(css_class)
+                .expect("Failed to add class to element.");
+            job_div
+                .append_child(&cell_div)
+                .expect("Failed to append cell_div!");
+        }
+
+        let job_div_on_click = Closure::wrap(Box::new(move || {
+            load_job_editor_into_body(job_guid);
+        }) as Box<dyn Fn()>);
+        job_div.set_onclick(Some(job_div_on_click.as_ref().unchecked_ref()));
+        job_div_on_click.forget();
+    }
+}
+
+/// next_job_id gives the next job number to use, one more than the largest number currently
+/// used by any job.
+fn next_job_id() -> String {
+    let largest_id = jobs_manager::retrieve_all_jobs()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|job| job.id.trim().parse::<i64>().ok())
+        .max()
+        .unwrap_or(0);
+
+    format!("{:06}", largest_id + 1)
+}
+
+/// save_job_with_guid saves the job in the editor with the given guid.
+pub fn save_job_with_guid(job_guid: Uuid) -> Result<bool, MoneyManagerError> {
+    let id = document_query_selector("#job_editor_id_input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap()
+        .value();
+    let name = document_query_selector("#job_editor_name_input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap()
+        .value();
+    let reference = document_query_selector("#job_editor_reference_input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap()
+        .value();
+    let customer = document_query_selector("#job_editor_customer_select")
+        .dyn_into::<web_sys::HtmlSelectElement>()
+        .unwrap()
+        .value();
+    let active = document_query_selector("#job_editor_active_checkbox")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap()
+        .checked();
+
+    if id.trim().is_empty() {
+        return Err(MoneyManagerError::Validation("Please give the job a number.".to_string()));
+    }
+    let customer = match dhu::convert_string_to_guid(customer) {
+        Ok(customer) => customer,
+        Err(_) => {
+            return Err(MoneyManagerError::Validation(
+                "Please pick the customer this job is for.".to_string(),
+            ));
+        }
+    };
+
+    jobs_manager::save_new_and_delete_current(Job {
+        guid: job_guid,
+        id,
+        name,
+        reference,
+        active: active as i64,
+        owner_type: Some(OwnerType::Customer),
+        owner_guid: Some(customer),
+    })
+}
+
+/// load_job_editor_into_body loads the job editor into the body for the given job_guid. Pass
+/// in a new GUID if you want to create a new job instead.
+pub fn load_job_editor_into_body(job_guid: Uuid) {
+    let customers = match customers_manager::retrieve_all_customers() {
+        Ok(customers) => customers,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+    let mut customer_options = vec![(String::new(), String::new())];
+    customer_options.extend(
+        customers
+            .into_iter()
+            .map(|customer| (dhu::convert_guid_to_sqlite_string(&customer.guid), customer.name)),
+    );
+
+    let job = jobs_manager::retrieve_job_for_guid(job_guid).ok();
+
+    //Clear out the body, and footer first
+    let body_div = document_query_selector("#body");
+    body_div.set_inner_html("");
+    let footer_div = document_query_selector("#footer");
+    footer_div.set_inner_html("");
+
+    let job_editor_div = document_create_element("div");
+    job_editor_div.set_id("job_editor");
+    body_div
+        .append_child(&job_editor_div)
+        .expect("Failed to append job_editor_div!");
+
+    let header = document_create_element("h3");
+    header.set_inner_html("Job");
+    job_editor_div.append_child(&header).unwrap();
+
+    //Setup the text fields
+    for (label_text, input_id, value) in [
+        (
+            "Number: ",
+            "job_editor_id_input",
+            job.as_ref()
+                .map(|job| job.id.clone())
+                .unwrap_or_else(next_job_id),
+        ),
+        (
+            "Name: ",
+            "job_editor_name_input",
+            job.as_ref().map(|job| job.name.clone()).unwrap_or_default(),
+        ),
+        (
+            "Reference: ",
+            "job_editor_reference_input",
+            job.as_ref()
+                .map(|job| job.reference.clone())
+                .unwrap_or_default(),
+        ),
+    ] {
+        let label = document_create_element("label");
+        label.set_inner_html(label_text);
+        let input = document_create_element("input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap();
+        input.set_id(input_id);
+        input.set_value(&value);
+        label.append_child(&input).unwrap();
+        job_editor_div.append_child(&label).unwrap();
+    }
+
+    let customer_label = document_create_element("label");
+    customer_label.set_inner_html("Customer: ");
+    let customer_select = document_create_select(
+        &customer_options,
+        &job.as_ref()
+            .and_then(|job| job.owner_guid)
+            .map(|owner_guid| dhu::convert_guid_to_sqlite_string(&owner_guid))
+            .unwrap_or_default(),
+    );
+    customer_select.set_id("job_editor_customer_select");
+    customer_label.append_child(&customer_select).unwrap();
+    job_editor_div.append_child(&customer_label).unwrap();
+
+    let active_label = document_create_element("label");
+    active_label.set_inner_html("Active: ");
+    let active_checkbox = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    active_checkbox.set_type("checkbox");
+    active_checkbox.set_id("job_editor_active_checkbox");
+    active_checkbox.set_checked(job.as_ref().map(|job| job.active == 1).unwrap_or(true));
+    active_label.append_child(&active_checkbox).unwrap();
+    job_editor_div.append_child(&active_label).unwrap();
+
+    let save_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    save_button.set_type("button");
+    save_button.set_value("Save");
+    let save_button_on_click = Closure::wrap(Box::new(move || {
+        match save_job_with_guid(job_guid) {
+            Ok(_) => load_jobs_into_body(),
+            Err(error) => display_error_banner(&error),
+        }
+    }) as Box<dyn Fn()>);
+    save_button.set_onclick(Some(save_button_on_click.as_ref().unchecked_ref()));
+    save_button_on_click.forget();
+    job_editor_div.append_child(&save_button).unwrap();
+
+    let cancel_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    cancel_button.set_type("button");
+    cancel_button.set_value("Back");
+    let cancel_button_on_click = Closure::wrap(Box::new(move || {
+        load_jobs_into_body();
+    }) as Box<dyn Fn()>);
+    cancel_button.set_onclick(Some(cancel_button_on_click.as_ref().unchecked_ref()));
+    cancel_button_on_click.forget();
+    job_editor_div.append_child(&cancel_button).unwrap();
+}
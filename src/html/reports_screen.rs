@@ -0,0 +1,398 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+
+use crate::database_tables::{
+    accounts_manager, billterms_manager, invoices_manager, lots_manager, splits_manager,
+};
+use crate::utility::database_helper_utility as dhu;
+use crate::utility::html_helper_utility::{display_error_banner, document_create_body_table_header, document_create_element, document_query_selector};
+
+/// AGING_BUCKET_NAMES are the names of the aging buckets, in order.
+pub const AGING_BUCKET_NAMES: [&str; 5] = ["Current", "1-30 Days", "31-60 Days", "61-90 Days", "90+ Days"];
+
+/// convert_post_date_to_date converts a post date in either the '20120801040000' or the
+/// '2012-08-01 04:00:00' format to a date.
+pub fn convert_post_date_to_date(post_date: &str) -> Option<NaiveDate> {
+    let digits: String = post_date
+        .chars()
+        .filter(|character| character.is_ascii_digit())
+        .take(8)
+        .collect();
+
+    NaiveDate::parse_from_str(&digits, "%Y%m%d").ok()
+}
+
+/// aging_bucket returns the index into AGING_BUCKET_NAMES for an amount that is the given
+/// number of days past its due date.
+pub fn aging_bucket(days_overdue: i64) -> usize {
+    match days_overdue {
+        i64::MIN..=0 => 0,
+        1..=30 => 1,
+        31..=60 => 2,
+        61..=90 => 3,
+        _ => 4,
+    }
+}
+
+/// summarize_tax_splits_by_month totals the given tax splits for each month and tax account.
+/// Credits to a tax account are tax collected. Debits posted from an invoice, or bill are tax
+/// paid on purchases, and other debits are tax remitted to the authority.
+/// It returns ((month, account_name), (collected, paid, remitted)) in order.
+pub fn summarize_tax_splits_by_month(
+    tax_splits: &[splits_manager::TaxSplit],
+) -> BTreeMap<(String, String), (f64, f64, f64)> {
+    let mut summary = BTreeMap::new();
+
+    for tax_split in tax_splits {
+        let month = convert_post_date_to_date(&tax_split.post_date)
+            .map(|date| date.format("%Y-%m").to_string())
+            .unwrap_or_default();
+        let amount = tax_split.value_num as f64 / tax_split.value_denom as f64;
+
+        let (collected, paid, remitted) = summary
+            .entry((month, tax_split.account_name.clone()))
+            .or_insert((0.0, 0.0, 0.0));
+        if amount < 0.0 {
+            *collected -= amount;
+        } else if tax_split.txn_type == invoices_manager::TXN_TYPE_INVOICE {
+            *paid += amount;
+        } else {
+            *remitted += amount;
+        }
+    }
+
+    summary
+}
+
+/// display_tax_summary_report displays the sales tax collected, and tax paid for each month
+/// and tax account between the given dates.
+pub fn display_tax_summary_report(from_date: chrono::NaiveDate, thru_date: chrono::NaiveDate) {
+    let tax_splits = match splits_manager::retrieve_tax_splits_between_dates(from_date, thru_date)
+    {
+        Ok(tax_splits) => tax_splits,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+
+    //clear out the body, and footer first
+    let body_div = document_query_selector("#body");
+    body_div.set_inner_html("");
+    let footer_div = document_query_selector("#footer");
+    footer_div.set_inner_html("");
+
+    let header = document_create_element("h3");
+    header.set_text_content(Some(&format!(
+        "Sales Tax Summary {} thru {}",
+        from_date.format("%Y-%m-%d"),
+        thru_date.format("%Y-%m-%d")
+    )));
+    body_div.append_child(&header).unwrap();
+
+    let headings = vec![
+        "Period".to_string(),
+        "Account".to_string(),
+        "Collected".to_string(),
+        "Paid".to_string(),
+        "Remitted".to_string(),
+        "Net".to_string(),
+    ];
+    body_div
+        .append_child(&document_create_body_table_header("div", headings, "tax_summary"))
+        .unwrap();
+
+    let tax_summary_div = document_create_element("div");
+    tax_summary_div.set_id("tax_summary_div");
+    tax_summary_div
+        .class_list()
+        .add_1("body_table")
+        .expect("Failed to add class to element.");
+    body_div.append_child(&tax_summary_div).unwrap();
+
+    let mut total_collected = 0.0;
+    let mut total_paid = 0.0;
+    let mut total_remitted = 0.0;
+    let summary = summarize_tax_splits_by_month(&tax_splits);
+
+    for ((month, account_name), (collected, paid, remitted)) in &summary {
+        total_collected += collected;
+        total_paid += paid;
+        total_remitted += remitted;
+
+        let row_div = document_create_element("div");
+        row_div
+            .class_list()
+            .add_1("body_row")
+            .expect("Failed to add class to element.");
+        for (css_class, text) in [
+            ("tax_summary_period", month.clone()),
+            ("tax_summary_account", account_name.clone()),
+            ("tax_summary_collected", dhu::format_money(*collected)),
+            ("tax_summary_paid", dhu::format_money(*paid)),
+            ("tax_summary_remitted", dhu::format_money(*remitted)),
+            ("tax_summary_net", dhu::format_money(collected - paid - remitted)),
+        ] {
+            let cell_div = document_create_element("div");
+            cell_div.set_text_content(Some(&text));
+            cell_div
+                .class_list()
+                .add_1(css_class)
+                .expect("Failed to add class to element.");
+            row_div.append_child(&cell_div).unwrap();
+        }
+        tax_summary_div.append_child(&row_div).unwrap();
+    }
+
+    footer_div.set_text_content(Some(&format!(
+        "Total Collected: {} Total Paid: {} Total Remitted: {} Net Owed: {}",
+        dhu::format_money(total_collected),
+        dhu::format_money(total_paid),
+        dhu::format_money(total_remitted),
+        dhu::format_money(total_collected - total_paid - total_remitted)
+    )));
+}
+
+/// summarize_aging_lots totals the given open lots into the aging buckets for each owner, as
+/// of the given date. Lots are due on their bill term's due date, or on the date posted.
+/// It returns ((account_type, owner_name, owner_guid), bucket amounts) in order. Payable amounts are
+/// shown as positive amounts owed.
+pub fn summarize_aging_lots(
+    aging_lots: &[lots_manager::AgingLot],
+    billterms: &[billterms_manager::BillTerm],
+    as_of_date: NaiveDate,
+) -> BTreeMap<(String, String, Option<uuid::Uuid>), [f64; 5]> {
+    let mut summary = BTreeMap::new();
+
+    for aging_lot in aging_lots {
+        let date_posted = aging_lot
+            .date_posted
+            .as_deref()
+            .and_then(convert_post_date_to_date)
+            .unwrap_or(as_of_date);
+        let due_date = aging_lot
+            .terms
+            .and_then(|terms| billterms.iter().find(|billterm| billterm.guid == terms))
+            .map(|billterm| billterm.due_date(date_posted))
+            .unwrap_or(date_posted);
+        let days_overdue = (as_of_date - due_date).num_days();
+
+        let amount = aging_lot.balance
+            * accounts_manager::AccountType::from(aging_lot.account_type.clone()).balance_sign();
+        let owner_name = if aging_lot.owner_name.is_empty() {
+            "(No Owner)".to_string()
+        } else {
+            aging_lot.owner_name.clone()
+        };
+
+        let buckets = summary
+            .entry((aging_lot.account_type.clone(), owner_name, aging_lot.owner_guid))
+            .or_insert([0.0; 5]);
+        buckets[aging_bucket(days_overdue)] += amount;
+    }
+
+    summary
+}
+
+/// display_aging_report displays who owes what, and how late it is, for the open lots on the
+/// RECEIVABLE and PAYABLE accounts as of today.
+pub fn display_aging_report() {
+    let aging_lots = match lots_manager::retrieve_open_lots_for_aging() {
+        Ok(aging_lots) => aging_lots,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+    let billterms = match billterms_manager::retrieve_all_billterms() {
+        Ok(billterms) => billterms,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+    let as_of_date = chrono::Local::now().naive_local().date();
+    let summary = summarize_aging_lots(&aging_lots, &billterms, as_of_date);
+
+    //clear out the body, and footer first
+    let body_div = document_query_selector("#body");
+    body_div.set_inner_html("");
+    let footer_div = document_query_selector("#footer");
+    footer_div.set_inner_html("");
+
+    for (account_type, title, owner_heading) in [
+        ("RECEIVABLE", "Accounts Receivable Aging", "Customer"),
+        ("PAYABLE", "Accounts Payable Aging", "Vendor"),
+    ] {
+        let header = document_create_element("h3");
+        header.set_text_content(Some(&format!(
+            "{} as of {}",
+            title,
+            as_of_date.format("%Y-%m-%d")
+        )));
+        body_div.append_child(&header).unwrap();
+
+        let mut headings = vec![owner_heading.to_string()];
+        headings.extend(AGING_BUCKET_NAMES.iter().map(|name| name.to_string()));
+        headings.push("Total".to_string());
+        body_div
+            .append_child(&document_create_body_table_header("div", headings, "aging"))
+            .unwrap();
+
+        let aging_div = document_create_element("div");
+        aging_div
+            .class_list()
+            .add_1("aging_table")
+            .expect("Failed to add class to element.");
+        body_div.append_child(&aging_div).unwrap();
+
+        let mut rows = summary
+            .iter()
+            .filter(|((row_account_type, _, _), _)| row_account_type == account_type)
+            .map(|((_, owner_name, _), buckets)| (owner_name.clone(), *buckets))
+            .collect::<Vec<(String, [f64; 5])>>();
+
+        //Finish with the totals of each bucket
+        let mut totals = [0.0; 5];
+        for (_, buckets) in &rows {
+            for (total, amount) in totals.iter_mut().zip(buckets.iter()) {
+                *total += amount;
+            }
+        }
+        rows.push(("Total".to_string(), totals));
+
+        for (owner_name, buckets) in rows {
+            let row_div = document_create_element("div");
+            row_div
+                .class_list()
+                .add_1("body_row")
+                .expect("Failed to add class to element.");
+
+            let mut cells = vec![("aging_owner", owner_name)];
+            for amount in buckets.iter() {
+                cells.push(("aging_amount", dhu::format_money(*amount)));
+            }
+            cells.push(("aging_amount", dhu::format_money(buckets.iter().sum())));
+
+            for (css_class, text) in cells {
+                let cell_div = document_create_element("div");
+                cell_div.set_text_content(Some(&text));
+                cell_div
+                    .class_list()
+                    .add_1(css_class)
+                    .expect("Failed to add class to element.");
+                row_div.append_child(&cell_div).unwrap();
+            }
+            aging_div.append_child(&row_div).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarize_tax_splits_by_month_splits_collected_and_paid() {
+        let tax_split = |post_date: &str, value_num: i64| splits_manager::TaxSplit {
+            post_date: post_date.to_string(),
+            account_name: "Sales Tax".to_string(),
+            value_num,
+            value_denom: 100,
+            txn_type: invoices_manager::TXN_TYPE_INVOICE.to_string(),
+        };
+        let summary = summarize_tax_splits_by_month(&[
+            tax_split("20240105105900", -625),
+            tax_split("2024-01-20 10:59:00", 200),
+            tax_split("20240201105900", -100),
+        ]);
+
+        assert_eq!(
+            summary[&("2024-01".to_string(), "Sales Tax".to_string())],
+            (6.25, 2.0, 0.0)
+        );
+        assert_eq!(
+            summary[&("2024-02".to_string(), "Sales Tax".to_string())],
+            (1.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn summarize_tax_splits_by_month_keeps_remittances_out_of_tax_paid() {
+        let tax_split = |value_num: i64, txn_type: &str| splits_manager::TaxSplit {
+            post_date: "20240315105900".to_string(),
+            account_name: "Sales Tax".to_string(),
+            value_num,
+            value_denom: 100,
+            txn_type: txn_type.to_string(),
+        };
+        //Tax collected on an invoice, paid on a bill, and the rest sent to the authority
+        let summary = summarize_tax_splits_by_month(&[
+            tax_split(-1000, invoices_manager::TXN_TYPE_INVOICE),
+            tax_split(300, invoices_manager::TXN_TYPE_INVOICE),
+            tax_split(700, ""),
+        ]);
+
+        let (collected, paid, remitted) =
+            summary[&("2024-03".to_string(), "Sales Tax".to_string())];
+        assert_eq!((collected, paid, remitted), (10.0, 3.0, 7.0));
+        assert_eq!(collected - paid - remitted, 0.0);
+    }
+
+    #[test]
+    fn aging_bucket_uses_thirty_day_buckets() {
+        assert_eq!(aging_bucket(-5), 0);
+        assert_eq!(aging_bucket(0), 0);
+        assert_eq!(aging_bucket(30), 1);
+        assert_eq!(aging_bucket(31), 2);
+        assert_eq!(aging_bucket(90), 3);
+        assert_eq!(aging_bucket(91), 4);
+    }
+
+    #[test]
+    fn summarize_aging_lots_ages_from_the_due_date() {
+        let aging_lot = |account_type: &str, date_posted: &str, balance: f64| {
+            lots_manager::AgingLot {
+                account_type: account_type.to_string(),
+                terms: Some(uuid::Uuid::nil()),
+                date_posted: Some(date_posted.to_string()),
+                owner_guid: None,
+                owner_name: "Acme".to_string(),
+                balance,
+            }
+        };
+        let net_30 = billterms_manager::BillTerm {
+            guid: uuid::Uuid::nil(),
+            name: "Net 30".to_string(),
+            description: String::new(),
+            refcount: 0,
+            invisible: 0,
+            parent: None,
+            term_type: "GNC_TERM_TYPE_DAYS".to_string(),
+            duedays: Some(30),
+            discountdays: None,
+            discount_num: None,
+            discount_denom: None,
+            cutoff: None,
+        };
+        let summary = summarize_aging_lots(
+            &[
+                aging_lot("RECEIVABLE", "20240101105900", 100.0),
+                aging_lot("RECEIVABLE", "20240301105900", 50.0),
+                aging_lot("PAYABLE", "20231001105900", -20.0),
+            ],
+            &[net_30],
+            NaiveDate::from_ymd_opt(2024, 3, 15).unwrap(),
+        );
+
+        assert_eq!(
+            summary[&("RECEIVABLE".to_string(), "Acme".to_string(), None)],
+            [50.0, 0.0, 100.0, 0.0, 0.0]
+        );
+        assert_eq!(
+            summary[&("PAYABLE".to_string(), "Acme".to_string(), None)],
+            [0.0, 0.0, 0.0, 0.0, 20.0]
+        );
+    }
+}
@@ -1,8 +1,14 @@
 use base64::{engine::general_purpose, Engine};
 use wasm_bindgen::{prelude::Closure, JsCast};
 
-use crate::{database_tables::slots_manager, utility::{html_helper_utility::*, js_helper_utility}};
+use crate::{database_tables::{slots_manager, versions_manager}, utility::{html_helper_utility::*, js_helper_utility}};
+use crate::html::{
+    accounts_screen, categorization_rules_screen, check_and_repair_screen, closing_screen,
+    transaction_templates_screen,
+};
+use crate::utility::{journal_helper_utility, json_book_helper_utility};
 use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
 
 
 /// save_setting_for_display_transactions_older_than_one_year saves the setting
@@ -20,15 +26,122 @@ pub fn save_setting_for_display_transactions_older_than_one_year() {
         if setting_checkbox.checked() { 1 } else { 0 },
     ) {
         Ok(_e) => {
-            js_helper_utility::alert("Successfully saved setting!");
+            display_message_banner("The setting was saved.");
         }
-        Err(_e) => {
-            js_helper_utility::alert("Failed to save setting!");
-            return;
+        Err(error) => {
+            display_error_banner(&error);
         }
     }
 }
 
+/// MAXIMUM_PREVIEW_LINES is how many of the transactions the categorization rules will
+/// categorize are listed before a journal is imported.
+const MAXIMUM_PREVIEW_LINES: usize = 20;
+
+/// import_journal_from_file_input reads the journal chosen in the import journal file input
+/// into the loaded book, then shows the accounts.
+pub fn import_journal_from_file_input() {
+    let import_journal_file_input = document_query_selector("#import_journal_file_input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    let Some(file) = import_journal_file_input.files().and_then(|files| files.get(0)) else {
+        return;
+    };
+    import_journal_file_input.set_value("");
+    let skip_probable_duplicates =
+        document_query_selector("#import_journal_skip_duplicates_checkbox")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap()
+            .checked();
+
+    let file_reader = match web_sys::FileReader::new() {
+        Ok(file_reader) => file_reader,
+        Err(_) => {
+            display_error_banner(&MoneyManagerError::Validation(
+                "There was an error creating a file reader".to_string(),
+            ));
+            return;
+        }
+    };
+
+    let file_reader_clone = file_reader.clone();
+    let file_reader_on_load_end = Closure::wrap(Box::new(move |_event: web_sys::ProgressEvent| {
+        let text = file_reader_clone
+            .result()
+            .ok()
+            .and_then(|result| result.as_string())
+            .unwrap_or_default();
+
+        //Show which transactions the categorization rules will categorize first
+        match journal_helper_utility::preview_journal_categorization(&text) {
+            Ok(categorized) if !categorized.is_empty() => {
+                let mut message = format!(
+                    "The categorization rules will categorize {} transactions:",
+                    categorized.len()
+                );
+                for line in categorized.iter().take(MAXIMUM_PREVIEW_LINES) {
+                    message.push_str(&format!("\n{}", line));
+                }
+                if categorized.len() > MAXIMUM_PREVIEW_LINES {
+                    message.push_str(&format!(
+                        "\n...and {} more.",
+                        categorized.len() - MAXIMUM_PREVIEW_LINES
+                    ));
+                }
+                message.push_str("\n\nImport the journal?");
+                if !js_helper_utility::confirm(&message) {
+                    return;
+                }
+            }
+            Ok(_) => {}
+            Err(error) => {
+                display_error_banner(&error);
+                return;
+            }
+        }
+
+        match journal_helper_utility::import_journal_into_database(&text, skip_probable_duplicates)
+        {
+            Ok(summary) => {
+                let mut message = format!(
+                    "Imported {} transactions, {} accounts, {} commodities, and {} prices.",
+                    summary.transactions, summary.accounts, summary.commodities, summary.prices
+                );
+                if !summary.categorized.is_empty() {
+                    message.push_str(&format!(
+                        "\n{} transactions were categorized by the categorization rules.",
+                        summary.categorized.len()
+                    ));
+                }
+                if !summary.duplicates.is_empty() {
+                    message.push_str(&format!(
+                        "\n{} probable duplicates were skipped:",
+                        summary.duplicates.len()
+                    ));
+                    for line in summary.duplicates.iter().take(MAXIMUM_PREVIEW_LINES) {
+                        message.push_str(&format!("\n{}", line));
+                    }
+                    if summary.duplicates.len() > MAXIMUM_PREVIEW_LINES {
+                        message.push_str(&format!(
+                            "\n...and {} more.",
+                            summary.duplicates.len() - MAXIMUM_PREVIEW_LINES
+                        ));
+                    }
+                }
+                for warning in &summary.warnings {
+                    message.push_str(&format!("\n{}", warning));
+                }
+                accounts_screen::load_accounts_with_balances_from_memory();
+                display_message_banner(&message);
+            }
+            Err(error) => display_error_banner(&error),
+        }
+    }) as Box<dyn Fn(web_sys::ProgressEvent)>);
+
+    file_reader.set_onloadend(Some(file_reader_on_load_end.as_ref().unchecked_ref()));
+    file_reader.read_as_text(&file).expect("file not readable");
+    file_reader_on_load_end.forget();
+}
 
 /// load_settings_into_body loads the settings into the body from the given slots
 pub fn load_settings_into_body(settings_slots: Vec<slots_manager::Slot>) {
@@ -49,6 +162,20 @@ pub fn load_settings_into_body(settings_slots: Vec<slots_manager::Slot>) {
     settings_header.set_inner_html("Settings");
     settings_div.append_child(&settings_header).unwrap();
 
+    //Then which version of GnuCash wrote the loaded file
+    if let Ok(versions) = versions_manager::retrieve_all_versions() {
+        let settings_gnucash_version = document_create_element("div");
+        settings_gnucash_version.set_id("settings_gnucash_version");
+        settings_gnucash_version.set_text_content(Some(
+            &match versions_manager::check_versions(&versions).gnucash_version {
+                _ if *crate::GNUCASH_XML.lock().unwrap() => "File is a GnuCash XML book".to_string(),
+                Some(gnucash_version) => format!("File written by GnuCash {}", gnucash_version),
+                None => "File not written by GnuCash".to_string(),
+            },
+        ));
+        settings_div.append_child(&settings_gnucash_version).unwrap();
+    }
+
     //Create a button that creates a new database
     let new_database_button = document_create_element("input")
         .dyn_into::<web_sys::HtmlInputElement>()
@@ -87,6 +214,201 @@ pub fn load_settings_into_body(settings_slots: Vec<slots_manager::Slot>) {
 
     settings_div.append_child(&new_database_button).unwrap();
 
+    //Create a button that checks the book for problems, and offers to repair them
+    let check_and_repair_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    check_and_repair_button.set_type("button");
+    check_and_repair_button.set_value("Check & Repair");
+    check_and_repair_button.set_id("check_and_repair_button");
+
+    let check_and_repair_button_on_click = Closure::wrap(Box::new(move || {
+        check_and_repair_screen::load_check_and_repair_into_body();
+    }) as Box<dyn Fn()>);
+
+    check_and_repair_button
+        .set_onclick(Some(check_and_repair_button_on_click.as_ref().unchecked_ref()));
+    check_and_repair_button_on_click.forget();
+
+    settings_div.append_child(&check_and_repair_button).unwrap();
+
+    //Create a button that closes the year, and locks the closed period
+    let close_year_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    close_year_button.set_type("button");
+    close_year_button.set_value("Close Year");
+    close_year_button.set_id("close_year_button");
+
+    let close_year_button_on_click = Closure::wrap(Box::new(move || {
+        closing_screen::load_closing_into_body();
+    }) as Box<dyn Fn()>);
+
+    close_year_button.set_onclick(Some(close_year_button_on_click.as_ref().unchecked_ref()));
+    close_year_button_on_click.forget();
+
+    settings_div.append_child(&close_year_button).unwrap();
+
+    //Create a button that manages the memorized transaction templates
+    let transaction_templates_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    transaction_templates_button.set_type("button");
+    transaction_templates_button.set_value("Transaction Templates");
+    transaction_templates_button.set_id("transaction_templates_button");
+
+    let transaction_templates_button_on_click = Closure::wrap(Box::new(move || {
+        transaction_templates_screen::load_transaction_templates_into_body();
+    }) as Box<dyn Fn()>);
+
+    transaction_templates_button
+        .set_onclick(Some(transaction_templates_button_on_click.as_ref().unchecked_ref()));
+    transaction_templates_button_on_click.forget();
+
+    settings_div.append_child(&transaction_templates_button).unwrap();
+
+    //Create a button that manages the auto-categorization rules
+    let categorization_rules_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    categorization_rules_button.set_type("button");
+    categorization_rules_button.set_value("Categorization Rules");
+    categorization_rules_button.set_id("categorization_rules_button");
+
+    let categorization_rules_button_on_click = Closure::wrap(Box::new(move || {
+        categorization_rules_screen::load_categorization_rules_into_body();
+    }) as Box<dyn Fn()>);
+
+    categorization_rules_button
+        .set_onclick(Some(categorization_rules_button_on_click.as_ref().unchecked_ref()));
+    categorization_rules_button_on_click.forget();
+
+    settings_div.append_child(&categorization_rules_button).unwrap();
+
+    //Create a button that exports the whole book as a JSON book
+    let export_json_book_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    export_json_book_button.set_type("button");
+    export_json_book_button.set_value("Export JSON");
+    export_json_book_button.set_id("export_json_book_button");
+
+    let export_json_book_button_on_click = Closure::wrap(Box::new(move || {
+        let filename = document_query_selector("#money_manager_filename_input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap()
+            .value();
+        let filename = match filename.rsplit_once('.') {
+            Some((stem, _)) if !stem.is_empty() => stem.to_string(),
+            _ if !filename.is_empty() => filename,
+            _ => "MoneyManagerFile".to_string(),
+        };
+
+        match json_book_helper_utility::export_database_to_json_book() {
+            Ok(json) => download_file_with_name(&format!("{}.json", filename), &json),
+            Err(error) => display_error_banner(&error),
+        }
+    }) as Box<dyn Fn()>);
+
+    export_json_book_button.set_onclick(Some(export_json_book_button_on_click.as_ref().unchecked_ref()));
+    export_json_book_button_on_click.forget();
+
+    settings_div.append_child(&export_json_book_button).unwrap();
+
+    //Create a button that imports a ledger, hledger, or beancount journal into the book
+    let import_journal_file_input = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    import_journal_file_input.set_type("file");
+    import_journal_file_input.set_id("import_journal_file_input");
+    import_journal_file_input.set_accept(".beancount,.bean,.ledger,.journal,.hledger,.dat,.txt");
+    import_journal_file_input
+        .style()
+        .set_property("display", "none")
+        .expect("Failed to modify import_journal_file_input style!");
+
+    let import_journal_file_input_on_change = Closure::wrap(Box::new(move || {
+        import_journal_from_file_input();
+    }) as Box<dyn Fn()>);
+
+    import_journal_file_input.set_onchange(Some(
+        import_journal_file_input_on_change.as_ref().unchecked_ref(),
+    ));
+    import_journal_file_input_on_change.forget();
+
+    settings_div.append_child(&import_journal_file_input).unwrap();
+
+    let import_journal_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    import_journal_button.set_type("button");
+    import_journal_button.set_value("Import Journal");
+    import_journal_button.set_id("import_journal_button");
+
+    let import_journal_button_on_click = Closure::wrap(Box::new(move || {
+        document_query_selector("#import_journal_file_input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap()
+            .click();
+    }) as Box<dyn Fn()>);
+
+    import_journal_button.set_onclick(Some(import_journal_button_on_click.as_ref().unchecked_ref()));
+    import_journal_button_on_click.forget();
+
+    settings_div.append_child(&import_journal_button).unwrap();
+
+    //Then whether importing skips transactions that look like ones already in the book
+    let import_journal_skip_duplicates_label = document_create_element("label");
+    import_journal_skip_duplicates_label
+        .set_inner_html("Skip Probable Duplicates When Importing? ");
+    settings_div
+        .append_child(&import_journal_skip_duplicates_label)
+        .unwrap();
+
+    let import_journal_skip_duplicates_checkbox = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    import_journal_skip_duplicates_checkbox.set_type("checkbox");
+    import_journal_skip_duplicates_checkbox.set_id("import_journal_skip_duplicates_checkbox");
+    import_journal_skip_duplicates_checkbox.set_checked(true);
+    import_journal_skip_duplicates_label
+        .append_child(&import_journal_skip_duplicates_checkbox)
+        .unwrap();
+
+    //Then which format the loaded file is saved as
+    let settings_save_format_label = document_create_element("label");
+    settings_save_format_label.set_inner_html("Save File As: ");
+    settings_div.append_child(&settings_save_format_label).unwrap();
+
+    let settings_save_format_select = document_create_element("select")
+        .dyn_into::<web_sys::HtmlSelectElement>()
+        .unwrap();
+    settings_save_format_select.set_id("settings_save_format_select");
+    settings_save_format_select.set_inner_html(
+        "<option value='sqlite'>GnuCash SQLite</option>
+         <option value='xml'>GnuCash XML (compressed)</option>",
+    );
+    settings_save_format_select.set_value(if *crate::GNUCASH_XML.lock().unwrap() {
+        "xml"
+    } else {
+        "sqlite"
+    });
+    settings_save_format_label
+        .append_child(&settings_save_format_select)
+        .unwrap();
+
+    let settings_save_format_select_on_change = Closure::wrap(Box::new(move || {
+        let settings_save_format_select = document_query_selector("#settings_save_format_select")
+            .dyn_into::<web_sys::HtmlSelectElement>()
+            .unwrap();
+        *crate::GNUCASH_XML.lock().unwrap() = settings_save_format_select.value() == "xml";
+    }) as Box<dyn Fn()>);
+
+    settings_save_format_select.set_onchange(Some(
+        settings_save_format_select_on_change.as_ref().unchecked_ref(),
+    ));
+    settings_save_format_select_on_change.forget();
+
     //Then the Display Transactions Older than 1 year Setting
     let settings_display_transactions_older_than_one_year_label = document_create_element("label");
     settings_display_transactions_older_than_one_year_label
@@ -129,10 +451,10 @@ pub fn load_settings_into_body(settings_slots: Vec<slots_manager::Slot>) {
             } else if settings_slot.int64_val == 0 {
                 settings_display_transactions_older_than_one_year_checkbox.set_checked(false);
             } else {
-                js_helper_utility::alert(&format!(
+                display_error_banner(&MoneyManagerError::Validation(format!(
                     "The settings_slot with name of {} is invalid! Please fix this!",
                     slots_manager::SLOT_NAME_DISPLAY_TRANSACTIONS_OLDER_THAN_ONE_YEAR
-                ));
+                )));
                 return;
             }
         }
@@ -0,0 +1,426 @@
+use uuid::Uuid;
+use wasm_bindgen::{prelude::Closure, JsCast};
+
+use crate::database_tables::accounts_manager;
+use crate::database_tables::taxtables_manager::{self, TaxAmountType, TaxTable, TaxTableEntry};
+use crate::utility::html_helper_utility::{
+    display_error_banner, document_create_body_table_header, document_create_element,
+    document_create_select,
+    document_query_selector, document_query_selector_all, element_query_selector,
+};
+use crate::utility::{database_helper_utility as dhu, js_helper_utility as js};
+use crate::utility::error_helper_utility::MoneyManagerError;
+
+/// load_taxtables_into_body loads the list of tax tables into the body.
+pub fn load_taxtables_into_body() {
+    let taxtables = match taxtables_manager::retrieve_all_taxtables() {
+        Ok(taxtables) => taxtables,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+
+    //Clear out the body, and footer first
+    let body_div = document_query_selector("#body");
+    body_div.set_inner_html("");
+    let footer_div = document_query_selector("#footer");
+    footer_div.set_inner_html("");
+
+    //Create the New Tax Table Button
+    let new_taxtable_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .expect("Failed to create New Tax Table Button!");
+    new_taxtable_button.set_type("button");
+    new_taxtable_button.set_id("new_taxtable_button");
+    new_taxtable_button.set_value("New Tax Table");
+
+    let new_taxtable_button_on_click = Closure::wrap(Box::new(move || {
+        load_taxtable_editor_into_body(Uuid::new_v4());
+    }) as Box<dyn Fn()>);
+
+    new_taxtable_button.set_onclick(Some(new_taxtable_button_on_click.as_ref().unchecked_ref()));
+    new_taxtable_button_on_click.forget();
+
+    body_div
+        .append_child(&new_taxtable_button)
+        .expect("Failed to append New Tax Table Button!");
+
+    //Create the header for the body
+    {
+        let headings = vec!["Name".to_string(), "Entries".to_string()];
+        let taxtables_header = document_create_body_table_header("div", headings, "taxtable");
+
+        body_div
+            .append_child(&taxtables_header)
+            .expect("Failed to append taxtables_header to body!");
+    }
+
+    //Create taxtables_div, and place it in the body
+    let taxtables_div = document_create_element("div");
+    taxtables_div.set_id("taxtables_div");
+    taxtables_div
+        .class_list()
+        .add_1("body_table")
+        .expect("Failed to add class to element.");
+    body_div
+        .append_child(&taxtables_div)
+        .expect("Failed to append taxtables_div to body!");
+
+    let account_names = accounts_manager::retrieve_all_account_names().unwrap_or_default();
+
+    for taxtable in taxtables {
+        let taxtable_guid = taxtable.guid;
+
+        let taxtable_div = document_create_element("div");
+        taxtable_div
+            .class_list()
+            .add_1("body_row")
+            .expect("Failed to add class to element.");
+        taxtables_div
+            .append_child(&taxtable_div)
+            .expect("Failed to append taxtable_div to taxtables_div!");
+
+        let taxtable_name_div = document_create_element("div");
+        taxtable_name_div.set_text_content(Some(&taxtable.name));
+        taxtable_name_div
+            .class_list()
+            .add_1("taxtable_name")
+            .expect("Failed to add class to element.");
+        taxtable_div
+            .append_child(&taxtable_name_div)
+            .expect("Failed to append taxtable_name_div!");
+
+        //Describe each of the entries, like '6.25% to Sales Tax Payable'
+        let entries = taxtables_manager::retrieve_taxtable_entries_for_taxtable_guid(taxtable.guid)
+            .unwrap_or_default();
+        let entries_description = entries
+            .iter()
+            .map(|entry| {
+                let account_name = account_names
+                    .iter()
+                    .find(|account_name| account_name.guid == entry.account)
+                    .map(|account_name| account_name.name.clone())
+                    .unwrap_or_default();
+                match entry.amount_type {
+                    TaxAmountType::Percent => format!("{}% to {}", entry.amount(), account_name),
+                    TaxAmountType::Value => {
+                        format!("{} to {}", dhu::format_money(entry.amount()), account_name)
+                    }
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        let taxtable_entries_div = document_create_element("div");
+        taxtable_entries_div.set_text_content(Some(&entries_description));
+        taxtable_entries_div
+            .class_list()
+            .add_1("taxtable_entries")
+            .expect("Failed to add class to element.");
+        taxtable_div
+            .append_child(&taxtable_entries_div)
+            .expect("Failed to append taxtable_entries_div!");
+
+        let taxtable_div_on_click = Closure::wrap(Box::new(move || {
+            load_taxtable_editor_into_body(taxtable_guid);
+        }) as Box<dyn Fn()>);
+        taxtable_div.set_onclick(Some(taxtable_div_on_click.as_ref().unchecked_ref()));
+        taxtable_div_on_click.forget();
+    }
+}
+
+/// document_create_taxtable_entry_row creates a row for the tax table editor with the
+/// account, type, and amount for the given entry.
+fn document_create_taxtable_entry_row(
+    account_options: &[(String, String)],
+    taxtable_entry: Option<&TaxTableEntry>,
+) -> web_sys::HtmlElement {
+    let row_div = document_create_element("div");
+    row_div
+        .class_list()
+        .add_1("taxtable_entry_row")
+        .expect("Failed to add class to element.");
+
+    let account_select = document_create_select(
+        account_options,
+        &taxtable_entry
+            .map(|entry| dhu::convert_guid_to_sqlite_string(&entry.account))
+            .unwrap_or_default(),
+    );
+    account_select
+        .class_list()
+        .add_1("taxtable_entry_account_select")
+        .expect("Failed to add class to element.");
+    row_div
+        .append_child(&account_select)
+        .expect("Failed to append account_select!");
+
+    let type_select = document_create_select(
+        &[
+            ("2".to_string(), "Percent".to_string()),
+            ("1".to_string(), "Value".to_string()),
+        ],
+        &taxtable_entry
+            .map(|entry| (entry.amount_type as u8).to_string())
+            .unwrap_or("2".to_string()),
+    );
+    type_select
+        .class_list()
+        .add_1("taxtable_entry_type_select")
+        .expect("Failed to add class to element.");
+    row_div
+        .append_child(&type_select)
+        .expect("Failed to append type_select!");
+
+    let amount_input = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    amount_input.set_type("number");
+    amount_input.set_step("any");
+    amount_input
+        .class_list()
+        .add_1("taxtable_entry_amount_input")
+        .expect("Failed to add class to element.");
+    if let Some(entry) = taxtable_entry {
+        amount_input.set_value(&entry.amount().to_string());
+    }
+    row_div
+        .append_child(&amount_input)
+        .expect("Failed to append amount_input!");
+
+    //Let the row remove itself
+    let remove_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    remove_button.set_type("button");
+    remove_button.set_value("Remove");
+    let row_div_to_remove = row_div.clone();
+    let remove_button_on_click = Closure::wrap(Box::new(move || {
+        row_div_to_remove.remove();
+    }) as Box<dyn Fn()>);
+    remove_button.set_onclick(Some(remove_button_on_click.as_ref().unchecked_ref()));
+    remove_button_on_click.forget();
+    row_div
+        .append_child(&remove_button)
+        .expect("Failed to append remove_button!");
+
+    row_div
+}
+
+/// save_taxtable_with_guid saves the tax table in the editor with the given guid, and goes
+/// back to the list of tax tables.
+pub fn save_taxtable_with_guid(taxtable_guid: Uuid) {
+    let name_input = document_query_selector("#taxtable_editor_name_input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+
+    let entries_div = document_query_selector("#taxtable_editor_entries_div");
+    let mut taxtable_entries = Vec::new();
+
+    for row in document_query_selector_all(&entries_div, ".taxtable_entry_row") {
+        let account_select = element_query_selector(&row, ".taxtable_entry_account_select")
+            .dyn_into::<web_sys::HtmlSelectElement>()
+            .unwrap();
+        let type_select = element_query_selector(&row, ".taxtable_entry_type_select")
+            .dyn_into::<web_sys::HtmlSelectElement>()
+            .unwrap();
+        let amount_input = element_query_selector(&row, ".taxtable_entry_amount_input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap();
+
+        let account = match dhu::convert_string_to_guid(account_select.value()) {
+            Ok(account) => account,
+            Err(_) => {
+                display_error_banner(&MoneyManagerError::Validation(
+                    "Please select an account for each tax table entry.".to_string(),
+                ));
+                return;
+            }
+        };
+        let amount: f64 = match amount_input.value().trim().parse() {
+            Ok(amount) => amount,
+            Err(_) => {
+                display_error_banner(&MoneyManagerError::Validation(
+                    "Please enter a valid amount for each tax table entry.".to_string(),
+                ));
+                return;
+            }
+        };
+
+        taxtable_entries.push(TaxTableEntry {
+            id: -1,
+            taxtable: taxtable_guid,
+            account,
+            amount_num: (amount * taxtables_manager::TAX_AMOUNT_DENOM as f64).round() as i64,
+            amount_denom: taxtables_manager::TAX_AMOUNT_DENOM,
+            amount_type: if type_select.value() == "1" {
+                TaxAmountType::Value
+            } else {
+                TaxAmountType::Percent
+            },
+        });
+    }
+
+    //Keep the refcount of an existing tax table
+    let refcount = taxtables_manager::retrieve_taxtable_for_guid(taxtable_guid)
+        .map(|taxtable| taxtable.refcount)
+        .unwrap_or(0);
+
+    let taxtable = TaxTable {
+        guid: taxtable_guid,
+        name: name_input.value(),
+        refcount,
+        invisible: accounts_manager::Bool::False,
+        parent: None,
+    };
+
+    match taxtables_manager::save_new_and_delete_current(taxtable, taxtable_entries) {
+        Ok(_) => load_taxtables_into_body(),
+        Err(error) => display_error_banner(&error),
+    }
+}
+
+/// load_taxtable_editor_into_body loads the tax table editor into the body for the given
+/// taxtable_guid. Pass in a new GUID if you want to create a new tax table instead.
+pub fn load_taxtable_editor_into_body(taxtable_guid: Uuid) {
+    let account_names = match accounts_manager::retrieve_all_account_names() {
+        Ok(account_names) => account_names,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+    let account_options: Vec<(String, String)> = account_names
+        .iter()
+        .map(|account_name| {
+            (
+                dhu::convert_guid_to_sqlite_string(&account_name.guid),
+                format!("{} ({})", account_name.name, account_name.account_type),
+            )
+        })
+        .collect();
+
+    let taxtable = taxtables_manager::retrieve_taxtable_for_guid(taxtable_guid).ok();
+    let taxtable_entries =
+        taxtables_manager::retrieve_taxtable_entries_for_taxtable_guid(taxtable_guid)
+            .unwrap_or_default();
+
+    //Clear out the body, and footer first
+    let body_div = document_query_selector("#body");
+    body_div.set_inner_html("");
+    let footer_div = document_query_selector("#footer");
+    footer_div.set_inner_html("");
+
+    let taxtable_editor_div = document_create_element("div");
+    taxtable_editor_div.set_id("taxtable_editor");
+    body_div
+        .append_child(&taxtable_editor_div)
+        .expect("Failed to append taxtable_editor_div!");
+
+    let header = document_create_element("h3");
+    header.set_inner_html("Tax Table");
+    taxtable_editor_div.append_child(&header).unwrap();
+
+    //Setup the name
+    let name_label = document_create_element("label");
+    name_label.set_inner_html("Name: ");
+    let name_input = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    name_input.set_id("taxtable_editor_name_input");
+    if let Some(taxtable) = &taxtable {
+        name_input.set_value(&taxtable.name);
+    }
+    name_label.append_child(&name_input).unwrap();
+    taxtable_editor_div.append_child(&name_label).unwrap();
+
+    //Setup the entries
+    {
+        let headings = vec![
+            "Account".to_string(),
+            "Type".to_string(),
+            "Amount".to_string(),
+        ];
+        let entries_header = document_create_body_table_header("div", headings, "taxtable_entry");
+        taxtable_editor_div.append_child(&entries_header).unwrap();
+    }
+
+    let entries_div = document_create_element("div");
+    entries_div.set_id("taxtable_editor_entries_div");
+    taxtable_editor_div.append_child(&entries_div).unwrap();
+
+    for taxtable_entry in &taxtable_entries {
+        entries_div
+            .append_child(&document_create_taxtable_entry_row(
+                &account_options,
+                Some(taxtable_entry),
+            ))
+            .unwrap();
+    }
+    if taxtable_entries.is_empty() {
+        entries_div
+            .append_child(&document_create_taxtable_entry_row(&account_options, None))
+            .unwrap();
+    }
+
+    //Setup the buttons
+    let add_entry_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    add_entry_button.set_type("button");
+    add_entry_button.set_value("Add Entry");
+    let add_entry_button_on_click = Closure::wrap(Box::new(move || {
+        let entries_div = document_query_selector("#taxtable_editor_entries_div");
+        entries_div
+            .append_child(&document_create_taxtable_entry_row(&account_options, None))
+            .unwrap();
+    }) as Box<dyn Fn()>);
+    add_entry_button.set_onclick(Some(add_entry_button_on_click.as_ref().unchecked_ref()));
+    add_entry_button_on_click.forget();
+    taxtable_editor_div.append_child(&add_entry_button).unwrap();
+
+    let okay_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    okay_button.set_type("button");
+    okay_button.set_value("Okay");
+    let okay_button_on_click = Closure::wrap(Box::new(move || {
+        save_taxtable_with_guid(taxtable_guid);
+    }) as Box<dyn Fn()>);
+    okay_button.set_onclick(Some(okay_button_on_click.as_ref().unchecked_ref()));
+    okay_button_on_click.forget();
+    taxtable_editor_div.append_child(&okay_button).unwrap();
+
+    let cancel_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    cancel_button.set_type("button");
+    cancel_button.set_value("Cancel");
+    let cancel_button_on_click = Closure::wrap(Box::new(move || {
+        load_taxtables_into_body();
+    }) as Box<dyn Fn()>);
+    cancel_button.set_onclick(Some(cancel_button_on_click.as_ref().unchecked_ref()));
+    cancel_button_on_click.forget();
+    taxtable_editor_div.append_child(&cancel_button).unwrap();
+
+    if taxtable.is_some() {
+        let delete_button = document_create_element("input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap();
+        delete_button.set_type("button");
+        delete_button.set_value("Delete");
+        let delete_button_on_click = Closure::wrap(Box::new(move || {
+            if !js::confirm("Are you sure you want to delete this tax table?") {
+                return;
+            }
+            match taxtables_manager::delete_taxtable(taxtable_guid) {
+                Ok(_) => load_taxtables_into_body(),
+                Err(error) => display_error_banner(&error),
+            }
+        }) as Box<dyn Fn()>);
+        delete_button.set_onclick(Some(delete_button_on_click.as_ref().unchecked_ref()));
+        delete_button_on_click.forget();
+        taxtable_editor_div.append_child(&delete_button).unwrap();
+    }
+}
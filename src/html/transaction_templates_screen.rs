@@ -0,0 +1,489 @@
+use uuid::Uuid;
+use wasm_bindgen::{prelude::Closure, JsCast};
+
+use crate::database_tables::accounts_manager;
+use crate::database_tables::transaction_templates_manager::{
+    self, TransactionTemplate, TransactionTemplateSplit,
+};
+use crate::utility::html_helper_utility::{
+    display_error_banner, document_create_body_table_header, document_create_element,
+    document_create_select, document_query_selector, document_query_selector_all,
+    element_query_selector,
+};
+use crate::utility::{database_helper_utility as dhu, js_helper_utility as js};
+use crate::utility::error_helper_utility::MoneyManagerError;
+
+/// load_transaction_templates_into_body loads the list of transaction templates into the body.
+pub fn load_transaction_templates_into_body() {
+    let templates = match transaction_templates_manager::retrieve_all_transaction_templates() {
+        Ok(templates) => templates,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+
+    //Clear out the body, and footer first
+    let body_div = document_query_selector("#body");
+    body_div.set_inner_html("");
+    let footer_div = document_query_selector("#footer");
+    footer_div.set_inner_html("");
+
+    //Create the New Template Button
+    let new_template_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .expect("Failed to create New Template Button!");
+    new_template_button.set_type("button");
+    new_template_button.set_id("new_transaction_template_button");
+    new_template_button.set_value("New Template");
+
+    let new_template_button_on_click = Closure::wrap(Box::new(move || {
+        load_transaction_template_editor_into_body(Uuid::new_v4());
+    }) as Box<dyn Fn()>);
+
+    new_template_button.set_onclick(Some(new_template_button_on_click.as_ref().unchecked_ref()));
+    new_template_button_on_click.forget();
+
+    body_div
+        .append_child(&new_template_button)
+        .expect("Failed to append New Template Button!");
+
+    //Create the header for the body
+    {
+        let headings = vec![
+            "Name".to_string(),
+            "Description".to_string(),
+            "Splits".to_string(),
+        ];
+        let templates_header =
+            document_create_body_table_header("div", headings, "transaction_template");
+
+        body_div
+            .append_child(&templates_header)
+            .expect("Failed to append templates_header to body!");
+    }
+
+    //Create transaction_templates_div, and place it in the body
+    let templates_div = document_create_element("div");
+    templates_div.set_id("transaction_templates_div");
+    templates_div
+        .class_list()
+        .add_1("body_table")
+        .expect("Failed to add class to element.");
+    body_div
+        .append_child(&templates_div)
+        .expect("Failed to append templates_div to body!");
+
+    let account_names = accounts_manager::retrieve_all_account_names().unwrap_or_default();
+
+    for template in templates {
+        let template_guid = template.guid;
+
+        let template_div = document_create_element("div");
+        template_div
+            .class_list()
+            .add_1("body_row")
+            .expect("Failed to add class to element.");
+        templates_div
+            .append_child(&template_div)
+            .expect("Failed to append template_div to templates_div!");
+
+        let template_name_div = document_create_element("div");
+        template_name_div.set_text_content(Some(&template.name));
+        template_name_div
+            .class_list()
+            .add_1("transaction_template_name")
+            .expect("Failed to add class to element.");
+        template_div
+            .append_child(&template_name_div)
+            .expect("Failed to append template_name_div!");
+
+        let template_description_div = document_create_element("div");
+        template_description_div.set_text_content(Some(&template.description));
+        template_description_div
+            .class_list()
+            .add_1("transaction_template_description")
+            .expect("Failed to add class to element.");
+        template_div
+            .append_child(&template_description_div)
+            .expect("Failed to append template_description_div!");
+
+        //Describe each of the splits, like '50.00 to Groceries'
+        let splits_description = template
+            .splits
+            .iter()
+            .map(|split| {
+                let account_name = match split.account_guid {
+                    Some(account_guid) => account_names
+                        .iter()
+                        .find(|account_name| account_name.guid == account_guid)
+                        .map(|account_name| account_name.name.clone())
+                        .unwrap_or_default(),
+                    None => "This Account".to_string(),
+                };
+                match split.amount {
+                    Some(amount) => format!("{} to {}", dhu::format_money(amount), account_name),
+                    None => format!("? to {}", account_name),
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        let template_splits_div = document_create_element("div");
+        template_splits_div.set_text_content(Some(&splits_description));
+        template_splits_div
+            .class_list()
+            .add_1("transaction_template_splits")
+            .expect("Failed to add class to element.");
+        template_div
+            .append_child(&template_splits_div)
+            .expect("Failed to append template_splits_div!");
+
+        let template_div_on_click = Closure::wrap(Box::new(move || {
+            load_transaction_template_editor_into_body(template_guid);
+        }) as Box<dyn Fn()>);
+        template_div.set_onclick(Some(template_div_on_click.as_ref().unchecked_ref()));
+        template_div_on_click.forget();
+    }
+}
+
+/// document_create_transaction_template_split_row creates a row for the template editor with
+/// the account, amount, and memo for the given split.
+fn document_create_transaction_template_split_row(
+    account_options: &[(String, String)],
+    template_split: Option<&TransactionTemplateSplit>,
+) -> web_sys::HtmlElement {
+    let row_div = document_create_element("div");
+    row_div
+        .class_list()
+        .add_1("transaction_template_split_row")
+        .expect("Failed to add class to element.");
+
+    let account_select = document_create_select(
+        account_options,
+        &template_split
+            .and_then(|split| split.account_guid)
+            .map(|account_guid| dhu::convert_guid_to_sqlite_string(&account_guid))
+            .unwrap_or_default(),
+    );
+    account_select
+        .class_list()
+        .add_1("transaction_template_split_account_select")
+        .expect("Failed to add class to element.");
+    row_div
+        .append_child(&account_select)
+        .expect("Failed to append account_select!");
+
+    let amount_input = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    amount_input.set_type("number");
+    amount_input.set_step("any");
+    amount_input.set_placeholder("Blank");
+    amount_input
+        .class_list()
+        .add_1("transaction_template_split_amount_input")
+        .expect("Failed to add class to element.");
+    if let Some(amount) = template_split.and_then(|split| split.amount) {
+        amount_input.set_value(&amount.to_string());
+    }
+    row_div
+        .append_child(&amount_input)
+        .expect("Failed to append amount_input!");
+
+    let memo_input = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    memo_input.set_placeholder("Memo");
+    memo_input
+        .class_list()
+        .add_1("transaction_template_split_memo_input")
+        .expect("Failed to add class to element.");
+    if let Some(split) = template_split {
+        memo_input.set_value(&split.memo);
+    }
+    row_div
+        .append_child(&memo_input)
+        .expect("Failed to append memo_input!");
+
+    //Let the row remove itself
+    let remove_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    remove_button.set_type("button");
+    remove_button.set_value("Remove");
+    let row_div_to_remove = row_div.clone();
+    let remove_button_on_click = Closure::wrap(Box::new(move || {
+        row_div_to_remove.remove();
+    }) as Box<dyn Fn()>);
+    remove_button.set_onclick(Some(remove_button_on_click.as_ref().unchecked_ref()));
+    remove_button_on_click.forget();
+    row_div
+        .append_child(&remove_button)
+        .expect("Failed to append remove_button!");
+
+    row_div
+}
+
+/// save_transaction_template_with_guid saves the template in the editor with the given guid,
+/// and goes back to the list of templates.
+pub fn save_transaction_template_with_guid(template_guid: Uuid) {
+    let input_value = |selector: &str| {
+        document_query_selector(selector)
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap()
+            .value()
+    };
+
+    let splits_div = document_query_selector("#transaction_template_editor_splits_div");
+    let mut splits = Vec::new();
+
+    for row in document_query_selector_all(&splits_div, ".transaction_template_split_row") {
+        let account_select =
+            element_query_selector(&row, ".transaction_template_split_account_select")
+                .dyn_into::<web_sys::HtmlSelectElement>()
+                .unwrap();
+        let amount_input = element_query_selector(&row, ".transaction_template_split_amount_input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap();
+        let memo_input = element_query_selector(&row, ".transaction_template_split_memo_input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap();
+
+        //The blank account is the account the template is entered from
+        let account_guid = if account_select.value().is_empty() {
+            None
+        } else {
+            match dhu::convert_string_to_guid(account_select.value()) {
+                Ok(account_guid) => Some(account_guid),
+                Err(error) => {
+                    display_error_banner(&error);
+                    return;
+                }
+            }
+        };
+        let amount = if amount_input.value().trim().is_empty() {
+            None
+        } else {
+            match amount_input.value().trim().parse::<f64>() {
+                Ok(amount) => Some(amount),
+                Err(_) => {
+                    display_error_banner(&MoneyManagerError::Validation(
+                        "Please enter a valid amount, or leave it blank, for each split."
+                            .to_string(),
+                    ));
+                    return;
+                }
+            }
+        };
+
+        splits.push(TransactionTemplateSplit {
+            account_guid,
+            amount,
+            memo: memo_input.value(),
+        });
+    }
+
+    let memo = document_query_selector("#transaction_template_editor_memo_textarea")
+        .dyn_into::<web_sys::HtmlTextAreaElement>()
+        .unwrap()
+        .value();
+
+    let template = TransactionTemplate {
+        guid: template_guid,
+        name: input_value("#transaction_template_editor_name_input")
+            .trim()
+            .to_string(),
+        description: input_value("#transaction_template_editor_description_input"),
+        num: input_value("#transaction_template_editor_num_input"),
+        memo,
+        splits,
+    };
+
+    match transaction_templates_manager::save_transaction_template(template) {
+        Ok(_) => load_transaction_templates_into_body(),
+        Err(error) => display_error_banner(&error),
+    }
+}
+
+/// load_transaction_template_editor_into_body loads the template editor into the body for the
+/// given template_guid. Pass in a new GUID if you want to create a new template instead.
+pub fn load_transaction_template_editor_into_body(template_guid: Uuid) {
+    let account_names = match accounts_manager::retrieve_all_account_names() {
+        Ok(account_names) => account_names,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+    let mut account_options: Vec<(String, String)> =
+        vec![(String::new(), "(This Account)".to_string())];
+    account_options.extend(account_names.iter().map(|account_name| {
+        (
+            dhu::convert_guid_to_sqlite_string(&account_name.guid),
+            format!("{} ({})", account_name.name, account_name.account_type),
+        )
+    }));
+
+    let template =
+        transaction_templates_manager::retrieve_transaction_template_for_guid(template_guid).ok();
+
+    //Clear out the body, and footer first
+    let body_div = document_query_selector("#body");
+    body_div.set_inner_html("");
+    let footer_div = document_query_selector("#footer");
+    footer_div.set_inner_html("");
+
+    let template_editor_div = document_create_element("div");
+    template_editor_div.set_id("transaction_template_editor");
+    body_div
+        .append_child(&template_editor_div)
+        .expect("Failed to append template_editor_div!");
+
+    let header = document_create_element("h3");
+    header.set_inner_html("Transaction Template");
+    template_editor_div.append_child(&header).unwrap();
+
+    //Setup the name, description, and num
+    for (label_text, id, value) in [
+        (
+            "Name: ",
+            "transaction_template_editor_name_input",
+            template.as_ref().map(|template| template.name.clone()),
+        ),
+        (
+            "Description: ",
+            "transaction_template_editor_description_input",
+            template.as_ref().map(|template| template.description.clone()),
+        ),
+        (
+            "Num: ",
+            "transaction_template_editor_num_input",
+            template.as_ref().map(|template| template.num.clone()),
+        ),
+    ] {
+        let label = document_create_element("label");
+        label.set_inner_html(label_text);
+        let input = document_create_element("input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap();
+        input.set_id(id);
+        input.set_value(&value.unwrap_or_default());
+        label.append_child(&input).unwrap();
+        template_editor_div.append_child(&label).unwrap();
+    }
+
+    //Setup the memo
+    let memo_textarea = document_create_element("textarea")
+        .dyn_into::<web_sys::HtmlTextAreaElement>()
+        .unwrap();
+    memo_textarea.set_id("transaction_template_editor_memo_textarea");
+    memo_textarea.set_placeholder("Memo");
+    if let Some(template) = &template {
+        memo_textarea.set_value(&template.memo);
+    }
+    template_editor_div.append_child(&memo_textarea).unwrap();
+
+    //Setup the splits
+    {
+        let headings = vec![
+            "Account".to_string(),
+            "Amount".to_string(),
+            "Memo".to_string(),
+        ];
+        let splits_header =
+            document_create_body_table_header("div", headings, "transaction_template_split");
+        template_editor_div.append_child(&splits_header).unwrap();
+    }
+
+    let splits_div = document_create_element("div");
+    splits_div.set_id("transaction_template_editor_splits_div");
+    template_editor_div.append_child(&splits_div).unwrap();
+
+    match &template {
+        Some(template) => {
+            for template_split in &template.splits {
+                splits_div
+                    .append_child(&document_create_transaction_template_split_row(
+                        &account_options,
+                        Some(template_split),
+                    ))
+                    .unwrap();
+            }
+        }
+        None => {
+            //Start with this account, and a category
+            for _ in 0..2 {
+                splits_div
+                    .append_child(&document_create_transaction_template_split_row(
+                        &account_options,
+                        None,
+                    ))
+                    .unwrap();
+            }
+        }
+    }
+
+    //Setup the buttons
+    let add_split_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    add_split_button.set_type("button");
+    add_split_button.set_value("Add Split");
+    let add_split_button_on_click = Closure::wrap(Box::new(move || {
+        let splits_div = document_query_selector("#transaction_template_editor_splits_div");
+        splits_div
+            .append_child(&document_create_transaction_template_split_row(
+                &account_options,
+                None,
+            ))
+            .unwrap();
+    }) as Box<dyn Fn()>);
+    add_split_button.set_onclick(Some(add_split_button_on_click.as_ref().unchecked_ref()));
+    add_split_button_on_click.forget();
+    template_editor_div.append_child(&add_split_button).unwrap();
+
+    let okay_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    okay_button.set_type("button");
+    okay_button.set_value("Okay");
+    let okay_button_on_click = Closure::wrap(Box::new(move || {
+        save_transaction_template_with_guid(template_guid);
+    }) as Box<dyn Fn()>);
+    okay_button.set_onclick(Some(okay_button_on_click.as_ref().unchecked_ref()));
+    okay_button_on_click.forget();
+    template_editor_div.append_child(&okay_button).unwrap();
+
+    let cancel_button = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    cancel_button.set_type("button");
+    cancel_button.set_value("Cancel");
+    let cancel_button_on_click = Closure::wrap(Box::new(move || {
+        load_transaction_templates_into_body();
+    }) as Box<dyn Fn()>);
+    cancel_button.set_onclick(Some(cancel_button_on_click.as_ref().unchecked_ref()));
+    cancel_button_on_click.forget();
+    template_editor_div.append_child(&cancel_button).unwrap();
+
+    if template.is_some() {
+        let delete_button = document_create_element("input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap();
+        delete_button.set_type("button");
+        delete_button.set_value("Delete");
+        let delete_button_on_click = Closure::wrap(Box::new(move || {
+            if !js::confirm("Are you sure you want to delete this template?") {
+                return;
+            }
+            match transaction_templates_manager::delete_transaction_template(template_guid) {
+                Ok(_) => load_transaction_templates_into_body(),
+                Err(error) => display_error_banner(&error),
+            }
+        }) as Box<dyn Fn()>);
+        delete_button.set_onclick(Some(delete_button_on_click.as_ref().unchecked_ref()));
+        delete_button_on_click.forget();
+        template_editor_div.append_child(&delete_button).unwrap();
+    }
+}
@@ -4,21 +4,45 @@ use wasm_bindgen::{JsCast, prelude::Closure};
 use web_sys::HtmlInputElement;
 
 use crate::database_tables::transactions_manager::TransactionWithSplitInformation;
+use crate::utility::error_helper_utility::MoneyManagerError;
 use crate::utility::js_helper_utility as js;
-use crate::utility::sql_helper_utility as shu;
 use crate::utility::{csv_helper_utility, database_helper_utility as dhu};
 use crate::{
     database_tables::{
         accounts_manager::{self, Account},
-        commodities_manager, transactions_manager,
+        categorization_rules_manager, commodities_manager, prices_manager, slots_manager,
+        splits_manager,
+        transaction_templates_manager::{self, TransactionTemplate},
+        transactions_manager,
     },
     html::{accounts_screen::load_account_editor_into_body, transactions_screen},
     utility::html_helper_utility::*,
 };
 use chrono::Duration;
 use chrono::prelude::*;
+use std::collections::HashMap;
+
+/// BULK_ACTION_PREVIEW_LINES is how many of the transactions a bulk action changes are listed
+/// in its preview.
+const BULK_ACTION_PREVIEW_LINES: usize = 20;
+
+/// REGISTER_OVERSCAN_ROWS is how many rows of the register are rendered above, and below the
+/// ones scrolled into view, so scrolling doesn't show blank rows.
+const REGISTER_OVERSCAN_ROWS: usize = 40;
+
+/// DEFAULT_REGISTER_ROW_HEIGHT is the height in pixels a register row is taken to be, until
+/// one has been rendered to measure.
+const DEFAULT_REGISTER_ROW_HEIGHT: f64 = 24.0;
+
+/// RegisterRow is one row of the register that's showing, whether it's rendered or not.
+#[derive(Debug, Clone)]
+pub struct RegisterRow {
+    pub transaction: transactions_manager::TransactionWithSplitInformation, //transaction is the row's transaction, and the split it shows.
+    pub balance: f64, //balance is the account's balance after this row.
+    pub checked: bool, //checked is whether the row is checked for a bulk action.
+}
 
-pub fn currently_loaded_account_guid() -> Result<Uuid, String> {
+pub fn currently_loaded_account_guid() -> Result<Uuid, MoneyManagerError> {
     dhu::convert_string_to_guid(
         document_query_selector("#currently_loaded_account_guid")
             .dyn_into::<web_sys::HtmlInputElement>()
@@ -27,11 +51,11 @@ pub fn currently_loaded_account_guid() -> Result<Uuid, String> {
     )
 }
 
-pub fn currently_loaded_account_guid_string() -> Result<String, String> {
+pub fn currently_loaded_account_guid_string() -> Result<String, MoneyManagerError> {
     return match document_query_selector("#currently_loaded_account_guid")
             .dyn_into::<web_sys::HtmlInputElement>() {
         Ok(result) => Ok(result.value()),
-        Err(_err) => Err("Failed to find currently loaded account guid string!".to_string())
+        Err(_err) => Err(MoneyManagerError::NotFound("Failed to find currently loaded account guid string!".to_string()))
     };    
 }
 
@@ -82,11 +106,19 @@ pub fn load_transactions_into_body(transactions_with_splits: Vec<transactions_ma
     let binding = Closure::once_into_js(move || {
         let account_guid = match currently_loaded_account_guid() {
             Ok(result) => result,
-            Err(_e) => { panic!() }
+            Err(error) => {
+                display_error_banner(&error);
+                return;
+            }
         };
         let transactions_with_split_information = 
-            transactions_manager::retrieve_transactions_with_split_information_for_account_guid_for_past_year(account_guid)
-            .expect("Failed to create set binding for export!");
+            match transactions_manager::retrieve_transactions_with_split_information_for_account_guid_for_past_year(account_guid) {
+                Ok(transactions_with_split_information) => transactions_with_split_information,
+                Err(error) => {
+                    display_error_banner(&error);
+                    return;
+                }
+            };
 
         csv_helper_utility::export_transactions_to_csv(account_guid_string, transactions_with_split_information);
     });
@@ -107,6 +139,13 @@ pub fn load_transactions_into_body(transactions_with_splits: Vec<transactions_ma
         .append_child(&transaction_header_div)
         .expect("Failed to append transaction_header_div to transactions_div!");
 
+    //Setup the bulk actions for the checked transactions
+    body_div
+        .append_child(&document_create_bulk_action_bar(
+            transactions_with_splits[0].excluded_account_guid,
+        ))
+        .expect("Failed to append bulk_action_bar to body_div!");
+
     //Create the transaction list div
     {
         let headers = vec![
@@ -134,230 +173,383 @@ pub fn load_transactions_into_body(transactions_with_splits: Vec<transactions_ma
         .append_child(&transactions_div)
         .expect("Failed to append transactions_div to body!");
 
+    //Work out the balance after every row first, then only render the rows scrolled into view,
+    //since an account can have far more transactions than the page can hold at once
     let mut balance_amount: f64 = 0.0;
+    *crate::REGISTER_ROWS.lock().unwrap() = transactions_with_splits
+        .into_iter()
+        .map(|txn| {
+            //The amount is negative because we are looking at the other end of the split
+            let amount = -(txn.value_num as f64 / txn.value_denom as f64);
+            //Update the balance, unless the query worked out the true balance already
+            balance_amount = txn.balance.unwrap_or(balance_amount + amount);
+            RegisterRow {
+                transaction: txn,
+                balance: balance_amount,
+                checked: false,
+            }
+        })
+        .collect();
 
-    for txn in transactions_with_splits {
-        //Setup the query_selector acceptable guid
-        let txn_guid_selector = format!(
-            "transaction_{}",
-            &dhu::convert_guid_to_sqlite_string(&txn.guid)
-        );
-
-        //Create transaction div
-        let transaction_div = document_create_element("div");
-        transaction_div
+    for spacer_id in ["register_top_spacer", "register_bottom_spacer"] {
+        let spacer = document_create_element("div");
+        spacer.set_id(spacer_id);
+        spacer
             .class_list()
-            .add_1("body_row")
+            .add_1("register_spacer")
             .expect("Failed to add class to element.");
-        //Put it inside the transactions div
         transactions_div
-            .append_child(&transaction_div)
-            .expect("Failed to append transaction_div to accounts_div!");
+            .append_child(&spacer)
+            .expect("Failed to append spacer to transactions_div!");
+    }
 
-        //Setup the transaction delete link, and place it inside the transactions div
-        let delete_link = document_create_element("a")
-            .dyn_into::<web_sys::HtmlAnchorElement>()
-            .unwrap();
-        delete_link.set_inner_html(
-            "<img src='/css/fontawesome-free-5.15.3-desktop/svgs/regular/trash-alt.svg' />",
-        );
-        delete_link.set_href("javascript:void(0);");
-        delete_link.set_id(&txn_guid_selector);
-        delete_link
-            .dataset()
-            .set("guid", &dhu::convert_guid_to_sqlite_string(&txn.guid))
-            .expect("Failed to set dataset's txn_guid!");
-        delete_link
-            .class_list()
-            .add_1("trashcan")
-            .expect("Failed to add class to element.");
-        transaction_div
-            .append_child(&delete_link)
-            .expect("Failed to append delete_link to div!");
-
-        //Setup the delete_link handler
-        let delete_link_on_click = Closure::wrap(Box::new(move || {
-            let delete_link = document_query_selector(&format!("#{}", txn_guid_selector));
-            if delete_link.dataset().get("guid").is_none() == true {
-                js::alert("The given guid is not valid!");
-                return;
-            }
-            if dhu::convert_string_to_guid(delete_link.dataset().get("guid").unwrap()).is_ok()
-                == false
-            {
-                js::alert("The given guid is not valid!");
-                return;
-            }
-            let txn_guid =
-                dhu::convert_string_to_guid(delete_link.dataset().get("guid").unwrap()).unwrap();
-
-            if js::confirm("Are you sure you want to delete this transaction?")
-                == true
-            {
-                match transactions_manager::delete_transaction(txn_guid) {
-                    Ok(_e) => {
-                        js::alert("The transaction was successfully deleted.");
-                        //Reload the transactions to see our newly entered one
-                        let account_guid_string = currently_loaded_account_guid_string().expect("Failed to find in delete_transaction!");
-                        let account_guid = currently_loaded_account_guid().expect("Failed to find in delete_transaction!");
-
-                        if display_transactions_older_than_one_year() {
-                            load_transactions_for_account_into_body_for_all_time(
-                                account_guid_string,
-                            );
-                        } else {
-                            load_transactions_for_account_into_body_for_one_year_from_memory(account_guid);
-                        }
-
-                        //Clear the transaction editor now
-                        clear_transaction_editor();
-
-                        //Set focus on description to continue
-                        document_query_selector("#description_input")
-                            .focus()
-                            .expect("Failed to focus description_input!");
-                    }
-                    Err(e) => {
-                        js::alert(&format!(
-                            "There was an error deleting the transaction. {}",
-                            e
-                        ));
-                    }
-                }
-            }
-            //load_transaction_editor_into_body(edit_link);
-        }) as Box<dyn Fn()>);
+    let transactions_div_on_scroll = Closure::wrap(Box::new(move || {
+        render_visible_register_rows();
+    }) as Box<dyn Fn()>);
+    transactions_div.set_onscroll(Some(transactions_div_on_scroll.as_ref().unchecked_ref()));
+    transactions_div_on_scroll.forget();
+
+    //One listener handles every row, however many times they're rendered
+    let transactions_div_on_row_event = Closure::wrap(Box::new(move |event: web_sys::Event| {
+        handle_register_row_event(event);
+    }) as Box<dyn Fn(web_sys::Event)>);
+    for event_type in ["click", "change"] {
+        transactions_div
+            .add_event_listener_with_callback(
+                event_type,
+                transactions_div_on_row_event.as_ref().unchecked_ref(),
+            )
+            .expect("Failed to add the register's row listener!");
+    }
+    transactions_div_on_row_event.forget();
 
-        delete_link.set_onclick(Some(delete_link_on_click.as_ref().unchecked_ref()));
-        delete_link_on_click.forget();
-
-        //Setup the transaction date
-        let txn_date = document_create_element("div");
-        let result = match dhu::convert_string_to_date(&txn.post_date) {
-            Ok(e) => e,
-            Err(_ex) => NaiveDateTime::new(
-                NaiveDate::from_ymd_opt(0, 1, 1).unwrap(),
-                NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
-            ),
-        };
-        txn_date.set_text_content(Some(&result.format("%m/%d/%Y").to_string()));
-        txn_date
-            .class_list()
-            .add_1("transaction_post_date")
-            .expect("Failed to add class to element.");
-        transaction_div
-            .append_child(&txn_date)
-            .expect("Failed to append txn_date!");
+    render_visible_register_rows();
+}
 
-        //Setup the transaction description, and place it inside the transaction div
-        let txn_description = document_create_element("a")
-            .dyn_into::<web_sys::HtmlAnchorElement>()
-            .unwrap();
-        txn_description.set_text_content(Some(format!("{}", &txn.description).as_str()));
-        txn_description
-            .class_list()
-            .add_1("transaction_description")
-            .expect("Failed to add class to element.");
-        transaction_div
-            .append_child(&txn_description)
-            .expect("Failed to append txn_description to div!");
-
-        let txn_memo: String = String::from(&txn.memo.clone());
-        if txn_memo != "" {
-            //Setup the transaction memo, and place it as a hyperlink for the description
-            txn_description.set_href("javascript:void(0);");
-            let txn_description_on_click = Closure::wrap(Box::new(move || {
-                let memo = dhu::sanitize_string(txn_memo.clone());
-                js::alert(&memo);
-            }) as Box<dyn Fn()>);
+/// document_create_transaction_row creates the row of the register for the given register row.
+/// Its checkbox, delete link, and memo link are handled by the register's listeners, so nothing
+/// is left behind when the row is thrown away.
+fn document_create_transaction_row(row: &RegisterRow) -> web_sys::HtmlElement {
+    let txn = &row.transaction;
 
-            txn_description.set_onclick(Some(txn_description_on_click.as_ref().unchecked_ref()));
-            txn_description_on_click.forget();
-        }
+    //Create transaction div
+    let transaction_div = document_create_element("div");
+    transaction_div
+        .class_list()
+        .add_1("body_row")
+        .expect("Failed to add class to element.");
+    transaction_div
+        .dataset()
+        .set("guid", &dhu::convert_guid_to_sqlite_string(&txn.guid))
+        .expect("Failed to set dataset's guid!");
 
-        //Setup the transaction category
-        let txn_category = document_create_element("div");
-        txn_category.set_text_content(Some(&format!("{}", &txn.account_name)));
-        txn_category
-            .class_list()
-            .add_1("transaction_category")
-            .expect("Failed to add class to element.");
-        transaction_div
-            .append_child(&txn_category)
-            .expect("Failed to append txn_category to div!");
-
-        //Setup the Decrease column
-        let txn_decrease = document_create_element("div");
-        txn_decrease.set_text_content(Some(&format!("{}", "0.00")));
-        txn_decrease
-            .class_list()
-            .add_1("transaction_decrease")
-            .expect("failed to decrease");
-        transaction_div
-            .append_child(&txn_decrease)
-            .expect("Failed to append txn_decrease to div!");
-
-        //Setup the Increase column
-        let txn_increase = document_create_element("div");
-        txn_increase.set_text_content(Some(&format!("{}", "0.00")));
-        txn_increase
-            .class_list()
-            .add_1("transaction_increase")
-            .expect("failed to increase");
-        transaction_div
-            .append_child(&txn_increase)
-            .expect("Failed to append txn_increase to div!");
+    //Setup the checkbox for bulk actions, which the balance row doesn't get
+    let select_input = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .expect("Failed to create select_input!");
+    select_input.set_type("checkbox");
+    select_input.set_checked(row.checked);
+    select_input
+        .class_list()
+        .add_1("transaction_select")
+        .expect("Failed to add class to element.");
+    if txn.guid.is_nil() {
+        select_input.set_disabled(true);
+        select_input
+            .style()
+            .set_property("visibility", "hidden")
+            .expect("Failed to hide select_input!");
+    }
+    transaction_div
+        .append_child(&select_input)
+        .expect("Failed to append select_input to div!");
+
+    //Setup the transaction delete link, and place it inside the transactions div
+    let delete_link = document_create_element("a")
+        .dyn_into::<web_sys::HtmlAnchorElement>()
+        .unwrap();
+    delete_link.set_inner_html(
+        "<img src='/css/fontawesome-free-5.15.3-desktop/svgs/regular/trash-alt.svg' />",
+    );
+    delete_link.set_href("javascript:void(0);");
+    delete_link
+        .class_list()
+        .add_1("trashcan")
+        .expect("Failed to add class to element.");
+    transaction_div
+        .append_child(&delete_link)
+        .expect("Failed to append delete_link to div!");
+
+    //Setup the transaction date
+    let txn_date = document_create_element("div");
+    let result = match dhu::convert_string_to_date(&txn.post_date) {
+        Ok(e) => e,
+        Err(_ex) => NaiveDateTime::new(
+            NaiveDate::from_ymd_opt(0, 1, 1).unwrap(),
+            NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+        ),
+    };
+    txn_date.set_text_content(Some(&result.format("%m/%d/%Y").to_string()));
+    txn_date
+        .class_list()
+        .add_1("transaction_post_date")
+        .expect("Failed to add class to element.");
+    transaction_div
+        .append_child(&txn_date)
+        .expect("Failed to append txn_date!");
+
+    //Setup the transaction description, and place it inside the transaction div
+    let txn_description = document_create_element("a")
+        .dyn_into::<web_sys::HtmlAnchorElement>()
+        .unwrap();
+    txn_description.set_text_content(Some(&txn.description));
+    txn_description
+        .class_list()
+        .add_1("transaction_description")
+        .expect("Failed to add class to element.");
+    transaction_div
+        .append_child(&txn_description)
+        .expect("Failed to append txn_description to div!");
+
+    if !txn.memo.is_empty() {
+        //Setup the transaction memo, and place it as a hyperlink for the description
+        txn_description.set_href("javascript:void(0);");
+    }
 
-        //Setup the amount, it's negative because we are looking at the other end of the split
-        let amount: f64 = (txn.value_num as f64 / txn.value_denom as f64) * -1.0;
+    //Setup the transaction category
+    let txn_category = document_create_element("div");
+    txn_category.set_text_content(Some(&txn.account_name));
+    txn_category
+        .class_list()
+        .add_1("transaction_category")
+        .expect("Failed to add class to element.");
+    transaction_div
+        .append_child(&txn_category)
+        .expect("Failed to append txn_category to div!");
+
+    //Setup the Decrease column
+    let txn_decrease = document_create_element("div");
+    txn_decrease.set_text_content(Some("0.00"));
+    txn_decrease
+        .class_list()
+        .add_1("transaction_decrease")
+        .expect("failed to decrease");
+    transaction_div
+        .append_child(&txn_decrease)
+        .expect("Failed to append txn_decrease to div!");
+
+    //Setup the Increase column
+    let txn_increase = document_create_element("div");
+    txn_increase.set_text_content(Some("0.00"));
+    txn_increase
+        .class_list()
+        .add_1("transaction_increase")
+        .expect("failed to increase");
+    transaction_div
+        .append_child(&txn_increase)
+        .expect("Failed to append txn_increase to div!");
+
+    //Setup the amount, it's negative because we are looking at the other end of the split
+    let amount: f64 = -(txn.value_num as f64 / txn.value_denom as f64);
+
+    //Setup the change amount, it's negative because we are looking at the other end of the split
+    let txn_change = document_create_element("div");
+    if txn.excluded_account_mnemonic == "USD" {
+        txn_change.set_text_content(Some(&dhu::format_money(amount)));
+    } else {
+        txn_change.set_text_content(Some(&amount.to_string()));
+    }
+    txn_change
+        .class_list()
+        .add_1("transaction_change")
+        .expect("failed to add class to change");
+    transaction_div
+        .append_child(&txn_change)
+        .expect("Failed to append txn_increase to div!");
+
+    let balance_amount = row.balance;
+
+    //Setup the Balance Column
+    let txn_balance = document_create_element("div");
+    if txn.excluded_account_mnemonic == "USD" {
+        txn_balance.set_text_content(Some(&dhu::format_money(balance_amount)));
+    } else {
+        txn_balance.set_text_content(Some(&balance_amount.to_string()));
+    }
+    txn_balance
+        .class_list()
+        .add_1("transaction_balance")
+        .expect("Failed to add class to element.");
+    transaction_div
+        .append_child(&txn_balance)
+        .expect("Failed to append txn_balance to div!");
 
-        //Setup the change amount, it's negative because we are looking at the other end of the split
-        let txn_change = document_create_element("div");
+    //If amount is positive then setup the positive amounts
+    if amount >= 0.0 {
         if txn.excluded_account_mnemonic == "USD" {
-            txn_change.set_text_content(Some(&format!("{}", dhu::format_money(amount))));
+            txn_increase.set_text_content(Some(&dhu::format_money(amount)));
         } else {
-            txn_change.set_text_content(Some(&format!("{}", amount)));
+            txn_increase.set_text_content(Some(&amount.to_string()));
         }
-        txn_change
-            .class_list()
-            .add_1("transaction_change")
-            .expect("failed to add class to change");
-        transaction_div
-            .append_child(&txn_change)
-            .expect("Failed to append txn_increase to div!");
-
-        //Update the balance
-        balance_amount = balance_amount + amount;
-
-        //Setup the Balance Column
-        let txn_balance = document_create_element("div");
+    } else {
+        //Otherwise we setup the negative amounts
         if txn.excluded_account_mnemonic == "USD" {
-            txn_balance.set_text_content(Some(&format!("{}", dhu::format_money(balance_amount))));
+            txn_decrease.set_text_content(Some(&dhu::format_money(amount)));
         } else {
-            txn_balance.set_text_content(Some(&format!("{}", balance_amount)));
+            txn_decrease.set_text_content(Some(&amount.to_string()));
         }
-        txn_balance
-            .class_list()
-            .add_1("transaction_balance")
-            .expect("Failed to add class to element.");
-        transaction_div
-            .append_child(&txn_balance)
-            .expect("Failed to append txn_balance to div!");
-
-        //If amount is positive then setup the positive amounts
-        if amount >= 0.0 {
-            if txn.excluded_account_mnemonic == "USD" {
-                txn_increase.set_text_content(Some(&format!("{}", dhu::format_money(amount))));
-            } else {
-                txn_increase.set_text_content(Some(&format!("{}", amount)));
+    }
+
+    transaction_div
+}
+
+/// handle_register_row_event handles a click, or change on any row of the register, for the
+/// row's transaction. The register has one listener for all of them, since its rows are thrown
+/// away, and made again as it scrolls.
+fn handle_register_row_event(event: web_sys::Event) {
+    let Some(target) = event
+        .target()
+        .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+    else {
+        return;
+    };
+    let Some(transaction_guid) = target
+        .closest(".body_row")
+        .ok()
+        .flatten()
+        .and_then(|row| row.get_attribute("data-guid"))
+        .and_then(|guid| dhu::convert_string_to_guid(guid).ok())
+    else {
+        return;
+    };
+    let within = |selector: &str| target.closest(selector).ok().flatten().is_some();
+
+    match event.type_().as_str() {
+        "change" if within(".transaction_select") => {
+            //Remember it's checked, since the row is thrown away once it's scrolled out of view
+            if let Ok(select_input) = target.dyn_into::<web_sys::HtmlInputElement>() {
+                check_register_rows(Some(transaction_guid), select_input.checked());
             }
-        } else {
-            //Otherwise we setup the negative amounts
-            if txn.excluded_account_mnemonic == "USD" {
-                txn_decrease.set_text_content(Some(&format!("{}", dhu::format_money(amount))));
+        }
+        "click" if within(".trashcan") => delete_register_transaction(transaction_guid),
+        "click" if within(".transaction_description") => {
+            let memo = crate::REGISTER_ROWS
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|row| row.transaction.guid == transaction_guid)
+                .map(|row| row.transaction.memo.clone())
+                .unwrap_or_default();
+            if !memo.is_empty() {
+                display_message_banner(&dhu::sanitize_string(memo));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// delete_register_transaction deletes the given transaction from the register after asking
+/// the user, and reloads the register.
+fn delete_register_transaction(txn_guid: Uuid) {
+    if !js::confirm("Are you sure you want to delete this transaction?") {
+        return;
+    }
+    match transactions_manager::delete_transaction(txn_guid) {
+        Ok(_e) => {
+            //Reload the transactions to see our newly entered one
+            let account_guid_string = currently_loaded_account_guid_string()
+                .expect("Failed to find in delete_transaction!");
+            let account_guid =
+                currently_loaded_account_guid().expect("Failed to find in delete_transaction!");
+
+            if display_transactions_older_than_one_year() {
+                load_transactions_for_account_into_body_for_all_time(account_guid_string);
             } else {
-                txn_decrease.set_text_content(Some(&format!("{}", amount)));
+                load_transactions_for_account_into_body_for_one_year_from_memory(account_guid);
             }
+
+            //Clear the transaction editor now
+            clear_transaction_editor();
+
+            //Set focus on description to continue
+            document_query_selector("#description_input")
+                .focus()
+                .expect("Failed to focus description_input!");
+
+            display_message_banner("The transaction was deleted.");
+        }
+        Err(error) => {
+            display_error_banner(&error);
+        }
+    }
+}
+
+/// render_visible_register_rows renders the rows of the register that are scrolled into view,
+/// along with REGISTER_OVERSCAN_ROWS on either side of them. The rest are left to spacers of the
+/// same height, so the scrollbar still covers the whole register.
+fn render_visible_register_rows() {
+    let transactions_div = document_query_selector("#transaction_div");
+    let row_count = crate::REGISTER_ROWS.lock().unwrap().len();
+    let row_height = document_query_selector_all(&transactions_div, ".body_row")
+        .first()
+        .map(|row| row.offset_height() as f64)
+        .filter(|row_height| *row_height > 0.0)
+        .unwrap_or(DEFAULT_REGISTER_ROW_HEIGHT);
+    let scroll_top = transactions_div.scroll_top() as f64;
+    let visible_first = ((scroll_top / row_height).floor() as usize).min(row_count);
+    let visible_last = (((scroll_top + transactions_div.client_height() as f64) / row_height).ceil()
+        as usize)
+        .min(row_count);
+
+    //Nothing changes while the rows in view are already rendered
+    let dataset = transactions_div.dataset();
+    let rendered = |key: &str| dataset.get(key).and_then(|value| value.parse::<usize>().ok());
+    if let (Some(rendered_first), Some(rendered_last)) =
+        (rendered("renderedFirst"), rendered("renderedLast"))
+        && rendered_first <= visible_first
+        && visible_last <= rendered_last
+    {
+        return;
+    }
+
+    //The first row rendered is always an even one, so the alternating colors don't shift
+    let first = visible_first.saturating_sub(REGISTER_OVERSCAN_ROWS) & !1;
+    let last = (visible_last + REGISTER_OVERSCAN_ROWS).min(row_count);
+    let rows = crate::REGISTER_ROWS.lock().unwrap()[first..last].to_vec();
+
+    for row in document_query_selector_all(&transactions_div, ".body_row") {
+        row.remove();
+    }
+    let bottom_spacer = document_query_selector("#register_bottom_spacer");
+    for row in &rows {
+        transactions_div
+            .insert_before(&document_create_transaction_row(row), Some(&bottom_spacer))
+            .expect("Failed to insert transaction row!");
+    }
+    document_query_selector("#register_top_spacer")
+        .style()
+        .set_property("height", &format!("{}px", first as f64 * row_height))
+        .expect("Failed to size register_top_spacer!");
+    bottom_spacer
+        .style()
+        .set_property("height", &format!("{}px", (row_count - last) as f64 * row_height))
+        .expect("Failed to size register_bottom_spacer!");
+    dataset
+        .set("renderedFirst", &first.to_string())
+        .expect("Failed to set dataset's renderedFirst!");
+    dataset
+        .set("renderedLast", &last.to_string())
+        .expect("Failed to set dataset's renderedLast!");
+}
+
+/// check_register_rows checks, or unchecks the register rows of the given transaction, or every
+/// transaction for None, whether they're rendered or not.
+fn check_register_rows(transaction_guid: Option<Uuid>, checked: bool) {
+    for row in crate::REGISTER_ROWS.lock().unwrap().iter_mut() {
+        if !row.transaction.guid.is_nil()
+            && transaction_guid.is_none_or(|transaction_guid| transaction_guid == row.transaction.guid)
+        {
+            row.checked = checked;
         }
     }
 }
@@ -596,12 +788,43 @@ pub fn clear_transaction_editor() {
         .expect("Failed to convert memo_textarea!");
 
     memo.set_value("");
+
+    //clear the num
+    let num_input = document_query_selector("#num_input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .expect("Failed to dyn_into #num_input");
+    num_input.set_value("");
+
+    //clear the template, and give back the category
+    let transaction_template_guid_input = document_query_selector("#transaction_template_guid_input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .expect("Failed to dyn_into #transaction_template_guid_input");
+    transaction_template_guid_input.set_value("");
+    category_select.set_disabled(false);
+
+    //clear which categorization rule fired
+    document_query_selector("#categorization_rule_preview").set_text_content(None);
+
+    //clear the exchange rate for the first category
+    for selector in ["#exchange_rate_input", "#received_input"] {
+        document_query_selector(selector)
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .expect("Failed to find exchange rate input!")
+            .set_value("");
+    }
+    update_exchange_rate_row();
 }
 
 /// enter_transaction_on_click() handles the enter key being pressed to enter a transaction.
 pub fn enter_transaction_on_click() {
-    let currently_loaded_account = accounts_manager::load_account_for_guid(currently_loaded_account_guid()
-        .expect("Failed to get currently loaded account_guid in enter_transaction_on_click!"));
+    let currently_loaded_account =
+        match currently_loaded_account_guid().and_then(accounts_manager::load_account_for_guid) {
+            Ok(currently_loaded_account) => currently_loaded_account,
+            Err(error) => {
+                display_error_banner(&error);
+                return;
+            }
+        };
     let post_date_date = document_query_selector("#date_input")
         .dyn_into::<web_sys::HtmlInputElement>()
         .expect("Failed to get post_date.")
@@ -616,19 +839,52 @@ pub fn enter_transaction_on_click() {
     );
     let post_date = match post_date {
         Ok(result) => result,
-        Err(e) => {
-            js::alert(&e);
+        Err(error) => {
+            display_error_banner(&error);
             dhu::null_date()
         }
     };
 
     //Handle a null date
     if post_date.year() < 1 {
-        js::alert(&format!(
+        display_error_banner(&MoneyManagerError::Validation(format!(
             "{}{} doesn't make a valid time.",
             post_date_date.replace("-", ""),
             post_date_time.replace("-", "").replace(":", ""),
-        ));
+        )));
+        return;
+    }
+
+    //Templates with more splits than the category select can show are entered on their own
+    let transaction_template_guid = document_query_selector("#transaction_template_guid_input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .expect("Failed to dyn_into #transaction_template_guid_input")
+        .value();
+    if !transaction_template_guid.is_empty() {
+        let entered = dhu::convert_string_to_guid(transaction_template_guid).and_then(
+            |transaction_template_guid| {
+                enter_transaction_template(
+                    transaction_template_guid,
+                    &currently_loaded_account,
+                    post_date,
+                )
+            },
+        );
+        match entered {
+            Ok(true) => reload_transactions_after_entering(),
+            Ok(false) => {}
+            Err(error) => display_error_banner(&error),
+        }
+        return;
+    }
+
+    //The split rows are entered instead of the category, and amount when they're shown
+    if is_split_mode() {
+        match enter_split_transaction(&currently_loaded_account, post_date) {
+            Ok(true) => reload_transactions_after_entering(),
+            Ok(false) => {}
+            Err(error) => display_error_banner(&error),
+        }
         return;
     }
 
@@ -648,10 +904,10 @@ pub fn enter_transaction_on_click() {
             //we're good!
         }
         Err(_e) => {
-            js::alert(&format!(
+            display_error_banner(&MoneyManagerError::Validation(format!(
                 "The given amount '{}' is not a valid number.",
                 change_input.value()
-            ));
+            )));
             return;
         }
     }
@@ -662,11 +918,20 @@ pub fn enter_transaction_on_click() {
         .expect("Amount number is not valid!");
 
     //Get the commodity for this transaction to determine the units of the denom
-    let commodity = commodities_manager::retrieve_commodity_for_guid(
-        currently_loaded_account
-            .commodity_guid
-            .expect("Missing Commodity Guid!"),
-    );
+    let commodity = match currently_loaded_account.commodity_guid {
+        Some(commodity_guid) => commodities_manager::retrieve_commodity_for_guid(commodity_guid),
+        None => Err(MoneyManagerError::NotFound(format!(
+            "The account '{}' does not have a commodity.",
+            currently_loaded_account.name
+        ))),
+    };
+    let commodity = match commodity {
+        Ok(commodity) => commodity,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
 
     let value_num = (amount * commodity.fraction as f64).round() as i64;
 
@@ -685,8 +950,13 @@ pub fn enter_transaction_on_click() {
                 .expect("Failed to find option!")
                 .dyn_into::<web_sys::HtmlOptionElement>()
                 .expect("Failed to find option!");
-            account_guid = dhu::convert_string_to_guid(option.value())
-                .expect("Failed to convert category guid!");
+            account_guid = match dhu::convert_string_to_guid(option.value()) {
+                Ok(account_guid) => account_guid,
+                Err(error) => {
+                    display_error_banner(&error);
+                    return;
+                }
+            };
             account_name = option.text();
             break;
         }
@@ -704,7 +974,10 @@ pub fn enter_transaction_on_click() {
         excluded_account_mnemonic: String::from(""),
         guid: uuid::Uuid::new_v4(), //guid is the GUID for this transaction
         currency_guid: commodity.guid,
-        num: String::from(""), //Num is the invoice.id that this transaction belongs to.
+        num: document_query_selector("#num_input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .expect("Failed to find num input!")
+            .value(), //Num is the invoice.id that this transaction belongs to.
         post_date: dhu::convert_date_to_string_format(post_date), //post_date is the date this transaction is posted. (Ex: '20120801040000' is 'Aug 1 2012')
         enter_date: dhu::convert_date_to_string_format(chrono::Local::now().naive_local()),
         description: document_query_selector("#description_input")
@@ -716,9 +989,33 @@ pub fn enter_transaction_on_click() {
         account_name: account_name,
         account_guid: account_guid,
         memo: memo,
+        balance: None,
+    };
+
+    //Check it hasn't been entered already
+    match confirm_entering_probable_duplicates(
+        txn.excluded_account_guid,
+        post_date,
+        txn.value_num,
+        txn.value_denom,
+        &txn.description,
+    ) {
+        Ok(true) => {}
+        Ok(false) => return,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    }
+
+    //A category in another commodity gets the quantity it received, and the rate is recorded
+    let saved = if is_exchange_rate_row_shown() {
+        enter_transfer_transaction(txn)
+    } else {
+        transactions_manager::save_transaction(txn)
     };
 
-    match transactions_manager::save_transaction(txn) {
+    match saved {
         Ok(_e) => {
             //Reload the transactions to see our newly entered one
             let account_guid_string = currently_loaded_account_guid_string().expect("Failed to find in save_transaction!");
@@ -738,8 +1035,8 @@ pub fn enter_transaction_on_click() {
                 .focus()
                 .expect("Failed to focus description_input!");
         }
-        Err(e) => {
-            js::alert(&e);
+        Err(error) => {
+            display_error_banner(&error);
         }
     }
 }
@@ -829,6 +1126,20 @@ pub fn document_create_transaction_editor(
             .expect(&error_message);
     }
 
+    // create the num input
+    {
+        let num_input = document_create_element("input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .expect(&error_message);
+        num_input.set_id("num_input");
+        num_input.set_type("text");
+        num_input.set_placeholder("Num");
+
+        transaction_editor_top_row
+            .append_child(&num_input)
+            .expect(&error_message);
+    }
+
     // create the description input and on_blur handler
     {
         let description_input = document_create_element("input")
@@ -846,7 +1157,9 @@ pub fn document_create_transaction_editor(
             .expect(&error_message);
 
         let description_on_blur = Closure::wrap(Box::new(move || {
-            load_last_transaction_for_account();
+            if !load_transaction_template_for_description() && !apply_categorization_rules_to_editor() {
+                load_last_transaction_for_account();
+            }
         }) as Box<dyn Fn()>);
 
         description_input.set_onblur(Some(description_on_blur.as_ref().unchecked_ref()));
@@ -893,16 +1206,35 @@ pub fn document_create_transaction_editor(
         let mut options = Vec::new();
         //Setup the categories to choose from now
         let mut accounts =
-            accounts_manager::load_all_accounts_except_root_and_template_from_memory();
+            match accounts_manager::load_all_accounts_except_root_and_template_from_memory() {
+                Ok(accounts) => accounts,
+                Err(error) => {
+                    display_error_banner(&error);
+                    Vec::new()
+                }
+            };
         accounts.sort_by(|a, b| a.name.cmp(&b.name));
         for account in accounts {
+            let commodity_guid = account
+                .commodity_guid
+                .map(|commodity_guid| dhu::convert_guid_to_sqlite_string(&commodity_guid))
+                .unwrap_or_default();
             //Don't load the current account we are in
-            if account.guid != account_guid_currently_loaded {
+            if account.guid == account_guid_currently_loaded {
+                category_select
+                    .dataset()
+                    .set("commodity", &commodity_guid)
+                    .expect(&error_message);
+            } else {
                 let option = document_create_element("option")
                     .dyn_into::<web_sys::HtmlOptionElement>()
                     .expect(&error_message);
                 option.set_value(&dhu::sanitize_string(account.guid.to_string()));
                 option.set_text_content(Some(&dhu::sanitize_string(account.name)));
+                option
+                    .dataset()
+                    .set("commodity", &commodity_guid)
+                    .expect(&error_message);
                 if !options.contains(&option) {
                     options.push(option);
                 }
@@ -916,6 +1248,13 @@ pub fn document_create_transaction_editor(
         transaction_editor_top_row
             .append_child(&category_select)
             .expect(&error_message);
+
+        let category_select_on_change = Closure::wrap(Box::new(move || {
+            update_exchange_rate_row();
+        }) as Box<dyn Fn()>);
+
+        category_select.set_onchange(Some(category_select_on_change.as_ref().unchecked_ref()));
+        category_select_on_change.forget();
     }
 
     //Setup the change input next
@@ -945,6 +1284,91 @@ pub fn document_create_transaction_editor(
 
         change_input.set_onkeydown(Some(change_input_on_input.as_ref().unchecked_ref()));
         change_input_on_input.forget();
+
+        //Rules with an amount range can only fire once the amount is known
+        let change_input_on_change = Closure::wrap(Box::new(move || {
+            apply_categorization_rules_to_editor();
+            update_received_amount();
+        }) as Box<dyn Fn()>);
+
+        change_input.set_onchange(Some(change_input_on_change.as_ref().unchecked_ref()));
+        change_input_on_change.forget();
+    }
+
+    //Setup the exchange rate row, hidden until the category is in another commodity
+    {
+        let transaction_exchange_rate_row = document_create_element("div");
+        transaction_exchange_rate_row.set_id("transaction_exchange_rate_row");
+        transaction_exchange_rate_row
+            .style()
+            .set_property("display", "none")
+            .expect("Failed to modify transaction_exchange_rate_row style!");
+        transaction_editor_div
+            .append_child(&transaction_exchange_rate_row)
+            .expect(&error_message);
+
+        let exchange_rate_label = document_create_element("span");
+        exchange_rate_label.set_id("exchange_rate_label");
+        transaction_exchange_rate_row
+            .append_child(&exchange_rate_label)
+            .expect(&error_message);
+
+        let exchange_rate_input = document_create_element("input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .expect(&error_message);
+        exchange_rate_input.set_id("exchange_rate_input");
+        exchange_rate_input.set_type("tel");
+        exchange_rate_input.set_placeholder("Exchange Rate");
+        transaction_exchange_rate_row
+            .append_child(&exchange_rate_input)
+            .expect(&error_message);
+
+        let exchange_rate_input_on_input = Closure::wrap(Box::new(move || {
+            update_received_amount();
+        }) as Box<dyn Fn()>);
+
+        exchange_rate_input
+            .set_oninput(Some(exchange_rate_input_on_input.as_ref().unchecked_ref()));
+        exchange_rate_input_on_input.forget();
+
+        let received_input = document_create_element("input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .expect(&error_message);
+        received_input.set_id("received_input");
+        received_input.set_type("tel");
+        received_input.set_placeholder("Received");
+        transaction_exchange_rate_row
+            .append_child(&received_input)
+            .expect(&error_message);
+
+        let received_input_on_input = Closure::wrap(Box::new(move || {
+            update_exchange_rate();
+        }) as Box<dyn Fn()>);
+
+        received_input.set_oninput(Some(received_input_on_input.as_ref().unchecked_ref()));
+        received_input_on_input.forget();
+    }
+
+    //Setup the split rows, hidden until the editor is switched to splits
+    {
+        let transaction_split_rows_div = document_create_element("div");
+        transaction_split_rows_div.set_id("transaction_split_rows_div");
+        transaction_split_rows_div
+            .style()
+            .set_property("display", "none")
+            .expect("Failed to modify transaction_split_rows_div style!");
+        transaction_editor_div
+            .append_child(&transaction_split_rows_div)
+            .expect(&error_message);
+
+        //Any change to a split row changes what's left to balance
+        let transaction_split_rows_div_on_input = Closure::wrap(Box::new(move || {
+            update_remaining_to_balance();
+        }) as Box<dyn Fn()>);
+
+        transaction_split_rows_div
+            .set_oninput(Some(transaction_split_rows_div_on_input.as_ref().unchecked_ref()));
+        transaction_split_rows_div_on_input.forget();
     }
 
     //Setup the bottom row
@@ -967,6 +1391,26 @@ pub fn document_create_transaction_editor(
             .expect(&error_message);
     }
 
+    //Create the Splits input next, which switches between one category, and split rows
+    {
+        let split_mode_input = document_create_element("input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .expect(&error_message);
+        split_mode_input.set_type("button");
+        split_mode_input.set_id("split_mode_input");
+        split_mode_input.set_value("Splits");
+        transaction_editor_bottom_row
+            .append_child(&split_mode_input)
+            .expect(&error_message);
+
+        let split_mode_on_click = Closure::wrap(Box::new(move || {
+            toggle_split_mode();
+        }) as Box<dyn Fn()>);
+
+        split_mode_input.set_onclick(Some(split_mode_on_click.as_ref().unchecked_ref()));
+        split_mode_on_click.forget();
+    }
+
     //Create the Enter Transaction input next
     {
         let enter_transaction_input = document_create_element("input")
@@ -989,29 +1433,1099 @@ pub fn document_create_transaction_editor(
         enter_transaction_on_click.forget();
     }
 
+    //Setup the template row, with a button for each template
+    {
+        let transaction_template_guid_input = document_create_element("input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .expect(&error_message);
+        transaction_template_guid_input.set_id("transaction_template_guid_input");
+        transaction_template_guid_input.set_type("hidden");
+        transaction_editor_div
+            .append_child(&transaction_template_guid_input)
+            .expect(&error_message);
+
+        let templates = match transaction_templates_manager::retrieve_all_transaction_templates() {
+            Ok(templates) => templates,
+            Err(error) => {
+                display_error_banner(&error);
+                Vec::new()
+            }
+        };
+
+        let transaction_editor_template_row = document_create_element("div");
+        transaction_editor_template_row.set_id("transaction_editor_template_row");
+        transaction_editor_div
+            .append_child(&transaction_editor_template_row)
+            .expect(&error_message);
+
+        for template in templates {
+            let template_button = document_create_element("input")
+                .dyn_into::<web_sys::HtmlInputElement>()
+                .expect(&error_message);
+            template_button.set_type("button");
+            template_button.set_value(&template.name);
+            template_button
+                .class_list()
+                .add_1("transaction_template_button")
+                .expect("Failed to add class to element.");
+
+            let template_button_on_click = Closure::wrap(Box::new(move || {
+                apply_transaction_template_to_editor(&template);
+            }) as Box<dyn Fn()>);
+
+            template_button.set_onclick(Some(template_button_on_click.as_ref().unchecked_ref()));
+            template_button_on_click.forget();
+
+            transaction_editor_template_row
+                .append_child(&template_button)
+                .expect(&error_message);
+        }
+
+        //Show which categorization rule fired, if any
+        let categorization_rule_preview = document_create_element("div");
+        categorization_rule_preview.set_id("categorization_rule_preview");
+        transaction_editor_template_row
+            .append_child(&categorization_rule_preview)
+            .expect(&error_message);
+    }
+
     return transaction_editor_div;
 }
 
-/// load_last_transaction_for_account loads the last transaction for the account.
-pub fn load_last_transaction_for_account() {
-    let error_message: String = String::from("Failed to load last transaction for account");
-    let currently_loaded_account_guid = document_query_selector("#currently_loaded_account_guid")
-        .dyn_into::<web_sys::HtmlInputElement>()
-        .expect(&error_message);
-    let currently_loaded_account_guid =
-        dhu::convert_string_to_guid(currently_loaded_account_guid.value()).expect(&format!(
-            "The given account_guid is not valid! '{}'",
-            currently_loaded_account_guid.value()
-        ));
-
-    let current_description = document_query_selector("#description_input")
+/// load_transaction_template_for_description fills in the transaction editor from the template
+/// named like the entered description, if there is one. It returns whether one was found.
+pub fn load_transaction_template_for_description() -> bool {
+    let description = document_query_selector("#description_input")
         .dyn_into::<web_sys::HtmlInputElement>()
-        .expect(&error_message)
+        .expect("Failed to find description input!")
         .value();
 
-    let transaction = transactions_manager::retrieve_transaction_with_split_information_for_account_guid_and_description(
-                                                                                                        currently_loaded_account_guid, current_description);
-    //If the transaction is just one, you can continue
+    let templates = match transaction_templates_manager::retrieve_all_transaction_templates() {
+        Ok(templates) => templates,
+        Err(error) => {
+            display_error_banner(&error);
+            return false;
+        }
+    };
+
+    match transaction_templates_manager::find_transaction_template_for_name(&templates, &description) {
+        Some(template) => {
+            apply_transaction_template_to_editor(template);
+            true
+        }
+        None => false,
+    }
+}
+
+/// apply_transaction_template_to_editor fills in the transaction editor from the given template.
+/// A template with just a category for the loaded account fills in the category, and amount.
+/// Any other template is entered with all of its splits, with the amount going to the loaded
+/// account's split.
+pub fn apply_transaction_template_to_editor(template: &TransactionTemplate) {
+    let account_guid = match currently_loaded_account_guid() {
+        Ok(account_guid) => account_guid,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+
+    let input = |selector: &str| {
+        document_query_selector(selector)
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .expect("Failed to find transaction editor input!")
+    };
+    input("#description_input").set_value(&template.description);
+    input("#num_input").set_value(&template.num);
+    document_query_selector("#memo_textarea")
+        .dyn_into::<web_sys::HtmlTextAreaElement>()
+        .expect("Failed to convert memo_textarea!")
+        .set_value(&template.memo);
+
+    let category_select = document_query_selector("#category_select")
+        .dyn_into::<web_sys::HtmlSelectElement>()
+        .expect("Failed to find category select!");
+
+    match template.category_splits(account_guid) {
+        Some((account_split, category_split)) => {
+            input("#transaction_template_guid_input").set_value("");
+            category_select.set_disabled(false);
+
+            select_category_for_account_guid(category_split.account_guid.unwrap_or_default());
+
+            let amount = account_split
+                .amount
+                .or(category_split.amount.map(|amount| -amount));
+            input("#change_input").set_value(
+                &amount
+                    .map(|amount| amount.to_string())
+                    .unwrap_or_default(),
+            );
+        }
+        None => {
+            input("#transaction_template_guid_input")
+                .set_value(&dhu::convert_guid_to_sqlite_string(&template.guid));
+            category_select.set_disabled(true);
+
+            let amount = template
+                .splits
+                .iter()
+                .find(|split| split.is_for_account(account_guid))
+                .and_then(|split| split.amount);
+            input("#change_input").set_value(
+                &amount
+                    .map(|amount| amount.to_string())
+                    .unwrap_or_default(),
+            );
+        }
+    }
+
+    input("#change_input")
+        .focus()
+        .expect("Failed to focus change_input!");
+}
+
+/// select_category_for_account_guid selects the given account in the category select, and
+/// returns its name, or None when it isn't one of the categories.
+fn select_category_for_account_guid(account_guid: Uuid) -> Option<String> {
+    let category_select = document_query_selector("#category_select")
+        .dyn_into::<web_sys::HtmlSelectElement>()
+        .expect("Failed to find category select!");
+    let options: web_sys::HtmlOptionsCollection = category_select.options();
+    for i in 0..options.length() {
+        let option = options
+            .item(i)
+            .expect("Failed to find option!")
+            .dyn_into::<web_sys::HtmlOptionElement>()
+            .expect("Failed to find option!");
+        if dhu::convert_string_to_guid(option.value()).ok() == Some(account_guid) {
+            category_select.set_selected_index(i as i32);
+            update_exchange_rate_row();
+            return Some(option.text());
+        }
+    }
+    None
+}
+
+/// apply_categorization_rules_to_editor picks the category, and rewrites the description in the
+/// transaction editor with the first categorization rule that fires for them, and shows which
+/// rule it was. It returns whether a rule fired.
+pub fn apply_categorization_rules_to_editor() -> bool {
+    //A template with its own splits has already picked the categories
+    let transaction_template_guid = document_query_selector("#transaction_template_guid_input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .expect("Failed to dyn_into #transaction_template_guid_input")
+        .value();
+    if !transaction_template_guid.is_empty() {
+        return false;
+    }
+
+    let description_input = document_query_selector("#description_input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .expect("Failed to find description input!");
+    let amount = document_query_selector("#change_input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .expect("Failed to dyn_into #change_input")
+        .value()
+        .replace([' ', '$', ','], "")
+        .parse::<f64>()
+        .ok();
+
+    let rules = match categorization_rules_manager::retrieve_all_categorization_rules() {
+        Ok(rules) => rules,
+        Err(error) => {
+            display_error_banner(&error);
+            return false;
+        }
+    };
+
+    let categorization_rule_preview = document_query_selector("#categorization_rule_preview");
+    let description = description_input.value();
+    let Some(rule) = categorization_rules_manager::find_matching_rule(&rules, &description, amount)
+    else {
+        categorization_rule_preview.set_text_content(None);
+        return false;
+    };
+
+    let Some(category_name) = select_category_for_account_guid(rule.account_guid) else {
+        categorization_rule_preview.set_text_content(Some(&format!(
+            "The rule '{}' fired, but its category can't be used for this account.",
+            rule.name
+        )));
+        return false;
+    };
+    description_input.set_value(&rule.rewrite_description(&description));
+    categorization_rule_preview.set_text_content(Some(&format!(
+        "The rule '{}' set the category to {}.",
+        rule.name, category_name
+    )));
+
+    true
+}
+
+/// enter_transaction_template enters the template with the given guid from the given account,
+/// with the description, num, and memo in the transaction editor, and the amount in the editor
+/// going to the account's split when the template leaves it blank.
+fn enter_transaction_template(
+    transaction_template_guid: Uuid,
+    account: &Account,
+    post_date: NaiveDateTime,
+) -> Result<bool, MoneyManagerError> {
+    let input_value = |selector: &str| {
+        document_query_selector(selector)
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .expect("Failed to find transaction editor input!")
+            .value()
+    };
+
+    let amount = input_value("#change_input")
+        .replace([' ', '$', ','], "");
+    let amount = if amount.is_empty() {
+        None
+    } else {
+        Some(amount.parse::<f64>().map_err(|_| {
+            MoneyManagerError::Validation(format!(
+                "The given amount '{}' is not a valid number.",
+                amount
+            ))
+        })?)
+    };
+
+    let template =
+        transaction_templates_manager::retrieve_transaction_template_for_guid(transaction_template_guid)?;
+    let template_splits = template.resolve_splits(account.guid, amount)?;
+
+    let commodity = match account.commodity_guid {
+        Some(commodity_guid) => commodities_manager::retrieve_commodity_for_guid(commodity_guid)?,
+        None => {
+            return Err(MoneyManagerError::NotFound(format!(
+                "The account '{}' does not have a commodity.",
+                account.name
+            )));
+        }
+    };
+
+    //Round each split to the currency, and let the last split take up any rounding difference
+    let mut value_nums: Vec<i64> = template_splits
+        .iter()
+        .map(|split| (split.amount.unwrap_or(0.0) * commodity.fraction as f64).round() as i64)
+        .collect();
+    let rounding_difference: i64 = value_nums.iter().sum();
+    if let Some(last_value_num) = value_nums.last_mut() {
+        *last_value_num -= rounding_difference;
+    }
+
+    let transaction = transactions_manager::Transaction {
+        guid: Uuid::new_v4(),
+        currency_guid: commodity.guid,
+        num: input_value("#num_input"),
+        post_date: dhu::convert_date_to_string_format(post_date),
+        enter_date: dhu::convert_date_to_string_format(chrono::Local::now().naive_local()),
+        description: input_value("#description_input"),
+    };
+    let splits: Vec<splits_manager::Split> = template_splits
+        .iter()
+        .zip(value_nums)
+        .map(|(split, value_num)| splits_manager::Split {
+            guid: Uuid::new_v4(),
+            tx_guid: transaction.guid,
+            account_guid: split.account_guid.unwrap_or(account.guid),
+            memo: split.memo.clone(),
+            action: String::new(),
+            reconcile_state: "n".to_string(),
+            reconcile_date: None,
+            value_num,
+            value_denom: commodity.fraction,
+            quantity_num: value_num,
+            quantity_denom: commodity.fraction,
+            lot_guid: None,
+        })
+        .collect();
+    if !confirm_entering_probable_duplicates_of_splits(
+        account.guid,
+        post_date,
+        &transaction,
+        &splits,
+    )? {
+        return Ok(false);
+    }
+    let transaction_guid = transaction.guid;
+    transactions_manager::save_transaction_with_splits(transaction, splits)?;
+
+    save_memo_as_notes(transaction_guid)
+}
+
+/// confirm_entering_probable_duplicates looks for transactions in the given account like the
+/// one being entered, and asks whether to enter it anyway when there are any.
+fn confirm_entering_probable_duplicates(
+    account_guid: Uuid,
+    post_date: NaiveDateTime,
+    value_num: i64,
+    value_denom: i64,
+    description: &str,
+) -> Result<bool, MoneyManagerError> {
+    let probable_duplicates = transactions_manager::retrieve_probable_duplicates(
+        account_guid,
+        post_date,
+        value_num,
+        value_denom,
+        description,
+    )?;
+    if probable_duplicates.is_empty() {
+        return Ok(true);
+    }
+
+    let mut message = format!(
+        "This looks like {} transaction(s) already entered:",
+        probable_duplicates.len()
+    );
+    for probable_duplicate in &probable_duplicates {
+        message.push_str(&format!("\n{}", probable_duplicate.describe()));
+    }
+    message.push_str("\n\nEnter it anyway?");
+
+    Ok(js::confirm(&message))
+}
+
+/// confirm_entering_probable_duplicates_of_splits asks like confirm_entering_probable_duplicates
+/// about a transaction being entered with the given splits, using the given account's share.
+fn confirm_entering_probable_duplicates_of_splits(
+    account_guid: Uuid,
+    post_date: NaiveDateTime,
+    transaction: &transactions_manager::Transaction,
+    splits: &[splits_manager::Split],
+) -> Result<bool, MoneyManagerError> {
+    let account_splits = splits
+        .iter()
+        .filter(|split| split.account_guid == account_guid)
+        .collect::<Vec<_>>();
+    let Some(account_split) = account_splits.first() else {
+        return Ok(true);
+    };
+
+    confirm_entering_probable_duplicates(
+        account_guid,
+        post_date,
+        account_splits.iter().map(|split| split.value_num).sum(),
+        account_split.value_denom,
+        &transaction.description,
+    )
+}
+
+/// save_memo_as_notes saves the memo in the transaction editor as the notes of the transaction
+/// with the given guid, like save_transaction does.
+fn save_memo_as_notes(transaction_guid: Uuid) -> Result<bool, MoneyManagerError> {
+    let memo = document_query_selector("#memo_textarea")
+        .dyn_into::<web_sys::HtmlTextAreaElement>()
+        .expect("Failed to convert memo_textarea!")
+        .value();
+
+    //The notes are replaced, not added to, when a transaction is saved again
+    slots_manager::delete_slots_for_obj_guid_and_name(
+        transaction_guid,
+        slots_manager::SLOT_NAME_NOTES,
+    )?;
+    if memo.trim().is_empty() {
+        return Ok(true);
+    }
+
+    slots_manager::save_slot(slots_manager::Slot {
+        id: -1,
+        obj_guid: transaction_guid,
+        name: slots_manager::SLOT_NAME_NOTES.to_string(),
+        slot_type: slots_manager::SLOT_TYPE_STRING,
+        int64_val: 0,
+        string_val: memo,
+        double_val: Some(0.0),
+        timespec_val: None,
+        guid_val: None,
+        numeric_val_num: Some(0),
+        numeric_val_denom: Some(1),
+        gdate_val: None,
+    })
+}
+
+/// is_split_mode checks whether the transaction editor is showing split rows, instead of one
+/// category, and amount.
+fn is_split_mode() -> bool {
+    document_query_selector("#transaction_split_rows_div")
+        .style()
+        .get_property_value("display")
+        .map(|display| display != "none")
+        .unwrap_or(false)
+}
+
+/// toggle_split_mode switches the transaction editor between one category, and amount, and
+/// split rows. The split rows start with the loaded account, and the category, with the
+/// amount that was entered.
+pub fn toggle_split_mode() {
+    let transaction_split_rows_div = document_query_selector("#transaction_split_rows_div");
+    let category_select = document_query_selector("#category_select")
+        .dyn_into::<web_sys::HtmlSelectElement>()
+        .expect("Failed to find category select!");
+    let change_input = document_query_selector("#change_input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .expect("Failed to dyn_into #change_input");
+    let split_mode_input = document_query_selector("#split_mode_input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .expect("Failed to dyn_into #split_mode_input");
+    let set_display = |element: &web_sys::HtmlElement, display: &str| {
+        element
+            .style()
+            .set_property("display", display)
+            .expect("Failed to change style!");
+    };
+
+    if is_split_mode() {
+        transaction_split_rows_div.set_inner_html("");
+        set_display(&transaction_split_rows_div, "none");
+        set_display(&category_select, "");
+        set_display(&change_input, "");
+        split_mode_input.set_value("Splits");
+        update_exchange_rate_row();
+        document_query_selector("#enter_transaction_input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .expect("Failed to dyn_into #enter_transaction_input")
+            .set_disabled(false);
+        return;
+    }
+
+    let account =
+        match currently_loaded_account_guid().and_then(accounts_manager::load_account_for_guid) {
+            Ok(account) => account,
+            Err(error) => {
+                display_error_banner(&error);
+                return;
+            }
+        };
+    let fraction = match account
+        .commodity_guid
+        .map(commodities_manager::retrieve_commodity_for_guid)
+    {
+        Some(Ok(commodity)) => commodity.fraction,
+        Some(Err(error)) => {
+            display_error_banner(&error);
+            return;
+        }
+        None => 100,
+    };
+    let mut accounts =
+        match accounts_manager::load_all_accounts_except_root_and_template_from_memory() {
+            Ok(accounts) => accounts,
+            Err(error) => {
+                display_error_banner(&error);
+                return;
+            }
+        };
+    accounts.sort_by(|a, b| a.name.cmp(&b.name));
+    let account_options: Vec<(String, String)> = accounts
+        .iter()
+        .map(|account| {
+            (
+                dhu::convert_guid_to_sqlite_string(&account.guid),
+                dhu::sanitize_string(account.name.clone()),
+            )
+        })
+        .collect();
+
+    //A template with its own splits can't be shown as split rows
+    let transaction_template_guid_input =
+        document_query_selector("#transaction_template_guid_input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .expect("Failed to dyn_into #transaction_template_guid_input");
+    transaction_template_guid_input.set_value("");
+    category_select.set_disabled(false);
+
+    transaction_split_rows_div
+        .dataset()
+        .set("fraction", &fraction.to_string())
+        .expect("Failed to set dataset's fraction!");
+
+    let amount = change_input
+        .value()
+        .replace([' ', '$', ','], "")
+        .parse::<f64>()
+        .ok();
+    let category_guid = dhu::convert_string_to_guid(category_select.value()).ok();
+    transaction_split_rows_div
+        .append_child(&document_create_transaction_split_row(
+            &account_options,
+            Some(account.guid),
+            amount,
+        ))
+        .expect("Failed to append split row!");
+    transaction_split_rows_div
+        .append_child(&document_create_transaction_split_row(
+            &account_options,
+            category_guid,
+            amount.map(|amount| -amount),
+        ))
+        .expect("Failed to append split row!");
+
+    //Setup the add split button, and what's left to balance
+    let split_rows_footer = document_create_element("div");
+    split_rows_footer.set_id("transaction_split_rows_footer");
+
+    let add_split_input = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .expect("Failed to create add_split_input!");
+    add_split_input.set_type("button");
+    add_split_input.set_value("Add Split");
+    let add_split_on_click = Closure::wrap(Box::new(move || {
+        let split_rows_footer = document_query_selector("#transaction_split_rows_footer");
+        document_query_selector("#transaction_split_rows_div")
+            .insert_before(
+                &document_create_transaction_split_row(&account_options, None, None),
+                Some(&split_rows_footer),
+            )
+            .expect("Failed to insert split row!");
+        update_remaining_to_balance();
+    }) as Box<dyn Fn()>);
+    add_split_input.set_onclick(Some(add_split_on_click.as_ref().unchecked_ref()));
+    add_split_on_click.forget();
+    split_rows_footer
+        .append_child(&add_split_input)
+        .expect("Failed to append add_split_input!");
+
+    let remaining_to_balance = document_create_element("div");
+    remaining_to_balance.set_id("remaining_to_balance");
+    split_rows_footer
+        .append_child(&remaining_to_balance)
+        .expect("Failed to append remaining_to_balance!");
+
+    transaction_split_rows_div
+        .append_child(&split_rows_footer)
+        .expect("Failed to append split_rows_footer!");
+
+    set_display(&transaction_split_rows_div, "");
+    set_display(&category_select, "none");
+    set_display(&change_input, "none");
+    set_display(&document_query_selector("#transaction_exchange_rate_row"), "none");
+    split_mode_input.set_value("Single Line");
+    update_remaining_to_balance();
+}
+
+/// document_create_transaction_split_row creates a split row for the transaction editor, with
+/// the account, memo, debit, credit, and quantity of one split.
+fn document_create_transaction_split_row(
+    account_options: &[(String, String)],
+    account_guid: Option<Uuid>,
+    amount: Option<f64>,
+) -> web_sys::HtmlElement {
+    let row_div = document_create_element("div");
+    row_div
+        .class_list()
+        .add_1("transaction_split_row")
+        .expect("Failed to add class to element.");
+
+    let account_select = document_create_select(
+        account_options,
+        &account_guid
+            .map(|account_guid| dhu::convert_guid_to_sqlite_string(&account_guid))
+            .unwrap_or_default(),
+    );
+    account_select
+        .class_list()
+        .add_1("transaction_split_account_select")
+        .expect("Failed to add class to element.");
+    row_div
+        .append_child(&account_select)
+        .expect("Failed to append account_select!");
+
+    for (class_name, placeholder, value) in [
+        ("transaction_split_memo_input", "Memo", None),
+        (
+            "transaction_split_debit_input",
+            "Debit",
+            amount.filter(|amount| *amount > 0.0),
+        ),
+        (
+            "transaction_split_credit_input",
+            "Credit",
+            amount.filter(|amount| *amount < 0.0).map(|amount| -amount),
+        ),
+        ("transaction_split_quantity_input", "Quantity", None),
+    ] {
+        let input = document_create_element("input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .expect("Failed to create split row input!");
+        input.set_type(if class_name == "transaction_split_memo_input" {
+            "text"
+        } else {
+            "tel"
+        });
+        input.set_placeholder(placeholder);
+        if let Some(value) = value {
+            input.set_value(&value.to_string());
+        }
+        input
+            .class_list()
+            .add_1(class_name)
+            .expect("Failed to add class to element.");
+        row_div
+            .append_child(&input)
+            .expect("Failed to append split row input!");
+    }
+
+    //Let the row remove itself
+    let remove_input = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .expect("Failed to create remove_input!");
+    remove_input.set_type("button");
+    remove_input.set_value("Remove");
+    let row_div_to_remove = row_div.clone();
+    let remove_on_click = Closure::wrap(Box::new(move || {
+        row_div_to_remove.remove();
+        update_remaining_to_balance();
+    }) as Box<dyn Fn()>);
+    remove_input.set_onclick(Some(remove_on_click.as_ref().unchecked_ref()));
+    remove_on_click.forget();
+    row_div
+        .append_child(&remove_input)
+        .expect("Failed to append remove_input!");
+
+    row_div
+}
+
+/// SplitRow is a split read from the split rows of the transaction editor.
+struct SplitRow {
+    account_guid: Uuid,    //account_guid is the account of the split.
+    memo: String,          //memo is the memo of the split.
+    value_num: i64,        //value_num is the value of the split, in the fraction of the split rows.
+    quantity: Option<f64>, //quantity is the quantity entered, without its sign, or None to use the value.
+}
+
+/// convert_debit_and_credit_to_value_num converts the debit, and credit entered for a split into
+/// its value in units of the given fraction, positive for a debit. Neither entered is None.
+pub fn convert_debit_and_credit_to_value_num(
+    debit: &str,
+    credit: &str,
+    fraction: i64,
+) -> Result<Option<i64>, MoneyManagerError> {
+    let parse = |amount: &str| -> Result<Option<f64>, MoneyManagerError> {
+        let amount = amount.replace([' ', '$', ','], "");
+        if amount.is_empty() {
+            return Ok(None);
+        }
+        amount.parse::<f64>().map(Some).map_err(|_| {
+            MoneyManagerError::Validation(format!(
+                "The given amount '{}' is not a valid number.",
+                amount
+            ))
+        })
+    };
+
+    let value = match (parse(debit)?, parse(credit)?) {
+        (None, None) => return Ok(None),
+        (Some(debit), None) => debit,
+        (None, Some(credit)) => -credit,
+        (Some(_), Some(_)) => {
+            return Err(MoneyManagerError::Validation(
+                "A split can have a debit, or a credit, but not both.".to_string(),
+            ));
+        }
+    };
+
+    Ok(Some((value * fraction as f64).round() as i64))
+}
+
+/// read_split_rows reads the split rows of the transaction editor, leaving out the rows without
+/// a debit, or credit.
+fn read_split_rows() -> Result<(Vec<SplitRow>, i64), MoneyManagerError> {
+    let transaction_split_rows_div = document_query_selector("#transaction_split_rows_div");
+    let fraction = transaction_split_rows_div
+        .dataset()
+        .get("fraction")
+        .and_then(|fraction| fraction.parse::<i64>().ok())
+        .unwrap_or(100);
+
+    let mut split_rows = Vec::new();
+    for row in document_query_selector_all(&transaction_split_rows_div, ".transaction_split_row") {
+        let input_value = |class_selector: &str| {
+            element_query_selector(&row, class_selector)
+                .dyn_into::<web_sys::HtmlInputElement>()
+                .expect("Failed to find split row input!")
+                .value()
+        };
+        let Some(value_num) = convert_debit_and_credit_to_value_num(
+            &input_value(".transaction_split_debit_input"),
+            &input_value(".transaction_split_credit_input"),
+            fraction,
+        )?
+        else {
+            continue;
+        };
+        let account_guid = element_query_selector(&row, ".transaction_split_account_select")
+            .dyn_into::<web_sys::HtmlSelectElement>()
+            .expect("Failed to find split row account!")
+            .value();
+        let account_guid = dhu::convert_string_to_guid(account_guid).map_err(|_| {
+            MoneyManagerError::Validation("Please select an account for each split.".to_string())
+        })?;
+        let quantity = input_value(".transaction_split_quantity_input").replace([' ', ','], "");
+        let quantity = if quantity.is_empty() {
+            None
+        } else {
+            Some(quantity.parse::<f64>().map(f64::abs).map_err(|_| {
+                MoneyManagerError::Validation(format!(
+                    "The given quantity '{}' is not a valid number.",
+                    quantity
+                ))
+            })?)
+        };
+
+        split_rows.push(SplitRow {
+            account_guid,
+            memo: input_value(".transaction_split_memo_input"),
+            value_num,
+            quantity,
+        });
+    }
+
+    Ok((split_rows, fraction))
+}
+
+/// update_remaining_to_balance shows what's left for the split rows to balance, and only lets
+/// the transaction be entered once they do.
+pub fn update_remaining_to_balance() {
+    let remaining_to_balance = document_query_selector("#remaining_to_balance");
+    let enter_transaction_input = document_query_selector("#enter_transaction_input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .expect("Failed to dyn_into #enter_transaction_input");
+
+    match read_split_rows() {
+        Ok((split_rows, fraction)) => {
+            let remaining: i64 = -split_rows
+                .iter()
+                .map(|split_row| split_row.value_num)
+                .sum::<i64>();
+            let remaining = remaining as f64 / fraction as f64;
+            remaining_to_balance.set_text_content(Some(&format!(
+                "Remaining to balance: {}",
+                dhu::format_money(remaining)
+            )));
+            enter_transaction_input.set_disabled(remaining != 0.0 || split_rows.len() < 2);
+        }
+        Err(error) => {
+            remaining_to_balance.set_text_content(Some(&error.to_string()));
+            enter_transaction_input.set_disabled(true);
+        }
+    }
+}
+
+/// enter_split_transaction enters the split rows of the transaction editor as a transaction in
+/// the loaded account's currency. Splits of accounts in another commodity need their quantity.
+fn enter_split_transaction(
+    account: &Account,
+    post_date: NaiveDateTime,
+) -> Result<bool, MoneyManagerError> {
+    let (split_rows, fraction) = read_split_rows()?;
+    if split_rows.len() < 2 {
+        return Err(MoneyManagerError::Validation(
+            "A transaction needs at least two splits with an amount.".to_string(),
+        ));
+    }
+    let remaining: i64 = split_rows.iter().map(|split_row| split_row.value_num).sum();
+    if remaining != 0 {
+        return Err(MoneyManagerError::Validation(format!(
+            "The splits are off by {}, so the transaction doesn't balance.",
+            dhu::format_money(remaining as f64 / fraction as f64)
+        )));
+    }
+
+    let currency_guid = account
+        .commodity_guid
+        .ok_or(MoneyManagerError::NotFound(format!(
+            "The account '{}' does not have a commodity.",
+            account.name
+        )))?;
+
+    let transaction = transactions_manager::Transaction {
+        guid: Uuid::new_v4(),
+        currency_guid,
+        num: document_query_selector("#num_input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .expect("Failed to find num input!")
+            .value(),
+        post_date: dhu::convert_date_to_string_format(post_date),
+        enter_date: dhu::convert_date_to_string_format(chrono::Local::now().naive_local()),
+        description: document_query_selector("#description_input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .expect("Failed to find description input!")
+            .value(),
+    };
+
+    let mut splits = Vec::new();
+    let mut split_commodity_guids = HashMap::new();
+    for split_row in split_rows {
+        let split_account = accounts_manager::load_account_for_guid(split_row.account_guid)?;
+        if let Some(commodity_guid) = split_account.commodity_guid {
+            split_commodity_guids.insert(split_account.guid, commodity_guid);
+        }
+        let (quantity_num, quantity_denom) = match split_account.commodity_guid {
+            Some(commodity_guid) if commodity_guid != currency_guid => {
+                let quantity = split_row
+                    .quantity
+                    .ok_or(MoneyManagerError::Validation(format!(
+                        "Please enter the quantity for '{}', since it's in another commodity.",
+                        split_account.name
+                    )))?;
+                let commodity_fraction =
+                    commodities_manager::retrieve_commodity_for_guid(commodity_guid)?.fraction;
+                (
+                    split_row.value_num.signum()
+                        * (quantity * commodity_fraction as f64).round() as i64,
+                    commodity_fraction,
+                )
+            }
+            _ => (split_row.value_num, fraction),
+        };
+        splits.push(splits_manager::Split {
+            guid: Uuid::new_v4(),
+            tx_guid: transaction.guid,
+            account_guid: split_row.account_guid,
+            memo: split_row.memo,
+            action: String::new(),
+            reconcile_state: "n".to_string(),
+            reconcile_date: None,
+            value_num: split_row.value_num,
+            value_denom: fraction,
+            quantity_num,
+            quantity_denom,
+            lot_guid: None,
+        });
+    }
+
+    if !confirm_entering_probable_duplicates_of_splits(
+        account.guid,
+        post_date,
+        &transaction,
+        &splits,
+    )? {
+        return Ok(false);
+    }
+
+    let transaction_guid = transaction.guid;
+    let post_date = transaction.post_date.clone();
+    let price_splits = splits.clone();
+    transactions_manager::save_transaction_with_splits(transaction, splits)?;
+
+    //Splits in another commodity record the rate they were transferred at, once they're saved
+    for split in &price_splits {
+        if let Some(commodity_guid) = split_commodity_guids.get(&split.account_guid)
+            && *commodity_guid != currency_guid
+        {
+            prices_manager::save_price_for_split(
+                split,
+                *commodity_guid,
+                currency_guid,
+                post_date.clone(),
+            )?;
+        }
+    }
+
+    save_memo_as_notes(transaction_guid)
+}
+
+/// is_exchange_rate_row_shown checks whether the transaction editor is asking for an exchange
+/// rate, since the category is in another commodity than the loaded account.
+fn is_exchange_rate_row_shown() -> bool {
+    document_query_selector("#transaction_exchange_rate_row")
+        .style()
+        .get_property_value("display")
+        .map(|display| display != "none")
+        .unwrap_or(false)
+}
+
+/// selected_category_commodity_guids returns the commodity of the loaded account, and of the
+/// category picked in the transaction editor.
+fn selected_category_commodity_guids() -> (Option<Uuid>, Option<Uuid>) {
+    let category_select = document_query_selector("#category_select")
+        .dyn_into::<web_sys::HtmlSelectElement>()
+        .expect("Failed to find category select!");
+    let commodity_guid = |element: &web_sys::HtmlElement| {
+        element
+            .dataset()
+            .get("commodity")
+            .and_then(|commodity_guid| dhu::convert_string_to_guid(commodity_guid).ok())
+    };
+
+    let category_commodity_guid = category_select
+        .selected_options()
+        .item(0)
+        .and_then(|option| option.dyn_into::<web_sys::HtmlElement>().ok())
+        .and_then(|option| commodity_guid(&option));
+
+    (commodity_guid(&category_select), category_commodity_guid)
+}
+
+/// update_exchange_rate_row shows the exchange rate, and received amount inputs when the picked
+/// category is in another commodity than the loaded account, and hides them otherwise.
+pub fn update_exchange_rate_row() {
+    let transaction_exchange_rate_row = document_query_selector("#transaction_exchange_rate_row");
+    let (currency_guid, category_commodity_guid) = selected_category_commodity_guids();
+
+    let commodities = match (currency_guid, category_commodity_guid) {
+        (Some(currency_guid), Some(category_commodity_guid))
+            if currency_guid != category_commodity_guid && !is_split_mode() =>
+        {
+            commodities_manager::retrieve_commodity_for_guid(currency_guid).and_then(|currency| {
+                commodities_manager::retrieve_commodity_for_guid(category_commodity_guid)
+                    .map(|commodity| (currency, commodity))
+            })
+        }
+        _ => {
+            transaction_exchange_rate_row
+                .style()
+                .set_property("display", "none")
+                .expect("Failed to modify transaction_exchange_rate_row style!");
+            return;
+        }
+    };
+
+    match commodities {
+        Ok((currency, commodity)) => {
+            document_query_selector("#exchange_rate_label").set_text_content(Some(&format!(
+                "{} to {}",
+                currency.mnemonic, commodity.mnemonic
+            )));
+            transaction_exchange_rate_row
+                .style()
+                .set_property("display", "")
+                .expect("Failed to modify transaction_exchange_rate_row style!");
+            update_received_amount();
+        }
+        Err(error) => display_error_banner(&error),
+    }
+}
+
+/// parse_editor_amount parses the amount in the given transaction editor input, ignoring the
+/// sign, spaces, dollar signs, and commas. A blank, or bad amount is None.
+fn parse_editor_amount(selector: &str) -> Option<f64> {
+    document_query_selector(selector)
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .expect("Failed to find transaction editor input!")
+        .value()
+        .replace([' ', '$', ','], "")
+        .parse::<f64>()
+        .ok()
+        .map(f64::abs)
+}
+
+/// update_received_amount works out the amount received in the category's commodity from the
+/// amount, and the exchange rate entered.
+fn update_received_amount() {
+    if let (Some(amount), Some(exchange_rate)) = (
+        parse_editor_amount("#change_input"),
+        parse_editor_amount("#exchange_rate_input"),
+    ) {
+        document_query_selector("#received_input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .expect("Failed to dyn_into #received_input")
+            .set_value(&format!("{}", amount * exchange_rate));
+    }
+}
+
+/// update_exchange_rate works out the exchange rate from the amount, and the amount received in
+/// the category's commodity.
+fn update_exchange_rate() {
+    if let (Some(amount), Some(received)) = (
+        parse_editor_amount("#change_input"),
+        parse_editor_amount("#received_input"),
+    ) && amount != 0.0
+    {
+        document_query_selector("#exchange_rate_input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .expect("Failed to dyn_into #exchange_rate_input")
+            .set_value(&format!("{}", received / amount));
+    }
+}
+
+/// enter_transfer_transaction enters the given transaction from the transaction editor with its
+/// category in another commodity, which gets the amount received in that commodity.
+fn enter_transfer_transaction(
+    txn: transactions_manager::TransactionWithSplitInformation,
+) -> Result<bool, MoneyManagerError> {
+    let (_, Some(commodity_guid)) = selected_category_commodity_guids() else {
+        return Err(MoneyManagerError::NotFound(format!(
+            "The account '{}' does not have a commodity.",
+            txn.account_name
+        )));
+    };
+    let received = parse_editor_amount("#received_input")
+        .or(parse_editor_amount("#exchange_rate_input").map(|exchange_rate| {
+            (txn.value_num as f64 / txn.value_denom as f64).abs() * exchange_rate
+        }))
+        .ok_or(MoneyManagerError::Validation(format!(
+            "Please enter the exchange rate, or the amount '{}' received.",
+            txn.account_name
+        )))?;
+
+    let commodity_fraction =
+        commodities_manager::retrieve_commodity_for_guid(commodity_guid)?.fraction;
+    //The category moves the other way from the loaded account
+    let quantity_num =
+        -txn.value_num.signum() * (received * commodity_fraction as f64).round() as i64;
+
+    transactions_manager::save_transfer_transaction(
+        txn,
+        quantity_num,
+        commodity_fraction,
+        commodity_guid,
+    )
+}
+
+/// reload_transactions_after_entering reloads the transactions of the loaded account to show
+/// the one just entered, and clears the transaction editor for the next one.
+fn reload_transactions_after_entering() {
+    let account_guid_string = currently_loaded_account_guid_string().expect("Failed to find in save_transaction!");
+    let account_guid = currently_loaded_account_guid().expect("Failed to find in save_transaction!");
+
+    if display_transactions_older_than_one_year() {
+        load_transactions_for_account_into_body_for_all_time(account_guid_string);
+    } else {
+        load_transactions_for_account_into_body_for_one_year_from_memory(account_guid);
+    }
+
+    //Clear the transaction editor now
+    clear_transaction_editor();
+
+    //Set focus on description to continue
+    document_query_selector("#description_input")
+        .focus()
+        .expect("Failed to focus description_input!");
+}
+
+/// load_last_transaction_for_account loads the last transaction for the account.
+pub fn load_last_transaction_for_account() {
+    let error_message: String = String::from("Failed to load last transaction for account");
+    let currently_loaded_account_guid = document_query_selector("#currently_loaded_account_guid")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .expect(&error_message);
+    let currently_loaded_account_guid =
+        match dhu::convert_string_to_guid(currently_loaded_account_guid.value()) {
+            Ok(currently_loaded_account_guid) => currently_loaded_account_guid,
+            Err(error) => {
+                display_error_banner(&error);
+                return;
+            }
+        };
+
+    let current_description = document_query_selector("#description_input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .expect(&error_message)
+        .value();
+
+    let transaction = transactions_manager::retrieve_transaction_with_split_information_for_account_guid_and_description(
+                                                                                                        currently_loaded_account_guid, current_description);
+    let transaction = match transaction {
+        Ok(transaction) => transaction,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+    //If the transaction is just one, you can continue
     if transaction.len() == 1 {
         //Setup the change amount
         let change_input = document_query_selector("#change_input")
@@ -1019,8 +2533,7 @@ pub fn load_last_transaction_for_account() {
             .expect(&error_message);
         change_input.set_value(&format!(
             "{:2}",
-            (((transaction[0].value_num as f64 / transaction[0].value_denom as f64) as f64)
-                * -1.00)
+            -(transaction[0].value_num as f64 / transaction[0].value_denom as f64)
         ));
         //Set the category part
         let category_select = document_query_selector("#category_select")
@@ -1043,6 +2556,7 @@ pub fn load_last_transaction_for_account() {
                 break;
             }
         }
+        update_exchange_rate_row();
     }
 }
 
@@ -1052,17 +2566,23 @@ pub fn load_transactions_for_account_into_body_for_one_year_from_memory(account_
     js::log(&format!("The next step is to load the transactions for account with guid:{}",account_guid.clone().to_string()));
 
     let transactions_with_split_information = 
-        transactions_manager::retrieve_transactions_with_split_information_for_account_guid_for_past_year(account_guid.clone())
-            .expect("Failed to retrieve transactions!");
+        match transactions_manager::retrieve_transactions_with_split_information_for_account_guid_for_past_year(account_guid) {
+            Ok(transactions_with_split_information) => transactions_with_split_information,
+            Err(error) => {
+                display_error_banner(&error);
+                return;
+            }
+        };
 
     if transactions_with_split_information.iter().count() < 1 {
-        js::alert(
+        display_message_banner(
             "No transactions were found that matched your request. Perhaps they are more than a year old?",
         );
         return;
     }
 
     transactions_screen::load_transactions_into_body(transactions_with_split_information.clone());
+    insert_register_filter_bar(account_guid, &transactions_manager::RegisterFilter::default());
 
     let footer_div = document_query_selector("#footer");
     let transaction_editor =
@@ -1081,113 +2601,180 @@ pub fn load_transactions_for_account_into_body_for_one_year_from_memory(account_
 }
 
 /// load_transactions_for_account_into_body_for_all_time loads the transactions for the given account
-/// guid from the beginning of time into the body of the form for display
+/// guid from the beginning of time into the body of the form for display. They are loaded a page
+/// at a time, starting with the newest page.
 pub fn load_transactions_for_account_into_body_for_all_time(account_guid_string: String) {
     //js::log(&format!("The next step is to load the transactions for account with guid:{}",account_guid));
 
-    if crate::DATABASE.lock().unwrap().len() == 0 {
-        unsafe { js::alert("Please select a database in order to view the account by the given guid.") };
-        return;
-    }
-
-    //Get the date we want to limit results to, which is the the max SQLite Date value
-    let date_to_use = chrono::NaiveDateTime::new(
-        NaiveDate::from_ymd_opt(9999, 12, 31).unwrap(),
-        NaiveTime::from_hms_milli_opt(23, 59, 59, 999).unwrap(),
-    );
+    let account_guid = match dhu::convert_string_to_guid(account_guid_string) {
+        Ok(account_guid) => account_guid,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
 
-    //Get the balance, and account information for the previous year
-    let mut accounts = Vec::new();
-    {
-        let stmt =
-            crate::DATABASE.lock().unwrap()[0].prepare(&shu::load_account_with_balance_for_guid());
+    load_transactions_for_account_page_into_body(account_guid, i64::MAX);
+}
 
-        let binding_object = serde_wasm_bindgen::to_value(&vec![&account_guid_string]).unwrap();
+/// load_transactions_for_account_page_into_body loads one page of the transactions for the given
+/// account guid into the body of the form for display, along with a bar to move between pages.
+/// Page 0 has the oldest transactions, and pages past the end load the newest page.
+pub fn load_transactions_for_account_page_into_body(account_guid: Uuid, page: i64) {
+    let page_count = match transactions_manager::retrieve_count_of_register_pages_for_account_guid(
+        account_guid,
+    ) {
+        Ok(page_count) => page_count,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+    let page = page.clamp(0, page_count - 1);
 
-        stmt.bind(binding_object.clone());
+    let transactions_with_splits =
+        match transactions_manager::retrieve_transactions_with_split_information_for_account_guid_for_page(account_guid, page) {
+            Ok(transactions_with_splits) => transactions_with_splits,
+            Err(error) => {
+                display_error_banner(&error);
+                return;
+            }
+        };
 
-        while stmt.step() {
-            let row = stmt.getAsObject();
-            //js::log(&("Here is a row: ".to_owned() + &js::stringify(row.clone()).to_owned()));
+    if transactions_with_splits.len() < 1 {
+        display_message_banner("No transactions were found.");
+        return;
+    }
 
-            let mut account: Account = serde_wasm_bindgen::from_value(row.clone()).unwrap();
-            let tags: serde_json::Value =
-                serde_json::from_str(js::stringify(row.clone()).as_str()).unwrap();
+    load_transactions_into_body(transactions_with_splits.clone());
 
-            let balance = format!("{}", tags["balance"])
-                .parse::<f64>()
-                .expect("Balance is not valid!");
-            account
-                .tags
-                .insert("balance".to_string(), balance.to_string());
+    if page_count > 1 {
+        document_query_selector("#transaction_header_div")
+            .after_with_node_1(&document_create_register_page_bar(account_guid, page, page_count))
+            .expect("Failed to append register page bar!");
+    }
+    insert_register_filter_bar(account_guid, &transactions_manager::RegisterFilter::default());
 
-            let mnemonic: String =
-                dhu::remove_first_and_last_double_quotes_from_string(tags["mnemonic"].to_string());
-            account
-                .tags
-                .insert("mnemonic".to_string(), mnemonic.clone());
+    let footer_div = document_query_selector("#footer");
+    let transaction_editor =
+        document_create_transaction_editor(account_guid, transactions_with_splits.clone());
+    footer_div
+        .append_child(&transaction_editor)
+        .expect("Failed to setup transaction editor!");
 
-            accounts.push(account);
-        }
+    // scroll to the bottom of the div
+    js::set_timeout(
+        Closure::once_into_js(move || {
+            js::scroll_to_the_bottom(document_query_selector("#transaction_div"))
+        }),
+        500,
+    );
+}
 
-        //Free the memory for the statement, and the bindings
-        stmt.free();
-        stmt.freemem();
-
-        //Exit if there were no results returned
-        if accounts.len() != 1 {
-            js::alert(&format!(
-                "Cannot continue! There were {} accounts retrieved for guid '{}', as of '{}'.",
-                accounts.len().to_string(),
-                &date_to_use.to_string(),
-                &account_guid_string
-            ));
-            return;
-        }
+/// document_create_register_page_bar creates the bar for moving between the pages of an
+/// account's register, with the given page showing out of page_count pages.
+fn document_create_register_page_bar(
+    account_guid: Uuid,
+    page: i64,
+    page_count: i64,
+) -> web_sys::HtmlElement {
+    let register_page_bar = document_create_element("div");
+    register_page_bar.set_id("register_page_bar");
+
+    let page_label = document_create_element("span");
+    page_label.set_id("register_page_label");
+    page_label.set_text_content(Some(&format!("Page {} of {}", page + 1, page_count)));
+
+    for element in [
+        document_create_register_page_input(account_guid, "Oldest", 0, page),
+        document_create_register_page_input(account_guid, "Older", (page - 1).max(0), page),
+        page_label,
+        document_create_register_page_input(
+            account_guid,
+            "Newer",
+            (page + 1).min(page_count - 1),
+            page,
+        ),
+        document_create_register_page_input(account_guid, "Newest", page_count - 1, page),
+    ] {
+        register_page_bar
+            .append_child(&element)
+            .expect("Failed to append to register_page_bar!");
     }
 
-    //Next now that we have a single account record, we can continue, and get the transactions loaded for the past year
-    let mut transactions_with_splits = Vec::new();
-
-    {
-        let stmt =
-            crate::DATABASE.lock().unwrap()[0].prepare(&shu::load_transactions_for_account());
-
-        let binding_object = serde_wasm_bindgen::to_value(&vec![
-            &account_guid_string,
-            &account_guid_string,
-            &account_guid_string,
-            &account_guid_string,
-        ])
-        .unwrap();
+    register_page_bar
+}
 
-        stmt.bind(binding_object.clone());
+/// document_create_register_page_input creates a button that loads the given target_page of the
+/// account's register, which is disabled when that page is already showing.
+fn document_create_register_page_input(
+    account_guid: Uuid,
+    value: &str,
+    target_page: i64,
+    page: i64,
+) -> web_sys::HtmlElement {
+    let page_input = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .expect("Failed to create page_input!");
+    page_input.set_type("button");
+    page_input.set_value(value);
+    page_input.set_disabled(target_page == page);
+    page_input
+        .class_list()
+        .add_1("register_page_input")
+        .expect("Failed to add class to element.");
 
-        while stmt.step() {
-            let row = stmt.getAsObject();
-            //js::log(&("Here is a row: ".to_owned() + &js::stringify(row.clone()).to_owned()));
+    let page_input_on_click = Closure::wrap(Box::new(move || {
+        load_transactions_for_account_page_into_body(account_guid, target_page);
+    }) as Box<dyn Fn()>);
+    page_input.set_onclick(Some(page_input_on_click.as_ref().unchecked_ref()));
+    page_input_on_click.forget();
 
-            let txn: transactions_manager::TransactionWithSplitInformation =
-                serde_wasm_bindgen::from_value(row.clone()).unwrap();
+    page_input.into()
+}
 
-            transactions_with_splits.push(txn);
+/// load_filtered_transactions_for_account_into_body loads the transactions for the given account
+/// guid that pass the given filter into the body of the form for display. An empty filter loads
+/// the register the usual way.
+pub fn load_filtered_transactions_for_account_into_body(
+    account_guid: Uuid,
+    filter: transactions_manager::RegisterFilter,
+) {
+    if filter.is_empty() {
+        if display_transactions_older_than_one_year() {
+            load_transactions_for_account_into_body_for_all_time(
+                dhu::convert_guid_to_sqlite_string(&account_guid),
+            );
+        } else {
+            load_transactions_for_account_into_body_for_one_year_from_memory(account_guid);
         }
-
-        //Free the memory for the statement, and the bindings
-        stmt.free();
-        stmt.freemem();
+        return;
     }
 
-    if transactions_with_splits.len() < 1 {
-        js::alert("No transactions were found.");
+    let filtered_transactions =
+        transactions_manager::retrieve_filtered_transactions_with_split_information_for_account_guid(
+            account_guid,
+            &filter,
+        );
+    let transactions_with_splits = match filtered_transactions {
+        Ok(transactions_with_splits) => transactions_with_splits,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+
+    if transactions_with_splits.is_empty() {
+        display_message_banner("No transactions were found that matched your filter.");
         return;
     }
 
     load_transactions_into_body(transactions_with_splits.clone());
+    insert_register_filter_bar(account_guid, &filter);
 
     let footer_div = document_query_selector("#footer");
     let transaction_editor =
-        document_create_transaction_editor(accounts[0].guid, transactions_with_splits.clone());
+        document_create_transaction_editor(account_guid, transactions_with_splits.clone());
     footer_div
         .append_child(&transaction_editor)
         .expect("Failed to setup transaction editor!");
@@ -1200,3 +2787,495 @@ pub fn load_transactions_for_account_into_body_for_all_time(account_guid_string:
         500,
     );
 }
+
+/// insert_register_filter_bar puts the filter bar for the given account, showing the given
+/// filter, right under the register's header.
+fn insert_register_filter_bar(account_guid: Uuid, filter: &transactions_manager::RegisterFilter) {
+    document_query_selector("#transaction_header_div")
+        .after_with_node_1(&document_create_register_filter_bar(account_guid, filter))
+        .expect("Failed to append register filter bar!");
+}
+
+/// document_create_register_filter_bar creates the bar for filtering the given account's register
+/// by dates, amounts, text, reconcile state, and another account, filled in with the given filter.
+fn document_create_register_filter_bar(
+    account_guid: Uuid,
+    filter: &transactions_manager::RegisterFilter,
+) -> web_sys::HtmlElement {
+    let register_filter_bar = document_create_element("div");
+    register_filter_bar.set_id("register_filter_bar");
+
+    let create_input = |id: &str, input_type: &str, placeholder: &str, value: String| {
+        let input = document_create_element("input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .expect("Failed to create filter input!");
+        input.set_id(id);
+        input.set_type(input_type);
+        input.set_placeholder(placeholder);
+        input.set_title(placeholder);
+        input.set_value(&value);
+        register_filter_bar
+            .append_child(&input)
+            .expect("Failed to append filter input!");
+    };
+    let format_date = |date: Option<NaiveDate>| {
+        date.map(|date| date.format("%Y-%m-%d").to_string())
+            .unwrap_or_default()
+    };
+    let format_amount =
+        |amount: Option<f64>| amount.map(|amount| amount.to_string()).unwrap_or_default();
+
+    create_input(
+        "register_filter_from_input",
+        "date",
+        "From",
+        format_date(filter.from_date),
+    );
+    create_input(
+        "register_filter_thru_input",
+        "date",
+        "To",
+        format_date(filter.thru_date),
+    );
+    create_input(
+        "register_filter_minimum_input",
+        "tel",
+        "Min Amount",
+        format_amount(filter.minimum_amount),
+    );
+    create_input(
+        "register_filter_maximum_input",
+        "tel",
+        "Max Amount",
+        format_amount(filter.maximum_amount),
+    );
+    create_input(
+        "register_filter_text_input",
+        "text",
+        "Description or Notes",
+        filter.text.clone(),
+    );
+
+    let reconcile_select = document_create_select(
+        &[
+            (String::new(), "(Any State)".to_string()),
+            ("n".to_string(), "Not Reconciled".to_string()),
+            ("c".to_string(), "Cleared".to_string()),
+            ("y".to_string(), "Reconciled".to_string()),
+        ],
+        filter.reconcile_state.as_deref().unwrap_or_default(),
+    );
+    reconcile_select.set_id("register_filter_reconcile_select");
+    register_filter_bar
+        .append_child(&reconcile_select)
+        .expect("Failed to append reconcile_select!");
+
+    let mut accounts = accounts_manager::load_all_accounts_except_root_and_template_from_memory()
+        .unwrap_or_default();
+    accounts.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut account_options = vec![(String::new(), "(Any Account)".to_string())];
+    account_options.extend(
+        accounts
+            .into_iter()
+            .filter(|account| account.guid != account_guid)
+            .map(|account| {
+                (
+                    dhu::convert_guid_to_sqlite_string(&account.guid),
+                    dhu::sanitize_string(account.name),
+                )
+            }),
+    );
+    let account_select = document_create_select(
+        &account_options,
+        &filter
+            .counter_account_guid
+            .map(|counter_account_guid| dhu::convert_guid_to_sqlite_string(&counter_account_guid))
+            .unwrap_or_default(),
+    );
+    account_select.set_id("register_filter_account_select");
+    register_filter_bar
+        .append_child(&account_select)
+        .expect("Failed to append account_select!");
+
+    //Setup the apply, and clear buttons
+    for (value, clear) in [("Filter", false), ("Clear", true)] {
+        let filter_input = document_create_element("input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .expect("Failed to create filter_input!");
+        filter_input.set_type("button");
+        filter_input.set_value(value);
+
+        let filter_input_on_click = Closure::wrap(Box::new(move || {
+            if clear {
+                load_filtered_transactions_for_account_into_body(
+                    account_guid,
+                    transactions_manager::RegisterFilter::default(),
+                );
+                return;
+            }
+            match read_register_filter() {
+                Ok(filter) => {
+                    load_filtered_transactions_for_account_into_body(account_guid, filter)
+                }
+                Err(error) => display_error_banner(&error),
+            }
+        }) as Box<dyn Fn()>);
+        filter_input.set_onclick(Some(filter_input_on_click.as_ref().unchecked_ref()));
+        filter_input_on_click.forget();
+
+        register_filter_bar
+            .append_child(&filter_input)
+            .expect("Failed to append filter_input!");
+    }
+
+    register_filter_bar
+}
+
+/// read_register_filter reads the filter entered in the register's filter bar.
+fn read_register_filter() -> Result<transactions_manager::RegisterFilter, MoneyManagerError> {
+    let value = |selector: &str| {
+        document_query_selector(selector)
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .expect("Failed to find filter input!")
+            .value()
+    };
+    let select_value = |selector: &str| {
+        document_query_selector(selector)
+            .dyn_into::<web_sys::HtmlSelectElement>()
+            .expect("Failed to find filter select!")
+            .value()
+    };
+    let parse_date = |selector: &str| -> Result<Option<NaiveDate>, MoneyManagerError> {
+        let date = value(selector);
+        if date.is_empty() {
+            return Ok(None);
+        }
+        NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map(Some)
+            .map_err(|_| {
+                MoneyManagerError::Validation(format!(
+                    "The given date '{}' is not a valid date.",
+                    date
+                ))
+            })
+    };
+    let parse_amount = |selector: &str| -> Result<Option<f64>, MoneyManagerError> {
+        let amount = value(selector).replace([' ', '$', ','], "");
+        if amount.is_empty() {
+            return Ok(None);
+        }
+        amount.parse::<f64>().map(Some).map_err(|_| {
+            MoneyManagerError::Validation(format!(
+                "The given amount '{}' is not a valid number.",
+                amount
+            ))
+        })
+    };
+
+    let reconcile_state = select_value("#register_filter_reconcile_select");
+    let counter_account_guid = select_value("#register_filter_account_select");
+
+    Ok(transactions_manager::RegisterFilter {
+        from_date: parse_date("#register_filter_from_input")?,
+        thru_date: parse_date("#register_filter_thru_input")?,
+        minimum_amount: parse_amount("#register_filter_minimum_input")?,
+        maximum_amount: parse_amount("#register_filter_maximum_input")?,
+        text: value("#register_filter_text_input"),
+        reconcile_state: (!reconcile_state.is_empty()).then_some(reconcile_state),
+        counter_account_guid: if counter_account_guid.is_empty() {
+            None
+        } else {
+            Some(dhu::convert_string_to_guid(counter_account_guid)?)
+        },
+    })
+}
+
+/// document_create_bulk_action_bar creates the bar for changing the checked transactions of
+/// the given account's register at once, with a preview first, and an undo after.
+fn document_create_bulk_action_bar(account_guid: Uuid) -> web_sys::HtmlElement {
+    let bulk_action_bar = document_create_element("div");
+    bulk_action_bar.set_id("bulk_action_bar");
+
+    //Setup the checkbox that checks every transaction
+    let select_all_input = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .expect("Failed to create select_all_input!");
+    select_all_input.set_type("checkbox");
+    select_all_input.set_id("bulk_select_all_input");
+    select_all_input.set_title("Check every transaction");
+    let select_all_input_on_change = Closure::wrap(Box::new(move || {
+        let checked = document_query_selector("#bulk_select_all_input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .expect("Failed to find bulk_select_all_input!")
+            .checked();
+        check_register_rows(None, checked);
+        for select_input in transaction_select_inputs() {
+            select_input.set_checked(checked);
+        }
+    }) as Box<dyn Fn()>);
+    select_all_input.set_onchange(Some(select_all_input_on_change.as_ref().unchecked_ref()));
+    select_all_input_on_change.forget();
+    bulk_action_bar
+        .append_child(&select_all_input)
+        .expect("Failed to append select_all_input!");
+
+    let action_select = document_create_select(
+        &[
+            ("category".to_string(), "Change Category".to_string()),
+            ("delete".to_string(), "Delete".to_string()),
+            ("cleared".to_string(), "Mark Cleared".to_string()),
+            ("shift".to_string(), "Shift Date".to_string()),
+        ],
+        "category",
+    );
+    action_select.set_id("bulk_action_select");
+    let action_select_on_change = Closure::wrap(Box::new(move || {
+        let action = document_query_selector("#bulk_action_select")
+            .dyn_into::<web_sys::HtmlSelectElement>()
+            .expect("Failed to find bulk_action_select!")
+            .value();
+        for (selector, shown_for) in [
+            ("#bulk_category_select", "category"),
+            ("#bulk_shift_days_input", "shift"),
+        ] {
+            document_query_selector(selector)
+                .style()
+                .set_property("display", if action == shown_for { "" } else { "none" })
+                .expect("Failed to show bulk action input!");
+        }
+    }) as Box<dyn Fn()>);
+    action_select.set_onchange(Some(action_select_on_change.as_ref().unchecked_ref()));
+    action_select_on_change.forget();
+    bulk_action_bar
+        .append_child(&action_select)
+        .expect("Failed to append action_select!");
+
+    let mut accounts = accounts_manager::load_all_accounts_except_root_and_template_from_memory()
+        .unwrap_or_default();
+    accounts.sort_by(|a, b| a.name.cmp(&b.name));
+    let category_options = accounts
+        .into_iter()
+        .filter(|account| account.guid != account_guid)
+        .map(|account| {
+            (
+                dhu::convert_guid_to_sqlite_string(&account.guid),
+                dhu::sanitize_string(account.name),
+            )
+        })
+        .collect::<Vec<_>>();
+    let category_select = document_create_select(&category_options, "");
+    category_select.set_id("bulk_category_select");
+    bulk_action_bar
+        .append_child(&category_select)
+        .expect("Failed to append category_select!");
+
+    let shift_days_input = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .expect("Failed to create shift_days_input!");
+    shift_days_input.set_id("bulk_shift_days_input");
+    shift_days_input.set_type("number");
+    shift_days_input.set_placeholder("Days");
+    shift_days_input.set_title("Days to move the post dates by, negative for earlier");
+    shift_days_input
+        .style()
+        .set_property("display", "none")
+        .expect("Failed to hide shift_days_input!");
+    bulk_action_bar
+        .append_child(&shift_days_input)
+        .expect("Failed to append shift_days_input!");
+
+    //Setup the apply, and undo buttons
+    let apply_input = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .expect("Failed to create apply_input!");
+    apply_input.set_type("button");
+    apply_input.set_value("Apply to Checked");
+    let apply_input_on_click = Closure::wrap(Box::new(move || {
+        apply_bulk_action_to_checked_transactions(account_guid);
+    }) as Box<dyn Fn()>);
+    apply_input.set_onclick(Some(apply_input_on_click.as_ref().unchecked_ref()));
+    apply_input_on_click.forget();
+    bulk_action_bar
+        .append_child(&apply_input)
+        .expect("Failed to append apply_input!");
+
+    let undo_input = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .expect("Failed to create undo_input!");
+    undo_input.set_type("button");
+    undo_input.set_value("Undo Last Bulk Change");
+    undo_input.set_disabled(!transactions_manager::has_bulk_action_to_undo());
+    let undo_input_on_click = Closure::wrap(Box::new(move || {
+        if !js::confirm("Are you sure you want to undo the last bulk change?") {
+            return;
+        }
+        match transactions_manager::undo_last_bulk_action() {
+            Ok(undo) => {
+                reload_transactions_after_entering();
+                let mut message = format!("{} transaction(s) were put back.", undo.restored);
+                for skipped in &undo.skipped {
+                    message.push_str(&format!("\n{}", skipped));
+                }
+                display_message_banner(&message);
+            }
+            Err(error) => display_error_banner(&error),
+        }
+    }) as Box<dyn Fn()>);
+    undo_input.set_onclick(Some(undo_input_on_click.as_ref().unchecked_ref()));
+    undo_input_on_click.forget();
+    bulk_action_bar
+        .append_child(&undo_input)
+        .expect("Failed to append undo_input!");
+
+    bulk_action_bar
+}
+
+/// transaction_select_inputs returns the checkboxes of the register's rendered transactions,
+/// leaving out the balance row's.
+fn transaction_select_inputs() -> Vec<web_sys::HtmlInputElement> {
+    document_query_selector_all(
+        &document_query_selector("#transaction_div"),
+        ".transaction_select:not(:disabled)",
+    )
+    .into_iter()
+    .filter_map(|element| element.dyn_into::<web_sys::HtmlInputElement>().ok())
+    .collect()
+}
+
+/// read_bulk_action reads the bulk action chosen in the bulk action bar.
+fn read_bulk_action(
+    account_guid: Uuid,
+) -> Result<transactions_manager::BulkAction, MoneyManagerError> {
+    let select_value = |selector: &str| {
+        document_query_selector(selector)
+            .dyn_into::<web_sys::HtmlSelectElement>()
+            .expect("Failed to find bulk action select!")
+            .value()
+    };
+
+    match select_value("#bulk_action_select").as_str() {
+        "category" => {
+            let category_guid =
+                dhu::convert_string_to_guid(select_value("#bulk_category_select"))?;
+            if category_guid == account_guid {
+                return Err(MoneyManagerError::Validation(
+                    "Please choose a category other than this account.".to_string(),
+                ));
+            }
+            Ok(transactions_manager::BulkAction::ChangeCategory(category_guid))
+        }
+        "delete" => Ok(transactions_manager::BulkAction::Delete),
+        "cleared" => Ok(transactions_manager::BulkAction::MarkCleared),
+        _ => {
+            let days = document_query_selector("#bulk_shift_days_input")
+                .dyn_into::<web_sys::HtmlInputElement>()
+                .expect("Failed to find bulk_shift_days_input!")
+                .value();
+            match days.trim().parse::<i64>() {
+                Ok(days) if days != 0 => Ok(transactions_manager::BulkAction::ShiftDate(days)),
+                _ => Err(MoneyManagerError::Validation(format!(
+                    "The given days '{}' is not a whole number of days to move by.",
+                    days
+                ))),
+            }
+        }
+    }
+}
+
+/// apply_bulk_action_to_checked_transactions previews the chosen bulk action on the checked
+/// transactions of the given account's register, and applies it once it's confirmed.
+fn apply_bulk_action_to_checked_transactions(account_guid: Uuid) {
+    //The rows that aren't rendered are checked too, so they're read from the register's rows
+    let mut transaction_guids: Vec<Uuid> = Vec::new();
+    for row in crate::REGISTER_ROWS.lock().unwrap().iter() {
+        if row.checked && !transaction_guids.contains(&row.transaction.guid) {
+            transaction_guids.push(row.transaction.guid);
+        }
+    }
+    if transaction_guids.is_empty() {
+        display_error_banner(&MoneyManagerError::Validation(
+            "Please check the transactions to change first.".to_string(),
+        ));
+        return;
+    }
+
+    let action = match read_bulk_action(account_guid) {
+        Ok(action) => action,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+
+    //Preview what will happen to each of them
+    let snapshots =
+        match transactions_manager::retrieve_transaction_snapshots_for_guids(&transaction_guids) {
+            Ok(snapshots) => snapshots,
+            Err(error) => {
+                display_error_banner(&error);
+                return;
+            }
+        };
+    let lock_date = match transactions_manager::retrieve_lock_date() {
+        Ok(lock_date) => lock_date,
+        Err(error) => {
+            display_error_banner(&error);
+            return;
+        }
+    };
+    let account_names = accounts_manager::load_all_accounts_except_root_and_template_from_memory()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|account| (account.guid, account.name))
+        .collect::<HashMap<Uuid, String>>();
+    let mut preview = snapshots
+        .iter()
+        .map(|snapshot| {
+            snapshot.describe_bulk_action(account_guid, &action, lock_date, &account_names)
+        })
+        .collect::<Vec<_>>();
+    if preview.len() > BULK_ACTION_PREVIEW_LINES {
+        let more = preview.len() - BULK_ACTION_PREVIEW_LINES;
+        preview.truncate(BULK_ACTION_PREVIEW_LINES);
+        preview.push(format!("...and {} more.", more));
+    }
+    if !js::confirm(&format!(
+        "These {} transaction(s) will be changed:\n\n{}\n\n\
+         Press OK to change them. This can be undone until the next bulk change.",
+        snapshots.len(),
+        preview.join("\n")
+    )) {
+        return;
+    }
+
+    match transactions_manager::apply_bulk_action(account_guid, &transaction_guids, &action) {
+        Ok(changed_count) => {
+            reload_transactions_after_entering();
+            display_message_banner(&format!("{} transaction(s) were changed.", changed_count));
+        }
+        Err(error) => display_error_banner(&error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_debit_and_credit_to_value_num_makes_credits_negative() {
+        assert_eq!(
+            convert_debit_and_credit_to_value_num("12.34", "", 100).unwrap(),
+            Some(1234)
+        );
+        assert_eq!(
+            convert_debit_and_credit_to_value_num("", "$1,000.5", 100).unwrap(),
+            Some(-100050)
+        );
+        assert_eq!(
+            convert_debit_and_credit_to_value_num(" ", "", 100).unwrap(),
+            None
+        );
+        assert!(convert_debit_and_credit_to_value_num("1", "1", 100).is_err());
+        assert!(convert_debit_and_credit_to_value_num("abc", "", 100).is_err());
+    }
+}
@@ -19,6 +19,27 @@ use utility::html_helper_utility as hhu;
 //static DATABASE : Vec<dhu::Database> = Vec::new();
 static DATABASE: Mutex<Vec<dhu::Database>> = Mutex::new(Vec::new());
 
+// READ_ONLY is true when the loaded file was opened read-only, because someone else has it
+// locked, so it must not be saved.
+static READ_ONLY: Mutex<bool> = Mutex::new(false);
+
+// GNUCASH_XML is true when the loaded file is saved as a compressed GnuCash XML book, instead
+// of a sqlite database.
+static GNUCASH_XML: Mutex<bool> = Mutex::new(false);
+
+// GNUCASH_XML_SKIPPED holds the records the loaded GnuCash XML book had that weren't read, so it
+// must not be saved over as XML. (Ex: '2 budgets')
+static GNUCASH_XML_SKIPPED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+// BULK_ACTION_UNDO holds the transactions as they were before, and after the last bulk action in
+// a register, so it can be undone.
+static BULK_ACTION_UNDO: Mutex<Vec<database_tables::transactions_manager::BulkActionChange>> =
+    Mutex::new(Vec::new());
+
+// REGISTER_ROWS holds every row of the register that's showing, since only the rows scrolled into
+// view are rendered.
+static REGISTER_ROWS: Mutex<Vec<html::transactions_screen::RegisterRow>> = Mutex::new(Vec::new());
+
 // Called when the wasm module is instantiated
 #[wasm_bindgen(start)]
 pub fn main() -> Result<(), JsValue> {
@@ -0,0 +1,28 @@
+-- Everything that refers to the accounts in the JSON array ?1, for deleting them. ?2, and ?3
+-- are the slot names of the rules, and templates, and ?4 is the JSON array of the account
+-- guids with dashes, the way the rules, and templates hold them. Scheduled transactions are
+-- counted from load_scheduled_transaction_slots.sql instead.
+SELECT
+	(SELECT COUNT(*) FROM splits
+	 WHERE account_guid IN (SELECT value FROM json_each(?1))) AS splits,
+
+	-- Reconciled splits of any account in the transactions that would be deleted
+	(SELECT COUNT(*) FROM splits
+	 WHERE reconcile_state = 'y'
+	 AND tx_guid IN (SELECT tx_guid FROM splits
+	                 WHERE account_guid IN (SELECT value FROM json_each(?1)))) AS reconciled_splits,
+
+	(SELECT COUNT(DISTINCT budget_guid) FROM budget_amounts
+	 WHERE account_guid IN (SELECT value FROM json_each(?1))) AS budgets,
+
+	(SELECT COUNT(*) FROM slots sl
+	 WHERE sl.name IN (?2, ?3)
+	 AND EXISTS (SELECT 1 FROM json_each(?4) WHERE instr(sl.string_val, value) > 0)) AS rules_and_templates,
+
+	(SELECT COUNT(*) FROM taxtable_entries
+	 WHERE account IN (SELECT value FROM json_each(?1)))
+	+ (SELECT COUNT(*) FROM entries
+	   WHERE i_acct IN (SELECT value FROM json_each(?1))
+	   OR b_acct IN (SELECT value FROM json_each(?1)))
+	+ (SELECT COUNT(*) FROM invoices
+	   WHERE post_acc IN (SELECT value FROM json_each(?1))) AS business_records
@@ -0,0 +1,20 @@
+WITH earlier_transactions AS (
+	SELECT t.guid FROM transactions AS t
+	INNER JOIN splits AS s ON s.tx_guid=t.guid
+	WHERE s.account_guid=?
+	GROUP BY t.guid
+	ORDER BY replace(replace(replace(t.post_date,'-',''),' ',''),':','') ASC, t.guid ASC
+	LIMIT ?
+)
+SELECT accounts.guid,accounts.name,accounts.account_type,accounts.commodity_guid,accounts.commodity_scu,
+accounts.non_std_scu,accounts.parent_guid,accounts.code,accounts.description,accounts.hidden,accounts.placeholder,
+COALESCE(ROUND(SUM(
+	CASE WHEN earlier_transactions.guid IS NOT NULL THEN splits.quantity_num / CAST(splits.quantity_denom AS REAL) END
+),8),0) AS balance,
+COALESCE(commodities.mnemonic,'') AS mnemonic
+FROM accounts
+LEFT JOIN commodities ON commodities.guid=accounts.commodity_guid
+LEFT JOIN splits ON splits.account_guid=accounts.guid
+LEFT JOIN earlier_transactions ON earlier_transactions.guid=splits.tx_guid
+WHERE accounts.guid=?
+GROUP BY accounts.guid
\ No newline at end of file
@@ -1,19 +1,16 @@
-SELECT guid,name,account_type,commodity_guid,commodity_scu,non_std_scu,parent_guid,code,description,hidden,placeholder,
-COALESCE((
-	SELECT ROUND(SUM(
-		splits.value_num / CAST(splits.value_denom AS REAL)
-	),8)
-	FROM splits
-	INNER JOIN transactions AS t ON splits.tx_guid=t.guid WHERE splits.account_guid = accounts.guid AND 
-	substr(t.post_date,0,5)||"-"||
-           substr(t.post_date,5,2)||"-"||
-           substr(t.post_date,7,2)||" "||
-           substr(t.post_date,9,2)||":"||
-           substr(t.post_date,11,2)||":"||
-           substr(t.post_date,13,2) <= ?
-),0) AS balance,
-(
-	SELECT commodities.mnemonic FROM commodities WHERE commodities.guid=commodity_guid
-) AS mnemonic
+SELECT accounts.guid,accounts.name,accounts.account_type,accounts.commodity_guid,accounts.commodity_scu,
+accounts.non_std_scu,accounts.parent_guid,accounts.code,accounts.description,accounts.hidden,accounts.placeholder,
+COALESCE(ROUND(SUM(
+	CASE WHEN t.guid IS NOT NULL THEN splits.quantity_num / CAST(splits.quantity_denom AS REAL) END
+),8),0) AS balance,
+COALESCE(commodities.mnemonic,'') AS mnemonic
 FROM accounts
-WHERE accounts.guid=?
\ No newline at end of file
+LEFT JOIN commodities ON commodities.guid=accounts.commodity_guid
+LEFT JOIN splits ON splits.account_guid=accounts.guid
+LEFT JOIN transactions AS t ON t.guid=splits.tx_guid AND (
+	(length(t.post_date)=14 AND t.post_date <= ?) OR
+	(length(t.post_date)<>14 AND t.post_date <= ?)
+)
+WHERE accounts.guid=?
+GROUP BY accounts.guid
+--The date is passed in the 14 char format, then the ISO format, since books can hold either
\ No newline at end of file
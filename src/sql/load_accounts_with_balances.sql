@@ -1,18 +1,18 @@
-SELECT guid,name,account_type,commodity_guid,commodity_scu,non_std_scu,parent_guid,code,description,hidden,placeholder,
-COALESCE((
-	SELECT ROUND(SUM(
-		splits.value_num / CAST(splits.value_denom AS REAL)
-	),8)
-	FROM splits WHERE splits.account_guid = accounts.guid
-),0) AS balance,
-(
-	SELECT commodities.mnemonic FROM commodities WHERE commodities.guid=commodity_guid
-) AS mnemonic
+SELECT accounts.guid,accounts.name,accounts.account_type,accounts.commodity_guid,accounts.commodity_scu,
+accounts.non_std_scu,accounts.parent_guid,accounts.code,accounts.description,accounts.hidden,accounts.placeholder,
+COALESCE(ROUND(SUM(
+	splits.quantity_num / CAST(splits.quantity_denom AS REAL)
+),8),0) AS balance,
+COALESCE(commodities.mnemonic,'') AS mnemonic
 FROM accounts
-WHERE (hidden=0) AND (placeholder=0) AND 
-	(NOT(account_type='ROOT')) AND 
-	(NOT(account_type='EXPENSE')) AND 
-	(NOT(account_type='EQUITY')) AND 
-	(NOT(account_type='INCOME')) AND 
-	(NOT(name='Expenses'))
-ORDER BY account_type, name
\ No newline at end of file
+LEFT JOIN commodities ON commodities.guid=accounts.commodity_guid
+LEFT JOIN splits ON splits.account_guid=accounts.guid
+WHERE (accounts.hidden=0) AND (accounts.placeholder=0) AND 
+	(NOT(accounts.account_type='ROOT')) AND 
+	(NOT(accounts.account_type='EXPENSE')) AND 
+	(NOT(accounts.account_type='EQUITY')) AND 
+	(NOT(accounts.account_type='INCOME')) AND 
+	(NOT(accounts.account_type='TRADING')) AND 
+	(NOT(accounts.name='Expenses'))
+GROUP BY accounts.guid
+ORDER BY accounts.account_type, accounts.name
\ No newline at end of file
@@ -0,0 +1,16 @@
+-- The balance of each INCOME, and EXPENSE account through the end of a year, for each quantity
+-- denominator its splits use, so they can be added up exactly. ?1 is the end of the year in
+-- the 14 character format, and ?2 in the ISO format.
+SELECT a.guid AS account_guid,
+	a.name AS account_name,
+	a.commodity_guid AS commodity_guid,
+	SUM(s.quantity_num) AS quantity_num,
+	s.quantity_denom AS quantity_denom
+FROM splits s
+JOIN accounts a ON a.guid = s.account_guid
+JOIN transactions t ON t.guid = s.tx_guid
+WHERE a.account_type IN ('INCOME', 'EXPENSE')
+	AND ((length(t.post_date) = 14 AND t.post_date <= ?1)
+		OR (length(t.post_date) <> 14 AND t.post_date <= ?2))
+GROUP BY a.guid, s.quantity_denom
+ORDER BY a.name, a.guid
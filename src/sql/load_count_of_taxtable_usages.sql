@@ -0,0 +1,6 @@
+SELECT (
+	(SELECT COUNT(*) FROM entries WHERE i_taxtable = ?) +
+	(SELECT COUNT(*) FROM entries WHERE b_taxtable = ?) +
+	(SELECT COUNT(*) FROM customers WHERE taxtable = ?) +
+	(SELECT COUNT(*) FROM vendors WHERE tax_table = ?)
+) AS usages
@@ -0,0 +1,46 @@
+WITH account_transactions AS (
+	SELECT t.guid,
+		replace(replace(replace(t.post_date,'-',''),' ',''),':','') AS sort_date,
+		SUM(account_split.quantity_num / CAST(account_split.quantity_denom AS REAL)) AS change,
+		MAX(account_split.reconcile_state) AS reconcile_state
+	FROM transactions AS t
+	INNER JOIN splits AS account_split ON account_split.tx_guid=t.guid
+	WHERE account_split.account_guid=?1
+	GROUP BY t.guid
+), running_balances AS (
+	SELECT guid, change, reconcile_state,
+		SUM(change) OVER (ORDER BY sort_date ASC, guid ASC ROWS UNBOUNDED PRECEDING) AS balance
+	FROM account_transactions
+)
+SELECT excluded_account.guid AS 'excluded_account_guid',
+excluded_account.name AS 'excluded_account_name',
+COALESCE(commodities.mnemonic,'') AS 'excluded_account_mnemonic',
+t.guid AS 'guid',
+t.currency_guid,
+t.num,
+t.post_date,
+t.enter_date,
+t.description,
+splits.value_num, splits.value_denom,
+COALESCE(accounts.name,'') AS 'account_name',
+splits.account_guid,
+COALESCE((SELECT string_val FROM slots WHERE obj_guid=t.guid AND name='notes' LIMIT 1),'') AS 'memo',
+ROUND(running_balances.balance,8) AS 'balance'
+
+FROM running_balances
+INNER JOIN transactions AS t ON t.guid=running_balances.guid
+INNER JOIN accounts AS excluded_account ON excluded_account.guid=?1
+INNER JOIN splits ON splits.tx_guid=t.guid AND splits.account_guid<>?1
+LEFT JOIN accounts ON accounts.guid=splits.account_guid
+LEFT JOIN commodities ON commodities.guid=t.currency_guid
+WHERE (?2 IS NULL OR (length(t.post_date)=14 AND t.post_date >= ?2) OR (length(t.post_date)<>14 AND t.post_date >= ?3))
+	AND (?4 IS NULL OR (length(t.post_date)=14 AND t.post_date <= ?4) OR (length(t.post_date)<>14 AND t.post_date <= ?5))
+	AND (?6 IS NULL OR ABS(running_balances.change) >= CAST(?6 AS REAL))
+	AND (?7 IS NULL OR ABS(running_balances.change) <= CAST(?7 AS REAL))
+	AND (?8 IS NULL OR t.description LIKE '%'||?8||'%' OR (SELECT string_val FROM slots WHERE obj_guid=t.guid AND name='notes' LIMIT 1) LIKE '%'||?8||'%')
+	AND (?9 IS NULL OR running_balances.reconcile_state=?9)
+	AND (?10 IS NULL OR t.guid IN (SELECT tx_guid FROM splits WHERE account_guid=?10))
+ORDER BY replace(replace(replace(t.post_date,'-',''),' ',''),':','') ASC, t.guid ASC;
+--The balance is worked out over every transaction before filtering, so it stays the account's true balance
+--The notes are read with a subquery, so a transaction with more than one notes slot is still one row
+--Books can mix the 14 char, and ISO post_date formats, so they're ordered with only their digits
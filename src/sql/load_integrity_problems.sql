@@ -0,0 +1,89 @@
+-- Transactions whose splits don't add up to zero
+SELECT 'IMBALANCED_TRANSACTION' AS kind,
+	t.guid AS guid,
+	MIN(s.account_guid) AS account_guid,
+	COALESCE(t.description, '') AS description,
+	SUM(CAST(s.value_num AS REAL) / s.value_denom) AS amount
+FROM transactions t
+JOIN splits s ON s.tx_guid = t.guid
+GROUP BY t.guid
+HAVING ABS(SUM(CAST(s.value_num AS REAL) / s.value_denom)) > 0.000001
+
+UNION ALL
+
+-- Splits whose account is gone
+SELECT 'SPLIT_WITH_MISSING_ACCOUNT', s.guid, NULL, COALESCE(t.description, s.memo),
+	CAST(s.value_num AS REAL) / s.value_denom
+FROM splits s
+LEFT JOIN transactions t ON t.guid = s.tx_guid
+WHERE s.account_guid NOT IN (SELECT guid FROM accounts)
+
+UNION ALL
+
+-- Splits whose transaction is gone
+SELECT 'SPLIT_WITH_MISSING_TRANSACTION', s.guid, a.guid, s.memo,
+	CAST(s.value_num AS REAL) / s.value_denom
+FROM splits s
+LEFT JOIN accounts a ON a.guid = s.account_guid
+WHERE s.tx_guid NOT IN (SELECT guid FROM transactions)
+
+UNION ALL
+
+-- Accounts without a commodity, other than the root accounts
+SELECT 'ACCOUNT_WITH_MISSING_COMMODITY', a.guid, a.guid, a.name, 0
+FROM accounts a
+WHERE a.account_type <> 'ROOT'
+	AND (a.commodity_guid IS NULL OR a.commodity_guid NOT IN (SELECT guid FROM commodities))
+
+UNION ALL
+
+-- Slots for objects that no longer exist. Frames are the objects of their own slots, and
+-- the settings, transaction template, and categorization rule slots don't belong to any object.
+SELECT 'SLOT_FOR_MISSING_OBJECT', sl.obj_guid, NULL, GROUP_CONCAT(sl.name, ', '), 0
+FROM slots sl
+WHERE sl.name NOT IN ('settings', 'transaction_template', 'categorization_rule')
+	AND sl.obj_guid NOT IN (SELECT guid_val FROM slots WHERE slot_type = 9 AND guid_val IS NOT NULL)
+	AND sl.obj_guid NOT IN (
+		SELECT guid FROM books UNION ALL
+		SELECT guid FROM accounts UNION ALL
+		SELECT guid FROM transactions UNION ALL
+		SELECT guid FROM splits UNION ALL
+		SELECT guid FROM commodities UNION ALL
+		SELECT guid FROM lots UNION ALL
+		SELECT guid FROM prices UNION ALL
+		SELECT guid FROM budgets UNION ALL
+		SELECT guid FROM schedxactions UNION ALL
+		SELECT guid FROM invoices UNION ALL
+		SELECT guid FROM entries UNION ALL
+		SELECT guid FROM customers UNION ALL
+		SELECT guid FROM vendors UNION ALL
+		SELECT guid FROM employees UNION ALL
+		SELECT guid FROM jobs UNION ALL
+		SELECT guid FROM orders UNION ALL
+		SELECT guid FROM taxtables UNION ALL
+		SELECT guid FROM billterms
+	)
+GROUP BY sl.obj_guid
+
+UNION ALL
+
+-- Root accounts other than the book's root, and template root
+SELECT 'DUPLICATE_ROOT_ACCOUNT', a.guid, NULL, a.name, 0
+FROM accounts a
+WHERE a.account_type = 'ROOT'
+	AND a.guid NOT IN (
+		SELECT root_account_guid FROM books UNION ALL
+		SELECT root_template_guid FROM books
+	)
+	AND (
+		EXISTS (SELECT 1 FROM books)
+		OR (
+			a.name <> 'Template Root'
+			AND a.guid <> (
+				SELECT r.guid FROM accounts r
+				WHERE r.account_type = 'ROOT' AND r.name <> 'Template Root'
+				ORDER BY (SELECT COUNT(*) FROM accounts c WHERE c.parent_guid = r.guid) DESC
+				LIMIT 1
+			)
+		)
+	)
@@ -0,0 +1,28 @@
+SELECT a.account_type AS account_type,
+    i.terms AS terms,
+    COALESCE(i.date_posted, MIN(t.post_date)) AS date_posted,
+    COALESCE(j.owner_guid, i.owner_guid, owner_guid.guid_val) AS owner_guid,
+    COALESCE(c.name, v.name, e.username, '') AS owner_name,
+    SUM(CAST(splits.value_num AS REAL) / splits.value_denom) AS balance
+FROM lots
+    INNER JOIN accounts AS a ON a.guid = lots.account_guid
+    INNER JOIN splits ON splits.lot_guid = lots.guid
+    INNER JOIN transactions AS t ON t.guid = splits.tx_guid
+    LEFT JOIN slots AS invoice_frame ON invoice_frame.obj_guid = lots.guid
+        AND invoice_frame.name = 'gncInvoice'
+    LEFT JOIN slots AS invoice_guid ON invoice_guid.obj_guid = invoice_frame.guid_val
+        AND invoice_guid.name = 'gncInvoice/invoice-guid'
+    LEFT JOIN invoices AS i ON i.guid = invoice_guid.guid_val
+    LEFT JOIN jobs AS j ON i.owner_type = 3 AND j.guid = i.owner_guid
+    LEFT JOIN slots AS owner_frame ON owner_frame.obj_guid = lots.guid
+        AND owner_frame.name = 'gncOwner'
+    LEFT JOIN slots AS owner_guid ON owner_guid.obj_guid = owner_frame.guid_val
+        AND owner_guid.name = 'gncOwner/owner-guid'
+    LEFT JOIN customers AS c ON c.guid = COALESCE(j.owner_guid, i.owner_guid, owner_guid.guid_val)
+    LEFT JOIN vendors AS v ON v.guid = COALESCE(j.owner_guid, i.owner_guid, owner_guid.guid_val)
+    LEFT JOIN employees AS e ON e.guid = COALESCE(j.owner_guid, i.owner_guid, owner_guid.guid_val)
+WHERE a.account_type IN ('RECEIVABLE', 'PAYABLE')
+    AND lots.is_closed = 0
+GROUP BY lots.guid
+HAVING ROUND(SUM(CAST(splits.value_num AS REAL) / splits.value_denom), 6) <> 0
+ORDER BY owner_name;
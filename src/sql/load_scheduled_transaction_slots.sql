@@ -0,0 +1,14 @@
+-- The slots of every scheduled transaction's template splits, and the slots inside of their
+-- 'sched-xaction' frames, named ?1. The frame's guid_val is the obj_guid of the
+-- 'sched-xaction/account' slot inside of it, which names the split's real account.
+SELECT sx.guid AS guid,
+	sx.template_act_guid AS template_act_guid,
+	sl.obj_guid AS obj_guid,
+	sl.name AS name,
+	sl.guid_val AS guid_val
+FROM schedxactions AS sx
+	INNER JOIN splits AS s ON s.account_guid = sx.template_act_guid
+	INNER JOIN slots AS frame ON frame.obj_guid = s.guid
+		AND frame.name = ?1
+	INNER JOIN slots AS sl ON sl.obj_guid = frame.obj_guid
+		OR sl.obj_guid = frame.guid_val;
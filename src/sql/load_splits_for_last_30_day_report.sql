@@ -1,23 +1,20 @@
-
-SELECT  
-    (SELECT t.Post_date FROM transactions AS t WHERE t.guid=splits.tx_guid) AS 'PostDate', 
-    splits.guid,splits.tx_guid,  
-    (Select t.description FROM transactions As t WHERE t.guid=splits.tx_guid) As 'Description', 
-    (SELECT c.mnemonic FROM commodities as c WHERE  
-    c.guid=(SELECT t.currency_guid FROM transactions as t WHERE t.guid=splits.tx_guid)) as 'Currency',  
-    (SELECT a.name FROM accounts as a WHERE a.guid=splits.account_guid) AS 'account_name',  
-    (SELECT a.account_type FROM accounts as a WHERE a.guid=splits.account_guid) AS 'AccountType',  
-    splits.account_guid,  
-    (SELECT CAST (value_num AS Real) / value_denom) as 'Amount',  
-    splits.memo,splits.action,splits.reconcile_state,splits.reconcile_date,splits.value_num, 
-    splits.value_denom,splits.quantity_num,splits.quantity_denom,splits.lot_guid FROM splits  
-    WHERE splits.tx_guid IN ( 
-    Select t.guid  
-        FROM transactions as t  
-    WHERE datetime(substr(t.post_date,1,4)||'-'||substr(t.post_date,5,2)||'-'||substr(t.post_date,7,2)||' '|| 
-    substr(t.post_date,9,2)||':'||substr(t.post_date,11,2)||':'||substr(t.post_date,13,2)) >=  
-    Datetime(?) AND datetime(substr(t.post_date,1,4)||'-'||substr(t.post_date,5,2)||'-'|| 
-    substr(t.post_date,7,2)||' '||substr(t.post_date,9,2)||':'||substr(t.post_date,11,2)||':'||substr(t.post_date,13,2))  
-        <= Datetime(?)  
-    ) AND AccountType=?
-ORDER BY Description;
\ No newline at end of file
+SELECT t.post_date AS 'PostDate',
+    splits.guid,splits.tx_guid,
+    t.description AS 'Description',
+    c.mnemonic AS 'Currency',
+    a.name AS 'account_name',
+    a.account_type AS 'AccountType',
+    splits.account_guid,
+    CAST(splits.value_num AS REAL) / splits.value_denom AS 'Amount',
+    splits.memo,splits.action,splits.reconcile_state,splits.reconcile_date,splits.value_num,
+    splits.value_denom,splits.quantity_num,splits.quantity_denom,splits.lot_guid
+FROM transactions AS t
+    INNER JOIN splits ON splits.tx_guid=t.guid
+    INNER JOIN accounts AS a ON a.guid=splits.account_guid
+    LEFT JOIN commodities AS c ON c.guid=t.currency_guid
+WHERE (
+        (length(t.post_date)=14 AND t.post_date BETWEEN ? AND ?) OR
+        (length(t.post_date)<>14 AND t.post_date BETWEEN ? AND ?)
+    ) AND a.account_type=?
+ORDER BY t.description;
+--The dates are passed in the 14 char format, then the ISO format, since books can hold either
\ No newline at end of file
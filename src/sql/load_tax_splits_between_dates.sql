@@ -0,0 +1,16 @@
+SELECT t.post_date AS post_date,
+    a.name AS account_name,
+    splits.value_num,
+    splits.value_denom,
+    COALESCE((SELECT string_val FROM slots
+              WHERE obj_guid = t.guid AND name = 'trans-txn-type' LIMIT 1), '') AS txn_type
+FROM transactions AS t
+    INNER JOIN splits ON splits.tx_guid = t.guid
+    INNER JOIN accounts AS a ON a.guid = splits.account_guid
+WHERE splits.account_guid IN (SELECT account FROM taxtable_entries)
+    AND (
+        (length(t.post_date) = 14 AND t.post_date BETWEEN ? AND ?)
+        OR (length(t.post_date) <> 14 AND t.post_date BETWEEN ? AND ?)
+    )
+ORDER BY t.post_date;
+
@@ -1,9 +1,14 @@
+WITH last_transaction AS (
+	SELECT t.guid FROM transactions AS t
+	INNER JOIN splits ON splits.tx_guid=t.guid
+	WHERE t.description = ? AND splits.account_guid = ?
+	ORDER BY t.post_date DESC
+	LIMIT 1
+)
 SELECT 
-(SELECT a.guid FROM accounts AS a WHERE a.guid=?) AS excluded_account_guid,
-(SELECT a.name FROM accounts AS a WHERE a.guid=?) AS excluded_account_name,
-(SELECT c.mnemonic FROM commodities AS c WHERE c.guid=
-	(SELECT a.commodity_guid FROM accounts AS a WHERE a.guid=?)
-)	AS excluded_account_mnemonic,
+excluded_account.guid AS excluded_account_guid,
+excluded_account.name AS excluded_account_name,
+COALESCE(c.mnemonic,'') AS excluded_account_mnemonic,
 t.guid,
 t.currency_guid,
 t.num,
@@ -12,18 +17,15 @@ t.enter_date,
 t.description,
 s.value_num,
 s.value_denom,
-(SELECT a.name FROM accounts AS a WHERE a.guid=s.account_guid) AS account_name,
-(SELECT a.guid FROM accounts AS a WHERE a.guid=s.account_guid)  AS account_guid,
-(COALESCE((SELECT slots.string_val FROM slots WHERE slots.obj_guid=s.tx_guid),'')) AS 'memo'
+a.name AS account_name,
+a.guid AS account_guid,
+(COALESCE((SELECT slots.string_val FROM slots WHERE slots.obj_guid=s.tx_guid AND slots.name='notes'),'')) AS 'memo'
 
-FROM splits AS s
-INNER JOIN transactions AS t ON s.tx_guid=t.guid
+FROM last_transaction
+INNER JOIN transactions AS t ON t.guid=last_transaction.guid
+INNER JOIN splits AS s ON s.tx_guid=t.guid
+INNER JOIN accounts AS excluded_account ON excluded_account.guid=?
+LEFT JOIN commodities AS c ON c.guid=excluded_account.commodity_guid
+LEFT JOIN accounts AS a ON a.guid=s.account_guid
 
-WHERE s.tx_guid = (
-SELECT t.guid 
-FROM transactions AS t
-WHERE t.description = ? AND
-		t.guid in (SELECT splits.tx_guid FROM splits WHERE splits.account_guid = ?)
-ORDER BY t.post_date DESC
-LIMIT 1
-) AND account_guid <> ? 
\ No newline at end of file
+WHERE s.account_guid <> excluded_account.guid 
\ No newline at end of file
@@ -0,0 +1,15 @@
+SELECT t.guid,
+t.post_date,
+t.description,
+splits.value_num,
+splits.value_denom
+
+FROM splits
+INNER JOIN transactions AS t ON t.guid=splits.tx_guid
+WHERE splits.account_guid=?1
+	AND splits.value_num*CAST(?3 AS INTEGER)=CAST(?2 AS INTEGER)*splits.value_denom
+	AND ((length(t.post_date)=14 AND t.post_date BETWEEN ?4 AND ?5) OR
+	(length(t.post_date)<>14 AND t.post_date BETWEEN ?6 AND ?7))
+ORDER BY t.post_date ASC;
+--The value is compared as a fraction, so it matches whatever denominator it was saved with
+--The dates are passed in the 14 char format, then the ISO format, since books can hold either
@@ -1,44 +1,40 @@
-SELECT (SELECT ?) AS 'excluded_account_guid',
+WITH account_transactions AS (
+	SELECT t.guid,
+		replace(replace(replace(t.post_date,'-',''),' ',''),':','') AS sort_date,
+		SUM(account_split.quantity_num / CAST(account_split.quantity_denom AS REAL)) AS change
+	FROM transactions AS t
+	INNER JOIN splits AS account_split ON account_split.tx_guid=t.guid
+	WHERE account_split.account_guid=?
+	GROUP BY t.guid
+), running_balances AS (
+	SELECT guid,
+		SUM(change) OVER (ORDER BY sort_date ASC, guid ASC ROWS UNBOUNDED PRECEDING) AS balance
+	FROM account_transactions
+)
+SELECT ? AS 'excluded_account_guid',
 (SELECT a.name FROM accounts AS a WHERE a.guid=?) AS 'excluded_account_name',
-(SELECT commodities.mnemonic FROM commodities WHERE commodities.guid=(
-		SELECT t.currency_guid FROM transactions AS t WHERE t.guid=splits.tx_guid
-	)
-) AS 'excluded_account_mnemonic',
-splits.tx_guid AS 'guid',
-(SELECT t.currency_guid FROM transactions AS t WHERE t.guid=splits.tx_guid) AS 'currency_guid', 
-(SELECT t.num FROM transactions AS t WHERE t.guid=splits.tx_guid) AS 'num', 
-(SELECT t.post_date FROM transactions AS t WHERE t.guid=splits.tx_guid) AS 'post_date', 
-(SELECT t.enter_date FROM transactions AS t WHERE t.guid=splits.tx_guid) AS 'enter_date', 
-(SELECT t.description FROM transactions AS t WHERE t.guid=splits.tx_guid) As 'description', 
-splits.value_num, splits.value_denom, 
-(SELECT a.name FROM accounts AS a WHERE a.guid=splits.account_guid) As 'account_name', 
+COALESCE(commodities.mnemonic,'') AS 'excluded_account_mnemonic',
+t.guid AS 'guid',
+t.currency_guid,
+t.num,
+t.post_date,
+t.enter_date,
+t.description,
+splits.value_num, splits.value_denom,
+COALESCE(accounts.name,'') AS 'account_name',
 splits.account_guid,
-(COALESCE((SELECT slots.string_val FROM slots WHERE slots.obj_guid=splits.tx_guid),'')) AS 'memo'
+COALESCE((SELECT slots.string_val FROM slots WHERE slots.obj_guid=t.guid AND slots.name='notes'),'') AS 'memo',
+ROUND(running_balances.balance,8) AS 'balance'
 
-FROM splits WHERE splits.tx_guid In (
-	SELECT transactions.guid FROM transactions where transactions.guid IN (
-		SELECT splits.tx_guid FROM splits where splits.account_guid=?
-	) 
-) AND splits.account_guid NOT IN (?) AND 
-	       substr(post_date,0,5)||"-"||
-           substr(post_date,5,2)||"-"||
-           substr(post_date,7,2)||" "||
-           substr(post_date,9,2)||":"||
-           substr(post_date,11,2)||":"||
-           substr(post_date,13,2) >= ? AND
-           substr(post_date,0,5)||"-"||
-           substr(post_date,5,2)||"-"||
-           substr(post_date,7,2)||" "||
-           substr(post_date,9,2)||":"||
-           substr(post_date,11,2)||":"||
-           substr(post_date,13,2) <= ?
-    
-ORDER BY (
-    SELECT substr(post_date,0,5)||"-"||
-           substr(post_date,5,2)||"-"||
-           substr(post_date,7,2)||" "||
-           substr(post_date,9,2)||":"||
-           substr(post_date,11,2)||":"||
-           substr(post_date,13,2)
-    ) ASC;
---20150312050000 = 2015-03-12 05:00:00 we need to get to an SQLite sortable date value with the substr above
\ No newline at end of file
+FROM transactions AS t
+INNER JOIN running_balances ON running_balances.guid=t.guid
+INNER JOIN splits AS account_split ON account_split.tx_guid=t.guid AND account_split.account_guid=?
+INNER JOIN splits ON splits.tx_guid=t.guid AND splits.account_guid<>account_split.account_guid
+LEFT JOIN accounts ON accounts.guid=splits.account_guid
+LEFT JOIN commodities ON commodities.guid=t.currency_guid
+WHERE (length(t.post_date)=14 AND t.post_date BETWEEN ? AND ?) OR
+	(length(t.post_date)<>14 AND t.post_date BETWEEN ? AND ?)
+ORDER BY replace(replace(replace(t.post_date,'-',''),' ',''),':','') ASC, t.guid ASC;
+--The dates are passed in the 14 char format, then the ISO format, since books can hold either
+--The balance is of the account's own quantities, so it's in the account's commodity, not the transaction's currency
+--Books can mix the 14 char, and ISO post_date formats, so they're ordered with only their digits
@@ -0,0 +1,45 @@
+WITH page AS (
+	SELECT t.guid FROM transactions AS t
+	INNER JOIN splits AS s ON s.tx_guid=t.guid
+	WHERE s.account_guid=?
+	GROUP BY t.guid
+	ORDER BY replace(replace(replace(t.post_date,'-',''),' ',''),':','') ASC, t.guid ASC
+	LIMIT ? OFFSET ?
+), account_transactions AS (
+	SELECT t.guid,
+		replace(replace(replace(t.post_date,'-',''),' ',''),':','') AS sort_date,
+		SUM(account_split.quantity_num / CAST(account_split.quantity_denom AS REAL)) AS change
+	FROM transactions AS t
+	INNER JOIN splits AS account_split ON account_split.tx_guid=t.guid
+	WHERE account_split.account_guid=?
+	GROUP BY t.guid
+), running_balances AS (
+	SELECT guid,
+		SUM(change) OVER (ORDER BY sort_date ASC, guid ASC ROWS UNBOUNDED PRECEDING) AS balance
+	FROM account_transactions
+)
+SELECT ? AS 'excluded_account_guid',
+(SELECT a.name FROM accounts AS a WHERE a.guid=?) AS 'excluded_account_name',
+COALESCE(commodities.mnemonic,'') AS 'excluded_account_mnemonic',
+t.guid AS 'guid',
+t.currency_guid,
+t.num,
+t.post_date,
+t.enter_date,
+t.description,
+splits.value_num, splits.value_denom,
+COALESCE(accounts.name,'') AS 'account_name',
+splits.account_guid,
+COALESCE((SELECT slots.string_val FROM slots WHERE slots.obj_guid=t.guid AND slots.name='notes'),'') AS 'memo',
+ROUND(running_balances.balance,8) AS 'balance'
+
+FROM page
+INNER JOIN transactions AS t ON t.guid=page.guid
+INNER JOIN running_balances ON running_balances.guid=t.guid
+INNER JOIN splits ON splits.tx_guid=t.guid AND splits.account_guid<>?
+LEFT JOIN accounts ON accounts.guid=splits.account_guid
+LEFT JOIN commodities ON commodities.guid=t.currency_guid
+ORDER BY replace(replace(replace(t.post_date,'-',''),' ',''),':','') ASC, t.guid ASC;
+--The page is of transactions rather than splits, so a transaction with many splits is never cut in half
+--The balance is of the account's own quantities, so it's in the account's commodity, not the transaction's currency
+--Books can mix the 14 char, and ISO post_date formats, so they're ordered with only their digits
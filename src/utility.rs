@@ -1,9 +1,21 @@
+pub mod closing_helper_utility;
+
 pub mod css_helper_utility;
 
 pub mod database_helper_utility;
 
+pub mod error_helper_utility;
+
+pub mod gnucash_xml_helper_utility;
+
 pub mod html_helper_utility;
 
+pub mod integrity_helper_utility;
+
+pub mod journal_helper_utility;
+
+pub mod json_book_helper_utility;
+
 pub mod js_helper_utility;
 
 pub mod sql_helper_utility;
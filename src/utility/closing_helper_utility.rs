@@ -0,0 +1,181 @@
+use chrono::NaiveDate;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::database_tables::accounts_manager::{self, Account, AccountType, Bool};
+use crate::database_tables::commodities_manager;
+use crate::database_tables::splits_manager::Split;
+use crate::database_tables::transactions_manager::{self, Transaction};
+use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
+use crate::utility::sql_helper_utility as shu;
+
+/// RETAINED_EARNINGS_ACCOUNT_NAME is the name of the equity account a year is closed into,
+/// when another one isn't chosen.
+pub const RETAINED_EARNINGS_ACCOUNT_NAME: &str = "Retained Earnings";
+
+/// CLOSING_DESCRIPTION is the description of the closing transactions, the same as GnuCash's.
+pub const CLOSING_DESCRIPTION: &str = "Closing Entries";
+
+/// ClosingBalance is the balance of an INCOME, or EXPENSE account through the end of a year,
+/// for one of the quantity denominators its splits use.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ClosingBalance {
+    pub account_guid: Uuid,           //account_guid is the account being closed.
+    pub account_name: String,         //account_name is the name of the account.
+    pub commodity_guid: Option<Uuid>, //commodity_guid is the account's commodity.
+    pub quantity_num: i64,            //quantity_num is the sum of the splits with this denominator.
+    pub quantity_denom: i64,          //quantity_denom is the denominator of those splits.
+}
+
+/// ClosingSummary is what closing a year did.
+#[derive(Debug, Default)]
+pub struct ClosingSummary {
+    pub transactions: usize,  //transactions is the number of closing transactions created.
+    pub skipped: Vec<String>, //skipped are the accounts that weren't closed, and why.
+}
+
+/// close_year creates a closing transaction for each INCOME, and EXPENSE account on the given
+/// year end, that moves its balance into the given equity account, or the Retained Earnings
+/// account for None. Either all of them are created, or none of them are.
+pub fn close_year(
+    year_end: NaiveDate,
+    equity_account_guid: Option<Uuid>,
+) -> Result<ClosingSummary, MoneyManagerError> {
+    dhu::in_transaction(|| close_year_into_equity(year_end, equity_account_guid))
+}
+
+/// close_year_into_equity does the work of close_year. It should be run inside of a database
+/// transaction.
+fn close_year_into_equity(
+    year_end: NaiveDate,
+    equity_account_guid: Option<Uuid>,
+) -> Result<ClosingSummary, MoneyManagerError> {
+    let equity_account_guid = match equity_account_guid {
+        Some(equity_account_guid) => equity_account_guid,
+        None => retrieve_or_create_retained_earnings_account()?,
+    };
+    let equity_account = accounts_manager::retrieve_account_for_guid(equity_account_guid)?;
+    if equity_account.account_type.top_level_type() != AccountType::EQUITY {
+        return Err(MoneyManagerError::Validation(format!(
+            "{} is not an equity account.",
+            equity_account.name
+        )));
+    }
+    let Some(currency_guid) = equity_account.commodity_guid else {
+        return Err(MoneyManagerError::Validation(format!(
+            "{} doesn't have a currency.",
+            equity_account.name
+        )));
+    };
+
+    //The balances run through the very end of the year, so everything posted on it is closed
+    let end_of_year = year_end.and_hms_opt(23, 59, 59).unwrap();
+    let post_date_bounds = dhu::convert_dates_to_post_date_bounds(end_of_year, end_of_year);
+    let binding_object =
+        serde_wasm_bindgen::to_value(&vec![&post_date_bounds[1], &post_date_bounds[3]])?;
+    let balances: Vec<ClosingBalance> = dhu::query_rows(
+        &shu::load_closing_balances_through_date(),
+        Some(binding_object),
+    )?;
+
+    //The balances come grouped by account
+    let mut accounts: Vec<(&ClosingBalance, Vec<(i64, i64)>)> = Vec::new();
+    for balance in &balances {
+        match accounts.last_mut() {
+            Some((account, fractions)) if account.account_guid == balance.account_guid => {
+                fractions.push((balance.quantity_num, balance.quantity_denom));
+            }
+            _ => accounts.push((balance, vec![(balance.quantity_num, balance.quantity_denom)])),
+        }
+    }
+
+    let mut summary = ClosingSummary::default();
+    //The closing transactions are posted like any other date without a time, since the post
+    //date is saved in UTC, and the end of the day would be the next year east of it
+    let post_date =
+        dhu::convert_date_input_to_string_format(&year_end.format("%Y-%m-%d").to_string())?;
+    let enter_date = dhu::convert_date_to_string_format(chrono::Local::now().naive_local());
+    for (account, fractions) in accounts {
+        let (num, denom) = dhu::sum_fractions(&fractions)?;
+        if num == 0 {
+            continue;
+        }
+        if account.commodity_guid != Some(currency_guid) {
+            summary.skipped.push(format!(
+                "{} isn't in the same currency as {}.",
+                account.account_name, equity_account.name
+            ));
+            continue;
+        }
+
+        let transaction = Transaction {
+            guid: Uuid::new_v4(),
+            currency_guid,
+            num: String::new(),
+            post_date: post_date.clone(),
+            enter_date: enter_date.clone(),
+            description: CLOSING_DESCRIPTION.to_string(),
+        };
+        let split = |account_guid: Uuid, amount_num: i64| Split {
+            guid: Uuid::new_v4(),
+            tx_guid: transaction.guid,
+            account_guid,
+            memo: String::new(),
+            action: String::new(),
+            reconcile_state: "n".to_string(),
+            reconcile_date: None,
+            value_num: amount_num,
+            value_denom: denom,
+            quantity_num: amount_num,
+            quantity_denom: denom,
+            lot_guid: None,
+        };
+        let splits = vec![split(account.account_guid, -num), split(equity_account_guid, num)];
+        transactions_manager::save_transaction_with_splits(transaction, splits)?;
+        summary.transactions += 1;
+    }
+
+    Ok(summary)
+}
+
+/// GuidRow holds just a guid from a query.
+#[derive(Debug, Deserialize)]
+struct GuidRow {
+    guid: Uuid,
+}
+
+/// retrieve_or_create_retained_earnings_account retrieves the equity account named Retained
+/// Earnings, creating it under the top level equity account if it doesn't exist yet.
+pub fn retrieve_or_create_retained_earnings_account() -> Result<Uuid, MoneyManagerError> {
+    let binding_object =
+        serde_wasm_bindgen::to_value(&vec![RETAINED_EARNINGS_ACCOUNT_NAME.to_string()])?;
+    let accounts: Vec<GuidRow> = dhu::query_rows(
+        "SELECT guid FROM accounts WHERE name=? AND account_type='EQUITY' LIMIT 1",
+        Some(binding_object),
+    )?;
+    if let Some(account) = accounts.first() {
+        return Ok(account.guid);
+    }
+
+    let account_guid = Uuid::new_v4();
+    accounts_manager::save_new_and_delete_current(Account {
+        guid: account_guid,
+        name: RETAINED_EARNINGS_ACCOUNT_NAME.to_string(),
+        account_type: AccountType::EQUITY,
+        commodity_guid: Some(commodities_manager::retrieve_default_currency_guid()?),
+        commodity_scu: 0,
+        non_std_scu: 0,
+        parent_guid: Some(
+            accounts_manager::retrieve_account_for_account_type(AccountType::EQUITY.to_string())?
+                .guid,
+        ),
+        code: String::new(),
+        description: String::new(),
+        hidden: Bool::False,
+        placeholder: Bool::False,
+        tags: Default::default(),
+    })?;
+
+    Ok(account_guid)
+}
@@ -1,7 +1,6 @@
 /// database_helper_utility will be all the functions that have to do with database functionality, 
 /// and helper methods to deal with the database. Nothing user facing should show here, so no alerts,
 /// or other GUI things please.
-use unicode_segmentation::UnicodeSegmentation;
 use uuid::Uuid;
 
 use wasm_bindgen::prelude::*;
@@ -11,6 +10,13 @@ use regex::Regex;
 
 use crate::database_tables::commodities_manager;
 use crate::database_tables::accounts_manager;
+use crate::database_tables::versions_manager;
+use crate::database_tables::{
+    billterms_manager, books_manager, customers_manager, employees_manager, entries_manager,
+    invoices_manager, jobs_manager, lots_manager, slots_manager, splits_manager,
+    taxtables_manager, transactions_manager, vendors_manager,
+};
+use crate::utility::error_helper_utility::MoneyManagerError;
 
 #[wasm_bindgen()]
 extern "C" {
@@ -33,6 +39,12 @@ extern "C" {
 
     #[wasm_bindgen(method, js_name = run)]
     pub fn run_with_parameters(this: &Database, s: &str, p: JsValue) -> Database;
+
+    #[wasm_bindgen(method, catch, js_name = prepare)]
+    pub fn try_prepare(this: &Database, s: &str) -> Result<Statement, JsValue>;
+
+    #[wasm_bindgen(method, catch, js_name = run)]
+    pub fn try_run_with_parameters(this: &Database, s: &str, p: JsValue) -> Result<Database, JsValue>;
     
 }
     
@@ -63,21 +75,128 @@ extern "C" {
     #[wasm_bindgen(method)]
     pub fn step(this: &Statement) -> bool;
 
+    #[wasm_bindgen(method, catch, js_name = bind)]
+    pub fn try_bind(this: &Statement, binding_object : JsValue) -> Result<bool, JsValue>;
+
+    #[wasm_bindgen(method, catch, js_name = step)]
+    pub fn try_step(this: &Statement) -> Result<bool, JsValue>;
+
 }
 
 const FORMAT_STRING : &str = "%Y%m%d%H%M%S";
 
+/// ISO_FORMAT_STRING is the format newer GnuCash versions save dates in.
+const ISO_FORMAT_STRING: &str = "%Y-%m-%d %H:%M:%S";
+
+/// CREATE_MISSING_INDEXES_SQL creates the indexes the registers, and reports rely on, with the
+/// names GnuCash gives them, so books that already have them are left alone. The slots index
+/// is for looking up each transaction's notes.
+const CREATE_MISSING_INDEXES_SQL: &str = "
+    CREATE INDEX IF NOT EXISTS splits_account_guid_index ON splits (account_guid);
+    CREATE INDEX IF NOT EXISTS splits_tx_guid_index ON splits (tx_guid);
+    CREATE INDEX IF NOT EXISTS tx_post_date_index ON transactions (post_date);
+    CREATE INDEX IF NOT EXISTS slots_guid_index ON slots (obj_guid);";
+
+/// query_rows runs the given query on the loaded database with the given binding object, if
+/// any, and reads each row into a T. A bad row is returned as an error instead of panicking.
+pub fn query_rows<T: serde::de::DeserializeOwned>(
+    sql: &str,
+    binding_object: Option<JsValue>,
+) -> Result<Vec<T>, MoneyManagerError> {
+    let databases = crate::DATABASE.lock().unwrap();
+    let database = databases.first().ok_or(MoneyManagerError::NoDatabase)?;
+
+    let stmt = database.try_prepare(sql)?;
+
+    let mut rows = Vec::new();
+    let mut result = match binding_object {
+        Some(binding_object) => stmt.try_bind(binding_object).map(|_| ()).map_err(MoneyManagerError::from),
+        None => Ok(()),
+    };
+
+    while result.is_ok() {
+        match stmt.try_step() {
+            Ok(true) => match serde_wasm_bindgen::from_value(stmt.getAsObject()) {
+                Ok(row) => rows.push(row),
+                Err(error) => result = Err(error.into()),
+            },
+            Ok(false) => break,
+            Err(error) => result = Err(error.into()),
+        }
+    }
+
+    stmt.free();
+    stmt.freemem();
+
+    result.map(|_| rows)
+}
+
+/// query_first_row runs the given query like query_rows, and returns the first row, or a
+/// NotFound error with the given message when there are no rows.
+pub fn query_first_row<T: serde::de::DeserializeOwned>(
+    sql: &str,
+    binding_object: Option<JsValue>,
+    not_found_message: &str,
+) -> Result<T, MoneyManagerError> {
+    query_rows(sql, binding_object)?
+        .into_iter()
+        .next()
+        .ok_or(MoneyManagerError::NotFound(not_found_message.to_string()))
+}
+
+/// execute runs the given statement on the loaded database with the given binding object, if
+/// any. Without a binding object, the sql may hold several statements.
+pub fn execute(sql: &str, binding_object: Option<JsValue>) -> Result<(), MoneyManagerError> {
+    let databases = crate::DATABASE.lock().unwrap();
+    let database = databases.first().ok_or(MoneyManagerError::NoDatabase)?;
+    //Nothing is changed in a file opened read-only, since it can't be saved anyway
+    if *crate::READ_ONLY.lock().unwrap() {
+        return Err(MoneyManagerError::ReadOnly);
+    }
+
+    database.try_run_with_parameters(sql, binding_object.unwrap_or(JsValue::NULL))?;
+
+    Ok(())
+}
+
+/// in_transaction runs the given work inside of a database transaction. It's committed when the
+/// work succeeds, and rolled back when it fails, so either all of it is done, or none of it is.
+/// The work's own error is returned, even when rolling back fails too.
+pub fn in_transaction<T>(
+    work: impl FnOnce() -> Result<T, MoneyManagerError>,
+) -> Result<T, MoneyManagerError> {
+    execute("BEGIN TRANSACTION;", None)?;
+    let result = work().and_then(|value| execute("COMMIT;", None).map(|_| value));
+    if result.is_err() {
+        let _ = execute("ROLLBACK;", None);
+    }
+    result
+}
+
+/// require_database returns a NoDatabase error when no file has been loaded yet.
+pub fn require_database() -> Result<(), MoneyManagerError> {
+    if crate::DATABASE.lock().unwrap().is_empty() {
+        return Err(MoneyManagerError::NoDatabase);
+    }
+    Ok(())
+}
+
+/// create_missing_indexes creates the indexes on splits.account_guid, splits.tx_guid, and
+/// transactions.post_date in the loaded database, when it doesn't have them yet.
+pub fn create_missing_indexes() -> Result<(), MoneyManagerError> {
+    execute(CREATE_MISSING_INDEXES_SQL, None)
+}
+
 /// valid_database checks the database for the first 16 chars to determine if it's
 /// a valid database file. If it has this value, it probably is.
-#[allow(dead_code)]
-pub fn valid_database(incoming_database : js_sys::Uint8Array) -> Result<(),String> {    
+pub fn valid_database(incoming_database : js_sys::Uint8Array) -> Result<(),MoneyManagerError> {    
     
     if (&incoming_database).to_vec().len() < 16 {
         let error_message : String = String::from(
                 format!("The selected file is not a valid SQLite Database! Its length is {:?}.",
                 (&incoming_database).to_vec().len())
         );
-        return Err(error_message);
+        return Err(MoneyManagerError::Validation(error_message));
     }
 
     let first_16  = &js_sys::Uint8Array::new(&incoming_database).to_vec()[0..15];
@@ -88,14 +207,130 @@ pub fn valid_database(incoming_database : js_sys::Uint8Array) -> Result<(),Strin
     
     if !descriptor.starts_with("SQLITE FORMAT") {
         let error_message : String = String::from("The selected file does not have a valid SQLite Database header.");
-        return Err(error_message);
+        return Err(MoneyManagerError::Validation(error_message));
     }
     
     Ok(())
 }
 
+/// ColumnName holds the name of a column from a table's info.
+#[derive(Debug, serde::Deserialize)]
+struct ColumnName {
+    name: String,
+}
+
+/// RowCount holds the number of rows a query counted.
+#[derive(Debug, serde::Deserialize)]
+struct RowCount {
+    count: i64,
+}
+
+/// required_tables returns each table this app reads, along with the columns it reads from it.
+fn required_tables() -> Vec<(&'static str, String)> {
+    vec![
+        ("accounts", accounts_manager::FIELDS.to_string()),
+        ("billterms", billterms_manager::FIELDS.to_string()),
+        ("books", books_manager::_fields()),
+        ("commodities", commodities_manager::FIELDS.to_string()),
+        ("customers", customers_manager::FIELDS.to_string()),
+        ("employees", employees_manager::FIELDS.to_string()),
+        ("entries", entries_manager::FIELDS.to_string()),
+        ("invoices", invoices_manager::FIELDS.to_string()),
+        ("jobs", jobs_manager::FIELDS.to_string()),
+        ("lots", lots_manager::_fields()),
+        ("slots", slots_manager::_fields()),
+        ("splits", splits_manager::FIELDS.to_string()),
+        ("taxtable_entries", taxtables_manager::ENTRY_FIELDS.to_string()),
+        ("taxtables", taxtables_manager::FIELDS.to_string()),
+        ("transactions", transactions_manager::FIELDS.to_string()),
+        ("vendors", vendors_manager::FIELDS.to_string()),
+    ]
+}
+
+/// missing_columns returns the columns in the given comma separated fields that aren't in the
+/// given columns. Fields may be aliased, like 'Date AS date'. SQLite column names aren't case
+/// sensitive, so neither is this.
+pub fn missing_columns(fields: &str, columns: &[String]) -> Vec<String> {
+    fields
+        .split(',')
+        .filter_map(|field| field.split_whitespace().next())
+        .filter(|field| !columns.iter().any(|column| column.eq_ignore_ascii_case(field)))
+        .map(|field| field.to_string())
+        .collect()
+}
+
+/// valid_gnucash_database checks the loaded database has every table and column this app
+/// reads, and a books row with a root account, so it doesn't fail part way through reading
+/// a file that isn't a GnuCash book.
+pub fn valid_gnucash_database() -> Result<(), MoneyManagerError> {
+    let mut problems = Vec::new();
+
+    for (table_name, fields) in required_tables() {
+        let binding_object = serde_wasm_bindgen::to_value(&vec![table_name])?;
+        let columns: Vec<String> = query_rows::<ColumnName>(
+            "SELECT name FROM pragma_table_info(?)",
+            Some(binding_object),
+        )?
+        .into_iter()
+        .map(|column| column.name)
+        .collect();
+
+        if columns.is_empty() {
+            problems.push(format!("It has no {} table.", table_name));
+            continue;
+        }
+        let missing_columns = missing_columns(&fields, &columns);
+        if !missing_columns.is_empty() {
+            problems.push(format!(
+                "The {} table is missing the {} column(s).",
+                table_name,
+                missing_columns.join(", ")
+            ));
+        }
+    }
+
+    //Only look for the book once the tables are known to be there
+    if problems.is_empty() {
+        let book_count: i64 = query_rows::<RowCount>(
+            "SELECT COUNT(*) AS count FROM books b
+             JOIN accounts a ON a.guid = b.root_account_guid AND a.account_type = 'ROOT'",
+            None,
+        )?
+        .iter()
+        .map(|row_count| row_count.count)
+        .sum();
+
+        if book_count == 0 {
+            problems.push("It has no book with a root account.".to_string());
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(MoneyManagerError::Validation(format!(
+            "The selected file is not a GnuCash book.\n{}",
+            problems.join("\n")
+        )))
+    }
+}
+
+/// convert_to_binding_object converts the given parameters into a binding object for a statement.
+/// None values are bound as NULL, since sql.js refuses to bind an undefined value.
+pub fn convert_to_binding_object<T: serde::Serialize>(parameters: &T) -> JsValue {
+    parameters
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .expect("Failed to convert parameters to a binding object!")
+}
+
+/// convert_option_guid_to_sqlite_string converts an optional guid to an optional sqlite string,
+/// so a None can be bound as NULL.
+pub fn convert_option_guid_to_sqlite_string(incoming_guid: &Option<Uuid>) -> Option<String> {
+    incoming_guid.as_ref().map(convert_guid_to_sqlite_string)
+}
+
 ///convert_string_to_guid converts the result to a string, if possible.
-pub fn convert_string_to_guid(incoming_string : String) -> Result<Uuid,String> {
+pub fn convert_string_to_guid(incoming_string : String) -> Result<Uuid,MoneyManagerError> {
     let mut incoming_string = incoming_string;
     //If it's 32 characters, it needs dashes
     if incoming_string.chars().count() == 32 {
@@ -109,13 +344,15 @@ pub fn convert_string_to_guid(incoming_string : String) -> Result<Uuid,String> {
 
     return match Uuid::parse_str(&incoming_string) {
         Ok(guid) => Ok(guid),
-        Err(e) => Err(format!("{0}",e)),
+        Err(e) => Err(MoneyManagerError::Validation(format!("{0}",e))),
     };
 
 }
 
 
-pub fn create_default_database_tables(empty_database: Database) -> Database {
+/// create_database_tables creates the tables GnuCash uses in the given empty database, and
+/// fills in their versions.
+pub fn create_database_tables(empty_database: &Database) {
     let create_table_sql = 
     "CREATE TABLE accounts (guid text(32) PRIMARY KEY Not NULL, name text(2048) Not NULL, account_type text(2048) Not NULL, commodity_guid text(32), commodity_scu Integer Not NULL, non_std_scu Integer Not NULL, parent_guid text(32), code text(2048), description text(2048), hidden Integer, placeholder Integer);
     CREATE TABLE billterms (guid text(32) PRIMARY KEY Not NULL, name text(2048) Not NULL, description text(2048) Not NULL, refcount Integer Not NULL, invisible Integer Not NULL, parent text(32), type text(2048) Not NULL, duedays Integer, discountdays Integer, discount_num bigint, discount_denom bigint, cutoff Integer);
@@ -143,6 +380,16 @@ pub fn create_default_database_tables(empty_database: Database) -> Database {
     CREATE TABLE versions (table_name text(50) PRIMARY KEY Not NULL, table_version Integer Not NULL); ";
 
     empty_database.run(create_table_sql);
+    empty_database.run(CREATE_MISSING_INDEXES_SQL);
+
+    // Fill in the versions, the way GnuCash does
+    empty_database.run(&versions_manager::create_default_versions_sql());
+}
+
+/// create_default_database_tables creates the tables in the given empty database, along with a
+/// book of the default accounts.
+pub fn create_default_database_tables(empty_database: Database) -> Database {
+    create_database_tables(&empty_database);
 
     // Create a usd commodity
     let usd_commodity_guid = convert_guid_to_sqlite_string(&uuid::Uuid::new_v4());
@@ -341,6 +588,16 @@ pub fn create_default_database_tables(empty_database: Database) -> Database {
                                 income_account_guid=income_account_guid,
     );
     empty_database.run(create_bonus_account_sql);
+
+    // Create the books record, so GnuCash can find the root accounts
+    let create_books_sql = &format!(
+        "INSERT INTO books(guid,root_account_guid,root_template_guid) VALUES('{book_guid}',
+                                '{root_account_guid}','{template_root_account_guid}');",
+                                book_guid=convert_guid_to_sqlite_string(&uuid::Uuid::new_v4()),
+                                root_account_guid=root_account_guid,
+                                template_root_account_guid=template_root_account_guid,
+    );
+    empty_database.run(create_books_sql);
     
     return empty_database;
     // let binding_object =serde_wasm_bindgen::to_value(
@@ -399,25 +656,34 @@ pub fn format_money(incoming_float : f64) -> String {
     
 }
 
-/// remove_rist_and_last_double_quotes_from_string removes the first and last double
-/// quotes (") from th
-pub fn remove_first_and_last_double_quotes_from_string(incoming_string : String) -> String {
-    
-    //If the string does not have double quotes, return it
-    let regex = Regex::new(r#"".*""#).unwrap();
-
-    if !regex.is_match(&incoming_string) {
-        return incoming_string;
+/// sum_fractions adds the given fractions up exactly, over their least common denominator.
+pub fn sum_fractions(fractions: &[(i64, i64)]) -> Result<(i64, i64), MoneyManagerError> {
+    fn gcd(a: i128, b: i128) -> i128 {
+        if b == 0 { a.abs() } else { gcd(b, a % b) }
     }
 
-    //Skip the first double quote "
-    return String::from(incoming_string.graphemes(true)
-                                        .skip("\"".len())
-                                        .take(incoming_string.len() - "\"\"".len())
-                                        .collect::<String>()
-    );
+    let (mut num, mut denom): (i128, i128) = (0, 1);
+    for &(fraction_num, fraction_denom) in fractions {
+        if fraction_denom == 0 {
+            return Err(MoneyManagerError::Validation(
+                "A split has a denominator of 0.".to_string(),
+            ));
+        }
+        let (fraction_num, fraction_denom) = (fraction_num as i128, fraction_denom as i128);
+        let common_denom = denom / gcd(denom, fraction_denom) * fraction_denom;
+        num = num * (common_denom / denom) + fraction_num * (common_denom / fraction_denom);
+        denom = common_denom;
+    }
+    if denom < 0 {
+        (num, denom) = (-num, -denom);
+    }
 
-    
+    match (i64::try_from(num), i64::try_from(denom)) {
+        (Ok(num), Ok(denom)) => Ok((num, denom)),
+        _ => Err(MoneyManagerError::Validation(
+            "The balance is too large to close.".to_string(),
+        )),
+    }
 }
 
 ///sanitize_string sanitizes the string by removing everything except A-Z and 0-9 spaces,
@@ -449,6 +715,27 @@ pub fn convert_date_to_string_format(incoming_date : chrono::NaiveDateTime ) ->
     
 }
 
+/// convert_date_to_iso_string_format converts the given date to the ISO format newer GnuCash
+/// versions save dates in. (Ex: '2012-08-01 04:00:00')
+pub fn convert_date_to_iso_string_format(incoming_date: chrono::NaiveDateTime) -> String {
+    incoming_date.format(ISO_FORMAT_STRING).to_string()
+}
+
+/// convert_dates_to_post_date_bounds converts the given dates to the bounds a post_date is
+/// compared against directly, so its index can be used. They are the from, and thru dates in the
+/// 14 char format, then in the ISO format, since books can hold either.
+pub fn convert_dates_to_post_date_bounds(
+    from_date: chrono::NaiveDateTime,
+    thru_date: chrono::NaiveDateTime,
+) -> Vec<String> {
+    vec![
+        convert_date_to_string_format(from_date),
+        convert_date_to_string_format(thru_date),
+        convert_date_to_iso_string_format(from_date),
+        convert_date_to_iso_string_format(thru_date),
+    ]
+}
+
 /// null_date is the null value for a date which is currently 
 /// a NaiveDateTime 1/1/00 00:00:00
 pub fn null_date() -> chrono::NaiveDateTime {
@@ -474,17 +761,38 @@ pub fn convert_string_to_date_format(incoming_date : &mut chrono::NaiveDateTime,
 
 }
 
-/// convert_string_to_date_format attempts to convert a string to a NaiveDateTime
-/// Warning! Will panic if the string is invalid.
-pub fn convert_string_to_date(incoming_string: &str) -> Result<chrono::NaiveDateTime,String> {
-     match NaiveDateTime::parse_from_str(incoming_string, FORMAT_STRING) {
-        Ok(e) => {
-            return Ok(e);
-        },
-        Err(_) => {
-            return Err(format!("Failed to convert the given string '{}' to a date.", incoming_string));
-        }
-    };
+/// convert_string_to_date_format attempts to convert a string to a NaiveDateTime, in either the
+/// 14 char format, or the ISO format newer GnuCash versions use.
+pub fn convert_string_to_date(incoming_string: &str) -> Result<chrono::NaiveDateTime,MoneyManagerError> {
+    NaiveDateTime::parse_from_str(incoming_string, FORMAT_STRING)
+        .or_else(|_| NaiveDateTime::parse_from_str(incoming_string, ISO_FORMAT_STRING))
+        .map_err(|_| {
+            MoneyManagerError::Validation(format!(
+                "Failed to convert the given string '{}' to a date.",
+                incoming_string
+            ))
+        })
+}
+
+/// convert_date_input_to_string_format converts the value of a date input (Ex: '2012-08-01')
+/// to the sqlite database format, at 10:59:00 the way GnuCash saves dates without a time.
+pub fn convert_date_input_to_string_format(incoming_string: &str) -> Result<String, MoneyManagerError> {
+    match NaiveDate::parse_from_str(incoming_string, "%Y-%m-%d") {
+        Ok(date) => Ok(convert_date_to_string_format(date.and_hms_opt(10, 59, 0).unwrap())),
+        Err(_) => Err(MoneyManagerError::Validation(format!(
+            "The given date '{}' is not a valid date.",
+            incoming_string
+        ))),
+    }
+}
+
+/// convert_string_format_to_date_input converts a date in the sqlite database format to the
+/// value for a date input. (Ex: '2012-08-01')
+pub fn convert_string_format_to_date_input(incoming_string: &str) -> String {
+    match convert_string_to_date(incoming_string) {
+        Ok(date) => date.format("%Y-%m-%d").to_string(),
+        Err(_) => String::new(),
+    }
 }
 
 // /// MakeBackupCopiesOfFile makes backups of the file and saves copies
@@ -654,6 +962,43 @@ mod tests {
     //     assert_eq!(10, value);
     // }
 
+    #[test]
+    fn missing_columns_finds_missing_and_aliased_columns() {
+        let columns = vec!["guid".to_string(), "Date".to_string()];
+
+        assert_eq!(missing_columns("guid,Date AS date", &columns), Vec::<String>::new());
+        assert_eq!(missing_columns("guid, name", &columns), vec!["name".to_string()]);
+    }
+
+    #[test]
+    fn convert_string_to_date_reads_both_post_date_formats() {
+        let date = NaiveDate::from_ymd_opt(2015, 3, 12)
+            .unwrap()
+            .and_hms_opt(5, 0, 0)
+            .unwrap();
+
+        assert_eq!(convert_string_to_date("20150312050000").unwrap(), date);
+        assert_eq!(convert_string_to_date("2015-03-12 05:00:00").unwrap(), date);
+        assert_eq!(
+            convert_dates_to_post_date_bounds(date, date),
+            vec![
+                "20150312050000".to_string(),
+                "20150312050000".to_string(),
+                "2015-03-12 05:00:00".to_string(),
+                "2015-03-12 05:00:00".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn sum_fractions_adds_up_exactly() {
+        assert_eq!(sum_fractions(&[]).unwrap(), (0, 1));
+        assert_eq!(sum_fractions(&[(-500, 100), (-7, 1)]).unwrap(), (-1200, 100));
+        assert_eq!(sum_fractions(&[(1, 3), (1, 6)]).unwrap(), (3, 6));
+        assert_eq!(sum_fractions(&[(250, 100), (-5, 2)]).unwrap().0, 0);
+        assert!(sum_fractions(&[(1, 0)]).is_err());
+    }
+
     #[test]
     fn this_test_will_fail() {
         let value = 8;
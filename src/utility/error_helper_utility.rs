@@ -0,0 +1,80 @@
+/// error_helper_utility holds the error type every manager and screen returns, so that
+/// problems are reported to the user in one place instead of with alerts and panics.
+use std::fmt;
+
+use wasm_bindgen::{JsCast, JsValue};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MoneyManagerError {
+    NoDatabase,              //NoDatabase is when no file has been loaded yet.
+    Sql(String),             //Sql is when sql.js failed to run a statement, with its message.
+    Deserialization(String), //Deserialization is when a row couldn't be read into its struct.
+    Validation(String),      //Validation is when what the user entered can't be saved.
+    NotFound(String),        //NotFound is when the record asked for doesn't exist.
+    ReadOnly,                //ReadOnly is when the loaded file was opened read-only.
+}
+
+impl fmt::Display for MoneyManagerError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MoneyManagerError::NoDatabase => write!(fmt, "Please select a database first."),
+            MoneyManagerError::Sql(message) => {
+                write!(fmt, "There was a problem with the database: {}", message)
+            }
+            MoneyManagerError::Deserialization(message) => {
+                write!(fmt, "A record in the database could not be read: {}", message)
+            }
+            MoneyManagerError::Validation(message) => write!(fmt, "{}", message),
+            MoneyManagerError::NotFound(message) => write!(fmt, "{}", message),
+            MoneyManagerError::ReadOnly => write!(
+                fmt,
+                "This file was opened read-only, because someone else has it open, so it can't be \
+                 changed, or saved."
+            ),
+        }
+    }
+}
+
+impl From<serde_wasm_bindgen::Error> for MoneyManagerError {
+    fn from(error: serde_wasm_bindgen::Error) -> Self {
+        MoneyManagerError::Deserialization(error.to_string())
+    }
+}
+
+impl From<serde_json::Error> for MoneyManagerError {
+    fn from(error: serde_json::Error) -> Self {
+        MoneyManagerError::Deserialization(error.to_string())
+    }
+}
+
+impl From<JsValue> for MoneyManagerError {
+    /// from converts an exception thrown by sql.js into a Sql error.
+    fn from(error: JsValue) -> Self {
+        let message = match error.dyn_ref::<js_sys::Error>() {
+            Some(error) => String::from(error.message()),
+            None => error.as_string().unwrap_or(format!("{:?}", error)),
+        };
+        MoneyManagerError::Sql(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_describes_each_error() {
+        assert_eq!(
+            MoneyManagerError::NoDatabase.to_string(),
+            "Please select a database first."
+        );
+        assert_eq!(
+            MoneyManagerError::Validation("Please give the job a name.".to_string()).to_string(),
+            "Please give the job a name."
+        );
+        assert!(MoneyManagerError::Sql("no such table: jobs".to_string())
+            .to_string()
+            .ends_with("no such table: jobs"));
+        assert!(MoneyManagerError::ReadOnly.to_string().contains("read-only"));
+    }
+}
@@ -0,0 +1,1498 @@
+/// gnucash_xml_helper_utility reads, and writes books in GnuCash's compressed XML format. A book
+/// is read into a fresh database with the same tables GnuCash's sqlite format uses, so the rest
+/// of the app works the same on either format, and the database can be written back out as XML.
+/// Accounts, commodities, prices, lots, transactions, splits, and slots are carried over.
+/// Scheduled transactions, budgets, and business records are not, so a book that has any of them
+/// is never saved as XML.
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::database_tables::slots_manager;
+use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
+
+/// GZIP_MAGIC_NUMBER is the first two bytes of every gzip file.
+const GZIP_MAGIC_NUMBER: [u8; 2] = [0x1f, 0x8b];
+
+/// SQL_DATE_FORMAT is the format dates are saved in the database with.
+const SQL_DATE_FORMAT: &str = "%Y%m%d%H%M%S";
+
+/// XML_DATE_FORMAT is the format of a <ts:date> in a GnuCash XML book.
+const XML_DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S %z";
+
+/// XML_NAMESPACES are the namespaces GnuCash declares on the <gnc-v2> element.
+const XML_NAMESPACES: [&str; 29] = [
+    "gnc", "act", "book", "cd", "cmdty", "price", "slot", "split", "sx", "trn", "ts", "fs", "bgt",
+    "recurrence", "lot", "addr", "billterm", "bt-days", "bt-prox", "cust", "employee", "entry",
+    "invoice", "job", "order", "owner", "taxtable", "tte", "vendor",
+];
+
+/// SKIPPED_ELEMENTS are the elements of a GnuCash XML book that aren't read, along with what
+/// they hold.
+const SKIPPED_ELEMENTS: [(&str, &str); 12] = [
+    ("template-transactions", "scheduled transaction templates"),
+    ("schedxaction", "scheduled transactions"),
+    ("budget", "budgets"),
+    ("GncBillTerm", "bill terms"),
+    ("GncCustomer", "customers"),
+    ("GncEmployee", "employees"),
+    ("GncEntry", "invoice entries"),
+    ("GncInvoice", "invoices"),
+    ("GncJob", "jobs"),
+    ("GncOrder", "orders"),
+    ("GncTaxTable", "tax tables"),
+    ("GncVendor", "vendors"),
+];
+
+/// UNWRITTEN_TABLES are the tables of the database that aren't written to a GnuCash XML book,
+/// along with what they hold.
+const UNWRITTEN_TABLES: [(&str, &str); 11] = [
+    ("schedxactions", "scheduled transactions"),
+    ("budgets", "budgets"),
+    ("billterms", "bill terms"),
+    ("customers", "customers"),
+    ("employees", "employees"),
+    ("entries", "invoice entries"),
+    ("invoices", "invoices"),
+    ("jobs", "jobs"),
+    ("orders", "orders"),
+    ("taxtables", "tax tables"),
+    ("vendors", "vendors"),
+];
+
+/// XmlBook holds every record read from, or written to, a GnuCash XML book. Each record has
+/// the same columns as the table it is saved to, with guids as 32 character strings, and
+/// dates in the database's date format.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct XmlBook {
+    pub guid: String,                   //guid is the GUID for this book.
+    pub root_account_guid: String,      //root_account_guid is the GUID of the root account.
+    pub root_template_guid: String,     //root_template_guid is the GUID of the template root.
+    pub commodities: Vec<XmlCommodity>, //commodities are the currencies, and securities.
+    pub prices: Vec<XmlPrice>,          //prices are the price database.
+    pub accounts: Vec<XmlAccount>,      //accounts are the accounts, including the roots.
+    pub lots: Vec<XmlLot>,              //lots are the lots of every account.
+    pub transactions: Vec<XmlTransaction>, //transactions are the transactions.
+    pub splits: Vec<XmlSplit>,          //splits are the splits of every transaction.
+    pub slots: Vec<XmlSlot>,            //slots are the slots of all of the above.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<String>,           //skipped are the records that weren't read. (Ex: '2 budgets')
+}
+
+/// BookRow is a row of the books table.
+#[derive(Debug, Deserialize)]
+struct BookRow {
+    guid: String,
+    root_account_guid: String,
+    root_template_guid: String,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct XmlCommodity {
+    pub guid: String,                 //guid is the GUID for this commodity.
+    pub namespace: String,            //namespace is the namespace. (Ex: 'CURRENCY')
+    pub mnemonic: String,             //mnemonic is the symbol. (Ex: 'USD')
+    pub fullname: String,             //fullname is the full name. (Ex: 'US Dollar')
+    pub cusip: String,                //cusip is the code identifying the commodity. (Ex: '840')
+    pub fraction: i64,                //fraction is the smallest unit. (Ex: 100 for USD)
+    pub quote_flag: i64,              //quote_flag is 1 when quotes are retrieved online.
+    pub quote_source: Option<String>, //quote_source is where quotes come from. (Ex: 'currency')
+    pub quote_tz: Option<String>,     //quote_tz is the timezone of the quotes.
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct XmlPrice {
+    pub guid: String,               //guid is the GUID for this price.
+    pub commodity_guid: String,     //commodity_guid is the commodity being priced.
+    pub currency_guid: String,      //currency_guid is the currency of the price.
+    pub date: String,               //date is when the price was quoted.
+    pub source: Option<String>,     //source is where the price came from. (Ex: 'user:price')
+    pub price_type: Option<String>, //price_type is the type of price. (Ex: 'last')
+    pub value_num: i64,             //value_num is the numerator of the price.
+    pub value_denom: i64,           //value_denom is the denominator of the price.
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct XmlAccount {
+    pub guid: String,                   //guid is the GUID for this account.
+    pub name: String,                   //name is the name of the account.
+    pub account_type: String,           //account_type is the account type. (Ex: 'BANK')
+    pub commodity_guid: Option<String>, //commodity_guid is the commodity the account uses.
+    pub commodity_scu: i64,             //commodity_scu is the smallest unit. (Ex: 100)
+    pub non_std_scu: i64,               //non_std_scu is 1 when the scu isn't the commodity's.
+    pub parent_guid: Option<String>,    //parent_guid is the parent account's GUID.
+    pub code: String,                   //code is the account code.
+    pub description: String,            //description is the account description.
+    pub hidden: i64,                    //hidden is 1 when the account is hidden.
+    pub placeholder: i64,               //placeholder is 1 when the account is a placeholder.
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct XmlLot {
+    pub guid: String,         //guid is the GUID for this lot.
+    pub account_guid: String, //account_guid is the account the lot belongs to.
+    pub is_closed: i64,       //is_closed is 1 when the lot's splits balance out.
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct XmlTransaction {
+    pub guid: String,               //guid is the GUID for this transaction.
+    pub currency_guid: String,      //currency_guid is the currency of the transaction.
+    pub num: String,                //num is the check, or invoice number.
+    pub post_date: Option<String>,  //post_date is the date the transaction is posted.
+    pub enter_date: Option<String>, //enter_date is the date the transaction was entered.
+    pub description: String,        //description is the description.
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct XmlSplit {
+    pub guid: String,                   //guid is the GUID for this split.
+    pub tx_guid: String,                //tx_guid is the transaction the split belongs to.
+    pub account_guid: String,           //account_guid is the account the split posts to.
+    pub memo: String,                   //memo is the split's memo.
+    pub action: String,                 //action is the split's action.
+    pub reconcile_state: String,        //reconcile_state is 'n', 'c', 'y', 'f', or 'v'.
+    pub reconcile_date: Option<String>, //reconcile_date is the date it was reconciled.
+    pub value_num: i64,                 //value_num is the value in the transaction currency.
+    pub value_denom: i64,               //value_denom is the value's denominator.
+    pub quantity_num: i64,              //quantity_num is the amount in the account commodity.
+    pub quantity_denom: i64,            //quantity_denom is the quantity's denominator.
+    pub lot_guid: Option<String>,       //lot_guid is the lot the split belongs to.
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct XmlSlot {
+    pub obj_guid: String,               //obj_guid is the record, or frame, the slot belongs to.
+    pub name: String,                   //name is the full path of the slot. (Ex: 'options/Tax')
+    pub slot_type: i64,                 //slot_type is which of the values is used.
+    pub int64_val: Option<i64>,         //int64_val is the value of an integer slot.
+    pub string_val: Option<String>,     //string_val is the value of a string slot.
+    pub double_val: Option<f64>,        //double_val is the value of a double slot.
+    pub timespec_val: Option<String>,   //timespec_val is the value of a timespec slot.
+    pub guid_val: Option<String>,       //guid_val is the value of a guid slot, or a frame's guid.
+    pub numeric_val_num: Option<i64>,   //numeric_val_num is the numerator of a numeric slot.
+    pub numeric_val_denom: Option<i64>, //numeric_val_denom is the denominator of a numeric slot.
+    pub gdate_val: Option<String>,      //gdate_val is the value of a gdate slot. (Ex: '20240115')
+}
+
+impl XmlSlot {
+    /// new creates a slot with the defaults GnuCash saves for the values a slot doesn't use.
+    fn new(obj_guid: &str, name: &str, slot_type: i64) -> XmlSlot {
+        XmlSlot {
+            obj_guid: obj_guid.to_string(),
+            name: name.to_string(),
+            slot_type,
+            int64_val: Some(0),
+            double_val: Some(0.0),
+            numeric_val_num: Some(0),
+            numeric_val_denom: Some(1),
+            ..Default::default()
+        }
+    }
+}
+
+/// is_gnucash_xml checks whether the given file is a GnuCash XML book, either compressed or
+/// not, instead of a sqlite database.
+pub fn is_gnucash_xml(bytes: &[u8]) -> bool {
+    if bytes.starts_with(&GZIP_MAGIC_NUMBER) {
+        return true;
+    }
+    let start = String::from_utf8_lossy(&bytes[..bytes.len().min(64)]).to_string();
+    let start = start.trim_start_matches('\u{feff}').trim_start();
+    start.starts_with("<?xml") || start.starts_with("<gnc-v2")
+}
+
+/// decompress_gnucash_xml returns the XML of the given file, uncompressing it if GnuCash
+/// compressed it.
+pub fn decompress_gnucash_xml(bytes: &[u8]) -> Result<String, MoneyManagerError> {
+    if !bytes.starts_with(&GZIP_MAGIC_NUMBER) {
+        return String::from_utf8(bytes.to_vec()).map_err(|error| {
+            MoneyManagerError::Validation(format!("The GnuCash XML book is not valid UTF-8: {error}"))
+        });
+    }
+
+    let mut xml = String::new();
+    GzDecoder::new(bytes)
+        .read_to_string(&mut xml)
+        .map_err(|error| {
+            MoneyManagerError::Validation(format!("The GnuCash XML book could not be uncompressed: {error}"))
+        })?;
+    Ok(xml)
+}
+
+/// compress_gnucash_xml compresses the given XML the way GnuCash does by default.
+pub fn compress_gnucash_xml(xml: &str) -> Result<Vec<u8>, MoneyManagerError> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(xml.as_bytes())
+        .and_then(|_| encoder.finish())
+        .map_err(|error| {
+            MoneyManagerError::Validation(format!("The GnuCash XML book could not be compressed: {error}"))
+        })
+}
+
+/// parse_gnucash_xml reads the records of the given GnuCash XML book.
+pub fn parse_gnucash_xml(xml: &str) -> Result<XmlBook, MoneyManagerError> {
+    let document = roxmltree::Document::parse(xml).map_err(|error| {
+        MoneyManagerError::Validation(format!("The GnuCash XML book could not be read: {error}"))
+    })?;
+
+    let root = document.root_element();
+    let book_node = match child(root, "book") {
+        Some(book_node) if root.tag_name().name() == "gnc-v2" => book_node,
+        _ => {
+            return Err(MoneyManagerError::Validation(
+                "The selected file is not a GnuCash XML book.".to_string(),
+            ));
+        }
+    };
+
+    let mut book = XmlBook {
+        guid: child_text(book_node, "id").unwrap_or_else(new_guid),
+        root_template_guid: new_guid(),
+        ..Default::default()
+    };
+    let mut skipped_counts: Vec<String> = Vec::new();
+
+    for node in book_node.children().filter(|node| node.is_element()) {
+        match node.tag_name().name() {
+            "slots" => {
+                let book_guid = book.guid.clone();
+                read_slots(node, &book_guid, "", &mut book.slots)?;
+            }
+            "commodity" => read_commodity(node, &mut book),
+            "pricedb" => {
+                for price_node in node.children().filter(|node| node.has_tag_name_local("price")) {
+                    read_price(price_node, &mut book)?;
+                }
+            }
+            "account" => read_account(node, &mut book)?,
+            "transaction" => read_transaction(node, &mut book)?,
+            //The book's id is read above, and the counts are worked out again when it's written
+            "id" | "count-data" => {}
+            name => skipped_counts.push(name.to_string()),
+        }
+    }
+
+    //Count what wasn't read, so the book isn't saved over without it
+    for (element, description) in SKIPPED_ELEMENTS {
+        let count = skipped_counts.iter().filter(|name| *name == element).count();
+        if count > 0 {
+            book.skipped.push(format!("{count} {description}"));
+        }
+    }
+    for name in skipped_counts.iter() {
+        if !SKIPPED_ELEMENTS.iter().any(|(element, _)| element == name)
+            && !book.skipped.contains(name)
+        {
+            book.skipped.push(name.clone());
+        }
+    }
+
+    book.root_account_guid = match book
+        .accounts
+        .iter()
+        .find(|account| account.account_type == "ROOT" && account.parent_guid.is_none())
+    {
+        Some(root_account) => root_account.guid.clone(),
+        None => {
+            return Err(MoneyManagerError::Validation(
+                "The GnuCash XML book doesn't have a root account.".to_string(),
+            ));
+        }
+    };
+
+    //GnuCash needs a template root, even though the templates aren't read
+    book.accounts.push(XmlAccount {
+        guid: book.root_template_guid.clone(),
+        name: "Template Root".to_string(),
+        account_type: "ROOT".to_string(),
+        ..Default::default()
+    });
+
+    //The XML doesn't say whether a lot is closed, so work it out from its splits
+    for lot in book.lots.iter_mut() {
+        let lot_splits = book
+            .splits
+            .iter()
+            .filter(|split| split.lot_guid.as_deref() == Some(lot.guid.as_str()))
+            .map(|split| (split.quantity_num, split.quantity_denom));
+        let (count, total) = lot_splits.fold((0, (0, 1)), |(count, total), quantity| {
+            (count + 1, add_numeric(total, quantity))
+        });
+        lot.is_closed = (count > 0 && total.0 == 0) as i64;
+    }
+
+    Ok(book)
+}
+
+/// write_gnucash_xml writes the records of the given book as a GnuCash XML book. Accounts under
+/// the template root, and their transactions, are left out.
+pub fn write_gnucash_xml(book: &XmlBook) -> String {
+    let template_account_guids = descendant_guids(&book.accounts, &book.root_template_guid);
+    let accounts: Vec<&XmlAccount> = ordered_accounts(&book.accounts, &book.root_account_guid)
+        .into_iter()
+        .filter(|account| !template_account_guids.contains(&account.guid.as_str()))
+        .collect();
+    let transactions: Vec<&XmlTransaction> = book
+        .transactions
+        .iter()
+        .filter(|transaction| {
+            !book.splits.iter().any(|split| {
+                split.tx_guid == transaction.guid
+                    && template_account_guids.contains(&split.account_guid.as_str())
+            })
+        })
+        .collect();
+    let commodities: Vec<&XmlCommodity> = book
+        .commodities
+        .iter()
+        .filter(|commodity| commodity.namespace != "template")
+        .collect();
+
+    let mut slots_for_obj_guid: HashMap<&str, Vec<&XmlSlot>> = HashMap::new();
+    for slot in &book.slots {
+        slots_for_obj_guid.entry(slot.obj_guid.as_str()).or_default().push(slot);
+    }
+    let writer = XmlWriter {
+        book,
+        slots_for_obj_guid,
+    };
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\" ?>\n<gnc-v2");
+    for namespace in XML_NAMESPACES {
+        xml += &format!("\n     xmlns:{namespace}=\"http://www.gnucash.org/XML/{namespace}\"");
+    }
+    xml += ">\n<gnc:count-data cd:type=\"book\">1</gnc:count-data>\n<gnc:book version=\"2.0.0\">\n";
+    xml += &format!("<book:id type=\"guid\">{}</book:id>\n", book.guid);
+    xml += &writer.slots("book:slots", &book.guid, "");
+    xml += &format!("<gnc:count-data cd:type=\"commodity\">{}</gnc:count-data>\n", commodities.len());
+    xml += &format!("<gnc:count-data cd:type=\"account\">{}</gnc:count-data>\n", accounts.len());
+    xml += &format!("<gnc:count-data cd:type=\"transaction\">{}</gnc:count-data>\n", transactions.len());
+    if !book.prices.is_empty() {
+        xml += &format!("<gnc:count-data cd:type=\"price\">{}</gnc:count-data>\n", book.prices.len());
+    }
+
+    for commodity in commodities {
+        xml += &writer.commodity(commodity);
+    }
+    if !book.prices.is_empty() {
+        xml += "<gnc:pricedb version=\"1\">\n";
+        for price in &book.prices {
+            xml += &writer.price(price);
+        }
+        xml += "</gnc:pricedb>\n";
+    }
+    for account in accounts {
+        xml += &writer.account(account);
+    }
+    for transaction in transactions {
+        xml += &writer.transaction(transaction);
+    }
+
+    xml += "</gnc:book>\n</gnc-v2>\n\n";
+    xml
+}
+
+/// XmlWriter writes the parts of a book, looking up the commodities, and slots they refer to.
+struct XmlWriter<'a> {
+    book: &'a XmlBook,                                 //book is the book being written.
+    slots_for_obj_guid: HashMap<&'a str, Vec<&'a XmlSlot>>, //slots_for_obj_guid groups the slots.
+}
+
+impl XmlWriter<'_> {
+    /// commodity writes a <gnc:commodity> definition.
+    fn commodity(&self, commodity: &XmlCommodity) -> String {
+        let mut xml = String::from("<gnc:commodity version=\"2.0.0\">\n");
+        xml += &format!("  <cmdty:space>{}</cmdty:space>\n", escape(&commodity.namespace));
+        xml += &format!("  <cmdty:id>{}</cmdty:id>\n", escape(&commodity.mnemonic));
+        if commodity.namespace != "CURRENCY" {
+            xml += &format!("  <cmdty:name>{}</cmdty:name>\n", escape(&commodity.fullname));
+            if !commodity.cusip.is_empty() {
+                xml += &format!("  <cmdty:xcode>{}</cmdty:xcode>\n", escape(&commodity.cusip));
+            }
+            xml += &format!("  <cmdty:fraction>{}</cmdty:fraction>\n", commodity.fraction);
+        }
+        if commodity.quote_flag != 0 {
+            xml += "  <cmdty:get_quotes/>\n";
+        }
+        if let Some(quote_source) = commodity.quote_source.as_deref().filter(|source| !source.is_empty()) {
+            xml += &format!("  <cmdty:quote_source>{}</cmdty:quote_source>\n", escape(quote_source));
+        }
+        if commodity.quote_flag != 0 {
+            match commodity.quote_tz.as_deref().filter(|quote_tz| !quote_tz.is_empty()) {
+                Some(quote_tz) => xml += &format!("  <cmdty:quote_tz>{}</cmdty:quote_tz>\n", escape(quote_tz)),
+                None => xml += "  <cmdty:quote_tz/>\n",
+            }
+        }
+        xml += "</gnc:commodity>\n";
+        xml
+    }
+
+    /// commodity_reference writes the space, and id GnuCash uses to refer to a commodity.
+    fn commodity_reference(&self, tag: &str, commodity_guid: &str, indent: &str) -> String {
+        let (namespace, mnemonic) = match self
+            .book
+            .commodities
+            .iter()
+            .find(|commodity| commodity.guid == commodity_guid)
+        {
+            Some(commodity) => (commodity.namespace.as_str(), commodity.mnemonic.as_str()),
+            None => ("template", "template"),
+        };
+        format!(
+            "{indent}<{tag}>\n{indent}  <cmdty:space>{}</cmdty:space>\n{indent}  <cmdty:id>{}</cmdty:id>\n{indent}</{tag}>\n",
+            escape(namespace),
+            escape(mnemonic)
+        )
+    }
+
+    /// price writes a <price> of the price database.
+    fn price(&self, price: &XmlPrice) -> String {
+        let mut xml = String::from("  <price>\n");
+        xml += &format!("    <price:id type=\"guid\">{}</price:id>\n", price.guid);
+        xml += &self.commodity_reference("price:commodity", &price.commodity_guid, "    ");
+        xml += &self.commodity_reference("price:currency", &price.currency_guid, "    ");
+        xml += &date("price:time", &price.date, "    ");
+        if let Some(source) = &price.source {
+            xml += &format!("    <price:source>{}</price:source>\n", escape(source));
+        }
+        if let Some(price_type) = &price.price_type {
+            xml += &format!("    <price:type>{}</price:type>\n", escape(price_type));
+        }
+        xml += &format!("    <price:value>{}/{}</price:value>\n", price.value_num, price.value_denom);
+        xml += "  </price>\n";
+        xml
+    }
+
+    /// account writes a <gnc:account>, along with its lots. Hidden, and placeholder are saved
+    /// as slots in XML, so the columns replace any slots for them.
+    fn account(&self, account: &XmlAccount) -> String {
+        let mut xml = String::from("<gnc:account version=\"2.0.0\">\n");
+        xml += &format!("  <act:name>{}</act:name>\n", escape(&account.name));
+        xml += &format!("  <act:id type=\"guid\">{}</act:id>\n", account.guid);
+        xml += &format!("  <act:type>{}</act:type>\n", escape(&account.account_type));
+        if let Some(commodity_guid) = &account.commodity_guid {
+            xml += &self.commodity_reference("act:commodity", commodity_guid, "  ");
+            xml += &format!("  <act:commodity-scu>{}</act:commodity-scu>\n", account.commodity_scu);
+            if account.non_std_scu == 1 {
+                xml += "  <act:non-standard-scu/>\n";
+            }
+        }
+        if !account.code.is_empty() {
+            xml += &format!("  <act:code>{}</act:code>\n", escape(&account.code));
+        }
+        if !account.description.is_empty() {
+            xml += &format!("  <act:description>{}</act:description>\n", escape(&account.description));
+        }
+
+        let mut slots: Vec<XmlSlot> = self
+            .slots_for_obj_guid
+            .get(account.guid.as_str())
+            .map(|slots| {
+                slots
+                    .iter()
+                    .filter(|slot| slot.name != "hidden" && slot.name != "placeholder")
+                    .map(|slot| (*slot).clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        for (name, value) in [("hidden", account.hidden), ("placeholder", account.placeholder)] {
+            if value == 1 {
+                let mut slot = XmlSlot::new(&account.guid, name, slots_manager::SLOT_TYPE_STRING);
+                slot.string_val = Some("true".to_string());
+                slots.push(slot);
+            }
+        }
+        if !slots.is_empty() {
+            xml += "  <act:slots>\n";
+            for slot in &slots {
+                xml += &self.slot(slot, "", "    ");
+            }
+            xml += "  </act:slots>\n";
+        }
+
+        if let Some(parent_guid) = &account.parent_guid {
+            xml += &format!("  <act:parent type=\"guid\">{}</act:parent>\n", parent_guid);
+        }
+
+        let lots: Vec<&XmlLot> = self
+            .book
+            .lots
+            .iter()
+            .filter(|lot| lot.account_guid == account.guid)
+            .collect();
+        if !lots.is_empty() {
+            xml += "  <act:lots>\n";
+            for lot in lots {
+                xml += "    <gnc:lot version=\"2.0.0\">\n";
+                xml += &format!("      <lot:id type=\"guid\">{}</lot:id>\n", lot.guid);
+                xml += &self.slots("lot:slots", &lot.guid, "      ");
+                xml += "    </gnc:lot>\n";
+            }
+            xml += "  </act:lots>\n";
+        }
+
+        xml += "</gnc:account>\n";
+        xml
+    }
+
+    /// transaction writes a <gnc:transaction>, along with its splits.
+    fn transaction(&self, transaction: &XmlTransaction) -> String {
+        let mut xml = String::from("<gnc:transaction version=\"2.0.0\">\n");
+        xml += &format!("  <trn:id type=\"guid\">{}</trn:id>\n", transaction.guid);
+        xml += &self.commodity_reference("trn:currency", &transaction.currency_guid, "  ");
+        if !transaction.num.is_empty() {
+            xml += &format!("  <trn:num>{}</trn:num>\n", escape(&transaction.num));
+        }
+        if let Some(post_date) = &transaction.post_date {
+            xml += &date("trn:date-posted", post_date, "  ");
+        }
+        if let Some(enter_date) = &transaction.enter_date {
+            xml += &date("trn:date-entered", enter_date, "  ");
+        }
+        xml += &format!("  <trn:description>{}</trn:description>\n", escape(&transaction.description));
+        xml += &self.slots("trn:slots", &transaction.guid, "  ");
+
+        xml += "  <trn:splits>\n";
+        for split in self.book.splits.iter().filter(|split| split.tx_guid == transaction.guid) {
+            xml += "    <trn:split>\n";
+            xml += &format!("      <split:id type=\"guid\">{}</split:id>\n", split.guid);
+            if !split.memo.is_empty() {
+                xml += &format!("      <split:memo>{}</split:memo>\n", escape(&split.memo));
+            }
+            if !split.action.is_empty() {
+                xml += &format!("      <split:action>{}</split:action>\n", escape(&split.action));
+            }
+            xml += &format!(
+                "      <split:reconciled-state>{}</split:reconciled-state>\n",
+                escape(&split.reconcile_state)
+            );
+            if let Some(reconcile_date) = &split.reconcile_date {
+                xml += &date("split:reconcile-date", reconcile_date, "      ");
+            }
+            xml += &format!("      <split:value>{}/{}</split:value>\n", split.value_num, split.value_denom);
+            xml += &format!(
+                "      <split:quantity>{}/{}</split:quantity>\n",
+                split.quantity_num, split.quantity_denom
+            );
+            xml += &format!("      <split:account type=\"guid\">{}</split:account>\n", split.account_guid);
+            if let Some(lot_guid) = &split.lot_guid {
+                xml += &format!("      <split:lot type=\"guid\">{}</split:lot>\n", lot_guid);
+            }
+            xml += &self.slots("split:slots", &split.guid, "      ");
+            xml += "    </trn:split>\n";
+        }
+        xml += "  </trn:splits>\n";
+
+        xml += "</gnc:transaction>\n";
+        xml
+    }
+
+    /// slots writes the slots of the given record inside of the given tag, or nothing if it
+    /// doesn't have any.
+    fn slots(&self, tag: &str, obj_guid: &str, indent: &str) -> String {
+        let Some(slots) = self.slots_for_obj_guid.get(obj_guid) else {
+            return String::new();
+        };
+        let mut xml = format!("{indent}<{tag}>\n");
+        for slot in slots {
+            xml += &self.slot(slot, "", &format!("{indent}  "));
+        }
+        xml += &format!("{indent}</{tag}>\n");
+        xml
+    }
+
+    /// slot writes a single <slot>. A frame writes the slots inside of it as its value.
+    fn slot(&self, slot: &XmlSlot, frame_name: &str, indent: &str) -> String {
+        let key = slot
+            .name
+            .strip_prefix(&format!("{frame_name}/"))
+            .unwrap_or(&slot.name);
+        let (value_type, value) = match slot.slot_type {
+            slots_manager::SLOT_TYPE_INT64 => ("integer", slot.int64_val.unwrap_or(0).to_string()),
+            slots_manager::SLOT_TYPE_DOUBLE => ("double", slot.double_val.unwrap_or(0.0).to_string()),
+            slots_manager::SLOT_TYPE_NUMERIC => (
+                "numeric",
+                format!(
+                    "{}/{}",
+                    slot.numeric_val_num.unwrap_or(0),
+                    slot.numeric_val_denom.unwrap_or(1)
+                ),
+            ),
+            slots_manager::SLOT_TYPE_GUID => ("guid", escape(slot.guid_val.as_deref().unwrap_or(""))),
+            slots_manager::SLOT_TYPE_TIMESPEC => (
+                "timespec",
+                format!(
+                    "\n{indent}    <ts:date>{}</ts:date>\n{indent}  ",
+                    convert_sql_date_to_xml(slot.timespec_val.as_deref().unwrap_or(""))
+                ),
+            ),
+            slots_manager::SLOT_TYPE_GDATE => (
+                "gdate",
+                format!(
+                    "<gdate>{}</gdate>",
+                    convert_sql_gdate_to_xml(slot.gdate_val.as_deref().unwrap_or(""))
+                ),
+            ),
+            slots_manager::SLOT_TYPE_FRAME => {
+                let mut value = String::from("\n");
+                if let Some(frame_slots) = slot
+                    .guid_val
+                    .as_deref()
+                    .and_then(|guid_val| self.slots_for_obj_guid.get(guid_val))
+                {
+                    for frame_slot in frame_slots {
+                        value += &self.slot(frame_slot, &slot.name, &format!("{indent}    "));
+                    }
+                }
+                value += &format!("{indent}  ");
+                ("frame", value)
+            }
+            _ => ("string", escape(slot.string_val.as_deref().unwrap_or(""))),
+        };
+
+        format!(
+            "{indent}<slot>\n{indent}  <slot:key>{}</slot:key>\n{indent}  <slot:value type=\"{value_type}\">{value}</slot:value>\n{indent}</slot>\n",
+            escape(key)
+        )
+    }
+}
+
+/// date writes a date from the database as a GnuCash XML <ts:date> inside of the given tag.
+fn date(tag: &str, sql_date: &str, indent: &str) -> String {
+    format!(
+        "{indent}<{tag}>\n{indent}  <ts:date>{}</ts:date>\n{indent}</{tag}>\n",
+        convert_sql_date_to_xml(sql_date)
+    )
+}
+
+/// read_commodity reads a <gnc:commodity> definition, filling in the commodity if an account
+/// already referred to it.
+fn read_commodity(node: roxmltree::Node, book: &mut XmlBook) {
+    let guid = commodity_guid(node, book);
+    let Some(commodity) = book.commodities.iter_mut().find(|commodity| commodity.guid == guid) else {
+        return;
+    };
+    if let Some(fullname) = child_text(node, "name") {
+        commodity.fullname = fullname;
+    }
+    if let Some(cusip) = child_text(node, "xcode") {
+        commodity.cusip = cusip;
+    }
+    if let Some(fraction) = child_text(node, "fraction").and_then(|fraction| fraction.parse().ok()) {
+        commodity.fraction = fraction;
+    }
+    commodity.quote_flag = child(node, "get_quotes").is_some() as i64;
+    commodity.quote_source = child_text(node, "quote_source");
+    commodity.quote_tz = child_text(node, "quote_tz");
+}
+
+/// commodity_guid returns the guid of the commodity the given node refers to by its space, and
+/// id, adding the commodity to the book if it's not there yet.
+fn commodity_guid(node: roxmltree::Node, book: &mut XmlBook) -> String {
+    let namespace = match child_text(node, "space").unwrap_or_default().as_str() {
+        "ISO4217" => "CURRENCY".to_string(),
+        namespace => namespace.to_string(),
+    };
+    let mnemonic = child_text(node, "id").unwrap_or_default();
+
+    if let Some(commodity) = book
+        .commodities
+        .iter()
+        .find(|commodity| commodity.namespace == namespace && commodity.mnemonic == mnemonic)
+    {
+        return commodity.guid.clone();
+    }
+
+    let commodity = XmlCommodity {
+        guid: new_guid(),
+        fullname: mnemonic.clone(),
+        fraction: default_fraction(&namespace, &mnemonic),
+        quote_flag: (namespace == "CURRENCY") as i64,
+        quote_source: (namespace == "CURRENCY").then(|| "currency".to_string()),
+        quote_tz: Some(String::new()),
+        namespace,
+        mnemonic,
+        ..Default::default()
+    };
+    let guid = commodity.guid.clone();
+    book.commodities.push(commodity);
+    guid
+}
+
+/// default_fraction returns the smallest unit of a commodity, when the XML doesn't say. GnuCash
+/// leaves it out for currencies, since it knows them already.
+fn default_fraction(namespace: &str, mnemonic: &str) -> i64 {
+    if namespace != "CURRENCY" {
+        return 10000;
+    }
+    match mnemonic {
+        "JPY" | "KRW" | "VND" | "CLP" | "ISK" | "PYG" | "UGX" | "XAF" | "XOF" => 1,
+        "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 1000,
+        _ => 100,
+    }
+}
+
+/// read_price reads a <price> of the price database.
+fn read_price(node: roxmltree::Node, book: &mut XmlBook) -> Result<(), MoneyManagerError> {
+    let (value_num, value_denom) = parse_numeric(&child_text(node, "value").unwrap_or_default())?;
+    let price = XmlPrice {
+        guid: child_text(node, "id").unwrap_or_else(new_guid),
+        commodity_guid: required_commodity_guid(node, "commodity", book)?,
+        currency_guid: required_commodity_guid(node, "currency", book)?,
+        date: child_date(node, "time")?.unwrap_or_default(),
+        source: child_text(node, "source"),
+        price_type: child_text(node, "type"),
+        value_num,
+        value_denom,
+    };
+    book.prices.push(price);
+    Ok(())
+}
+
+/// read_account reads a <gnc:account>, along with its lots, and slots.
+fn read_account(node: roxmltree::Node, book: &mut XmlBook) -> Result<(), MoneyManagerError> {
+    let guid = child_text(node, "id").unwrap_or_else(new_guid);
+    let commodity_guid = child(node, "commodity").map(|commodity_node| commodity_guid(commodity_node, book));
+
+    let mut account_slots = Vec::new();
+    if let Some(slots_node) = child(node, "slots") {
+        read_slots(slots_node, &guid, "", &mut account_slots)?;
+    }
+    let slot_is_true = |name: &str| {
+        account_slots
+            .iter()
+            .any(|slot| slot.name == name && slot.string_val.as_deref() == Some("true")) as i64
+    };
+
+    let account = XmlAccount {
+        guid: guid.clone(),
+        name: child_text(node, "name").unwrap_or_default(),
+        account_type: child_text(node, "type").unwrap_or_default(),
+        commodity_guid,
+        commodity_scu: child_text(node, "commodity-scu")
+            .and_then(|commodity_scu| commodity_scu.parse().ok())
+            .unwrap_or(0),
+        non_std_scu: child(node, "non-standard-scu").is_some() as i64,
+        parent_guid: child_text(node, "parent"),
+        code: child_text(node, "code").unwrap_or_default(),
+        description: child_text(node, "description").unwrap_or_default(),
+        hidden: slot_is_true("hidden"),
+        placeholder: slot_is_true("placeholder"),
+    };
+    book.accounts.push(account);
+    book.slots.extend(account_slots);
+
+    if let Some(lots_node) = child(node, "lots") {
+        for lot_node in lots_node.children().filter(|node| node.has_tag_name_local("lot")) {
+            let lot = XmlLot {
+                guid: child_text(lot_node, "id").unwrap_or_else(new_guid),
+                account_guid: guid.clone(),
+                is_closed: 0,
+            };
+            if let Some(slots_node) = child(lot_node, "slots") {
+                read_slots(slots_node, &lot.guid, "", &mut book.slots)?;
+            }
+            book.lots.push(lot);
+        }
+    }
+    Ok(())
+}
+
+/// read_transaction reads a <gnc:transaction>, along with its splits, and slots.
+fn read_transaction(node: roxmltree::Node, book: &mut XmlBook) -> Result<(), MoneyManagerError> {
+    let transaction = XmlTransaction {
+        guid: child_text(node, "id").unwrap_or_else(new_guid),
+        currency_guid: required_commodity_guid(node, "currency", book)?,
+        num: child_text(node, "num").unwrap_or_default(),
+        post_date: child_date(node, "date-posted")?,
+        enter_date: child_date(node, "date-entered")?,
+        description: child_text(node, "description").unwrap_or_default(),
+    };
+    if let Some(slots_node) = child(node, "slots") {
+        read_slots(slots_node, &transaction.guid, "", &mut book.slots)?;
+    }
+
+    if let Some(splits_node) = child(node, "splits") {
+        for split_node in splits_node.children().filter(|node| node.has_tag_name_local("split")) {
+            let (value_num, value_denom) =
+                parse_numeric(&child_text(split_node, "value").unwrap_or_default())?;
+            let (quantity_num, quantity_denom) =
+                parse_numeric(&child_text(split_node, "quantity").unwrap_or_default())?;
+            let split = XmlSplit {
+                guid: child_text(split_node, "id").unwrap_or_else(new_guid),
+                tx_guid: transaction.guid.clone(),
+                account_guid: child_text(split_node, "account").ok_or_else(|| {
+                    MoneyManagerError::Validation(format!(
+                        "A split of the transaction '{}' doesn't have an account.",
+                        transaction.description
+                    ))
+                })?,
+                memo: child_text(split_node, "memo").unwrap_or_default(),
+                action: child_text(split_node, "action").unwrap_or_default(),
+                reconcile_state: child_text(split_node, "reconciled-state").unwrap_or_else(|| "n".to_string()),
+                reconcile_date: child_date(split_node, "reconcile-date")?,
+                value_num,
+                value_denom,
+                quantity_num,
+                quantity_denom,
+                lot_guid: child_text(split_node, "lot"),
+            };
+            if let Some(slots_node) = child(split_node, "slots") {
+                read_slots(slots_node, &split.guid, "", &mut book.slots)?;
+            }
+            book.splits.push(split);
+        }
+    }
+
+    book.transactions.push(transaction);
+    Ok(())
+}
+
+/// read_slots reads each <slot> in the given node. A frame is saved as a slot whose guid_val is
+/// the obj_guid of the slots inside of it, which are named with the frame's name as a prefix,
+/// the way GnuCash saves them in sqlite.
+fn read_slots(
+    node: roxmltree::Node,
+    obj_guid: &str,
+    frame_name: &str,
+    slots: &mut Vec<XmlSlot>,
+) -> Result<(), MoneyManagerError> {
+    for slot_node in node.children().filter(|node| node.has_tag_name_local("slot")) {
+        let key = child_text(slot_node, "key").unwrap_or_default();
+        let name = if frame_name.is_empty() {
+            key
+        } else {
+            format!("{frame_name}/{key}")
+        };
+        let Some(value_node) = child(slot_node, "value") else {
+            continue;
+        };
+        let text = value_node.text().unwrap_or_default().trim().to_string();
+        let invalid = || MoneyManagerError::Validation(format!("The slot '{name}' has an invalid value '{text}'."));
+
+        let slot = match value_node.attribute("type").unwrap_or("string") {
+            "integer" => XmlSlot {
+                int64_val: Some(text.parse().map_err(|_| invalid())?),
+                ..XmlSlot::new(obj_guid, &name, slots_manager::SLOT_TYPE_INT64)
+            },
+            "double" => XmlSlot {
+                double_val: Some(text.parse().map_err(|_| invalid())?),
+                ..XmlSlot::new(obj_guid, &name, slots_manager::SLOT_TYPE_DOUBLE)
+            },
+            "numeric" => {
+                let (num, denom) = parse_numeric(&text)?;
+                XmlSlot {
+                    numeric_val_num: Some(num),
+                    numeric_val_denom: Some(denom),
+                    ..XmlSlot::new(obj_guid, &name, slots_manager::SLOT_TYPE_NUMERIC)
+                }
+            }
+            "string" => XmlSlot {
+                string_val: Some(value_node.text().unwrap_or_default().to_string()),
+                ..XmlSlot::new(obj_guid, &name, slots_manager::SLOT_TYPE_STRING)
+            },
+            "guid" => XmlSlot {
+                guid_val: Some(text.clone()),
+                ..XmlSlot::new(obj_guid, &name, slots_manager::SLOT_TYPE_GUID)
+            },
+            "timespec" => XmlSlot {
+                timespec_val: Some(convert_xml_date_to_sql(&child_text(value_node, "date").unwrap_or_default())?),
+                ..XmlSlot::new(obj_guid, &name, slots_manager::SLOT_TYPE_TIMESPEC)
+            },
+            "gdate" => {
+                let gdate = child_text(value_node, "gdate").unwrap_or_default();
+                let gdate = NaiveDate::parse_from_str(&gdate, "%Y-%m-%d")
+                    .map_err(|_| MoneyManagerError::Validation(format!("The slot '{name}' has an invalid date '{gdate}'.")))?;
+                XmlSlot {
+                    gdate_val: Some(gdate.format("%Y%m%d").to_string()),
+                    ..XmlSlot::new(obj_guid, &name, slots_manager::SLOT_TYPE_GDATE)
+                }
+            }
+            "frame" => {
+                let frame_guid = new_guid();
+                slots.push(XmlSlot {
+                    guid_val: Some(frame_guid.clone()),
+                    ..XmlSlot::new(obj_guid, &name, slots_manager::SLOT_TYPE_FRAME)
+                });
+                read_slots(value_node, &frame_guid, &name, slots)?;
+                continue;
+            }
+            //Lists, and other rare types aren't kept
+            _ => continue,
+        };
+        slots.push(slot);
+    }
+    Ok(())
+}
+
+/// save_xml_book_into_database saves every record of the given book into the loaded database,
+/// which should have its tables created, and be empty.
+pub fn save_xml_book_into_database(book: &XmlBook) -> Result<(), MoneyManagerError> {
+    dhu::in_transaction(|| insert_xml_book(book))
+}
+
+/// insert_xml_book inserts the records of the book, for save_xml_book_into_database.
+fn insert_xml_book(book: &XmlBook) -> Result<(), MoneyManagerError> {
+    dhu::execute(
+        "INSERT INTO books(guid,root_account_guid,root_template_guid) VALUES(?,?,?)",
+        Some(dhu::convert_to_binding_object(&(
+            &book.guid,
+            &book.root_account_guid,
+            &book.root_template_guid,
+        ))),
+    )?;
+
+    for commodity in &book.commodities {
+        dhu::execute(
+            "INSERT INTO commodities(guid,namespace,mnemonic,fullname,cusip,fraction,quote_flag,quote_source,quote_tz)
+             VALUES(?,?,?,?,?,?,?,?,?)",
+            Some(dhu::convert_to_binding_object(&(
+                &commodity.guid,
+                &commodity.namespace,
+                &commodity.mnemonic,
+                &commodity.fullname,
+                &commodity.cusip,
+                commodity.fraction,
+                commodity.quote_flag,
+                &commodity.quote_source,
+                &commodity.quote_tz,
+            ))),
+        )?;
+    }
+    for price in &book.prices {
+        dhu::execute(
+            "INSERT INTO prices(guid,commodity_guid,currency_guid,date,source,type,value_num,value_denom)
+             VALUES(?,?,?,?,?,?,?,?)",
+            Some(dhu::convert_to_binding_object(&(
+                &price.guid,
+                &price.commodity_guid,
+                &price.currency_guid,
+                &price.date,
+                &price.source,
+                &price.price_type,
+                price.value_num,
+                price.value_denom,
+            ))),
+        )?;
+    }
+    for account in &book.accounts {
+        dhu::execute(
+            "INSERT INTO accounts(guid,name,account_type,commodity_guid,commodity_scu,non_std_scu,parent_guid,code,description,hidden,placeholder)
+             VALUES(?,?,?,?,?,?,?,?,?,?,?)",
+            Some(dhu::convert_to_binding_object(&(
+                &account.guid,
+                &account.name,
+                &account.account_type,
+                &account.commodity_guid,
+                account.commodity_scu,
+                account.non_std_scu,
+                &account.parent_guid,
+                &account.code,
+                &account.description,
+                account.hidden,
+                account.placeholder,
+            ))),
+        )?;
+    }
+    for lot in &book.lots {
+        dhu::execute(
+            "INSERT INTO lots(guid,account_guid,is_closed) VALUES(?,?,?)",
+            Some(dhu::convert_to_binding_object(&(&lot.guid, &lot.account_guid, lot.is_closed))),
+        )?;
+    }
+    for transaction in &book.transactions {
+        dhu::execute(
+            "INSERT INTO transactions(guid,currency_guid,num,post_date,enter_date,description)
+             VALUES(?,?,?,?,?,?)",
+            Some(dhu::convert_to_binding_object(&(
+                &transaction.guid,
+                &transaction.currency_guid,
+                &transaction.num,
+                &transaction.post_date,
+                &transaction.enter_date,
+                &transaction.description,
+            ))),
+        )?;
+    }
+    for split in &book.splits {
+        dhu::execute(
+            "INSERT INTO splits(guid,tx_guid,account_guid,memo,action,reconcile_state,reconcile_date,value_num,value_denom,quantity_num,quantity_denom,lot_guid)
+             VALUES(?,?,?,?,?,?,?,?,?,?,?,?)",
+            Some(dhu::convert_to_binding_object(&(
+                &split.guid,
+                &split.tx_guid,
+                &split.account_guid,
+                &split.memo,
+                &split.action,
+                &split.reconcile_state,
+                &split.reconcile_date,
+                split.value_num,
+                split.value_denom,
+                split.quantity_num,
+                split.quantity_denom,
+                &split.lot_guid,
+            ))),
+        )?;
+    }
+    for slot in &book.slots {
+        dhu::execute(
+            "INSERT INTO slots(obj_guid,name,slot_type,int64_val,string_val,double_val,timespec_val,guid_val,numeric_val_num,numeric_val_denom,gdate_val)
+             VALUES(?,?,?,?,?,?,?,?,?,?,?)",
+            Some(dhu::convert_to_binding_object(&(
+                &slot.obj_guid,
+                &slot.name,
+                slot.slot_type,
+                slot.int64_val,
+                &slot.string_val,
+                slot.double_val,
+                &slot.timespec_val,
+                &slot.guid_val,
+                slot.numeric_val_num,
+                slot.numeric_val_denom,
+                &slot.gdate_val,
+            ))),
+        )?;
+    }
+    Ok(())
+}
+
+/// load_gnucash_xml_into_new_database reads the given GnuCash XML file into a new database,
+/// which is added to the loaded databases.
+pub fn load_gnucash_xml_into_new_database(bytes: &[u8]) -> Result<(), MoneyManagerError> {
+    let book = parse_gnucash_xml(&decompress_gnucash_xml(bytes)?)?;
+
+    let database = dhu::Database::new_empty();
+    dhu::create_database_tables(&database);
+    crate::DATABASE.lock().unwrap().push(database);
+    *crate::GNUCASH_XML_SKIPPED.lock().unwrap() = book.skipped.clone();
+
+    save_xml_book_into_database(&book)
+}
+
+/// CountRow holds the number of rows a query counted.
+#[derive(Debug, Deserialize)]
+struct CountRow {
+    count: i64,
+}
+
+/// retrieve_records_left_out_of_xml returns the records a GnuCash XML book of the loaded
+/// database would lose, both those that weren't read from the XML book it was loaded from, and
+/// those in the tables that aren't written. (Ex: '2 budgets')
+pub fn retrieve_records_left_out_of_xml() -> Result<Vec<String>, MoneyManagerError> {
+    let mut left_out = crate::GNUCASH_XML_SKIPPED.lock().unwrap().clone();
+    for (table_name, description) in UNWRITTEN_TABLES {
+        let count_row: CountRow = dhu::query_first_row(
+            &format!("SELECT COUNT(*) AS count FROM {table_name}"),
+            None,
+            &format!("The {table_name} table couldn't be counted."),
+        )?;
+        if count_row.count > 0 {
+            left_out.push(format!("{} {description}", count_row.count));
+        }
+    }
+    Ok(left_out)
+}
+
+/// export_database_to_gnucash_xml writes the loaded database as a compressed GnuCash XML book.
+pub fn export_database_to_gnucash_xml() -> Result<Vec<u8>, MoneyManagerError> {
+    compress_gnucash_xml(&write_gnucash_xml(&retrieve_xml_book_from_database()?))
+}
+
+/// retrieve_xml_book_from_database reads the records of the loaded database that a GnuCash XML
+/// book holds.
+pub fn retrieve_xml_book_from_database() -> Result<XmlBook, MoneyManagerError> {
+    let book_row: BookRow = dhu::query_first_row(
+        "SELECT guid,root_account_guid,root_template_guid FROM books",
+        None,
+        "This book doesn't have a books record, so its root account can't be found.",
+    )?;
+
+    Ok(XmlBook {
+        guid: book_row.guid,
+        root_account_guid: book_row.root_account_guid,
+        root_template_guid: book_row.root_template_guid,
+        commodities: dhu::query_rows(
+            "SELECT guid,namespace,mnemonic,COALESCE(fullname,'') AS fullname,COALESCE(cusip,'') AS cusip,
+                    fraction,quote_flag,quote_source,quote_tz
+             FROM commodities ORDER BY namespace,mnemonic,guid",
+            None,
+        )?,
+        prices: dhu::query_rows(
+            "SELECT guid,commodity_guid,currency_guid,date AS date,source,type AS price_type,value_num,value_denom
+             FROM prices ORDER BY date,guid",
+            None,
+        )?,
+        accounts: dhu::query_rows(
+            "SELECT guid,name,account_type,commodity_guid,commodity_scu,non_std_scu,parent_guid,
+                    COALESCE(code,'') AS code,COALESCE(description,'') AS description,
+                    COALESCE(hidden,0) AS hidden,COALESCE(placeholder,0) AS placeholder
+             FROM accounts ORDER BY guid",
+            None,
+        )?,
+        lots: dhu::query_rows(
+            "SELECT guid,COALESCE(account_guid,'') AS account_guid,is_closed FROM lots ORDER BY guid",
+            None,
+        )?,
+        transactions: dhu::query_rows(
+            "SELECT guid,currency_guid,num,post_date,enter_date,COALESCE(description,'') AS description
+             FROM transactions ORDER BY post_date,enter_date,guid",
+            None,
+        )?,
+        splits: dhu::query_rows(
+            "SELECT guid,tx_guid,account_guid,memo,action,reconcile_state,reconcile_date,
+                    value_num,value_denom,quantity_num,quantity_denom,lot_guid
+             FROM splits ORDER BY tx_guid,guid",
+            None,
+        )?,
+        slots: dhu::query_rows(
+            "SELECT obj_guid,name,slot_type,int64_val,string_val,double_val,timespec_val,guid_val,
+                    numeric_val_num,numeric_val_denom,gdate_val
+             FROM slots ORDER BY id",
+            None,
+        )?,
+        skipped: Vec::new(),
+    })
+}
+
+/// ordered_accounts returns the accounts with each parent before its children, starting at the
+/// root account, the way GnuCash writes them. Accounts that can't be reached from the root come
+/// last.
+fn ordered_accounts<'a>(accounts: &'a [XmlAccount], root_account_guid: &str) -> Vec<&'a XmlAccount> {
+    let mut ordered: Vec<&XmlAccount> = Vec::new();
+    let mut to_visit: Vec<&XmlAccount> = accounts
+        .iter()
+        .filter(|account| account.guid == root_account_guid)
+        .collect();
+    while let Some(account) = to_visit.pop() {
+        ordered.push(account);
+        let mut children: Vec<&XmlAccount> = accounts
+            .iter()
+            .filter(|child| child.parent_guid.as_deref() == Some(account.guid.as_str()))
+            .filter(|child| !ordered.iter().any(|ordered| ordered.guid == child.guid))
+            .collect();
+        children.sort_by(|a, b| b.name.cmp(&a.name));
+        to_visit.extend(children);
+    }
+    for account in accounts {
+        if !ordered.iter().any(|ordered| ordered.guid == account.guid) {
+            ordered.push(account);
+        }
+    }
+    ordered
+}
+
+/// descendant_guids returns the guid of the given account, and every account under it.
+fn descendant_guids<'a>(accounts: &'a [XmlAccount], account_guid: &'a str) -> Vec<&'a str> {
+    let mut guids = vec![account_guid];
+    let mut index = 0;
+    while index < guids.len() {
+        let parent_guid = guids[index];
+        for account in accounts {
+            if account.parent_guid.as_deref() == Some(parent_guid) && !guids.contains(&account.guid.as_str()) {
+                guids.push(&account.guid);
+            }
+        }
+        index += 1;
+    }
+    guids
+}
+
+/// NodeExtensions adds a check of an element's name without its namespace prefix.
+trait NodeExtensions {
+    fn has_tag_name_local(&self, name: &str) -> bool;
+}
+
+impl NodeExtensions for roxmltree::Node<'_, '_> {
+    /// has_tag_name_local checks whether this is an element with the given name, whatever its
+    /// namespace. (Ex: 'split' matches <trn:split>)
+    fn has_tag_name_local(&self, name: &str) -> bool {
+        self.is_element() && self.tag_name().name() == name
+    }
+}
+
+/// child returns the first child element of the node with the given name.
+fn child<'a, 'input>(node: roxmltree::Node<'a, 'input>, name: &str) -> Option<roxmltree::Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name_local(name))
+}
+
+/// child_text returns the text of the first child element of the node with the given name.
+fn child_text(node: roxmltree::Node, name: &str) -> Option<String> {
+    child(node, name).map(|child| child.text().unwrap_or_default().to_string())
+}
+
+/// child_date returns the date of the <ts:date> in the child element with the given name.
+fn child_date(node: roxmltree::Node, name: &str) -> Result<Option<String>, MoneyManagerError> {
+    match child(node, name).and_then(|child| child_text(child, "date")) {
+        Some(date) => Ok(Some(convert_xml_date_to_sql(&date)?)),
+        None => Ok(None),
+    }
+}
+
+/// required_commodity_guid returns the guid of the commodity the child element with the given
+/// name refers to.
+fn required_commodity_guid(
+    node: roxmltree::Node,
+    name: &str,
+    book: &mut XmlBook,
+) -> Result<String, MoneyManagerError> {
+    match child(node, name) {
+        Some(commodity_node) => Ok(commodity_guid(commodity_node, book)),
+        None => Err(MoneyManagerError::Validation(format!(
+            "A {} is missing its {name}.",
+            node.tag_name().name()
+        ))),
+    }
+}
+
+/// convert_xml_date_to_sql converts a <ts:date> to UTC, in the database's date format.
+/// (Ex: '2024-01-15 10:59:00 -0500' is '20240115155900')
+fn convert_xml_date_to_sql(xml_date: &str) -> Result<String, MoneyManagerError> {
+    match DateTime::parse_from_str(xml_date.trim(), XML_DATE_FORMAT) {
+        Ok(date) => Ok(date.naive_utc().format(SQL_DATE_FORMAT).to_string()),
+        Err(_) => Err(MoneyManagerError::Validation(format!(
+            "The date '{xml_date}' in the GnuCash XML book is not valid."
+        ))),
+    }
+}
+
+/// convert_sql_date_to_xml converts a date from the database to the format of a <ts:date>.
+/// Dates in GnuCash's newer 'YYYY-MM-DD HH:MM:SS' format are read too.
+fn convert_sql_date_to_xml(sql_date: &str) -> String {
+    NaiveDateTime::parse_from_str(sql_date, SQL_DATE_FORMAT)
+        .or_else(|_| NaiveDateTime::parse_from_str(sql_date, "%Y-%m-%d %H:%M:%S"))
+        .unwrap_or_default()
+        .format("%Y-%m-%d %H:%M:%S +0000")
+        .to_string()
+}
+
+/// convert_sql_gdate_to_xml converts a gdate_val to the format of a <gdate>.
+/// (Ex: '20240115' is '2024-01-15')
+fn convert_sql_gdate_to_xml(gdate: &str) -> String {
+    NaiveDate::parse_from_str(gdate, "%Y%m%d")
+        .map(|gdate| gdate.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// parse_numeric parses a GnuCash numeric. (Ex: '1050/100' is 10.50)
+fn parse_numeric(numeric: &str) -> Result<(i64, i64), MoneyManagerError> {
+    let invalid = || MoneyManagerError::Validation(format!("The amount '{numeric}' in the GnuCash XML book is not valid."));
+    let (num, denom) = numeric.trim().split_once('/').ok_or_else(invalid)?;
+    Ok((num.parse().map_err(|_| invalid())?, denom.parse().map_err(|_| invalid())?))
+}
+
+/// add_numeric adds two numerics, keeping them reduced so they don't overflow.
+fn add_numeric(a: (i64, i64), b: (i64, i64)) -> (i64, i64) {
+    let num = a.0 as i128 * b.1 as i128 + b.0 as i128 * a.1 as i128;
+    let denom = a.1 as i128 * b.1 as i128;
+    let divisor = greatest_common_divisor(num.abs(), denom.abs()).max(1);
+    ((num / divisor) as i64, (denom / divisor) as i64)
+}
+
+/// greatest_common_divisor is the greatest common divisor of two numbers.
+fn greatest_common_divisor(a: i128, b: i128) -> i128 {
+    if b == 0 { a } else { greatest_common_divisor(b, a % b) }
+}
+
+/// escape escapes the characters XML doesn't allow in text, and attributes.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// new_guid creates a new guid in the 32 character format GnuCash uses.
+fn new_guid() -> String {
+    dhu::convert_guid_to_sqlite_string(&Uuid::new_v4())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOOK_XML: &str = r#"<?xml version="1.0" encoding="utf-8" ?>
+<gnc-v2
+     xmlns:gnc="http://www.gnucash.org/XML/gnc"
+     xmlns:act="http://www.gnucash.org/XML/act"
+     xmlns:book="http://www.gnucash.org/XML/book"
+     xmlns:cd="http://www.gnucash.org/XML/cd"
+     xmlns:cmdty="http://www.gnucash.org/XML/cmdty"
+     xmlns:slot="http://www.gnucash.org/XML/slot"
+     xmlns:split="http://www.gnucash.org/XML/split"
+     xmlns:trn="http://www.gnucash.org/XML/trn"
+     xmlns:ts="http://www.gnucash.org/XML/ts">
+<gnc:count-data cd:type="book">1</gnc:count-data>
+<gnc:book version="2.0.0">
+<book:id type="guid">b0000000000000000000000000000001</book:id>
+<gnc:commodity version="2.0.0">
+  <cmdty:space>ISO4217</cmdty:space>
+  <cmdty:id>USD</cmdty:id>
+  <cmdty:get_quotes/>
+  <cmdty:quote_source>currency</cmdty:quote_source>
+  <cmdty:quote_tz/>
+</gnc:commodity>
+<gnc:account version="2.0.0">
+  <act:name>Root Account</act:name>
+  <act:id type="guid">a0000000000000000000000000000001</act:id>
+  <act:type>ROOT</act:type>
+</gnc:account>
+<gnc:account version="2.0.0">
+  <act:name>Checking &amp; Savings</act:name>
+  <act:id type="guid">a0000000000000000000000000000002</act:id>
+  <act:type>BANK</act:type>
+  <act:commodity>
+    <cmdty:space>ISO4217</cmdty:space>
+    <cmdty:id>USD</cmdty:id>
+  </act:commodity>
+  <act:commodity-scu>100</act:commodity-scu>
+  <act:slots>
+    <slot>
+      <slot:key>placeholder</slot:key>
+      <slot:value type="string">true</slot:value>
+    </slot>
+    <slot>
+      <slot:key>reconcile-info</slot:key>
+      <slot:value type="frame">
+        <slot>
+          <slot:key>last-interval</slot:key>
+          <slot:value type="integer">30</slot:value>
+        </slot>
+      </slot:value>
+    </slot>
+  </act:slots>
+  <act:parent type="guid">a0000000000000000000000000000001</act:parent>
+</gnc:account>
+<gnc:account version="2.0.0">
+  <act:name>Groceries</act:name>
+  <act:id type="guid">a0000000000000000000000000000003</act:id>
+  <act:type>EXPENSE</act:type>
+  <act:commodity>
+    <cmdty:space>ISO4217</cmdty:space>
+    <cmdty:id>USD</cmdty:id>
+  </act:commodity>
+  <act:commodity-scu>100</act:commodity-scu>
+  <act:parent type="guid">a0000000000000000000000000000001</act:parent>
+</gnc:account>
+<gnc:transaction version="2.0.0">
+  <trn:id type="guid">70000000000000000000000000000001</trn:id>
+  <trn:currency>
+    <cmdty:space>ISO4217</cmdty:space>
+    <cmdty:id>USD</cmdty:id>
+  </trn:currency>
+  <trn:date-posted>
+    <ts:date>2024-01-15 10:59:00 -0500</ts:date>
+  </trn:date-posted>
+  <trn:date-entered>
+    <ts:date>2024-01-15 12:00:00 +0000</ts:date>
+  </trn:date-entered>
+  <trn:description>Corner Store</trn:description>
+  <trn:splits>
+    <trn:split>
+      <split:id type="guid">50000000000000000000000000000001</split:id>
+      <split:reconciled-state>c</split:reconciled-state>
+      <split:value>-1050/100</split:value>
+      <split:quantity>-1050/100</split:quantity>
+      <split:account type="guid">a0000000000000000000000000000002</split:account>
+    </trn:split>
+    <trn:split>
+      <split:id type="guid">50000000000000000000000000000002</split:id>
+      <split:memo>Milk</split:memo>
+      <split:reconciled-state>n</split:reconciled-state>
+      <split:value>1050/100</split:value>
+      <split:quantity>1050/100</split:quantity>
+      <split:account type="guid">a0000000000000000000000000000003</split:account>
+    </trn:split>
+  </trn:splits>
+</gnc:transaction>
+</gnc:book>
+</gnc-v2>
+"#;
+
+    #[test]
+    fn parse_gnucash_xml_reads_the_records_of_a_book() {
+        let book = parse_gnucash_xml(BOOK_XML).unwrap();
+
+        assert_eq!(book.guid, "b0000000000000000000000000000001");
+        assert_eq!(book.root_account_guid, "a0000000000000000000000000000001");
+        assert_eq!(book.commodities.len(), 1);
+        assert_eq!(book.commodities[0].namespace, "CURRENCY");
+        assert_eq!(book.commodities[0].fraction, 100);
+
+        //The three accounts, and the template root
+        assert_eq!(book.accounts.len(), 4);
+        let checking = &book.accounts[1];
+        assert_eq!(checking.name, "Checking & Savings");
+        assert_eq!(checking.placeholder, 1);
+        assert_eq!(checking.hidden, 0);
+        assert_eq!(checking.commodity_guid.as_ref(), Some(&book.commodities[0].guid));
+
+        let frame = book.slots.iter().find(|slot| slot.name == "reconcile-info").unwrap();
+        let interval = book.slots.iter().find(|slot| slot.name == "reconcile-info/last-interval").unwrap();
+        assert_eq!(frame.slot_type, slots_manager::SLOT_TYPE_FRAME);
+        assert_eq!(frame.guid_val.as_ref(), Some(&interval.obj_guid));
+        assert_eq!(interval.int64_val, Some(30));
+
+        assert_eq!(book.transactions.len(), 1);
+        assert_eq!(book.transactions[0].post_date.as_deref(), Some("20240115155900"));
+        assert_eq!(book.splits.len(), 2);
+        assert_eq!(book.splits[0].value_num, -1050);
+        assert_eq!(book.splits[0].reconcile_state, "c");
+        assert_eq!(book.splits[1].memo, "Milk");
+    }
+
+    #[test]
+    fn write_gnucash_xml_reads_back_the_same_book() {
+        let xml = write_gnucash_xml(&parse_gnucash_xml(BOOK_XML).unwrap());
+        let book = parse_gnucash_xml(&xml).unwrap();
+
+        assert_eq!(write_gnucash_xml(&book), xml);
+        assert_eq!(book.accounts.len(), 4);
+        assert_eq!(book.splits.len(), 2);
+        assert!(xml.contains("<act:name>Checking &amp; Savings</act:name>"));
+        assert!(xml.contains("<ts:date>2024-01-15 15:59:00 +0000</ts:date>"));
+    }
+
+    #[test]
+    fn compress_gnucash_xml_can_be_uncompressed() {
+        let compressed = compress_gnucash_xml(BOOK_XML).unwrap();
+
+        assert!(is_gnucash_xml(&compressed));
+        assert!(is_gnucash_xml(BOOK_XML.as_bytes()));
+        assert!(!is_gnucash_xml(b"SQLite format 3\0"));
+        assert_eq!(decompress_gnucash_xml(&compressed).unwrap(), BOOK_XML);
+    }
+
+    #[test]
+    fn add_numeric_reduces_the_sum() {
+        assert_eq!(add_numeric((-1050, 100), (21, 2)), (0, 1));
+        assert_eq!(add_numeric((1, 3), (1, 6)), (1, 2));
+    }
+
+    #[test]
+    fn parse_gnucash_xml_lists_what_it_skips() {
+        let xml = BOOK_XML.replace(
+            "</gnc:book>",
+            "<gnc:budget version=\"2.0.0\"/>\n<gnc:budget version=\"2.0.0\"/>\n\
+             <gnc:GncInvoice version=\"2.0.0\"/>\n</gnc:book>",
+        );
+
+        assert!(parse_gnucash_xml(BOOK_XML).unwrap().skipped.is_empty());
+        assert_eq!(parse_gnucash_xml(&xml).unwrap().skipped, vec!["2 budgets", "1 invoices"]);
+    }
+
+    #[test]
+    fn parse_gnucash_xml_rejects_other_xml() {
+        assert!(parse_gnucash_xml("<gnc-v2/>").is_err());
+        assert!(parse_gnucash_xml("<html></html>").is_err());
+    }
+}
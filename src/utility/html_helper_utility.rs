@@ -13,6 +13,10 @@ use std::convert::TryInto;
 use crate::database_tables::accounts_manager::Account;
 use crate::database_tables::*;
 use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
+use crate::utility::gnucash_xml_helper_utility;
+use crate::utility::journal_helper_utility;
+use crate::utility::json_book_helper_utility;
 use crate::utility::js_helper_utility as js;
 use chrono::Duration;
 use chrono::prelude::*;
@@ -65,6 +69,172 @@ pub fn load_reports_into_body() {
             .unchecked_ref(),
     ));
     reports_last_30_days_report_button_on_click.forget();
+
+    //Setup the date range for the reports that need one
+    let today = Local::now().naive_local().date();
+    for (input_id, label_text, value) in [
+        (
+            "reports_from_date_input",
+            "From: ",
+            NaiveDate::from_ymd_opt(today.year(), 1, 1).unwrap(),
+        ),
+        ("reports_thru_date_input", "Thru: ", today),
+    ] {
+        let label = document_create_element("label");
+        label.set_inner_html(label_text);
+        let input = document_create_element("input")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap();
+        input.set_type("date");
+        input.set_id(input_id);
+        input.set_value(&value.format("%Y-%m-%d").to_string());
+        label.append_child(&input).unwrap();
+        reports_div.append_child(&label).unwrap();
+    }
+
+    //Setup the sales tax summary report
+    let reports_tax_summary_report_button = document_create_element("button");
+    reports_tax_summary_report_button.set_inner_html("Sales Tax Summary");
+    reports_tax_summary_report_button.set_id("reports_tax_summary_report_button");
+    reports_div
+        .append_child(&reports_tax_summary_report_button)
+        .unwrap();
+
+    let reports_tax_summary_report_button_on_click = Closure::wrap(Box::new(move || {
+        match reports_date_range() {
+            Ok((from_date, thru_date)) => {
+                reports_screen::display_tax_summary_report(from_date, thru_date)
+            }
+            Err(error) => display_error_banner(&error),
+        }
+    }) as Box<dyn Fn()>);
+
+    reports_tax_summary_report_button.set_onclick(Some(
+        reports_tax_summary_report_button_on_click
+            .as_ref()
+            .unchecked_ref(),
+    ));
+    reports_tax_summary_report_button_on_click.forget();
+
+    //Setup the aging report
+    let reports_aging_report_button = document_create_element("button");
+    reports_aging_report_button.set_inner_html("A/R & A/P Aging");
+    reports_aging_report_button.set_id("reports_aging_report_button");
+    reports_div.append_child(&reports_aging_report_button).unwrap();
+
+    let reports_aging_report_button_on_click = Closure::wrap(Box::new(move || {
+        reports_screen::display_aging_report();
+    }) as Box<dyn Fn()>);
+
+    reports_aging_report_button.set_onclick(Some(
+        reports_aging_report_button_on_click.as_ref().unchecked_ref(),
+    ));
+    reports_aging_report_button_on_click.forget();
+
+    //Setup the plain text journal export, for the date range or the whole book
+    let reports_journal_format_select = document_create_element("select")
+        .dyn_into::<web_sys::HtmlSelectElement>()
+        .unwrap();
+    reports_journal_format_select.set_id("reports_journal_format_select");
+    reports_journal_format_select.set_inner_html(
+        "<option value='ledger'>ledger</option>
+         <option value='hledger'>hledger</option>
+         <option value='beancount'>beancount</option>",
+    );
+    reports_div
+        .append_child(&reports_journal_format_select)
+        .unwrap();
+
+    let reports_journal_whole_book_label = document_create_element("label");
+    reports_journal_whole_book_label.set_inner_html("Whole Book? ");
+    let reports_journal_whole_book_checkbox = document_create_element("input")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    reports_journal_whole_book_checkbox.set_type("checkbox");
+    reports_journal_whole_book_checkbox.set_id("reports_journal_whole_book_checkbox");
+    reports_journal_whole_book_label
+        .append_child(&reports_journal_whole_book_checkbox)
+        .unwrap();
+    reports_div
+        .append_child(&reports_journal_whole_book_label)
+        .unwrap();
+
+    let reports_export_journal_button = document_create_element("button");
+    reports_export_journal_button.set_inner_html("Export Journal");
+    reports_export_journal_button.set_id("reports_export_journal_button");
+    reports_div
+        .append_child(&reports_export_journal_button)
+        .unwrap();
+
+    let reports_export_journal_button_on_click = Closure::wrap(Box::new(move || {
+        let format_name = document_query_selector("#reports_journal_format_select")
+            .dyn_into::<web_sys::HtmlSelectElement>()
+            .unwrap()
+            .value();
+        let Some(format) = journal_helper_utility::JournalFormat::from_name(&format_name) else {
+            display_error_banner(&MoneyManagerError::Validation(format!(
+                "The journal format '{}' is not supported.",
+                format_name
+            )));
+            return;
+        };
+
+        let whole_book = document_query_selector("#reports_journal_whole_book_checkbox")
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap()
+            .checked();
+        let date_range = if whole_book {
+            Ok((None, None))
+        } else {
+            reports_date_range().map(|(from_date, thru_date)| (Some(from_date), Some(thru_date)))
+        };
+
+        match date_range.and_then(|(from_date, thru_date)| {
+            journal_helper_utility::export_database_to_journal(format, from_date, thru_date)
+        }) {
+            Ok(journal) => download_file_with_name(
+                &format!("money_manager_export.{}", format.file_extension()),
+                &journal,
+            ),
+            Err(error) => display_error_banner(&error),
+        }
+    }) as Box<dyn Fn()>);
+
+    reports_export_journal_button.set_onclick(Some(
+        reports_export_journal_button_on_click
+            .as_ref()
+            .unchecked_ref(),
+    ));
+    reports_export_journal_button_on_click.forget();
+}
+
+/// reports_date_range returns the from and thru dates entered on the reports screen.
+pub fn reports_date_range() -> Result<(NaiveDate, NaiveDate), MoneyManagerError> {
+    let mut dates = Vec::new();
+
+    for input_id in ["#reports_from_date_input", "#reports_thru_date_input"] {
+        let value = document_query_selector(input_id)
+            .dyn_into::<web_sys::HtmlInputElement>()
+            .unwrap()
+            .value();
+        match NaiveDate::parse_from_str(&value, "%Y-%m-%d") {
+            Ok(date) => dates.push(date),
+            Err(_) => {
+                return Err(MoneyManagerError::Validation(format!(
+                    "The given date '{}' is not a valid date.",
+                    value
+                )));
+            }
+        }
+    }
+
+    if dates[0] > dates[1] {
+        return Err(MoneyManagerError::Validation(
+            "The from date needs to be before the thru date.".to_string(),
+        ));
+    }
+
+    Ok((dates[0], dates[1]))
 }
 
 /// generate_html_for_report_for_account_type generates an HTML report for the given
@@ -73,12 +243,12 @@ pub fn generate_html_for_report_for_account_type(
     from_date: chrono::NaiveDateTime,
     thru_date: chrono::NaiveDateTime,
     account_type: String,
-) -> String {
+) -> Result<String, MoneyManagerError> {
     let report_splits = splits_manager::retrieve_splits_for_dates_report(
         from_date,
         thru_date,
         account_type.clone(),
-    );
+    )?;
     let mut final_html = String::from("");
     //Get the categories
     let mut categories = Vec::<String>::new();
@@ -204,8 +374,7 @@ pub fn generate_html_for_report_for_account_type(
         let split_amount = dhu::format_money(split_amount);
         final_html += &format!(
             "<li>{}: {} - {}:{}</li>",
-            chrono::NaiveDateTime::parse_from_str(&split.post_date, "%Y%m%d%H%M%S")
-                .unwrap()
+            dhu::convert_string_to_date(&split.post_date)?
                 .format("%Y-%m-%d")
                 .to_string(),
             split.description,
@@ -216,7 +385,7 @@ pub fn generate_html_for_report_for_account_type(
 
     final_html += "</ul>";
 
-    return final_html;
+    Ok(final_html)
 }
 
 /// display_last_30_days_report displays the last 30 days worth of data in a nice
@@ -246,13 +415,20 @@ pub fn display_last_30_days_report() {
 
     let mut final_html = String::from("");
 
-    //First setup the Expenses Report Part
-    final_html +=
-        &generate_html_for_report_for_account_type(from_date, thru_date, String::from("EXPENSE"));
-
-    //Next we setup the Income Report Part
-    final_html +=
-        &generate_html_for_report_for_account_type(from_date, thru_date, String::from("INCOME"));
+    //First setup the Expenses Report Part, then the Income Report Part
+    for account_type in ["EXPENSE", "INCOME"] {
+        match generate_html_for_report_for_account_type(
+            from_date,
+            thru_date,
+            String::from(account_type),
+        ) {
+            Ok(html) => final_html += &html,
+            Err(error) => {
+                display_error_banner(&error);
+                return;
+            }
+        }
+    }
 
     body_div.set_inner_html(&final_html);
 }
@@ -271,11 +447,15 @@ pub fn load_accounts_with_balances_into_memory(
         .expect("Failed to get filelist from File Input!");
     //Do not allow blank inputs
     if filelist.length() < 1 {
-        js::alert("Please select at least one file.");
+        display_error_banner(&MoneyManagerError::Validation(
+            "Please select at least one file.".to_string(),
+        ));
         return;
     }
     if filelist.get(0) == None {
-        js::alert("Please select a valid file");
+        display_error_banner(&MoneyManagerError::Validation(
+            "Please select a valid file".to_string(),
+        ));
         return;
     }
 
@@ -284,7 +464,9 @@ pub fn load_accounts_with_balances_into_memory(
     let file_reader: web_sys::FileReader = match web_sys::FileReader::new() {
         Ok(f) => f,
         Err(e) => {
-            js::alert("There was an error creating a file reader");
+            display_error_banner(&MoneyManagerError::Validation(
+                "There was an error creating a file reader".to_string(),
+            ));
             js::log(&JsValue::as_string(&e).expect("error converting jsvalue to string."));
             web_sys::FileReader::new().expect("")
         }
@@ -297,24 +479,86 @@ pub fn load_accounts_with_balances_into_memory(
         //let len = array.byte_length() as usize;
         //js::log(&format!("Blob received {}bytes: {:?}", len, array.to_vec()));
 
-        //Check for a valid database now that we have the bytes
-        // match dhu::valid_database(array.clone()) {
-        //     Ok(()) => {},
-        //     Err(error_message) => {
-        //         js::alert(&error_message);
-        //         hide_loading_message();
-        //         return;
-        //     }
-        // }
-
-        if crate::DATABASE.lock().unwrap().len() > 0 {
-            crate::DATABASE.lock().unwrap().clear();
+        //Check for a valid database now that we have the bytes, unless it's a GnuCash XML book,
+        //or a JSON book
+        let bytes = array.to_vec();
+        let gnucash_xml = gnucash_xml_helper_utility::is_gnucash_xml(&bytes);
+        let json_book = !gnucash_xml && json_book_helper_utility::is_json_book(&bytes);
+        if !gnucash_xml && !json_book && let Err(error) = dhu::valid_database(array.clone()) {
+            file_input.set_files(None);
+            file_input.set_value("");
+            hide_loading_message();
+            display_error_banner(&error);
+            return;
         }
 
-        crate::DATABASE
-            .lock()
-            .unwrap()
-            .push(dhu::Database::new(array.clone()));
+        //Keep the current book, in case the new file can't be read
+        let previous_databases = std::mem::take(&mut *crate::DATABASE.lock().unwrap());
+        let previous_read_only = std::mem::take(&mut *crate::READ_ONLY.lock().unwrap());
+        let previous_skipped = std::mem::take(&mut *crate::GNUCASH_XML_SKIPPED.lock().unwrap());
+
+        let loaded = if gnucash_xml {
+            gnucash_xml_helper_utility::load_gnucash_xml_into_new_database(&bytes)
+        } else if json_book {
+            json_book_helper_utility::load_json_book_into_new_database(&bytes)
+        } else {
+            crate::DATABASE
+                .lock()
+                .unwrap()
+                .push(dhu::Database::new(array.clone()));
+            Ok(())
+        };
+
+        //Check it's a GnuCash book, which GnuCash version wrote it, and whether its tables
+        //can be read, then index it for the registers, and reports
+        let version_report = loaded.and_then(|_| dhu::valid_gnucash_database()).and_then(|_| {
+            let version_report = versions_manager::check_versions(
+                &versions_manager::retrieve_all_versions().unwrap_or_default(),
+            );
+            if version_report.errors.is_empty() {
+                dhu::create_missing_indexes().map(|_| version_report)
+            } else {
+                Err(MoneyManagerError::Validation(version_report.errors.join("\n")))
+            }
+        });
+        let version_report = match version_report {
+            Ok(version_report) => version_report,
+            Err(error) => {
+                *crate::DATABASE.lock().unwrap() = previous_databases;
+                *crate::READ_ONLY.lock().unwrap() = previous_read_only;
+                *crate::GNUCASH_XML_SKIPPED.lock().unwrap() = previous_skipped;
+                file_input.set_files(None);
+                file_input.set_value("");
+                hide_loading_message();
+                display_error_banner(&MoneyManagerError::Validation(format!(
+                    "This file can't be opened. {}",
+                    error
+                )));
+                return;
+            }
+        };
+        //Warn when GnuCash has the file open, and lock it for ourselves. Our own lock rows are
+        //left by copies saved before they were finished, so they are replaced without asking.
+        let locks = gnclock_manager::locks_held_by_others(
+            gnclock_manager::retrieve_all_locks().unwrap_or_default(),
+        );
+        let read_only = match locks.first() {
+            Some(lock) => !js::confirm(&format!(
+                "This file is open in GnuCash on '{}' (process {}). Opening it here could lose \
+                 changes made there.\n\n\
+                 Press OK to take over the file, or Cancel to open it read-only.",
+                lock.hostname, lock.pid
+            )),
+            None => false,
+        };
+        *crate::READ_ONLY.lock().unwrap() = read_only;
+        *crate::GNUCASH_XML.lock().unwrap() = gnucash_xml;
+        crate::BULK_ACTION_UNDO.lock().unwrap().clear();
+        let lock_result = if read_only {
+            Ok(true)
+        } else {
+            gnclock_manager::save_new_and_delete_current(gnclock_manager::Lock::new_for_this_app())
+        };
 
         //Create a new input with the filename
         let money_manager_filename_input = document_query_selector("#money_manager_filename_input")
@@ -327,6 +571,41 @@ pub fn load_accounts_with_balances_into_memory(
         file_input.set_value("");
 
         hide_loading_message();
+
+        //Report which version of GnuCash wrote the file, and whether it's read-only
+        let footer_div = document_query_selector("#footer");
+        footer_div.set_text_content(Some(&format!(
+            "{}{}",
+            match &version_report.gnucash_version {
+                _ if gnucash_xml => "This file is a GnuCash XML book.".to_string(),
+                _ if json_book => "This file is a JSON book, and will be saved as a GnuCash SQLite file.".to_string(),
+                Some(gnucash_version) => format!("This file was written by GnuCash {}.", gnucash_version),
+                None => "This file was not written by GnuCash.".to_string(),
+            },
+            if read_only { " It is open read-only." } else { "" }
+        )));
+
+        //Show the version warnings in the banner, along with any error there is
+        let error = if read_only { Some(MoneyManagerError::ReadOnly) } else { lock_result.err() };
+        let mut warnings = version_report.warnings.join("\n");
+        let skipped = crate::GNUCASH_XML_SKIPPED.lock().unwrap().clone();
+        if !skipped.is_empty() {
+            warnings.push_str(&format!(
+                "{}These records weren't read, so this book will be saved as a GnuCash SQLite \
+                 file instead: {}.",
+                if warnings.is_empty() { "" } else { "\n" },
+                skipped.join(", ")
+            ));
+        }
+        match error {
+            Some(error) if !warnings.is_empty() => display_error_banner(
+                &MoneyManagerError::Validation(format!("{}\n{}", error, warnings)),
+            ),
+            Some(error) => display_error_banner(&error),
+            None if !warnings.is_empty() => display_message_banner(&warnings),
+            None => {}
+        }
+
         if load_accounts_into_body_after_load {
             //load_accounts_with_balances_from_memory();
         }
@@ -432,9 +711,10 @@ pub fn wireup_controls() {
                 let slots: Vec<slots_manager::Slot> = Vec::new();
                 settings_screen::load_settings_into_body(slots);
             } else {
-                let slots = slots_manager::load_slots_for_name("settings".to_string())
-                    .expect("Failed to load slots for the name 'settings'!");
-                settings_screen::load_settings_into_body(slots);
+                match slots_manager::load_slots_for_name("settings".to_string()) {
+                    Ok(slots) => settings_screen::load_settings_into_body(slots),
+                    Err(error) => display_error_banner(&error),
+                }
             }
         }) as Box<dyn Fn()>);
 
@@ -453,6 +733,17 @@ pub fn wireup_controls() {
         main_menu_reports.set_onclick(Some(main_menu_reports_on_click.as_ref().unchecked_ref()));
         main_menu_reports_on_click.forget();
     }
+
+    {
+        //Setup the business button handler
+        let main_menu_business_on_click = Closure::wrap(Box::new(move || {
+            business_screen::load_business_into_body();
+        }) as Box<dyn Fn()>);
+
+        let main_menu_business = document_query_selector("#main_menu_business");
+        main_menu_business.set_onclick(Some(main_menu_business_on_click.as_ref().unchecked_ref()));
+        main_menu_business_on_click.forget();
+    }
 }
 
 /// show_loading_message shows a loading message with the String you choose to display.
@@ -534,8 +825,8 @@ pub fn display_transactions_older_than_one_year() -> bool {
                 return true;
             }
         }
-        Err(e) => {
-            js::alert(&e);
+        Err(error) => {
+            display_error_banner(&error);
         }
     }
 
@@ -546,43 +837,102 @@ pub fn display_transactions_older_than_one_year() -> bool {
 /// Currently used to pass data between the web assembly, and the javascript caller.
 #[allow(dead_code)]
 #[wasm_bindgen]
-pub fn get_database_array() -> js_sys::Uint8Array {
-    if crate::DATABASE.lock().unwrap().len() == 0 {
-        panic!("Please select a database to refresh your accounts view.");
+pub fn get_database_array() -> Result<js_sys::Uint8Array, JsError> {
+    match crate::DATABASE.lock().unwrap().first() {
+        Some(database) => Ok(database.export()),
+        None => Err(JsError::new(&MoneyManagerError::NoDatabase.to_string())),
     }
-
-    return crate::DATABASE.lock().unwrap()[0].export();
 }
 
 /// get_database_array gets you a Uint8Array of the database. Crashes all major browsers.
 /// Currently used to pass data between the web assembly, and the javascript caller.
 #[allow(dead_code)]
 #[wasm_bindgen]
-pub fn get_database_blob() -> web_sys::Blob {
-    if crate::DATABASE.lock().unwrap().len() == 0 {
-        panic!("Please select a database to refresh your accounts view.");
-    }
+pub fn get_database_blob() -> Result<web_sys::Blob, JsError> {
+    let array = get_database_array()?;
 
-    let blob =
-        web_sys::Blob::new_with_u8_array_sequence(&crate::DATABASE.lock().unwrap()[0].export())
-            .unwrap();
-
-    return blob;
+    web_sys::Blob::new_with_u8_array_sequence(&array)
+        .map_err(|error| JsError::new(&MoneyManagerError::from(error).to_string()))
 }
 
 /// save_database allows the user to save the database to a file. Doesn't currently work in firefox android.
 pub fn save_database() {
     if crate::DATABASE.lock().unwrap().len() == 0 {
-        js::alert("Please select a database to refresh your accounts view.");
+        display_error_banner(&MoneyManagerError::NoDatabase);
+        return;
+    }
+    if *crate::READ_ONLY.lock().unwrap() {
+        display_error_banner(&MoneyManagerError::ReadOnly);
         return;
     }
+
+    let mut filename = loaded_filename();
+
+    //A GnuCash XML book keeps its lock in a separate file, so there is no lock to save with it.
+    //It's only saved over when it can hold everything in the book, otherwise a SQLite file is.
+    if *crate::GNUCASH_XML.lock().unwrap() {
+        let left_out = match gnucash_xml_helper_utility::retrieve_records_left_out_of_xml() {
+            Ok(left_out) => left_out,
+            Err(error) => {
+                display_error_banner(&error);
+                return;
+            }
+        };
+        if left_out.is_empty() {
+            match gnucash_xml_helper_utility::export_database_to_gnucash_xml() {
+                Ok(bytes) => download_file_with_name(&filename, &bytes),
+                Err(error) => display_error_banner(&error),
+            }
+            return;
+        }
+        if !js::confirm(&format!(
+            "This book can't be saved as a GnuCash XML book without losing these records: {}.\n\n\
+             Press OK to save it as a GnuCash SQLite file instead, or Cancel to not save it.",
+            left_out.join(", ")
+        )) {
+            return;
+        }
+        filename = format!(
+            "{}.sqlite.gnucash",
+            filename.trim_end_matches(".gnucash").trim_end_matches(".xml")
+        );
+    }
+
+    //A final copy is saved without our lock, so GnuCash can open it without a warning. Other
+    //copies keep the lock, so GnuCash warns that they are still being worked on.
+    let final_copy = js::confirm(
+        "Is this your final copy?\n\nPress OK to unlock the file, so GnuCash can open it, \
+         or Cancel to keep it locked while you keep working.",
+    );
+    if final_copy && let Err(error) = gnclock_manager::delete_all_locks() {
+        display_error_banner(&error);
+        return;
+    }
+
     let blob = crate::DATABASE.lock().unwrap()[0].export();
-    let b64 = general_purpose::STANDARD_NO_PAD.encode(blob.to_vec());
 
-    let filename = document_query_selector("#money_manager_filename_input")
+    //Keep the file locked here, in case there are more changes after all
+    if final_copy
+        && let Err(error) =
+            gnclock_manager::save_new_and_delete_current(gnclock_manager::Lock::new_for_this_app())
+    {
+        display_error_banner(&error);
+    }
+
+    download_file_with_name(&filename, &blob.to_vec());
+}
+
+/// loaded_filename returns the name of the loaded file.
+fn loaded_filename() -> String {
+    document_query_selector("#money_manager_filename_input")
         .dyn_into::<web_sys::HtmlInputElement>()
         .unwrap()
-        .value();
+        .value()
+}
+
+/// download_file_with_name downloads the given bytes as a file with the given name.
+pub fn download_file_with_name(filename: &str, bytes: &[u8]) {
+    let b64 = general_purpose::STANDARD_NO_PAD.encode(bytes);
 
     let body = document_query_selector("#body");
     let div = document_create_element("div");
@@ -605,6 +955,52 @@ pub fn save_database() {
     // js::log(&format!("Blob received {}bytes: {:?}", len, array.to_vec()));
 }
 
+/// display_error_banner shows the given error in a banner at the top of the footer, in place of
+/// any banner already there, until the user dismisses it.
+pub fn display_error_banner(error: &MoneyManagerError) {
+    js::log(&format!("{:?}", error));
+    display_banner("error_banner", "alert", &error.to_string());
+}
+
+/// display_message_banner shows the given message, that isn't an error, in a banner at the top of
+/// the footer, in place of any banner already there, until the user dismisses it.
+pub fn display_message_banner(message: &str) {
+    display_banner("message_banner", "status", message);
+}
+
+/// display_banner shows a banner with the given class name, role, and message at the top of the
+/// footer, in place of any banner already there, with a button to dismiss it.
+fn display_banner(class_name: &str, role: &str, message: &str) {
+    let footer_div = document_query_selector("#footer");
+    if let Ok(Some(banner)) = footer_div.query_selector(".error_banner, .message_banner") {
+        banner.remove();
+    }
+
+    let banner = document_create_element("div");
+    banner.set_class_name(class_name);
+    banner.set_attribute("role", role).unwrap();
+
+    let banner_message = document_create_element("span");
+    banner_message.set_class_name(&format!("{class_name}_message"));
+    banner_message.set_text_content(Some(message));
+    banner.append_child(&banner_message).unwrap();
+
+    let banner_dismiss_button = document_create_element("button");
+    banner_dismiss_button.set_class_name(&format!("{class_name}_dismiss_button"));
+    banner_dismiss_button.set_inner_html("Dismiss");
+    banner.append_child(&banner_dismiss_button).unwrap();
+
+    let banner_to_remove = banner.clone();
+    let banner_dismiss_button_on_click = Closure::wrap(Box::new(move || {
+        banner_to_remove.remove();
+    }) as Box<dyn Fn()>);
+    banner_dismiss_button
+        .set_onclick(Some(banner_dismiss_button_on_click.as_ref().unchecked_ref()));
+    banner_dismiss_button_on_click.forget();
+
+    footer_div.prepend_with_node_1(&banner).unwrap();
+}
+
 /// hide_loading_message attempts to hide the loading message.
 pub fn hide_loading_message() {
     let loading_message = document_query_selector("#loading_message");
@@ -693,3 +1089,60 @@ pub fn document_create_element(tag: &str) -> web_sys::HtmlElement {
         .dyn_into::<web_sys::HtmlElement>()
         .expect(&error_message);
 }
+
+/// document_query_selector_all returns every element under the given parent matching the
+/// given query selector.
+pub fn document_query_selector_all(
+    parent: &web_sys::HtmlElement,
+    query_selector: &str,
+) -> Vec<web_sys::HtmlElement> {
+    let error_message: String = format!("was not able to find {}", query_selector);
+
+    let node_list = parent.query_selector_all(query_selector).expect(&error_message);
+
+    (0..node_list.length())
+        .filter_map(|index| node_list.get(index))
+        .filter_map(|node| node.dyn_into::<web_sys::HtmlElement>().ok())
+        .collect()
+}
+
+/// element_query_selector returns the first element under the given parent matching the
+/// given query selector.
+pub fn element_query_selector(
+    parent: &web_sys::HtmlElement,
+    query_selector: &str,
+) -> web_sys::HtmlElement {
+    let error_message: String = format!("was not able to find {}", query_selector);
+
+    parent
+        .query_selector(query_selector)
+        .expect(&error_message)
+        .expect(&error_message)
+        .dyn_into::<web_sys::HtmlElement>()
+        .expect(&error_message)
+}
+
+/// document_create_select creates a select with the given (value, text) options, with the
+/// option matching selected_value selected.
+pub fn document_create_select(
+    options: &[(String, String)],
+    selected_value: &str,
+) -> web_sys::HtmlSelectElement {
+    let select = document_create_element("select")
+        .dyn_into::<web_sys::HtmlSelectElement>()
+        .expect("Failed to create select!");
+
+    for (value, text) in options {
+        let option = document_create_element("option")
+            .dyn_into::<web_sys::HtmlOptionElement>()
+            .expect("Failed to create option!");
+        option.set_value(value);
+        option.set_text_content(Some(text));
+        option.set_selected(value == selected_value);
+        select
+            .append_child(&option)
+            .expect("Failed to append option to select!");
+    }
+
+    select
+}
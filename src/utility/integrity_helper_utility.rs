@@ -0,0 +1,351 @@
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::database_tables::accounts_manager::{self, Account, AccountType, Bool};
+use crate::database_tables::commodities_manager;
+use crate::database_tables::slots_manager;
+use crate::database_tables::splits_manager::{self, Split};
+use crate::database_tables::transactions_manager;
+use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
+use crate::utility::sql_helper_utility as shu;
+
+/// ProblemKind is the kind of problem the integrity check can find in a book.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ProblemKind {
+    ImbalancedTransaction,
+    SplitWithMissingAccount,
+    SplitWithMissingTransaction,
+    AccountWithMissingCommodity,
+    SlotForMissingObject,
+    DuplicateRootAccount,
+}
+
+impl ProblemKind {
+    /// title returns a short description of the problem, for the user.
+    pub fn title(&self) -> &'static str {
+        match self {
+            ProblemKind::ImbalancedTransaction => "Imbalanced transaction",
+            ProblemKind::SplitWithMissingAccount => "Split with a missing account",
+            ProblemKind::SplitWithMissingTransaction => "Split with a missing transaction",
+            ProblemKind::AccountWithMissingCommodity => "Account with a missing commodity",
+            ProblemKind::SlotForMissingObject => "Slots for a deleted item",
+            ProblemKind::DuplicateRootAccount => "Duplicate root account",
+        }
+    }
+
+    /// repair_description returns what repairing the problem will do, for the user.
+    pub fn repair_description(&self) -> &'static str {
+        match self {
+            ProblemKind::ImbalancedTransaction => "Add an Imbalance split",
+            ProblemKind::SplitWithMissingAccount => "Move the split to an Orphan account",
+            ProblemKind::SplitWithMissingTransaction => "Delete the split",
+            ProblemKind::AccountWithMissingCommodity => "Use the book's currency",
+            ProblemKind::SlotForMissingObject => "Delete the slots",
+            ProblemKind::DuplicateRootAccount => "Move its accounts to the book's root, and delete it",
+        }
+    }
+}
+
+/// IntegrityProblem is one problem the integrity check found in the book.
+#[derive(Debug, Deserialize, Clone)]
+pub struct IntegrityProblem {
+    pub kind: ProblemKind,             //kind is what is wrong.
+    pub guid: Uuid,                    //guid is the guid of the transaction, split, account or slot object that is wrong.
+    pub account_guid: Option<Uuid>,    //account_guid is an account the problem can be looked at in, if there is one.
+    pub description: Option<String>,   //description describes the offending item. (Ex: a transaction's description)
+    pub amount: f64,                   //amount is the imbalance, or the split's value, or 0.
+}
+
+/// GuidRow holds just a guid from a query.
+#[derive(Debug, Deserialize)]
+struct GuidRow {
+    guid: Uuid,
+}
+
+/// ValueRow holds the value of a split and its transaction's currency.
+#[derive(Debug, Deserialize)]
+struct ValueRow {
+    currency_guid: Uuid,
+    value_num: i64,
+    value_denom: i64,
+}
+
+/// SplitAccountRow holds the guid, and currency of a split's transaction, if it still exists.
+#[derive(Debug, Deserialize)]
+struct SplitAccountRow {
+    tx_guid: Option<Uuid>,
+    currency_guid: Option<Uuid>,
+}
+
+/// retrieve_integrity_problems checks the book, and retrieves everything wrong with it.
+pub fn retrieve_integrity_problems() -> Result<Vec<IntegrityProblem>, MoneyManagerError> {
+    dhu::query_rows(&shu::load_integrity_problems(), None)
+}
+
+/// repair_integrity_problem fixes the given problem, the way its repair_description says.
+/// Transactions in the closed period aren't changed, and return a Validation error saying so.
+pub fn repair_integrity_problem(problem: &IntegrityProblem) -> Result<bool, MoneyManagerError> {
+    dhu::require_database()?;
+
+    match problem.kind {
+        ProblemKind::ImbalancedTransaction => add_imbalance_split(problem.guid),
+        ProblemKind::SplitWithMissingAccount => move_split_to_orphan_account(problem.guid),
+        ProblemKind::SplitWithMissingTransaction => {
+            run_with_guid("DELETE FROM splits WHERE guid=?", problem.guid)?;
+            Ok(true)
+        }
+        ProblemKind::AccountWithMissingCommodity => {
+            let currency_guid = commodities_manager::retrieve_default_currency_guid()?;
+            let binding_object = serde_wasm_bindgen::to_value(&vec![
+                dhu::convert_guid_to_sqlite_string(&currency_guid),
+                dhu::convert_guid_to_sqlite_string(&currency_guid),
+                dhu::convert_guid_to_sqlite_string(&problem.guid),
+            ])?;
+            dhu::execute(
+                "UPDATE accounts SET commodity_guid=?,
+                        commodity_scu=(SELECT fraction FROM commodities WHERE guid=?)
+                 WHERE guid=?",
+                Some(binding_object),
+            )?;
+            Ok(true)
+        }
+        ProblemKind::SlotForMissingObject => {
+            delete_slots_for_obj_guid(problem.guid)?;
+            Ok(true)
+        }
+        ProblemKind::DuplicateRootAccount => {
+            let root_account_guid = retrieve_book_root_account_guid()?;
+            if root_account_guid == problem.guid {
+                return Err(MoneyManagerError::Validation(
+                    "This is the book's root account, so it can't be removed.".to_string(),
+                ));
+            }
+            let binding_object = serde_wasm_bindgen::to_value(&vec![
+                dhu::convert_guid_to_sqlite_string(&root_account_guid),
+                dhu::convert_guid_to_sqlite_string(&problem.guid),
+            ])?;
+            dhu::execute(
+                "UPDATE accounts SET parent_guid=? WHERE parent_guid=?",
+                Some(binding_object),
+            )?;
+            run_with_guid("DELETE FROM accounts WHERE guid=?", problem.guid)?;
+            delete_slots_for_obj_guid(problem.guid)?;
+            Ok(true)
+        }
+    }
+}
+
+/// calculate_imbalance_split_amount calculates the (num, denom) amount of the split that
+/// exactly balances the given (value_num, value_denom) values, in units of the given fraction
+/// when the imbalance can be written in them, or (0, fraction) when they're already balanced.
+pub fn calculate_imbalance_split_amount(
+    values: &[(i64, i64)],
+    fraction: i64,
+) -> Result<(i64, i64), MoneyManagerError> {
+    let (num, denom) = dhu::sum_fractions(values)?;
+    if num == 0 {
+        return Ok((0, fraction));
+    }
+    if fraction > 0 && (num as i128 * fraction as i128) % denom as i128 == 0 {
+        return Ok((-((num as i128 * fraction as i128) / denom as i128) as i64, fraction));
+    }
+    Ok((-num, denom))
+}
+
+/// add_imbalance_split balances the transaction with the given guid with a split to the
+/// Imbalance account for its currency, like GnuCash does.
+fn add_imbalance_split(transaction_guid: Uuid) -> Result<bool, MoneyManagerError> {
+    transactions_manager::check_transaction_is_open(transaction_guid, None)?;
+
+    let binding_object =
+        serde_wasm_bindgen::to_value(&vec![dhu::convert_guid_to_sqlite_string(&transaction_guid)])?;
+    let value_rows: Vec<ValueRow> = dhu::query_rows(
+        "SELECT t.currency_guid AS currency_guid, s.value_num AS value_num,
+                s.value_denom AS value_denom
+         FROM splits s JOIN transactions t ON t.guid = s.tx_guid
+         WHERE s.tx_guid=?",
+        Some(binding_object),
+    )?;
+
+    let currency_guid = value_rows
+        .last()
+        .map(|value| value.currency_guid)
+        .ok_or(MoneyManagerError::NotFound(
+            "This transaction has no splits to balance.".to_string(),
+        ))?;
+    let values: Vec<(i64, i64)> = value_rows
+        .iter()
+        .map(|value| (value.value_num, value.value_denom))
+        .collect();
+    let fraction = commodities_manager::retrieve_commodity_for_guid(currency_guid)?.fraction;
+    //The split balances the exact imbalance the check finds, even when it's below the fraction
+    let (amount_num, amount_denom) = calculate_imbalance_split_amount(&values, fraction)?;
+    if amount_num == 0 {
+        return Ok(true);
+    }
+
+    let account_guid = retrieve_or_create_account_for_currency("Imbalance", currency_guid)?;
+
+    splits_manager::save_split(Split {
+        guid: Uuid::new_v4(),
+        tx_guid: transaction_guid,
+        account_guid,
+        memo: String::new(),
+        action: String::new(),
+        reconcile_state: "n".to_string(),
+        reconcile_date: None,
+        value_num: amount_num,
+        value_denom: amount_denom,
+        quantity_num: amount_num,
+        quantity_denom: amount_denom,
+        lot_guid: None,
+    })
+}
+
+/// move_split_to_orphan_account moves the split with the given guid, whose account is missing,
+/// to the Orphan account for its transaction's currency, like GnuCash does.
+fn move_split_to_orphan_account(split_guid: Uuid) -> Result<bool, MoneyManagerError> {
+    let binding_object =
+        serde_wasm_bindgen::to_value(&vec![dhu::convert_guid_to_sqlite_string(&split_guid)])?;
+    let split_accounts: Vec<SplitAccountRow> = dhu::query_rows(
+        "SELECT t.guid AS tx_guid, t.currency_guid AS currency_guid
+         FROM splits s LEFT JOIN transactions t ON t.guid = s.tx_guid
+         WHERE s.guid=?",
+        Some(binding_object),
+    )?;
+    if let Some(tx_guid) = split_accounts.last().and_then(|split| split.tx_guid) {
+        transactions_manager::check_transaction_is_open(tx_guid, None)?;
+    }
+
+    let currency_guid = match split_accounts.last().and_then(|split| split.currency_guid) {
+        Some(currency_guid) => currency_guid,
+        None => commodities_manager::retrieve_default_currency_guid()?,
+    };
+    let account_guid = retrieve_or_create_account_for_currency("Orphan", currency_guid)?;
+
+    let binding_object = serde_wasm_bindgen::to_value(&vec![
+        dhu::convert_guid_to_sqlite_string(&account_guid),
+        dhu::convert_guid_to_sqlite_string(&split_guid),
+    ])?;
+    dhu::execute(
+        "UPDATE splits SET account_guid=? WHERE guid=?",
+        Some(binding_object),
+    )?;
+
+    Ok(true)
+}
+
+/// retrieve_book_root_account_guid retrieves the guid of the book's root account. Without a
+/// books row, it's the root account, other than the template root, with the most accounts.
+pub fn retrieve_book_root_account_guid() -> Result<Uuid, MoneyManagerError> {
+    let root_account: GuidRow = dhu::query_first_row(
+        "SELECT r.guid AS guid FROM accounts r
+         WHERE r.account_type = 'ROOT'
+            AND (r.guid IN (SELECT root_account_guid FROM books)
+                 OR (NOT EXISTS (SELECT 1 FROM books) AND r.name <> 'Template Root'))
+         ORDER BY (SELECT COUNT(*) FROM accounts c WHERE c.parent_guid = r.guid) DESC
+         LIMIT 1",
+        None,
+        "This book has no root account.",
+    )?;
+
+    Ok(root_account.guid)
+}
+
+/// retrieve_or_create_account_for_currency retrieves the top level account named like
+/// 'Imbalance-USD' for the given prefix and currency, creating it if it doesn't exist yet.
+fn retrieve_or_create_account_for_currency(
+    prefix: &str,
+    currency_guid: Uuid,
+) -> Result<Uuid, MoneyManagerError> {
+    let root_account_guid = retrieve_book_root_account_guid()?;
+    let mnemonic = commodities_manager::retrieve_commodity_for_guid(currency_guid)?.mnemonic;
+    let name = format!("{}-{}", prefix, mnemonic);
+
+    let binding_object = serde_wasm_bindgen::to_value(&vec![
+        name.clone(),
+        dhu::convert_guid_to_sqlite_string(&root_account_guid),
+    ])?;
+    let accounts: Vec<GuidRow> = dhu::query_rows(
+        "SELECT guid FROM accounts WHERE name=? AND parent_guid=? LIMIT 1",
+        Some(binding_object),
+    )?;
+
+    if let Some(account) = accounts.first() {
+        return Ok(account.guid);
+    }
+
+    let account_guid = Uuid::new_v4();
+    accounts_manager::save_new_and_delete_current(Account {
+        guid: account_guid,
+        name,
+        account_type: AccountType::BANK,
+        commodity_guid: Some(currency_guid),
+        commodity_scu: 0,
+        non_std_scu: 0,
+        parent_guid: Some(root_account_guid),
+        code: String::new(),
+        description: String::new(),
+        hidden: Bool::False,
+        placeholder: Bool::False,
+        tags: Default::default(),
+    })?;
+
+    Ok(account_guid)
+}
+
+/// delete_slots_for_obj_guid deletes the slots of the given object, along with the slots of
+/// any of its frames.
+fn delete_slots_for_obj_guid(obj_guid: Uuid) -> Result<(), MoneyManagerError> {
+    let mut obj_guids = vec![obj_guid];
+
+    while let Some(obj_guid) = obj_guids.pop() {
+        let binding_object =
+            serde_wasm_bindgen::to_value(&vec![dhu::convert_guid_to_sqlite_string(&obj_guid)])?;
+        let frames: Vec<GuidRow> = dhu::query_rows(
+            &format!(
+                "SELECT guid_val AS guid FROM slots WHERE obj_guid=? AND slot_type={}
+                    AND guid_val IS NOT NULL",
+                slots_manager::SLOT_TYPE_FRAME
+            ),
+            Some(binding_object),
+        )?;
+
+        obj_guids.extend(frames.iter().map(|frame| frame.guid));
+
+        run_with_guid("DELETE FROM slots WHERE obj_guid=?", obj_guid)?;
+    }
+
+    Ok(())
+}
+
+/// run_with_guid runs the given statement with the given guid as its only parameter.
+fn run_with_guid(sql: &str, guid: Uuid) -> Result<(), MoneyManagerError> {
+    let binding_object =
+        serde_wasm_bindgen::to_value(&vec![dhu::convert_guid_to_sqlite_string(&guid)])?;
+    dhu::execute(sql, Some(binding_object))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calculate_imbalance_split_amount_balances_values() {
+        let amount = |values: &[(i64, i64)]| calculate_imbalance_split_amount(values, 100).unwrap();
+        assert_eq!(amount(&[(1000, 100), (-900, 100)]), (-100, 100));
+        assert_eq!(amount(&[(1000, 100), (-1000, 100)]).0, 0);
+        assert_eq!(amount(&[(1000, 100), (-9995, 1000)]), (-5, 1000));
+    }
+
+    #[test]
+    fn calculate_imbalance_split_amount_keeps_imbalances_below_the_fraction() {
+        //0.001 USD is found by the check, so the repair has to balance it, not round it away
+        let amount = |values: &[(i64, i64)]| calculate_imbalance_split_amount(values, 100).unwrap();
+        assert_eq!(amount(&[(1001, 1000), (-1, 1)]), (-1, 1000));
+        assert_eq!(amount(&[(1, 3)]), (-1, 3));
+        assert_eq!(amount(&[(1010, 1000), (-1, 1)]), (-1, 100));
+    }
+}
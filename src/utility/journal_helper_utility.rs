@@ -0,0 +1,1785 @@
+/// journal_helper_utility writes the book, or a date range of it, as plain text accounting
+/// journals that ledger, hledger, and beancount can read. Accounts are written with their full
+/// colon separated path, amounts are written exactly from the numerators and denominators, and
+/// the notes of each transaction are written as comments. It also reads those journals back
+/// into the book, through the table managers.
+use std::collections::{HashMap, HashSet};
+
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+use crate::database_tables::accounts_manager::{self, Account, AccountType, Bool};
+use crate::database_tables::categorization_rules_manager::{self, CategorizationRule};
+use crate::database_tables::{
+    books_manager, commodities_manager, prices_manager, slots_manager, splits_manager,
+    transactions_manager,
+};
+use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
+use crate::utility::gnucash_xml_helper_utility::{self, XmlAccount, XmlBook};
+
+/// DECIMAL_PLACES_FOR_INEXACT_AMOUNTS is how many decimal places an amount is rounded to when
+/// its denominator can't be written exactly as a decimal. (Ex: 1/3)
+const DECIMAL_PLACES_FOR_INEXACT_AMOUNTS: u32 = 10;
+
+/// BEANCOUNT_OPEN_DATE is when accounts are opened in a beancount journal without transactions.
+const BEANCOUNT_OPEN_DATE: &str = "1970-01-01";
+
+/// JournalFormat is which plain text accounting program the journal is written for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JournalFormat {
+    Ledger,
+    Hledger,
+    Beancount,
+}
+
+impl JournalFormat {
+    /// from_name returns the format with the given name. (Ex: 'beancount')
+    pub fn from_name(name: &str) -> Option<JournalFormat> {
+        match name.to_lowercase().as_str() {
+            "ledger" => Some(JournalFormat::Ledger),
+            "hledger" => Some(JournalFormat::Hledger),
+            "beancount" => Some(JournalFormat::Beancount),
+            _ => None,
+        }
+    }
+
+    /// file_extension returns the extension journals of this format are usually saved with.
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            JournalFormat::Ledger => "ledger",
+            JournalFormat::Hledger => "journal",
+            JournalFormat::Beancount => "beancount",
+        }
+    }
+}
+
+/// export_database_to_journal writes the loaded database as a journal of the given format,
+/// including only the transactions posted from the from_date thru the thru_date when given.
+pub fn export_database_to_journal(
+    format: JournalFormat,
+    from_date: Option<NaiveDate>,
+    thru_date: Option<NaiveDate>,
+) -> Result<Vec<u8>, MoneyManagerError> {
+    let book = gnucash_xml_helper_utility::retrieve_xml_book_from_database()?;
+    Ok(write_journal(&book, format, from_date, thru_date).into_bytes())
+}
+
+/// write_journal writes the transactions of the given book as a journal of the given format,
+/// including only the transactions posted from the from_date thru the thru_date when given.
+/// Scheduled transaction templates are left out.
+pub fn write_journal(
+    book: &XmlBook,
+    format: JournalFormat,
+    from_date: Option<NaiveDate>,
+    thru_date: Option<NaiveDate>,
+) -> String {
+    let mnemonics: HashMap<&str, &str> = book
+        .commodities
+        .iter()
+        .map(|commodity| (commodity.guid.as_str(), commodity.mnemonic.as_str()))
+        .collect();
+    let accounts: HashMap<&str, &XmlAccount> = book
+        .accounts
+        .iter()
+        .map(|account| (account.guid.as_str(), account))
+        .collect();
+    let account_names: HashMap<&str, String> = book
+        .accounts
+        .iter()
+        .filter_map(|account| {
+            account_path(&accounts, &book.root_account_guid, account)
+                .map(|path| (account.guid.as_str(), journal_account_name(format, &account.account_type, &path)))
+        })
+        .collect();
+
+    //Pick out the transactions in the date range, leaving out templates
+    let mut transactions = Vec::new();
+    for transaction in &book.transactions {
+        let Some(post_date) = transaction
+            .post_date
+            .as_deref()
+            .and_then(convert_sql_date_to_date)
+        else {
+            continue;
+        };
+        if from_date.is_some_and(|from_date| post_date < from_date)
+            || thru_date.is_some_and(|thru_date| post_date > thru_date)
+        {
+            continue;
+        }
+        let splits: Vec<_> = book
+            .splits
+            .iter()
+            .filter(|split| split.tx_guid == transaction.guid)
+            .collect();
+        if splits.is_empty()
+            || splits
+                .iter()
+                .any(|split| !account_names.contains_key(split.account_guid.as_str()))
+        {
+            continue;
+        }
+        transactions.push((post_date, transaction, splits));
+    }
+    transactions.sort_by(|a, b| (a.0, &a.1.enter_date).cmp(&(b.0, &b.1.enter_date)));
+
+    let mut journal = String::new();
+
+    //Declare the accounts first, so every posting goes to a known account
+    let mut ordered_account_names: Vec<(&str, &String)> = account_names
+        .iter()
+        .map(|(guid, name)| (*guid, name))
+        .collect();
+    ordered_account_names.sort_by(|a, b| a.1.cmp(b.1));
+    let open_date = transactions
+        .first()
+        .map(|(post_date, _, _)| post_date.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| BEANCOUNT_OPEN_DATE.to_string());
+    for (account_guid, account_name) in &ordered_account_names {
+        let account_type = accounts[account_guid].account_type.as_str();
+        journal.push_str(&match format {
+            JournalFormat::Ledger => format!("account {account_name}\n"),
+            JournalFormat::Hledger => format!(
+                "account {account_name}  ; type: {}\n",
+                hledger_account_type(account_type)
+            ),
+            JournalFormat::Beancount => format!("{open_date} open {account_name}\n"),
+        });
+    }
+
+    for (post_date, transaction, splits) in transactions {
+        journal.push('\n');
+        let currency = mnemonics
+            .get(transaction.currency_guid.as_str())
+            .copied()
+            .unwrap_or_default();
+
+        //Then the header line of the transaction
+        let date = post_date.format("%Y-%m-%d");
+        match format {
+            JournalFormat::Ledger | JournalFormat::Hledger => {
+                journal.push_str(&date.to_string());
+                if !transaction.num.is_empty() {
+                    journal.push_str(&format!(" ({})", transaction.num.replace(')', "")));
+                }
+                journal.push_str(&format!(" {}\n", single_line(&transaction.description)));
+            }
+            JournalFormat::Beancount => {
+                journal.push_str(&format!(
+                    "{date} * \"{}\"\n",
+                    escape_beancount_string(&single_line(&transaction.description))
+                ));
+                if !transaction.num.is_empty() {
+                    journal.push_str(&format!(
+                        "    num: \"{}\"\n",
+                        escape_beancount_string(&transaction.num)
+                    ));
+                }
+            }
+        }
+
+        //Then the notes, as comments
+        for notes_slot in book
+            .slots
+            .iter()
+            .filter(|slot| slot.obj_guid == transaction.guid && slot.name == "notes")
+        {
+            for line in notes_slot.string_val.as_deref().unwrap_or_default().lines() {
+                journal.push_str(&format!("    ; {}\n", line.trim_end()));
+            }
+        }
+
+        //Then the postings, with the amount in the account's commodity
+        for split in splits {
+            let account = accounts[split.account_guid.as_str()];
+            let commodity = account
+                .commodity_guid
+                .as_deref()
+                .and_then(|commodity_guid| mnemonics.get(commodity_guid).copied())
+                .unwrap_or(currency);
+            let value = format_numeric(split.value_num, split.value_denom);
+
+            let mut posting = String::from("    ");
+            posting.push_str(match split.reconcile_state.as_str() {
+                "y" => "* ",
+                "c" => "! ",
+                _ => "",
+            });
+            posting.push_str(&account_names[split.account_guid.as_str()]);
+            posting.push_str("  ");
+            if commodity == currency || split.quantity_num == 0 {
+                posting.push_str(&format_amount(format, &value, currency));
+            } else {
+                posting.push_str(&format_amount(
+                    format,
+                    &format_numeric(split.quantity_num, split.quantity_denom),
+                    commodity,
+                ));
+                posting.push_str(" @@ ");
+                posting.push_str(&format_amount(format, value.trim_start_matches('-'), currency));
+            }
+            if !split.memo.is_empty() {
+                posting.push_str(&format!("  ; {}", single_line(&split.memo)));
+            }
+            journal.push_str(&posting);
+            journal.push('\n');
+        }
+    }
+
+    journal
+}
+
+/// account_path returns the names of the accounts from the top level down to the given account,
+/// or None when the account isn't under the given root account, such as a template account.
+fn account_path(
+    accounts: &HashMap<&str, &XmlAccount>,
+    root_account_guid: &str,
+    account: &XmlAccount,
+) -> Option<Vec<String>> {
+    let mut path = Vec::new();
+    let mut current = account;
+    while current.guid != root_account_guid {
+        //Stop on a loop in the parents, rather than walking it forever
+        if path.len() > accounts.len() {
+            return None;
+        }
+        path.push(current.name.clone());
+        current = accounts.get(current.parent_guid.as_deref()?)?;
+    }
+    if path.is_empty() {
+        return None;
+    }
+    path.reverse();
+    Some(path)
+}
+
+/// journal_account_name joins the given account path into a name the given format accepts.
+/// Beancount names have to start with the top level for the account's type, and each part has
+/// to start with a capital letter, or a number. (Ex: 'Assets:Current-Assets:Checking')
+fn journal_account_name(format: JournalFormat, account_type: &str, path: &[String]) -> String {
+    match format {
+        JournalFormat::Ledger | JournalFormat::Hledger => path
+            .iter()
+            .map(|name| name.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect::<Vec<_>>()
+            .join(":"),
+        JournalFormat::Beancount => {
+            let top_level = beancount_top_level(account_type);
+            let mut parts: Vec<String> = path.iter().map(|name| beancount_account_part(name)).collect();
+            if parts.first().map(String::as_str) != Some(top_level) {
+                parts.insert(0, top_level.to_string());
+            }
+            parts.join(":")
+        }
+    }
+}
+
+/// beancount_top_level returns the beancount top level account for the given account type.
+fn beancount_top_level(account_type: &str) -> &'static str {
+    match AccountType::from(account_type.to_string()).top_level_type() {
+        AccountType::ASSET => "Assets",
+        AccountType::LIABILITY => "Liabilities",
+        AccountType::INCOME => "Income",
+        AccountType::EXPENSE => "Expenses",
+        _ => "Equity",
+    }
+}
+
+/// hledger_account_type returns hledger's letter for the given account type. (Ex: 'A' for assets)
+fn hledger_account_type(account_type: &str) -> &'static str {
+    match beancount_top_level(account_type) {
+        "Assets" => "A",
+        "Liabilities" => "L",
+        "Income" => "R",
+        "Expenses" => "X",
+        _ => "E",
+    }
+}
+
+/// beancount_account_part converts an account name into a part of a beancount account name, by
+/// replacing what beancount doesn't allow with dashes, and capitalizing it.
+fn beancount_account_part(name: &str) -> String {
+    let mut part = String::new();
+    for character in name.chars() {
+        if character.is_ascii_alphanumeric() {
+            part.push(character);
+        } else if !part.is_empty() && !part.ends_with('-') {
+            part.push('-');
+        }
+    }
+    let part = part.trim_end_matches('-');
+
+    let mut characters = part.chars();
+    match characters.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + characters.as_str(),
+        None => "Unnamed".to_string(),
+    }
+}
+
+/// format_amount writes the amount with its commodity the way the given format expects.
+/// Ledger quotes commodities that have anything but letters in them, and beancount only allows
+/// capitals, numbers, and a few symbols.
+fn format_amount(format: JournalFormat, amount: &str, commodity: &str) -> String {
+    match format {
+        JournalFormat::Ledger | JournalFormat::Hledger => {
+            if !commodity.is_empty() && commodity.chars().all(char::is_alphabetic) {
+                format!("{amount} {commodity}")
+            } else {
+                format!("{amount} \"{}\"", commodity.replace('"', ""))
+            }
+        }
+        JournalFormat::Beancount => {
+            let mut currency: String = commodity
+                .to_uppercase()
+                .chars()
+                .map(|character| {
+                    if character.is_ascii_alphanumeric() || "'._-".contains(character) {
+                        character
+                    } else {
+                        '-'
+                    }
+                })
+                .collect();
+            if !currency.starts_with(|character: char| character.is_ascii_uppercase()) {
+                currency.insert(0, 'X');
+            }
+            while currency.ends_with(|character: char| !character.is_ascii_alphanumeric()) {
+                currency.pop();
+            }
+            format!("{amount} {currency}")
+        }
+    }
+}
+
+/// format_numeric writes the given fraction as a decimal. The amount is exact whenever the
+/// denominator allows it, keeping the places of a power of ten denominator. (Ex: 1250/100 is
+/// '12.50') Otherwise it's rounded to DECIMAL_PLACES_FOR_INEXACT_AMOUNTS places.
+pub fn format_numeric(num: i64, denom: i64) -> String {
+    if denom == 0 {
+        return "0".to_string();
+    }
+    let (mut num, mut denom) = (num as i128, denom as i128);
+    if denom < 0 {
+        num = -num;
+        denom = -denom;
+    }
+
+    let places = decimal_places(denom).or_else(|| {
+        let divisor = greatest_common_divisor(num.abs(), denom);
+        num /= divisor;
+        denom /= divisor;
+        decimal_places(denom)
+    });
+    let (scaled, places) = match places {
+        Some(places) => (num * 10_i128.pow(places) / denom, places),
+        None => {
+            let scale = 10_i128.pow(DECIMAL_PLACES_FOR_INEXACT_AMOUNTS);
+            let scaled = (num * scale * 2 + num.signum() * denom) / (denom * 2);
+            (scaled, DECIMAL_PLACES_FOR_INEXACT_AMOUNTS)
+        }
+    };
+
+    let sign = if scaled < 0 { "-" } else { "" };
+    let digits = format!("{:0>width$}", scaled.abs(), width = places as usize + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - places as usize);
+    if fraction.is_empty() {
+        format!("{sign}{whole}")
+    } else {
+        format!("{sign}{whole}.{fraction}")
+    }
+}
+
+/// decimal_places returns how many decimal places it takes to write a fraction with the given
+/// denominator exactly, or None when it can't be. (Ex: 8 takes 3, for 0.125)
+fn decimal_places(denom: i128) -> Option<u32> {
+    let mut remainder = denom;
+    let mut twos = 0;
+    let mut fives = 0;
+    while remainder % 2 == 0 {
+        remainder /= 2;
+        twos += 1;
+    }
+    while remainder % 5 == 0 {
+        remainder /= 5;
+        fives += 1;
+    }
+    if remainder == 1 {
+        Some(u32::max(twos, fives))
+    } else {
+        None
+    }
+}
+
+/// greatest_common_divisor returns the largest number that divides both a, and b.
+fn greatest_common_divisor(a: i128, b: i128) -> i128 {
+    if b == 0 { a.max(1) } else { greatest_common_divisor(b, a % b) }
+}
+
+/// convert_sql_date_to_date converts a date in either the '20120801040000' or the
+/// '2012-08-01 04:00:00' format to a date.
+fn convert_sql_date_to_date(sql_date: &str) -> Option<NaiveDate> {
+    let digits: String = sql_date
+        .chars()
+        .filter(|character| character.is_ascii_digit())
+        .take(8)
+        .collect();
+    NaiveDate::parse_from_str(&digits, "%Y%m%d").ok()
+}
+
+/// single_line joins the lines of the given text, so it fits on one line of the journal.
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// escape_beancount_string escapes the given text for a beancount string.
+fn escape_beancount_string(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// SECURITY_NAMESPACE is the namespace commodities are created in when the journal prices
+/// them, rather than using them as a currency.
+const SECURITY_NAMESPACE: &str = "FUND";
+
+/// UNCATEGORIZED_ACCOUNT_NAMES are the account names, ignoring case, that journals post to when
+/// the category isn't known yet, so the categorization rules can pick one instead. Accounts
+/// starting with 'Imbalance' are uncategorized too.
+const UNCATEGORIZED_ACCOUNT_NAMES: [&str; 3] = ["unknown", "uncategorized", "uncategorised"];
+
+/// JournalAmount is an amount read from a journal, as an exact fraction of its commodity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalAmount {
+    pub num: i64,          //num is the numerator of the amount.
+    pub denom: i64,        //denom is the denominator of the amount, a power of ten.
+    pub commodity: String, //commodity is the commodity's mnemonic, or blank when none was given.
+}
+
+/// JournalPosting is one posting of a transaction read from a journal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalPosting {
+    pub account: String,                   //account is the full account name. (Ex: 'Assets:Checking')
+    pub amount: Option<JournalAmount>,     //amount is the amount, or None when it's left to balance the transaction.
+    pub total_cost: Option<JournalAmount>, //total_cost is what the whole amount cost, from its cost, or its price.
+    pub memo: String,                      //memo is the posting's comment.
+    pub reconcile_state: String,           //reconcile_state is 'y' for a cleared posting, 'c' for a pending one, or 'n'.
+}
+
+/// JournalTransaction is a transaction read from a journal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalTransaction {
+    pub line_number: usize,           //line_number is the line of the journal the transaction starts on.
+    pub date: NaiveDate,              //date is the date of the transaction.
+    pub num: String,                  //num is the transaction's code. (Ex: a check number)
+    pub description: String,          //description is the payee, and narration.
+    pub notes: Vec<String>,           //notes are the comment lines of the transaction.
+    pub reconcile_state: String,       //reconcile_state is the state of postings that don't have their own.
+    pub postings: Vec<JournalPosting>, //postings are the postings of the transaction.
+}
+
+/// JournalPrice is a price read from a journal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalPrice {
+    pub date: NaiveDate,      //date is the date of the price.
+    pub commodity: String,    //commodity is the commodity being priced. (Ex: 'AAPL')
+    pub price: JournalAmount, //price is the price of one unit of the commodity.
+}
+
+/// Journal is everything read from a ledger, hledger, or beancount journal.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Journal {
+    pub accounts: Vec<(String, Option<String>)>, //accounts are the declared accounts, with the commodity they are opened with.
+    pub commodities: Vec<String>,                //commodities are the declared commodities.
+    pub prices: Vec<JournalPrice>,               //prices are the prices.
+    pub transactions: Vec<JournalTransaction>,   //transactions are the transactions.
+    pub warnings: Vec<String>,                   //warnings are what was left out of the journal.
+}
+
+/// parse_journal reads a ledger, hledger, or beancount journal. Includes, automated, and
+/// periodic transactions, and balance assertions are skipped.
+pub fn parse_journal(text: &str) -> Result<Journal, MoneyManagerError> {
+    let mut journal = Journal::default();
+    let mut current: Option<JournalTransaction> = None;
+    let mut skipping_block = false;
+
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim_end();
+        let error = |message: String| MoneyManagerError::Validation(format!("Line {line_number}: {message}"));
+        let line_error = |parse_error: MoneyManagerError| error(parse_error.to_string());
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        //Indented lines belong to the transaction above them
+        if line.starts_with([' ', '\t']) {
+            let Some(transaction) = current.as_mut().filter(|_| !skipping_block) else {
+                continue;
+            };
+            let trimmed = line.trim();
+            if let Some(comment) = trimmed.strip_prefix([';', '#']) {
+                transaction.notes.push(comment.trim().to_string());
+            } else if let Some((key, value)) = beancount_metadata(trimmed) {
+                if key == "num" {
+                    transaction.num = unquote(value);
+                }
+            } else if let Some(mut posting) = parse_posting(trimmed).map_err(line_error)? {
+                if posting.reconcile_state.is_empty() {
+                    posting.reconcile_state = transaction.reconcile_state.clone();
+                }
+                transaction.postings.push(posting);
+            }
+            continue;
+        }
+
+        //Any other line ends the transaction before it
+        journal.transactions.extend(current.take());
+        skipping_block = false;
+        if line.starts_with([';', '#', '%', '|', '*']) {
+            continue;
+        }
+        if line.starts_with(['~', '=']) {
+            skipping_block = true;
+            continue;
+        }
+
+        let (line, comment) = split_comment(line);
+        let mut words = line.split_whitespace();
+        let first_word = words.next().unwrap_or_default();
+        match first_word {
+            "account" => journal
+                .accounts
+                .push((line["account".len()..].trim().to_string(), None)),
+            "commodity" => journal
+                .commodities
+                .push(normalize_commodity(words.next().unwrap_or_default())),
+            "P" => {
+                let date_word = words.next().unwrap_or_default();
+                let date = parse_date(date_word)
+                    .ok_or_else(|| error(format!("'{date_word}' is not a valid date.")))?;
+                let mut price_words: Vec<&str> = words.collect();
+                if price_words.first().is_some_and(|word| word.contains(':')) {
+                    price_words.remove(0);
+                }
+                let Some((commodity, price)) = price_words.split_first() else {
+                    return Err(error("The price is missing its commodity.".to_string()));
+                };
+                journal.prices.push(JournalPrice {
+                    date,
+                    commodity: normalize_commodity(commodity),
+                    price: parse_amount(&price.join(" ")).map_err(line_error)?,
+                });
+            }
+            _ if first_word.starts_with(|character: char| character.is_ascii_digit()) => {
+                let date = parse_date(first_word)
+                    .ok_or_else(|| error(format!("'{first_word}' is not a valid date.")))?;
+                let rest = line[first_word.len()..].trim();
+                let mut rest_words = rest.split_whitespace();
+                match rest_words.next().unwrap_or_default() {
+                    "open" => {
+                        let account = rest_words.next().unwrap_or_default().to_string();
+                        let commodity = rest_words
+                            .next()
+                            .and_then(|commodities| commodities.split(',').next())
+                            .filter(|commodity| !commodity.starts_with('"'))
+                            .map(normalize_commodity);
+                        journal.accounts.push((account, commodity));
+                    }
+                    "commodity" => journal
+                        .commodities
+                        .push(normalize_commodity(rest_words.next().unwrap_or_default())),
+                    "price" => {
+                        let commodity = normalize_commodity(rest_words.next().unwrap_or_default());
+                        let price = parse_amount(&rest_words.collect::<Vec<_>>().join(" "))
+                            .map_err(line_error)?;
+                        journal.prices.push(JournalPrice { date, commodity, price });
+                    }
+                    "pad" => journal.warnings.push(format!(
+                        "Line {line_number}: pad directives aren't imported, so balances may differ."
+                    )),
+                    "close" | "balance" | "note" | "document" | "event" | "custom" | "query" => {}
+                    _ => current = Some(parse_transaction_header(line_number, date, rest, comment)),
+                }
+            }
+            _ => {}
+        }
+    }
+    journal.transactions.extend(current.take());
+
+    Ok(journal)
+}
+
+/// parse_transaction_header reads the first line of a transaction, after its date. Ledger
+/// headers look like '* (101) Market', and beancount headers like '* "Market" "Milk"'.
+fn parse_transaction_header(
+    line_number: usize,
+    date: NaiveDate,
+    header: &str,
+    comment: Option<&str>,
+) -> JournalTransaction {
+    let mut header = header.trim();
+    let mut reconcile_state = "n";
+    if let Some(rest) = header.strip_prefix('*') {
+        header = rest.trim_start();
+        reconcile_state = "y";
+    } else if let Some(rest) = header.strip_prefix('!') {
+        header = rest.trim_start();
+        reconcile_state = "c";
+    } else if let Some(rest) = header.strip_prefix("txn ") {
+        header = rest.trim_start();
+    }
+
+    let mut num = String::new();
+    if header.starts_with('(')
+        && let Some(end) = header.find(')')
+    {
+        num = header[1..end].trim().to_string();
+        header = header[end + 1..].trim_start();
+    }
+
+    //Beancount's flag only says the transaction is complete, so it isn't a cleared state
+    let description = if header.starts_with('"') {
+        reconcile_state = "n";
+        quoted_strings(header)
+            .into_iter()
+            .filter(|string| !string.is_empty())
+            .collect::<Vec<_>>()
+            .join(" - ")
+    } else {
+        header.to_string()
+    };
+
+    JournalTransaction {
+        line_number,
+        date,
+        num,
+        description,
+        notes: comment.map(|comment| vec![comment.trim().to_string()]).unwrap_or_default(),
+        reconcile_state: reconcile_state.to_string(),
+        postings: Vec::new(),
+    }
+}
+
+/// parse_posting reads a posting line, such as '* Assets:Checking  -12.50 USD  ; memo'. It
+/// returns None for ledger's virtual postings in parentheses, which don't have to balance.
+fn parse_posting(line: &str) -> Result<Option<JournalPosting>, MoneyManagerError> {
+    let (body, memo) = split_comment(line);
+    let mut body = body.trim();
+    let mut reconcile_state = "";
+    if let Some(rest) = body.strip_prefix("* ") {
+        body = rest.trim_start();
+        reconcile_state = "y";
+    } else if let Some(rest) = body.strip_prefix("! ") {
+        body = rest.trim_start();
+        reconcile_state = "c";
+    }
+
+    //The account ends at two spaces, or a tab, though beancount only needs one space
+    let separator = [body.find("  "), body.find('\t')].into_iter().flatten().min();
+    let (account, amounts) = match separator {
+        Some(separator) => (body[..separator].trim(), parse_posting_amounts(&body[separator..])?),
+        None => match body
+            .split_once(' ')
+            .and_then(|(account, amounts)| Some((account, parse_posting_amounts(amounts).ok()?)))
+        {
+            Some((account, amounts)) => (account, amounts),
+            None => (body, (None, None)),
+        },
+    };
+    if account.starts_with('(') {
+        return Ok(None);
+    }
+    let account = account.trim_start_matches('[').trim_end_matches(']').trim();
+    if account.is_empty() {
+        return Err(MoneyManagerError::Validation(format!("'{line}' is missing its account.")));
+    }
+
+    Ok(Some(JournalPosting {
+        account: account.to_string(),
+        amount: amounts.0,
+        total_cost: amounts.1,
+        memo: memo.map(|memo| memo.trim().to_string()).unwrap_or_default(),
+        reconcile_state: reconcile_state.to_string(),
+    }))
+}
+
+/// parse_posting_amounts reads the amount of a posting, along with what the whole amount cost
+/// from its cost in braces, or its price after an '@'. (Ex: '10 AAPL @ 150.00 USD')
+fn parse_posting_amounts(
+    text: &str,
+) -> Result<(Option<JournalAmount>, Option<JournalAmount>), MoneyManagerError> {
+    //Balance assertions, and assignments aren't imported
+    let text = text.split('=').next().unwrap_or_default().trim();
+    if text.is_empty() {
+        return Ok((None, None));
+    }
+
+    let amount_end = text.find(['{', '@', '[', '(']).unwrap_or(text.len());
+    let amount = parse_amount(&text[..amount_end])?;
+
+    let mut total_cost = None;
+    if let Some(start) = text.find('{') {
+        let end = text[start..]
+            .find('}')
+            .map(|end| start + end)
+            .ok_or_else(|| {
+                MoneyManagerError::Validation(format!("'{text}' is missing a closing brace."))
+            })?;
+        let cost = text[start..end].trim_start_matches('{');
+        let cost = cost.split(',').next().unwrap_or_default().trim();
+        if !cost.is_empty() && !cost.starts_with('"') && parse_date(cost).is_none() {
+            let cost = parse_amount(cost)?;
+            total_cost = Some(if text[start..].starts_with("{{") {
+                absolute_amount(cost)
+            } else {
+                multiply_amounts(&amount, &cost)?
+            });
+        }
+    }
+    if total_cost.is_none()
+        && let Some(start) = text.find('@')
+    {
+        let price = text[start..].trim_start_matches('@');
+        let price = parse_amount(&price[..price.find(['{', '[', '(']).unwrap_or(price.len())])?;
+        total_cost = Some(if text[start..].starts_with("@@") {
+            absolute_amount(price)
+        } else {
+            multiply_amounts(&amount, &price)?
+        });
+    }
+
+    Ok((Some(amount), total_cost))
+}
+
+/// parse_amount reads an amount with its commodity either before, or after the number.
+/// (Ex: '-12.50 USD', 'USD -12.50', '-$12.50', or '10 "VANGUARD 500"')
+pub fn parse_amount(text: &str) -> Result<JournalAmount, MoneyManagerError> {
+    let text = text.trim();
+    let invalid = || MoneyManagerError::Validation(format!("'{text}' is not a valid amount."));
+
+    //A quoted commodity can have spaces, and numbers in it
+    let (number, commodity) = if let Some(start) = text.find('"') {
+        let end = text[start + 1..]
+            .find('"')
+            .map(|end| start + 1 + end)
+            .ok_or_else(invalid)?;
+        (
+            format!("{}{}", &text[..start], &text[end + 1..]).trim().to_string(),
+            text[start + 1..end].to_string(),
+        )
+    } else {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let is_number = |word: &str| {
+            word.trim_start_matches(['-', '+'])
+                .starts_with(|character: char| character.is_ascii_digit() || character == '.')
+        };
+        match words.as_slice() {
+            [number, commodity] if is_number(number) => (number.to_string(), commodity.to_string()),
+            [commodity, number] if is_number(number) => (number.to_string(), commodity.to_string()),
+            [word] => {
+                //A symbol can be stuck to the number, as in '-$12.50', or '12.50USD'
+                let start = word
+                    .find(|character: char| character.is_ascii_digit() || character == '.')
+                    .ok_or_else(invalid)?;
+                let end = word[start..]
+                    .find(|character: char| !character.is_ascii_digit() && character != '.' && character != ',')
+                    .map(|end| start + end)
+                    .unwrap_or(word.len());
+                let prefix = &word[..start];
+                let sign = if prefix.contains('-') { "-" } else { "" };
+                (
+                    format!("{sign}{}", &word[start..end]),
+                    format!("{}{}", prefix.replace(['-', '+'], ""), &word[end..]),
+                )
+            }
+            _ => return Err(invalid()),
+        }
+    };
+
+    let (num, denom) = parse_decimal(&number).ok_or_else(invalid)?;
+    Ok(JournalAmount {
+        num,
+        denom,
+        commodity: normalize_commodity(&commodity),
+    })
+}
+
+/// parse_decimal reads a decimal number as an exact fraction, with a power of ten denominator.
+/// (Ex: '-1,234.50' is -123450/100)
+fn parse_decimal(number: &str) -> Option<(i64, i64)> {
+    let number = number.replace(',', "");
+    let (negative, digits) = match number.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, number.trim_start_matches('+')),
+    };
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if whole.is_empty() && fraction.is_empty()
+        || !whole.chars().chain(fraction.chars()).all(|character| character.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let denom = 10_i64.checked_pow(fraction.len() as u32)?;
+    let num = format!("{whole}{fraction}").parse::<i64>().ok()?;
+    Some((if negative { -num } else { num }, denom))
+}
+
+/// parse_date reads a date in the '2024-01-15', '2024/01/15', or '2024.01.15' formats,
+/// ignoring ledger's auxiliary date after an '='.
+fn parse_date(text: &str) -> Option<NaiveDate> {
+    let text = text.split('=').next().unwrap_or_default();
+    ["%Y-%m-%d", "%Y/%m/%d", "%Y.%m.%d"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(text, format).ok())
+}
+
+/// normalize_commodity converts ledger's currency symbols to their mnemonics. (Ex: '$' is 'USD')
+fn normalize_commodity(commodity: &str) -> String {
+    match commodity.trim().trim_matches('"') {
+        "$" => "USD".to_string(),
+        "€" => "EUR".to_string(),
+        "£" => "GBP".to_string(),
+        "¥" => "JPY".to_string(),
+        commodity => commodity.to_string(),
+    }
+}
+
+/// absolute_amount returns the amount without its sign.
+fn absolute_amount(amount: JournalAmount) -> JournalAmount {
+    JournalAmount {
+        num: amount.num.abs(),
+        ..amount
+    }
+}
+
+/// multiply_amounts returns what the whole of the given amount costs at the given unit price.
+fn multiply_amounts(
+    amount: &JournalAmount,
+    price: &JournalAmount,
+) -> Result<JournalAmount, MoneyManagerError> {
+    let overflow = || {
+        MoneyManagerError::Validation(format!(
+            "{} {} at {} is too large.",
+            amount.num, amount.commodity, price.num
+        ))
+    };
+    let mut num = amount.num.abs().checked_mul(price.num.abs()).ok_or_else(overflow)?;
+    let mut denom = amount.denom.checked_mul(price.denom).ok_or_else(overflow)?;
+    while denom > 1 && num % 10 == 0 && denom % 10 == 0 {
+        num /= 10;
+        denom /= 10;
+    }
+    Ok(JournalAmount {
+        num,
+        denom,
+        commodity: price.commodity.clone(),
+    })
+}
+
+/// split_comment splits a line at the ';' that starts its comment, if it has one outside of
+/// quotes.
+fn split_comment(line: &str) -> (&str, Option<&str>) {
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for (index, character) in line.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match character {
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => return (&line[..index], Some(&line[index + 1..])),
+            _ => {}
+        }
+    }
+    (line, None)
+}
+
+/// quoted_strings returns each of the double quoted strings in the text, unescaped.
+fn quoted_strings(text: &str) -> Vec<String> {
+    let mut strings = Vec::new();
+    let mut characters = text.chars();
+    while let Some(character) = characters.next() {
+        if character != '"' {
+            continue;
+        }
+        let mut string = String::new();
+        while let Some(character) = characters.next() {
+            match character {
+                '\\' => string.extend(characters.next()),
+                '"' => break,
+                _ => string.push(character),
+            }
+        }
+        strings.push(string);
+    }
+    strings
+}
+
+/// unquote returns the text without its surrounding double quotes, unescaped.
+fn unquote(text: &str) -> String {
+    quoted_strings(text)
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| text.trim().to_string())
+}
+
+/// beancount_metadata returns the key, and value of a beancount metadata line, such as
+/// 'num: "101"'.
+fn beancount_metadata(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.split_once(':')?;
+    let is_key = key.starts_with(|character: char| character.is_ascii_lowercase())
+        && key
+            .chars()
+            .all(|character| character.is_ascii_alphanumeric() || character == '-' || character == '_');
+    if is_key && (value.is_empty() || value.starts_with(char::is_whitespace)) {
+        Some((key, value.trim()))
+    } else {
+        None
+    }
+}
+
+/// JournalImportSummary counts what import_journal_into_database added to the book.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct JournalImportSummary {
+    pub commodities: usize,       //commodities is how many commodities were created.
+    pub accounts: usize,          //accounts is how many accounts were created.
+    pub transactions: usize,      //transactions is how many transactions were created.
+    pub prices: usize,            //prices is how many prices were created.
+    pub warnings: Vec<String>,    //warnings are what was left out of the journal.
+    pub categorized: Vec<String>, //categorized describes each transaction a categorization rule fired for.
+    pub duplicates: Vec<String>,  //duplicates describes each transaction skipped as a probable duplicate, and what it matched.
+}
+
+/// is_uncategorized_account checks whether the given full account name is one journals post to
+/// when they don't know the category. (Ex: 'Expenses:Unknown')
+fn is_uncategorized_account(account: &str) -> bool {
+    let name = account.rsplit(':').next().unwrap_or_default().trim().to_lowercase();
+    UNCATEGORIZED_ACCOUNT_NAMES.contains(&name.as_str()) || name.starts_with("imbalance")
+}
+
+/// categorize_journal_transaction finds the first of the given rules that fires for the given
+/// transaction, along with the index of its uncategorized posting the rule's category replaces.
+/// The amount a rule is matched against is the uncategorized posting's amount, or the largest
+/// of the others when it's left out.
+pub fn categorize_journal_transaction<'a>(
+    rules: &'a [CategorizationRule],
+    transaction: &JournalTransaction,
+) -> Option<(usize, &'a CategorizationRule)> {
+    let (index, posting) = transaction
+        .postings
+        .iter()
+        .enumerate()
+        .find(|(_, posting)| is_uncategorized_account(&posting.account))?;
+    let amount_of = |amount: &JournalAmount| amount.num as f64 / amount.denom as f64;
+    let amount = match &posting.amount {
+        Some(amount) => Some(amount_of(amount)),
+        None => transaction
+            .postings
+            .iter()
+            .filter_map(|posting| posting.amount.as_ref())
+            .map(|amount| amount_of(amount).abs())
+            .reduce(f64::max),
+    };
+
+    categorization_rules_manager::find_matching_rule(rules, &transaction.description, amount)
+        .map(|rule| (index, rule))
+}
+
+/// describe_categorization describes what the given rule does to the given transaction, for
+/// showing before, and after it's imported.
+fn describe_categorization(transaction: &JournalTransaction, rule: &CategorizationRule) -> String {
+    let description = rule.rewrite_description(&transaction.description);
+    if description == transaction.description {
+        format!(
+            "Line {}: '{}' categorized by the rule '{}'",
+            transaction.line_number, transaction.description, rule.name
+        )
+    } else {
+        format!(
+            "Line {}: '{}' categorized by the rule '{}' as '{}'",
+            transaction.line_number, transaction.description, rule.name, description
+        )
+    }
+}
+
+/// preview_journal_categorization describes each transaction of the given journal that a
+/// categorization rule will fire for when it's imported, without importing anything.
+pub fn preview_journal_categorization(text: &str) -> Result<Vec<String>, MoneyManagerError> {
+    let journal = parse_journal(text)?;
+    let rules = categorization_rules_manager::retrieve_all_categorization_rules()?;
+
+    Ok(journal
+        .transactions
+        .iter()
+        .filter_map(|transaction| {
+            categorize_journal_transaction(&rules, transaction)
+                .map(|(_, rule)| describe_categorization(transaction, rule))
+        })
+        .collect())
+}
+
+/// import_journal_into_database reads a ledger, hledger, or beancount journal into the loaded
+/// book, creating the commodities, and accounts it's missing. Either the whole journal is
+/// imported, or nothing is. With skip_probable_duplicates, transactions that look like ones
+/// already in the book are left out.
+pub fn import_journal_into_database(
+    text: &str,
+    skip_probable_duplicates: bool,
+) -> Result<JournalImportSummary, MoneyManagerError> {
+    let journal = parse_journal(text)?;
+
+    dhu::in_transaction(|| JournalImporter::new(skip_probable_duplicates)?.import(&journal))
+}
+
+/// JournalImporter finds, or creates the book's commodities, and accounts by name while a
+/// journal is imported.
+struct JournalImporter {
+    root_account_guid: Uuid,                  //root_account_guid is the book's root account.
+    commodities: HashMap<String, Uuid>,       //commodities are the guids of the commodities by mnemonic.
+    securities: Vec<String>,                  //securities are the mnemonics that aren't currencies.
+    accounts: HashMap<String, Uuid>,          //accounts are the guids of the accounts by full name.
+    account_types: HashMap<Uuid, AccountType>, //account_types are the types of the accounts.
+    rules: Vec<CategorizationRule>,           //rules are the categorization rules for uncategorized postings.
+    skip_probable_duplicates: bool,           //skip_probable_duplicates leaves out transactions that look like ones in the book.
+    imported_transactions: HashSet<Uuid>,     //imported_transactions are the guids of the transactions imported so far.
+    summary: JournalImportSummary,            //summary is what has been imported so far.
+}
+
+impl JournalImporter {
+    /// new reads the book's commodities, and accounts.
+    fn new(skip_probable_duplicates: bool) -> Result<JournalImporter, MoneyManagerError> {
+        let root_account_guid = books_manager::retrieve_book()?.root_account_guid;
+
+        let mut securities = Vec::new();
+        let mut commodities = HashMap::new();
+        for commodity in commodities_manager::retrieve_all_commodities()? {
+            if commodity.namespace != "CURRENCY" {
+                securities.push(commodity.mnemonic.clone());
+            }
+            commodities.insert(commodity.mnemonic, commodity.guid);
+        }
+
+        //Each account can be found by its full name, as either ledger, or beancount writes it
+        let account_nodes = accounts_manager::retrieve_all_account_nodes()?;
+        let nodes: HashMap<Uuid, &accounts_manager::AccountNode> =
+            account_nodes.iter().map(|node| (node.guid, node)).collect();
+        let mut accounts = HashMap::new();
+        let mut account_types = HashMap::new();
+        for node in &account_nodes {
+            let Some(path) = account_node_path(&nodes, root_account_guid, node) else {
+                continue;
+            };
+            for format in [JournalFormat::Ledger, JournalFormat::Beancount] {
+                accounts.insert(journal_account_name(format, &node.account_type, &path), node.guid);
+            }
+            accounts.insert(path.join(":"), node.guid);
+            account_types.insert(node.guid, convert_gnucash_account_type(&node.account_type));
+        }
+
+        Ok(JournalImporter {
+            root_account_guid,
+            commodities,
+            securities,
+            accounts,
+            account_types,
+            rules: categorization_rules_manager::retrieve_all_categorization_rules()?,
+            skip_probable_duplicates,
+            imported_transactions: HashSet::new(),
+            summary: JournalImportSummary::default(),
+        })
+    }
+
+    /// import saves the journal into the book, and returns what was saved.
+    fn import(&mut self, journal: &Journal) -> Result<JournalImportSummary, MoneyManagerError> {
+        self.summary.warnings = journal.warnings.clone();
+        self.create_commodities(journal)?;
+        let default_currency = commodities_manager::retrieve_default_currency_guid().ok();
+
+        //Accounts are opened in their commodity, or else the commodity first posted to them
+        let mut account_commodities: HashMap<&str, &str> = HashMap::new();
+        for (account, commodity) in &journal.accounts {
+            if let Some(commodity) = commodity {
+                account_commodities.insert(account, commodity);
+            }
+        }
+        for transaction in &journal.transactions {
+            for posting in &transaction.postings {
+                if let Some(amount) = posting.amount.as_ref().filter(|amount| !amount.commodity.is_empty()) {
+                    account_commodities.entry(&posting.account).or_insert(&amount.commodity);
+                }
+            }
+        }
+        for (account, _) in &journal.accounts {
+            self.account_guid(account, account_commodities.get(account.as_str()).copied(), default_currency)?;
+        }
+
+        for transaction in &journal.transactions {
+            self.import_transaction(transaction, &account_commodities, default_currency)?;
+        }
+
+        for price in &journal.prices {
+            prices_manager::save_new_and_delete_current(prices_manager::Price {
+                guid: Uuid::new_v4(),
+                commodity_guid: self.commodity_guid(&price.commodity, default_currency)?,
+                currency_guid: self.commodity_guid(&price.price.commodity, default_currency)?,
+                date: convert_date_to_sql_date(price.date),
+                source: Some(prices_manager::SOURCE_USER_PRICE.to_string()),
+                price_type: Some("unknown".to_string()),
+                value_num: price.price.num,
+                value_denom: price.price.denom,
+            })?;
+            self.summary.prices += 1;
+        }
+
+        Ok(self.summary.clone())
+    }
+
+    /// create_commodities creates the commodities the journal uses that the book doesn't have.
+    /// Commodities that are priced are created as securities, and the rest as currencies.
+    fn create_commodities(&mut self, journal: &Journal) -> Result<(), MoneyManagerError> {
+        let mut decimal_places: Vec<(&str, u32)> = Vec::new();
+        let mut priced: Vec<&str> = Vec::new();
+        for commodity in &journal.commodities {
+            decimal_places.push((commodity, 0));
+        }
+        for (_, commodity) in &journal.accounts {
+            decimal_places.extend(commodity.as_deref().map(|commodity| (commodity, 0)));
+        }
+        for price in &journal.prices {
+            priced.push(&price.commodity);
+            decimal_places.push((&price.commodity, 0));
+            decimal_places.push((&price.price.commodity, decimal_places_of(price.price.denom)));
+        }
+        for posting in journal.transactions.iter().flat_map(|transaction| &transaction.postings) {
+            if let Some(amount) = &posting.amount {
+                decimal_places.push((&amount.commodity, decimal_places_of(amount.denom)));
+                if let Some(total_cost) = &posting.total_cost {
+                    decimal_places.push((&total_cost.commodity, decimal_places_of(total_cost.denom)));
+                    if total_cost.commodity != amount.commodity {
+                        priced.push(&amount.commodity);
+                    }
+                }
+            }
+        }
+
+        let mut new_commodities: Vec<&str> = Vec::new();
+        for (commodity, _) in &decimal_places {
+            if !commodity.is_empty() && !self.commodities.contains_key(*commodity) && !new_commodities.contains(commodity) {
+                new_commodities.push(commodity);
+            }
+        }
+        for mnemonic in new_commodities {
+            let is_security = priced.contains(&mnemonic);
+            let places = decimal_places
+                .iter()
+                .filter(|(commodity, _)| *commodity == mnemonic)
+                .map(|(_, places)| *places)
+                .max()
+                .unwrap_or_default()
+                .max(if is_security { 4 } else { 2 });
+            let commodity = commodities_manager::Commodity {
+                guid: Uuid::new_v4(),
+                namespace: if is_security { SECURITY_NAMESPACE } else { "CURRENCY" }.to_string(),
+                mnemonic: mnemonic.to_string(),
+                fullname: mnemonic.to_string(),
+                cusip: String::new(),
+                fraction: 10_i64.pow(places.min(9)),
+                quote_flag: 0,
+                quote_source: if is_security { "" } else { "currency" }.to_string(),
+                quote_tz: String::new(),
+            };
+            self.commodities.insert(mnemonic.to_string(), commodity.guid);
+            if is_security {
+                self.securities.push(mnemonic.to_string());
+            }
+            commodities_manager::save_new_and_delete_current(commodity)?;
+            self.summary.commodities += 1;
+        }
+
+        Ok(())
+    }
+
+    /// commodity_guid returns the guid of the commodity with the given mnemonic, or the book's
+    /// currency when the mnemonic is blank.
+    fn commodity_guid(&self, mnemonic: &str, default_currency: Option<Uuid>) -> Result<Uuid, MoneyManagerError> {
+        if mnemonic.is_empty() {
+            return default_currency.ok_or_else(|| {
+                MoneyManagerError::Validation(
+                    "Amounts without a commodity need a currency in the book first.".to_string(),
+                )
+            });
+        }
+        self.commodities.get(mnemonic).copied().ok_or_else(|| {
+            MoneyManagerError::NotFound(format!("The commodity '{mnemonic}' is not in the book."))
+        })
+    }
+
+    /// account_guid returns the guid of the account with the given full name, creating it, and
+    /// any of its parents that are missing. New top level accounts get their type from their
+    /// name, and new children get the type of their parent.
+    fn account_guid(
+        &mut self,
+        name: &str,
+        commodity: Option<&str>,
+        default_currency: Option<Uuid>,
+    ) -> Result<Uuid, MoneyManagerError> {
+        if let Some(guid) = self.accounts.get(name) {
+            return Ok(*guid);
+        }
+        let parts: Vec<&str> = name.split(':').map(str::trim).collect();
+        if parts.iter().any(|part| part.is_empty()) {
+            return Err(MoneyManagerError::Validation(format!(
+                "The account name '{name}' is not valid."
+            )));
+        }
+
+        let mut parent_guid = self.root_account_guid;
+        for index in 0..parts.len() {
+            let full_name = parts[..=index].join(":");
+            if let Some(guid) = self.accounts.get(&full_name) {
+                parent_guid = *guid;
+                continue;
+            }
+
+            let account_type = match self.account_types.get(&parent_guid) {
+                Some(parent_type) => parent_type.clone(),
+                None => convert_top_level_account_type(parts[0]).ok_or_else(|| {
+                    MoneyManagerError::Validation(format!(
+                        "The account '{name}' doesn't start with Assets, Liabilities, Equity, Income, or Expenses, so its type is unknown."
+                    ))
+                })?,
+            };
+            let commodity = if index == parts.len() - 1 { commodity.unwrap_or_default() } else { "" };
+            let account = Account {
+                guid: Uuid::new_v4(),
+                name: parts[index].to_string(),
+                account_type: account_type.clone(),
+                commodity_guid: Some(self.commodity_guid(commodity, default_currency)?),
+                commodity_scu: 0,
+                non_std_scu: 0,
+                parent_guid: Some(parent_guid),
+                code: String::new(),
+                description: String::new(),
+                hidden: Bool::False,
+                placeholder: Bool::False,
+                tags: HashMap::new(),
+            };
+            parent_guid = account.guid;
+            self.accounts.insert(full_name, account.guid);
+            self.account_types.insert(account.guid, account_type);
+            accounts_manager::save_new_and_delete_current(account)?;
+            self.summary.accounts += 1;
+        }
+
+        Ok(parent_guid)
+    }
+
+    /// import_transaction saves a journal transaction, with a split for each posting. The
+    /// transaction's currency is the one its costs are in, or else its first currency, and a
+    /// posting left without an amount gets whatever balances the transaction. An uncategorized
+    /// posting goes to the category of the first categorization rule that fires for it.
+    fn import_transaction(
+        &mut self,
+        transaction: &JournalTransaction,
+        account_commodities: &HashMap<&str, &str>,
+        default_currency: Option<Uuid>,
+    ) -> Result<(), MoneyManagerError> {
+        let error = |message: String| {
+            MoneyManagerError::Validation(format!("Line {}: {}", transaction.line_number, message))
+        };
+        let amounts = || transaction.postings.iter().filter_map(|posting| posting.amount.as_ref());
+        let currency = transaction
+            .postings
+            .iter()
+            .find_map(|posting| posting.total_cost.as_ref())
+            .or_else(|| amounts().find(|amount| !self.securities.contains(&amount.commodity)))
+            .or_else(|| amounts().next())
+            .map(|amount| amount.commodity.clone())
+            .unwrap_or_default();
+        let currency_guid = self.commodity_guid(&currency, default_currency)?;
+
+        //Work out the value of each posting in the transaction's currency
+        let mut values: Vec<Option<(i64, i64)>> = Vec::new();
+        let mut total: (i128, i128) = (0, 1);
+        for posting in &transaction.postings {
+            let Some(amount) = &posting.amount else {
+                values.push(None);
+                continue;
+            };
+            let value = if self.commodity_guid(&amount.commodity, default_currency)? == currency_guid {
+                (amount.num, amount.denom)
+            } else {
+                match &posting.total_cost {
+                    Some(total_cost) if self.commodity_guid(&total_cost.commodity, default_currency)? == currency_guid => {
+                        (amount.num.signum() * total_cost.num, total_cost.denom)
+                    }
+                    _ => {
+                        return Err(error(format!(
+                            "The {} amount posted to '{}' needs a price in {}, such as '@@ 100.00 {}'.",
+                            amount.commodity, posting.account, currency, currency
+                        )));
+                    }
+                }
+            };
+            total = add_fractions(total, (value.0 as i128, value.1 as i128));
+            values.push(Some(value));
+        }
+
+        match values.iter().filter(|value| value.is_none()).count() {
+            0 if total.0 != 0 => {
+                return Err(error(format!(
+                    "The transaction is off by {} {}.",
+                    format_numeric(total.0 as i64, total.1 as i64),
+                    currency
+                )));
+            }
+            0 => {}
+            1 => {
+                let balance = (i64::try_from(-total.0), i64::try_from(total.1));
+                let (Ok(num), Ok(denom)) = balance else {
+                    return Err(error("The transaction's amounts are too large.".to_string()));
+                };
+                values = values.into_iter().map(|value| value.or(Some((num, denom)))).collect();
+            }
+            _ => return Err(error("Only one posting can leave out its amount.".to_string())),
+        }
+
+        let categorization = categorize_journal_transaction(&self.rules, transaction)
+            .map(|(index, rule)| (index, rule.clone()));
+        let description = match &categorization {
+            Some((_, rule)) => rule.rewrite_description(&transaction.description),
+            None => transaction.description.clone(),
+        };
+
+        let transaction_guid = Uuid::new_v4();
+        let post_date = convert_date_to_sql_date(transaction.date);
+        let mut splits = Vec::new();
+        for (index, (posting, value)) in transaction.postings.iter().zip(values).enumerate() {
+            let (value_num, value_denom) = value.unwrap_or((0, 1));
+            let (quantity_num, quantity_denom) = posting
+                .amount
+                .as_ref()
+                .map(|amount| (amount.num, amount.denom))
+                .unwrap_or((value_num, value_denom));
+            let commodity = posting
+                .amount
+                .as_ref()
+                .map(|amount| amount.commodity.as_str())
+                .or_else(|| account_commodities.get(posting.account.as_str()).copied());
+            let account_guid = match &categorization {
+                Some((categorized_index, rule)) if *categorized_index == index => rule.account_guid,
+                _ => self.account_guid(&posting.account, commodity, default_currency)?,
+            };
+            splits.push(splits_manager::Split {
+                guid: Uuid::new_v4(),
+                tx_guid: transaction_guid,
+                account_guid,
+                memo: posting.memo.clone(),
+                action: String::new(),
+                reconcile_state: posting.reconcile_state.clone(),
+                reconcile_date: if posting.reconcile_state == "y" { Some(post_date.clone()) } else { None },
+                value_num,
+                value_denom,
+                quantity_num,
+                quantity_denom,
+                lot_guid: None,
+            });
+        }
+
+        if self.skip_probable_duplicates
+            && let Some(probable_duplicate) =
+                self.find_probable_duplicate(transaction, &description, &splits)?
+        {
+            self.summary.duplicates.push(format!(
+                "Line {}: '{}' matches {}",
+                transaction.line_number,
+                transaction.description,
+                probable_duplicate.describe()
+            ));
+            return Ok(());
+        }
+        if let Some((_, rule)) = &categorization {
+            self.summary.categorized.push(describe_categorization(transaction, rule));
+        }
+
+        transactions_manager::save_transaction_with_splits(
+            transactions_manager::Transaction {
+                guid: transaction_guid,
+                currency_guid,
+                num: transaction.num.clone(),
+                post_date,
+                enter_date: dhu::convert_date_to_string_format(chrono::Local::now().naive_local()),
+                description,
+            },
+            splits,
+        )?;
+
+        if !transaction.notes.is_empty() {
+            slots_manager::save_slot(slots_manager::Slot {
+                id: -1,
+                obj_guid: transaction_guid,
+                name: slots_manager::SLOT_NAME_NOTES.to_string(),
+                slot_type: slots_manager::SLOT_TYPE_STRING,
+                int64_val: 0,
+                string_val: transaction.notes.join("\n"),
+                double_val: Some(0.0),
+                timespec_val: None,
+                guid_val: None,
+                numeric_val_num: Some(0),
+                numeric_val_denom: Some(1),
+                gdate_val: None,
+            })?;
+        }
+        self.imported_transactions.insert(transaction_guid);
+        self.summary.transactions += 1;
+
+        Ok(())
+    }
+
+    /// find_probable_duplicate finds a transaction already in the book that looks like the given
+    /// journal transaction, with the given splits. Either its description, or the one a rule
+    /// rewrote it to can match, but not transactions imported from this journal.
+    fn find_probable_duplicate(
+        &self,
+        transaction: &JournalTransaction,
+        description: &str,
+        splits: &[splits_manager::Split],
+    ) -> Result<Option<transactions_manager::ProbableDuplicate>, MoneyManagerError> {
+        let post_date = transaction.date.and_time(chrono::NaiveTime::MIN);
+        for split in splits {
+            let probable_duplicates = transactions_manager::retrieve_probable_duplicates(
+                split.account_guid,
+                post_date,
+                split.value_num,
+                split.value_denom,
+                "",
+            )?;
+            let probable_duplicate = probable_duplicates.into_iter().find(|probable_duplicate| {
+                !self.imported_transactions.contains(&probable_duplicate.guid)
+                    && (transactions_manager::is_similar_description(
+                        &transaction.description,
+                        &probable_duplicate.description,
+                    ) || transactions_manager::is_similar_description(
+                        description,
+                        &probable_duplicate.description,
+                    ))
+            });
+            if probable_duplicate.is_some() {
+                return Ok(probable_duplicate);
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// account_node_path returns the names of the accounts from the top level down to the given
+/// account, or None when the account isn't under the root account.
+fn account_node_path(
+    nodes: &HashMap<Uuid, &accounts_manager::AccountNode>,
+    root_account_guid: Uuid,
+    node: &accounts_manager::AccountNode,
+) -> Option<Vec<String>> {
+    let mut path = Vec::new();
+    let mut current = node;
+    while current.guid != root_account_guid {
+        if path.len() > nodes.len() {
+            return None;
+        }
+        path.push(current.name.clone());
+        current = nodes.get(&current.parent_guid?)?;
+    }
+    if path.is_empty() {
+        return None;
+    }
+    path.reverse();
+    Some(path)
+}
+
+/// convert_gnucash_account_type converts a GnuCash account type to its AccountType, or EQUITY
+/// when GnuCash doesn't write it. (Ex: 'NONE' is EQUITY)
+fn convert_gnucash_account_type(account_type: &str) -> AccountType {
+    match AccountType::from(account_type.to_string()) {
+        AccountType::OTHER(_) => AccountType::EQUITY,
+        account_type => account_type,
+    }
+}
+
+/// convert_top_level_account_type returns the account type for a top level account name.
+/// (Ex: 'Expenses' is an EXPENSE)
+fn convert_top_level_account_type(name: &str) -> Option<AccountType> {
+    match name.to_lowercase().as_str() {
+        "assets" | "asset" => Some(AccountType::ASSET),
+        "liabilities" | "liability" => Some(AccountType::LIABILITY),
+        "equity" => Some(AccountType::EQUITY),
+        "income" | "revenue" | "revenues" => Some(AccountType::INCOME),
+        "expenses" | "expense" => Some(AccountType::EXPENSE),
+        _ => None,
+    }
+}
+
+/// add_fractions adds two fractions, reducing the sum.
+fn add_fractions(a: (i128, i128), b: (i128, i128)) -> (i128, i128) {
+    let num = a.0 * b.1 + b.0 * a.1;
+    let denom = a.1 * b.1;
+    let divisor = greatest_common_divisor(num.abs(), denom);
+    (num / divisor, denom / divisor)
+}
+
+/// decimal_places_of returns how many decimal places the given power of ten denominator has.
+fn decimal_places_of(denom: i64) -> u32 {
+    decimal_places(denom as i128).unwrap_or_default()
+}
+
+/// convert_date_to_sql_date converts a journal date to the database's date format, at the
+/// 10:59 UTC GnuCash uses for dates without a time.
+fn convert_date_to_sql_date(date: NaiveDate) -> String {
+    dhu::convert_date_to_string_format(date.and_hms_opt(10, 59, 0).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utility::gnucash_xml_helper_utility::{
+        XmlCommodity, XmlSlot, XmlSplit, XmlTransaction,
+    };
+
+    fn account(guid: &str, name: &str, account_type: &str, parent_guid: &str, commodity_guid: &str) -> XmlAccount {
+        XmlAccount {
+            guid: guid.to_string(),
+            name: name.to_string(),
+            account_type: account_type.to_string(),
+            commodity_guid: Some(commodity_guid.to_string()),
+            parent_guid: if parent_guid.is_empty() { None } else { Some(parent_guid.to_string()) },
+            ..Default::default()
+        }
+    }
+
+    fn split(tx_guid: &str, account_guid: &str, value_num: i64, quantity_num: i64) -> XmlSplit {
+        XmlSplit {
+            tx_guid: tx_guid.to_string(),
+            account_guid: account_guid.to_string(),
+            reconcile_state: "n".to_string(),
+            value_num,
+            value_denom: 100,
+            quantity_num,
+            quantity_denom: 100,
+            ..Default::default()
+        }
+    }
+
+    fn book() -> XmlBook {
+        XmlBook {
+            root_account_guid: "root".to_string(),
+            root_template_guid: "template".to_string(),
+            commodities: vec![
+                XmlCommodity { guid: "usd".to_string(), mnemonic: "USD".to_string(), ..Default::default() },
+                XmlCommodity { guid: "aapl".to_string(), mnemonic: "AAPL".to_string(), ..Default::default() },
+            ],
+            accounts: vec![
+                account("root", "Root Account", "ROOT", "", "usd"),
+                account("template", "Template Root", "ROOT", "", "usd"),
+                account("assets", "Assets", "ASSET", "root", "usd"),
+                account("checking", "Checking  Account", "BANK", "assets", "usd"),
+                account("stock", "Apple stock", "STOCK", "assets", "aapl"),
+                account("groceries", "Groceries", "EXPENSE", "root", "usd"),
+                account("scheduled", "scheduled", "BANK", "template", "usd"),
+            ],
+            transactions: vec![
+                XmlTransaction {
+                    guid: "t1".to_string(),
+                    currency_guid: "usd".to_string(),
+                    num: "101".to_string(),
+                    post_date: Some("20240115100000".to_string()),
+                    description: "Market \"Fresh\"".to_string(),
+                    ..Default::default()
+                },
+                XmlTransaction {
+                    guid: "t2".to_string(),
+                    currency_guid: "usd".to_string(),
+                    post_date: Some("2024-02-01 10:00:00".to_string()),
+                    description: "Buy stock".to_string(),
+                    ..Default::default()
+                },
+                XmlTransaction {
+                    guid: "t3".to_string(),
+                    currency_guid: "usd".to_string(),
+                    post_date: Some("20240301100000".to_string()),
+                    description: "Scheduled".to_string(),
+                    ..Default::default()
+                },
+            ],
+            splits: vec![
+                XmlSplit { memo: "milk".to_string(), ..split("t1", "groceries", 1250, 1250) },
+                XmlSplit { reconcile_state: "y".to_string(), ..split("t1", "checking", -1250, -1250) },
+                split("t2", "stock", 150000, 1000),
+                split("t2", "checking", -150000, -150000),
+                split("t3", "scheduled", 100, 100),
+            ],
+            slots: vec![XmlSlot {
+                obj_guid: "t1".to_string(),
+                name: "notes".to_string(),
+                string_val: Some("weekly shop".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn format_numeric_writes_exact_decimals() {
+        assert_eq!(format_numeric(1250, 100), "12.50");
+        assert_eq!(format_numeric(-5, 100), "-0.05");
+        assert_eq!(format_numeric(1, 8), "0.125");
+        assert_eq!(format_numeric(42, 1), "42");
+        assert_eq!(format_numeric(2, 6), "0.3333333333");
+        assert_eq!(format_numeric(-2, 3), "-0.6666666667");
+    }
+
+    #[test]
+    fn write_journal_writes_ledger_postings() {
+        let journal = write_journal(&book(), JournalFormat::Ledger, None, None);
+
+        assert!(journal.contains("account Assets:Checking Account\n"));
+        assert!(journal.contains(
+            "2024-01-15 (101) Market \"Fresh\"\n    ; weekly shop\n    Groceries  12.50 USD  ; milk\n    * Assets:Checking Account  -12.50 USD\n"
+        ));
+        assert!(journal.contains("    Assets:Apple stock  10.00 AAPL @@ 1500.00 USD\n"));
+        assert!(!journal.contains("Scheduled"));
+    }
+
+    #[test]
+    fn write_journal_writes_beancount_for_a_date_range() {
+        let journal = write_journal(
+            &book(),
+            JournalFormat::Beancount,
+            NaiveDate::from_ymd_opt(2024, 2, 1),
+            NaiveDate::from_ymd_opt(2024, 2, 29),
+        );
+
+        assert!(journal.contains("2024-02-01 open Assets:Checking-Account\n"));
+        assert!(journal.contains("2024-02-01 open Expenses:Groceries\n"));
+        assert!(journal.contains(
+            "2024-02-01 * \"Buy stock\"\n    Assets:Apple-stock  10.00 AAPL @@ 1500.00 USD\n    Assets:Checking-Account  -1500.00 USD\n"
+        ));
+        assert!(!journal.contains("Fresh"));
+    }
+
+    #[test]
+    fn parse_amount_reads_commodities_on_either_side() {
+        let amount = |num: i64, denom: i64, commodity: &str| JournalAmount {
+            num,
+            denom,
+            commodity: commodity.to_string(),
+        };
+
+        assert_eq!(parse_amount("-12.50 USD"), Ok(amount(-1250, 100, "USD")));
+        assert_eq!(parse_amount("EUR 1,234.5"), Ok(amount(12345, 10, "EUR")));
+        assert_eq!(parse_amount("-$12.50"), Ok(amount(-1250, 100, "USD")));
+        assert_eq!(parse_amount("10 \"VANGUARD 500\""), Ok(amount(10, 1, "VANGUARD 500")));
+        assert_eq!(
+            parse_amount("Account"),
+            Err(MoneyManagerError::Validation("'Account' is not a valid amount.".to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_journal_reads_beancount() {
+        let journal = parse_journal(
+            r#"option "title" "Test"
+2024-01-01 open Assets:Checking-Account USD
+2024-01-01 commodity AAPL
+2024-01-20 price AAPL 151.25 USD
+
+2024-02-01 * "Broker" "Buy stock" #invest
+    num: "7"
+    ; bought on the dip
+    Assets:Brokerage  10 AAPL {150.00 USD}
+    Assets:Checking-Account  -1500.00 USD ; "from; savings"
+2024-02-02 balance Assets:Checking-Account 0 USD
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(journal.accounts, vec![("Assets:Checking-Account".to_string(), Some("USD".to_string()))]);
+        assert_eq!(journal.commodities, vec!["AAPL".to_string()]);
+        assert_eq!(journal.prices[0].price.num, 15125);
+        let transaction = &journal.transactions[0];
+        assert_eq!(transaction.description, "Broker - Buy stock");
+        assert_eq!(transaction.num, "7");
+        assert_eq!(transaction.notes, vec!["bought on the dip".to_string()]);
+        assert_eq!(transaction.postings[0].total_cost.as_ref().unwrap().num, 1500);
+        assert_eq!(transaction.postings[0].reconcile_state, "n");
+        assert_eq!(transaction.postings[1].memo, "\"from; savings\"");
+    }
+
+    #[test]
+    fn parse_journal_reads_ledger() {
+        let journal = parse_journal(
+            "; a comment
+account Assets:Checking Account
+P 2024/01/20 AAPL $151.25
+
+2024/01/15 * (101) Market  ; weekly shop
+    Expenses:Groceries    $12.50  ; milk
+    ! Assets:Checking Account
+    (Budget:Groceries)    $-12.50
+
+~ Monthly
+    Expenses:Rent    $1000.00
+    Assets:Checking Account
+",
+        )
+        .unwrap();
+
+        assert_eq!(journal.accounts[0].0, "Assets:Checking Account");
+        assert_eq!(journal.prices[0].commodity, "AAPL");
+        assert_eq!(journal.transactions.len(), 1);
+        let transaction = &journal.transactions[0];
+        assert_eq!(transaction.num, "101");
+        assert_eq!(transaction.description, "Market");
+        assert_eq!(transaction.notes, vec!["weekly shop".to_string()]);
+        assert_eq!(transaction.postings.len(), 2);
+        assert_eq!(transaction.postings[0].reconcile_state, "y");
+        assert_eq!(transaction.postings[0].memo, "milk");
+        assert_eq!(transaction.postings[1].account, "Assets:Checking Account");
+        assert_eq!(transaction.postings[1].amount, None);
+        assert_eq!(transaction.postings[1].reconcile_state, "c");
+    }
+
+    #[test]
+    fn categorize_journal_transaction_replaces_the_unknown_posting() {
+        let journal = parse_journal(
+            "2024-01-15 * \"KROGER #123\"
+    Assets:Checking  -42.10 USD
+    Expenses:Unknown
+",
+        )
+        .unwrap();
+        let groceries = CategorizationRule {
+            guid: Uuid::new_v4(),
+            name: "Groceries".to_string(),
+            priority: 0,
+            match_type: categorization_rules_manager::RuleMatchType::Contains,
+            pattern: "kroger".to_string(),
+            minimum_amount: None,
+            maximum_amount: Some(100.0),
+            account_guid: Uuid::new_v4(),
+            description: "Kroger".to_string(),
+        };
+        let rules = vec![groceries.clone()];
+
+        let transaction = &journal.transactions[0];
+        assert_eq!(
+            categorize_journal_transaction(&rules, transaction),
+            Some((1, &groceries))
+        );
+        assert_eq!(
+            describe_categorization(transaction, &groceries),
+            "Line 1: 'KROGER #123' categorized by the rule 'Groceries' as 'Kroger'"
+        );
+        assert!(!is_uncategorized_account("Expenses:Unknown Fees"));
+    }
+
+}
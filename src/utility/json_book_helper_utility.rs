@@ -0,0 +1,158 @@
+/// json_book_helper_utility writes the whole book as a versioned JSON document, and reads one
+/// back into a new database. The document holds the records of every table a GnuCash XML book
+/// holds, with all of their columns, so a book can be exported, and imported again without
+/// losing anything. The managers' structs aren't used for this, since they leave out columns,
+/// and can't hold every GnuCash account type.
+use serde::{Deserialize, Serialize};
+
+use crate::utility::database_helper_utility as dhu;
+use crate::utility::error_helper_utility::MoneyManagerError;
+use crate::utility::gnucash_xml_helper_utility::{self, XmlBook};
+
+/// JSON_BOOK_FORMAT is the format name every JSON book starts with, so other JSON files can be
+/// told apart.
+pub const JSON_BOOK_FORMAT: &str = "money_manager_book";
+
+/// JSON_BOOK_VERSION is the version of the JSON book format this app writes. Books of this
+/// version, or older ones, can be read.
+pub const JSON_BOOK_VERSION: i64 = 1;
+
+/// JsonBook is the JSON document for a book, which is the book's records along with the
+/// format, and its version.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonBook {
+    pub format: String, //format is always JSON_BOOK_FORMAT.
+    pub version: i64,   //version is the version of the format the book was written in. (Ex: 1)
+    #[serde(flatten)]
+    pub book: XmlBook, //book is the records of the book.
+}
+
+/// JsonBookHeader is just the format, and version of a JSON document, for checking them before
+/// the rest is read.
+#[derive(Debug, Deserialize)]
+struct JsonBookHeader {
+    format: Option<String>,
+    version: Option<i64>,
+}
+
+/// is_json_book checks whether the given file is a JSON document, instead of a sqlite database.
+pub fn is_json_book(bytes: &[u8]) -> bool {
+    String::from_utf8_lossy(&bytes[..bytes.len().min(64)])
+        .trim_start_matches('\u{feff}')
+        .trim_start()
+        .starts_with('{')
+}
+
+/// write_json_book writes the given book as a JSON book, with one field per line so two exports
+/// can be compared with a diff.
+pub fn write_json_book(book: &XmlBook) -> Result<String, MoneyManagerError> {
+    Ok(serde_json::to_string_pretty(&JsonBook {
+        format: JSON_BOOK_FORMAT.to_string(),
+        version: JSON_BOOK_VERSION,
+        book: book.clone(),
+    })?)
+}
+
+/// parse_json_book reads the records of a JSON book, after checking it's a JSON book this
+/// version of the app can read.
+pub fn parse_json_book(json: &str) -> Result<XmlBook, MoneyManagerError> {
+    let header: JsonBookHeader = serde_json::from_str(json).map_err(|error| {
+        MoneyManagerError::Validation(format!("This file is not valid JSON: {error}"))
+    })?;
+    if header.format.as_deref() != Some(JSON_BOOK_FORMAT) {
+        return Err(MoneyManagerError::Validation(
+            "This JSON file is not a book exported by this app.".to_string(),
+        ));
+    }
+    match header.version {
+        Some(version) if (1..=JSON_BOOK_VERSION).contains(&version) => {}
+        Some(version) => {
+            return Err(MoneyManagerError::Validation(format!(
+                "This book is in version {version} of the JSON book format, but only versions up to {JSON_BOOK_VERSION} can be read. Please update the app."
+            )));
+        }
+        None => {
+            return Err(MoneyManagerError::Validation(
+                "This JSON book is missing its version.".to_string(),
+            ));
+        }
+    }
+
+    let json_book: JsonBook = serde_json::from_str(json)?;
+    Ok(json_book.book)
+}
+
+/// export_database_to_json_book writes the loaded database as a JSON book.
+pub fn export_database_to_json_book() -> Result<Vec<u8>, MoneyManagerError> {
+    Ok(write_json_book(&gnucash_xml_helper_utility::retrieve_xml_book_from_database()?)?.into_bytes())
+}
+
+/// load_json_book_into_new_database reads the given JSON book into a new database, with the
+/// tables GnuCash's sqlite format uses, and makes it the loaded database.
+pub fn load_json_book_into_new_database(bytes: &[u8]) -> Result<(), MoneyManagerError> {
+    let json = String::from_utf8(bytes.to_vec()).map_err(|error| {
+        MoneyManagerError::Validation(format!("The JSON book is not valid UTF-8: {error}"))
+    })?;
+    let book = parse_json_book(json.trim_start_matches('\u{feff}'))?;
+
+    let database = dhu::Database::new_empty();
+    dhu::create_database_tables(&database);
+    crate::DATABASE.lock().unwrap().push(database);
+
+    gnucash_xml_helper_utility::save_xml_book_into_database(&book)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utility::gnucash_xml_helper_utility::{XmlAccount, XmlSlot, XmlSplit};
+
+    fn book() -> XmlBook {
+        XmlBook {
+            guid: "b0000000000000000000000000000001".to_string(),
+            root_account_guid: "a0000000000000000000000000000001".to_string(),
+            accounts: vec![XmlAccount {
+                guid: "a0000000000000000000000000000001".to_string(),
+                name: "Root Account".to_string(),
+                account_type: "ROOT".to_string(),
+                ..Default::default()
+            }],
+            splits: vec![XmlSplit {
+                value_num: -1250,
+                value_denom: 100,
+                reconcile_date: Some("20240115105900".to_string()),
+                ..Default::default()
+            }],
+            slots: vec![XmlSlot {
+                name: "notes".to_string(),
+                string_val: Some("Line one\nLine \"two\"".to_string()),
+                double_val: Some(0.5),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn write_json_book_reads_back_the_same_book() {
+        let json = write_json_book(&book()).unwrap();
+
+        assert!(json.contains("\"format\": \"money_manager_book\""));
+        assert!(json.contains("\"version\": 1"));
+        assert_eq!(parse_json_book(&json).unwrap(), book());
+        assert!(is_json_book(json.as_bytes()));
+    }
+
+    #[test]
+    fn parse_json_book_rejects_other_json_and_newer_versions() {
+        assert!(parse_json_book("{\"accounts\": []}").is_err());
+
+        let json = write_json_book(&book())
+            .unwrap()
+            .replace("\"version\": 1", "\"version\": 2");
+        match parse_json_book(&json) {
+            Err(MoneyManagerError::Validation(message)) => assert!(message.contains("version 2")),
+            result => panic!("Expected a validation error, not {:?}", result),
+        }
+    }
+}
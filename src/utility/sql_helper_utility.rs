@@ -1,8 +1,8 @@
 ///sql_helper_utility is all the sql files in use by the program.
 /// There should only be complex ones here, because simple ones don't need their own file.
 
-/// load_splits_for_last_30_day_report loads the last 30 days of splits.
-/// The 3 parameters are start_date, end_date, and account_type.
+/// load_splits_for_last_30_day_report loads the last 30 days of splits. The parameters are the
+/// start, and end dates from dhu::convert_dates_to_post_date_bounds, then the account_type.
 pub fn load_splits_for_last_30_day_report() -> String {
     let bytes = include_bytes!("../sql/load_splits_for_last_30_day_report.sql");
     String::from_utf8_lossy(bytes).to_string()
@@ -25,10 +25,26 @@ pub fn load_accounts_with_balances() -> String {
   
 }
 
-/// load_transactions_for_account loads all the transactions for an account.
-/// You will need to pass the account_guid 4 times.
-pub fn load_transactions_for_account() -> String {
-    let bytes = include_bytes!("../sql/load_transactions_for_account.sql");
+/// load_transactions_for_account_page loads one page of the transactions for an account, oldest
+/// first. The parameters are account_guid, the page size, the offset, then account_guid 3 times.
+pub fn load_transactions_for_account_page() -> String {
+    let bytes = include_bytes!("../sql/load_transactions_for_account_page.sql");
+    String::from_utf8_lossy(bytes).to_string()
+}
+
+/// load_filtered_transactions_for_account loads the transactions for an account that pass a
+/// register filter, each with the account's balance after it. The parameters are made by
+/// transactions_manager::RegisterFilter::binding_parameters.
+pub fn load_filtered_transactions_for_account() -> String {
+    let bytes = include_bytes!("../sql/load_filtered_transactions_for_account.sql");
+    String::from_utf8_lossy(bytes).to_string()
+}
+
+/// load_account_with_balance_before_page_for_guid loads the account with its balance from the
+/// transactions before a page of its register. The parameters are account_guid, the offset, then
+/// account_guid again.
+pub fn load_account_with_balance_before_page_for_guid() -> String {
+    let bytes = include_bytes!("../sql/load_account_with_balance_before_page_for_guid.sql");
     String::from_utf8_lossy(bytes).to_string()
 }
 
@@ -38,27 +54,30 @@ pub fn load_commodity_for_guid() -> String {
     String::from_utf8_lossy(bytes).to_string()
 }
 
-/// load_account_with_balance_for_date_and_guid loads the account with balance based on 
+/// load_account_with_balance_for_date_and_guid loads the account with balance based on
 /// the date the account was opened until the given date, for the account with given guid.
+/// The parameters are the date in the 14 char format, the date in the ISO format, then the guid.
 pub fn load_account_with_balance_for_date_and_guid() -> String {
     let bytes = include_bytes!("../sql/load_account_with_balance_for_date_and_guid.sql");
     String::from_utf8_lossy(bytes).to_string()
 }
 
-/// load_account_with_balance_for_guid loads the account with balance for the account with
-/// the given guid.
-pub fn load_account_with_balance_for_guid() -> String {
-    let bytes = include_bytes!("../sql/load_account_with_balance_for_guid.sql");
-    String::from_utf8_lossy(bytes).to_string()
-}
-
 /// load_transaction_for_account_between_dates loads the transactions based on the beginning
-/// dates give. You should have 4 guids for the account, the beginning date, then the end after it.
+/// dates given. You should have 3 guids for the account, then the dates from
+/// dhu::convert_dates_to_post_date_bounds.
 pub fn load_transactions_for_account_between_dates() -> String {
     let bytes = include_bytes!("../sql/load_transactions_for_account_between_dates.sql");
     String::from_utf8_lossy(bytes).to_string()
 }
 
+/// load_transactions_for_account_and_value_between_dates loads the transactions where the
+/// account's split has the given value. You should have the account guid, the value_num, and
+/// value_denom, then the dates from dhu::convert_dates_to_post_date_bounds.
+pub fn load_transactions_for_account_and_value_between_dates() -> String {
+    let bytes = include_bytes!("../sql/load_transactions_for_account_and_value_between_dates.sql");
+    String::from_utf8_lossy(bytes).to_string()
+}
+
 /// load_all_accounts_except_root_and_template loads all the accounts >100 of them except the root
 /// and the template ones.
 pub fn load_all_accounts_except_root_and_template() -> String {
@@ -67,9 +86,58 @@ pub fn load_all_accounts_except_root_and_template() -> String {
 }
 
 /// load_transaction_for_account_guid_and_description loads the last other half of the transaction
-/// for a particular account guid, and the description. Should have 3 account guids, then a description,
-/// then another account guid to make this work.
+/// for a particular account guid, and the description. Should have the description, then 2 account
+/// guids to make this work.
 pub fn load_transaction_for_account_guid_and_description() -> String {
     let bytes = include_bytes!("../sql/load_transaction_for_account_guid_and_description.sql");
     String::from_utf8_lossy(bytes).to_string()
 }
+
+/// load_count_of_taxtable_usages counts the entries, customers, and vendors using a tax table.
+/// You will need to pass the taxtable guid 4 times.
+pub fn load_count_of_taxtable_usages() -> String {
+    let bytes = include_bytes!("../sql/load_count_of_taxtable_usages.sql");
+    String::from_utf8_lossy(bytes).to_string()
+}
+
+/// load_tax_splits_between_dates loads the splits posted to tax table accounts between two dates,
+/// given as the bounds from dhu::convert_dates_to_post_date_bounds.
+pub fn load_tax_splits_between_dates() -> String {
+    let bytes = include_bytes!("../sql/load_tax_splits_between_dates.sql");
+    String::from_utf8_lossy(bytes).to_string()
+}
+
+/// load_open_lots_for_aging loads the open lots on RECEIVABLE and PAYABLE accounts, with the
+/// invoice, owner and balance of each lot.
+pub fn load_open_lots_for_aging() -> String {
+    let bytes = include_bytes!("../sql/load_open_lots_for_aging.sql");
+    String::from_utf8_lossy(bytes).to_string()
+}
+
+/// load_integrity_problems loads everything the integrity check finds wrong with the book,
+/// with the kind of problem, the guid of the offending item, and an account to look at it in.
+pub fn load_integrity_problems() -> String {
+    let bytes = include_bytes!("../sql/load_integrity_problems.sql");
+    String::from_utf8_lossy(bytes).to_string()
+}
+
+/// load_account_references counts the splits, reconciled splits, budgets, scheduled transactions,
+/// rules, templates, and business records that refer to a set of accounts.
+pub fn load_account_references() -> String {
+    let bytes = include_bytes!("../sql/load_account_references.sql");
+    String::from_utf8_lossy(bytes).to_string()
+}
+
+/// load_scheduled_transaction_slots loads the slots of the scheduled transactions' template
+/// splits, and of the frames inside of them, to find the accounts the splits are for.
+pub fn load_scheduled_transaction_slots() -> String {
+    let bytes = include_bytes!("../sql/load_scheduled_transaction_slots.sql");
+    String::from_utf8_lossy(bytes).to_string()
+}
+
+/// load_closing_balances_through_date loads the balance of each INCOME, and EXPENSE account
+/// through a date, for each quantity denominator its splits use.
+pub fn load_closing_balances_through_date() -> String {
+    let bytes = include_bytes!("../sql/load_closing_balances_through_date.sql");
+    String::from_utf8_lossy(bytes).to_string()
+}